@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Any input must parse or give an error; a panic, stack overflow or hang is
+// a bug.
+fuzz_target!(|bytes: &[u8]| {
+    let _ = mini_json::parse_from_bytes(bytes);
+});
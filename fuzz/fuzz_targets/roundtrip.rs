@@ -0,0 +1,24 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mini_json::{parse_from_bytes, parse_from_str, OutputStyle, SerializeOptions};
+
+// Whatever parses must serialize, in either style, to text that parses
+// back to the same tree.
+fuzz_target!(|bytes: &[u8]| {
+    let json = match parse_from_bytes(bytes) {
+        Ok(json) => json,
+        Err(_) => return,
+    };
+
+    for style in [OutputStyle::Minified, OutputStyle::Pretty] {
+        let text = match json.to_string_with(&SerializeOptions::new(style)) {
+            Ok(text) => text,
+            Err(error) => panic!("{} serializing a parsed document", error),
+        };
+        match parse_from_str(&text) {
+            Ok(reparsed) => assert_eq!(reparsed, json, "{}", text),
+            Err(error) => panic!("{} reparsing {}", error, text),
+        }
+    }
+});
@@ -0,0 +1,58 @@
+/* C interface to mini-json, built with `cargo build --features ffi`.
+ *
+ * Values are borrowed from their document and stay valid until it's freed
+ * with mini_json_free. Functions returning int return 0 on success and -1
+ * on failure. See src/ffi.rs for each function's contract. */
+
+#ifndef MINI_JSON_H
+#define MINI_JSON_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define MINI_JSON_NULL 0
+#define MINI_JSON_BOOLEAN 1
+#define MINI_JSON_INTEGER 2
+#define MINI_JSON_FLOAT 3
+#define MINI_JSON_STRING 4
+#define MINI_JSON_ARRAY 5
+#define MINI_JSON_OBJECT 6
+
+typedef struct MiniJsonDoc MiniJsonDoc;
+typedef struct MiniJsonValue MiniJsonValue;
+
+typedef struct MiniJsonError {
+    size_t line;
+    size_t column;
+    char *message;
+} MiniJsonError;
+
+int mini_json_parse(const char *input, size_t len, MiniJsonDoc **out, MiniJsonError *err);
+void mini_json_free(MiniJsonDoc *doc);
+void mini_json_error_free(MiniJsonError *err);
+
+const MiniJsonValue *mini_json_root(const MiniJsonDoc *doc);
+int mini_json_type(const MiniJsonValue *value);
+const MiniJsonValue *mini_json_object_get(const MiniJsonValue *value, const char *key, size_t key_len);
+size_t mini_json_len(const MiniJsonValue *value);
+const MiniJsonValue *mini_json_array_get(const MiniJsonValue *value, size_t index);
+
+int mini_json_get_bool(const MiniJsonValue *value, bool *out);
+int mini_json_get_integer(const MiniJsonValue *value, int64_t *out);
+int mini_json_get_double(const MiniJsonValue *value, double *out);
+/* Not NUL-terminated; the bytes belong to the document. */
+int mini_json_get_string(const MiniJsonValue *value, const char **out, size_t *out_len);
+
+char *mini_json_to_string(const MiniJsonValue *value, int pretty);
+void mini_json_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,476 @@
+#![allow(clippy::needless_return)]
+
+//! `#[derive(FromJson, ToJson)]` for `mini-json`, usually enabled through
+//! that crate's `derive` feature.
+//!
+//! Structs with named fields map to objects and enums with only unit
+//! variants map to strings. Fields and variants accept `#[json(...)]`
+//! attributes:
+//!
+//! - `rename = "name"` uses a different key or variant name in the JSON.
+//! - `default` fills a missing or `null` field with `Default::default()`.
+//! - `skip` leaves the field out of the JSON and fills it with
+//!   `Default::default()` when converting back.
+//!
+//! Tuple structs, unit structs, generic types and enum variants with data
+//! are rejected with a compile error.
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+#[derive(Default)]
+struct Options {
+    rename: Option<String>,
+    default: bool,
+    skip: bool,
+}
+
+struct Field {
+    ident: String,
+    options: Options,
+}
+
+impl Field {
+    fn key(&self) -> String {
+        match &self.options.rename {
+            Some(rename) => rename.clone(),
+            None => self.ident.trim_start_matches("r#").to_string(),
+        }
+    }
+}
+
+enum Shape {
+    Struct(Vec<Field>),
+    Enum(Vec<Field>),
+}
+
+struct Input {
+    name: String,
+    shape: Shape,
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    match parse_input(input, "FromJson") {
+        Ok(input) => {
+            return expand_from_json(&input);
+        }
+        Err(message) => {
+            return compile_error(&message);
+        }
+    }
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    match parse_input(input, "ToJson") {
+        Ok(input) => {
+            return expand_to_json(&input);
+        }
+        Err(message) => {
+            return compile_error(&message);
+        }
+    }
+}
+
+fn compile_error(message: &str) -> TokenStream {
+    return format!("::core::compile_error!({:?});", message)
+        .parse()
+        .unwrap();
+}
+
+fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+    return matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == c);
+}
+
+fn is_ident(token: Option<&TokenTree>, name: &str) -> bool {
+    return matches!(token, Some(TokenTree::Ident(ident)) if ident.to_string() == name);
+}
+
+fn parse_input(input: TokenStream, derive: &str) -> Result<Input, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut current = 0;
+
+    // Container attributes such as doc comments and other derives are
+    // passed through untouched.
+    let options = parse_attributes(&tokens, &mut current)?;
+    if options.rename.is_some() || options.default || options.skip {
+        return Err(format!(
+            "#[json(...)] is only supported on fields and variants in #[derive({})]",
+            derive
+        ));
+    }
+
+    skip_visibility(&tokens, &mut current);
+
+    let kind = match tokens.get(current) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err(format!("#[derive({})] expects a struct or enum", derive)),
+    };
+    current += 1;
+
+    let name = match tokens.get(current) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err(format!("#[derive({})] expects a type name", derive)),
+    };
+    current += 1;
+
+    if is_punct(tokens.get(current), '<') {
+        return Err(format!(
+            "#[derive({})] does not support generic types yet",
+            derive
+        ));
+    }
+
+    let body = match tokens.get(current) {
+        Some(TokenTree::Group(group)) => group,
+        _ => {
+            return Err(format!(
+                "#[derive({})] only supports structs with named fields",
+                derive
+            ));
+        }
+    };
+
+    match (kind.as_str(), body.delimiter()) {
+        ("struct", Delimiter::Brace) => {
+            let fields = parse_fields(body.stream(), derive)?;
+            return Ok(Input {
+                name,
+                shape: Shape::Struct(fields),
+            });
+        }
+        ("struct", _) => {
+            return Err(format!(
+                "#[derive({})] does not support tuple structs; use named fields",
+                derive
+            ));
+        }
+        ("enum", Delimiter::Brace) => {
+            let variants = parse_variants(body.stream(), derive)?;
+            return Ok(Input {
+                name,
+                shape: Shape::Enum(variants),
+            });
+        }
+        _ => {
+            return Err(format!(
+                "#[derive({})] only supports structs and enums",
+                derive
+            ));
+        }
+    }
+}
+
+fn skip_visibility(tokens: &[TokenTree], current: &mut usize) {
+    if is_ident(tokens.get(*current), "pub") {
+        *current += 1;
+
+        if let Some(TokenTree::Group(group)) = tokens.get(*current) {
+            if group.delimiter() == Delimiter::Parenthesis {
+                *current += 1;
+            }
+        }
+    }
+}
+
+fn parse_attributes(tokens: &[TokenTree], current: &mut usize) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    while is_punct(tokens.get(*current), '#') {
+        let group = match tokens.get(*current + 1) {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => group,
+            _ => return Err("Malformed attribute".to_string()),
+        };
+        *current += 2;
+
+        let attribute: Vec<TokenTree> = group.stream().into_iter().collect();
+        if !is_ident(attribute.first(), "json") {
+            continue;
+        }
+
+        match attribute.get(1) {
+            Some(TokenTree::Group(arguments))
+                if arguments.delimiter() == Delimiter::Parenthesis =>
+            {
+                parse_options(arguments.stream(), &mut options)?;
+            }
+            _ => {
+                return Err("Expected #[json(...)]".to_string());
+            }
+        }
+    }
+
+    return Ok(options);
+}
+
+fn parse_options(arguments: TokenStream, options: &mut Options) -> Result<(), String> {
+    let tokens: Vec<TokenTree> = arguments.into_iter().collect();
+    let mut current = 0;
+
+    while current < tokens.len() {
+        let name = match &tokens[current] {
+            TokenTree::Ident(ident) => ident.to_string(),
+            other => return Err(format!("Unexpected `{}` in #[json(...)]", other)),
+        };
+        current += 1;
+
+        match name.as_str() {
+            "rename" => {
+                if !is_punct(tokens.get(current), '=') {
+                    return Err("Expected `rename = \"name\"`".to_string());
+                }
+
+                match tokens.get(current + 1) {
+                    Some(TokenTree::Literal(literal)) => {
+                        options.rename = Some(parse_string_literal(&literal.to_string())?);
+                    }
+                    _ => {
+                        return Err("Expected `rename = \"name\"`".to_string());
+                    }
+                }
+                current += 2;
+            }
+            "default" => {
+                options.default = true;
+            }
+            "skip" => {
+                options.skip = true;
+            }
+            _ => {
+                return Err(format!("Unknown json attribute `{}`", name));
+            }
+        }
+
+        if current < tokens.len() {
+            if !is_punct(tokens.get(current), ',') {
+                return Err("Expected `,` between json attributes".to_string());
+            }
+            current += 1;
+        }
+    }
+
+    return Ok(());
+}
+
+fn parse_string_literal(literal: &str) -> Result<String, String> {
+    if literal.len() < 2 || !literal.starts_with('"') || !literal.ends_with('"') {
+        return Err(format!("Expected a string literal, found {}", literal));
+    }
+
+    let mut result = String::new();
+    let mut chars = literal[1..literal.len() - 1].chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            _ => {
+                return Err(format!("Unsupported escape in {}", literal));
+            }
+        }
+    }
+
+    return Ok(result);
+}
+
+// Skips to just past the next comma outside of any `<...>`, since generic
+// arguments in a field type aren't grouped into a single token.
+fn skip_past_comma(tokens: &[TokenTree], current: &mut usize) {
+    let mut depth = 0;
+    let mut after_dash = false;
+
+    while *current < tokens.len() {
+        let token = &tokens[*current];
+        *current += 1;
+
+        if let TokenTree::Punct(punct) = token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !after_dash => depth -= 1,
+                ',' if depth == 0 => return,
+                _ => {}
+            }
+
+            after_dash = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            after_dash = false;
+        }
+    }
+}
+
+fn parse_fields(body: TokenStream, derive: &str) -> Result<Vec<Field>, String> {
+    let tokens: Vec<TokenTree> = body.into_iter().collect();
+    let mut fields = Vec::new();
+    let mut current = 0;
+
+    while current < tokens.len() {
+        let options = parse_attributes(&tokens, &mut current)?;
+        skip_visibility(&tokens, &mut current);
+
+        let ident = match tokens.get(current) {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err(format!("#[derive({})] expects a field name", derive)),
+        };
+        current += 1;
+
+        if !is_punct(tokens.get(current), ':') {
+            return Err(format!("Expected `:` after field `{}`", ident));
+        }
+
+        skip_past_comma(&tokens, &mut current);
+        fields.push(Field { ident, options });
+    }
+
+    return Ok(fields);
+}
+
+fn parse_variants(body: TokenStream, derive: &str) -> Result<Vec<Field>, String> {
+    let tokens: Vec<TokenTree> = body.into_iter().collect();
+    let mut variants = Vec::new();
+    let mut current = 0;
+
+    while current < tokens.len() {
+        let options = parse_attributes(&tokens, &mut current)?;
+        if options.default || options.skip {
+            return Err("Only `rename` is supported on enum variants".to_string());
+        }
+
+        let ident = match tokens.get(current) {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err(format!("#[derive({})] expects a variant name", derive)),
+        };
+        current += 1;
+
+        if let Some(TokenTree::Group(_)) = tokens.get(current) {
+            return Err(format!(
+                "#[derive({})] only supports enums with unit variants, but `{}` has fields",
+                derive, ident
+            ));
+        }
+
+        skip_past_comma(&tokens, &mut current);
+        variants.push(Field { ident, options });
+    }
+
+    return Ok(variants);
+}
+
+fn expand_from_json(input: &Input) -> TokenStream {
+    let body = match &input.shape {
+        Shape::Struct(fields) => {
+            let mut initializers = String::new();
+
+            for field in fields {
+                let value = if field.options.skip {
+                    "::std::default::Default::default()".to_string()
+                } else if field.options.default {
+                    format!(
+                        "value.field_or({:?}, ::std::default::Default::default())?",
+                        field.key()
+                    )
+                } else {
+                    format!("value.field({:?})?", field.key())
+                };
+
+                initializers.push_str(&format!("{}: {},", field.ident, value));
+            }
+
+            format!(
+                "::std::result::Result::Ok({} {{ {} }})",
+                input.name, initializers
+            )
+        }
+        Shape::Enum(variants) => {
+            let mut arms = String::new();
+
+            for variant in variants {
+                arms.push_str(&format!(
+                    "{:?} => ::std::result::Result::Ok({}::{}),",
+                    variant.key(),
+                    input.name,
+                    variant.ident
+                ));
+            }
+
+            format!(
+                "let name = <::std::string::String as ::mini_json::FromJson>::from_json(value)?;
+                match name.as_str() {{
+                    {}
+                    other => ::std::result::Result::Err(::mini_json::FromJsonError::new(
+                        ::std::format!(\"unknown variant `{{}}`\", other),
+                    )),
+                }}",
+                arms
+            )
+        }
+    };
+
+    return format!(
+        "impl ::mini_json::FromJson for {} {{
+            fn from_json(
+                value: &::mini_json::JsonValue,
+            ) -> ::std::result::Result<Self, ::mini_json::FromJsonError> {{
+                {}
+            }}
+        }}",
+        input.name, body
+    )
+    .parse()
+    .unwrap();
+}
+
+fn expand_to_json(input: &Input) -> TokenStream {
+    let body = match &input.shape {
+        Shape::Struct(fields) => {
+            let mut inserts = String::new();
+
+            for field in fields.iter().filter(|field| !field.options.skip) {
+                inserts.push_str(&format!(
+                    "object.insert(::std::string::String::from({:?}), ::mini_json::ToJson::to_json(&self.{}));",
+                    field.key(),
+                    field.ident
+                ));
+            }
+
+            format!(
+                "#[allow(unused_mut)]
+                let mut object = ::std::collections::HashMap::new();
+                {}
+                ::mini_json::JsonValue::Object(object)",
+                inserts
+            )
+        }
+        Shape::Enum(variants) => {
+            let mut arms = String::new();
+
+            for variant in variants {
+                arms.push_str(&format!(
+                    "{}::{} => ::mini_json::JsonValue::String(::std::string::String::from({:?})),",
+                    input.name,
+                    variant.ident,
+                    variant.key()
+                ));
+            }
+
+            format!("match *self {{ {} }}", arms)
+        }
+    };
+
+    return format!(
+        "impl ::mini_json::ToJson for {} {{
+            fn to_json(&self) -> ::mini_json::JsonValue {{
+                {}
+            }}
+        }}",
+        input.name, body
+    )
+    .parse()
+    .unwrap();
+}
@@ -0,0 +1,215 @@
+use crate::json::JsonValue;
+use crate::mapping::FromJsonError;
+use crate::number::JsonNumber;
+
+/// The count, sum, minimum and maximum of some numbers, from
+/// `number_summary`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberSummary {
+    pub count: usize,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl NumberSummary {
+    pub fn mean(&self) -> f64 {
+        return self.sum / self.count as f64;
+    }
+}
+
+// Integers are summed exactly in an `i128`, which no array of `i64`s can
+// overflow, and floats separately in an `f64`. The two are only combined,
+// and rounded, at the end.
+#[derive(Default)]
+struct Totals {
+    count: usize,
+    integers: i128,
+    floats: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Totals {
+    fn add(&mut self, number: JsonNumber) {
+        match number {
+            JsonNumber::Integer(integer) => self.integers += integer as i128,
+            JsonNumber::Float(float) => self.floats += float,
+        }
+
+        let value = number.as_f64();
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+    }
+
+    fn finish(self) -> Option<NumberSummary> {
+        if self.count == 0 {
+            return None;
+        }
+
+        return Some(NumberSummary {
+            count: self.count,
+            sum: self.integers as f64 + self.floats,
+            min: self.min,
+            max: self.max,
+        });
+    }
+}
+
+fn summarize<'a>(values: impl Iterator<Item = &'a JsonValue>) -> Option<NumberSummary> {
+    let mut totals = Totals::default();
+    for number in values.filter_map(JsonValue::as_number) {
+        totals.add(number);
+    }
+
+    return totals.finish();
+}
+
+// The lenient aggregates skip elements that aren't numbers, and give `None`
+// for non-arrays and arrays without any numbers.
+impl JsonValue {
+    fn lenient_summary(&self) -> Option<NumberSummary> {
+        match self {
+            JsonValue::Array(array) => summarize(array.iter()),
+            _ => None,
+        }
+    }
+
+    pub fn sum_numbers(&self) -> Option<f64> {
+        return self.lenient_summary().map(|summary| summary.sum);
+    }
+
+    pub fn min_number(&self) -> Option<f64> {
+        return self.lenient_summary().map(|summary| summary.min);
+    }
+
+    pub fn max_number(&self) -> Option<f64> {
+        return self.lenient_summary().map(|summary| summary.max);
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        return self.lenient_summary().map(|summary| summary.mean());
+    }
+
+    /// Sums the number at the JSON Pointer `pointer` in each element, as in
+    /// `sum_by("/price")` over an array of objects. Elements without a
+    /// number there are skipped.
+    pub fn sum_by(&self, pointer: &str) -> Option<f64> {
+        let array = match self {
+            JsonValue::Array(array) => array,
+            _ => return None,
+        };
+
+        let values = array.iter().filter_map(|element| element.pointer(pointer));
+        return summarize(values).map(|summary| summary.sum);
+    }
+
+    /// Summarizes an array that must hold only numbers, failing on the first
+    /// element that isn't one with its index, as `to_vec` does. An empty
+    /// array gives `Ok(None)`.
+    pub fn number_summary(&self) -> Result<Option<NumberSummary>, FromJsonError> {
+        let array = match self {
+            JsonValue::Array(array) => array,
+            _ => return Err(FromJsonError::new(self.wrong_kind("array").to_string())),
+        };
+
+        let mut totals = Totals::default();
+        for (index, element) in array.iter().enumerate() {
+            match element.as_number() {
+                Some(number) => totals.add(number),
+                None => {
+                    let error = element.wrong_kind("number").to_string();
+                    return Err(FromJsonError::new(error).at_index(index));
+                }
+            }
+        }
+
+        return Ok(totals.finish());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregate::NumberSummary;
+    use crate::json;
+    use crate::path::PathSegment;
+
+    #[test]
+    fn mixed_integer_and_float_arrays() {
+        let numbers = json!([3, 1.5, -2, "7", null, 4.5]);
+
+        assert_eq!(numbers.sum_numbers(), Some(7.0));
+        assert_eq!(numbers.min_number(), Some(-2.0));
+        assert_eq!(numbers.max_number(), Some(4.5));
+        assert_eq!(numbers.mean(), Some(1.75));
+    }
+
+    #[test]
+    fn no_numbers_give_none() {
+        for value in [
+            json!([]),
+            json!(["1", true, [2]]),
+            json!({"a": 1}),
+            json!(5),
+        ] {
+            assert_eq!(value.sum_numbers(), None, "{}", value);
+            assert_eq!(value.min_number(), None);
+            assert_eq!(value.max_number(), None);
+            assert_eq!(value.mean(), None);
+        }
+    }
+
+    #[test]
+    fn large_integers_are_summed_exactly() {
+        let value = json!([i64::MAX, i64::MAX, -(i64::MAX), 1]);
+        assert_eq!(value.sum_numbers(), Some(i64::MAX as f64 + 1.0));
+
+        // Summed as floats, 2^53 + 1 would round away.
+        let value = json!([9007199254740992_i64, 1, 1]);
+        assert_eq!(value.sum_numbers(), Some(9007199254740994.0));
+    }
+
+    #[test]
+    fn sum_by_adds_a_member_of_each_object() {
+        let orders = json!([
+            {"price": 10, "qty": 1},
+            {"price": 2.5},
+            {"price": "free"},
+            {"item": {"price": 100}},
+            7
+        ]);
+
+        assert_eq!(orders.sum_by("/price"), Some(12.5));
+        assert_eq!(orders.sum_by("/item/price"), Some(100.0));
+        assert_eq!(orders.sum_by("/missing"), None);
+        assert_eq!(json!({"price": 1}).sum_by("/price"), None);
+    }
+
+    #[test]
+    fn number_summary_is_strict() {
+        assert_eq!(
+            json!([2, 0.5, 6]).number_summary(),
+            Ok(Some(NumberSummary {
+                count: 3,
+                sum: 8.5,
+                min: 0.5,
+                max: 6.0,
+            }))
+        );
+        assert_eq!(json!([]).number_summary(), Ok(None));
+
+        let error = json!([1, 2, "3"]).number_summary().unwrap_err();
+        assert_eq!(error.path, [PathSegment::Index(2)]);
+        assert_eq!(error.to_string(), "[2]: expected number, found string");
+        assert_eq!(
+            json!(null).number_summary().unwrap_err().to_string(),
+            "expected array, found null"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::json::JsonValue;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApproxOptions {
+    pub absolute_epsilon: f64,
+    pub relative_epsilon: f64,
+    pub integer_equals_float: bool,
+}
+
+impl Default for ApproxOptions {
+    fn default() -> ApproxOptions {
+        ApproxOptions {
+            absolute_epsilon: 1e-12,
+            relative_epsilon: 1e-9,
+            integer_equals_float: true,
+        }
+    }
+}
+
+fn floats_close(a: f64, b: f64, opts: &ApproxOptions) -> bool {
+    if a == b {
+        return true;
+    }
+
+    let difference = (a - b).abs();
+
+    if difference <= opts.absolute_epsilon {
+        return true;
+    }
+
+    return difference <= opts.relative_epsilon * a.abs().max(b.abs());
+}
+
+impl JsonValue {
+    /// Compares two trees like `==`, except that floats are equal when they
+    /// are within either epsilon of `opts`, and `Integer(n)` equals
+    /// `Float(n as f64)` when `integer_equals_float` is set.
+    pub fn approx_eq(&self, other: &JsonValue, opts: &ApproxOptions) -> bool {
+        match (self, other) {
+            (JsonValue::Float(a), JsonValue::Float(b)) => {
+                return floats_close(*a, *b, opts);
+            }
+            (JsonValue::Integer(a), JsonValue::Float(b))
+            | (JsonValue::Float(b), JsonValue::Integer(a)) => {
+                return opts.integer_equals_float && floats_close(*a as f64, *b, opts);
+            }
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                return a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, opts));
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) => {
+                return a.len() == b.len()
+                    && a.iter().all(|(key, a)| match b.get(key) {
+                        Some(b) => a.approx_eq(b, opts),
+                        None => false,
+                    });
+            }
+            _ => {
+                return self == other;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::approx::ApproxOptions;
+    use crate::json;
+
+    #[test]
+    fn floats_within_either_epsilon_are_equal() {
+        let opts = ApproxOptions::default();
+
+        assert!(json!(0.1 + 0.2).approx_eq(&json!(0.3), &opts));
+        assert!(json!(1e-13).approx_eq(&json!(0.0), &opts));
+        assert!(json!(1e20).approx_eq(&json!(1e20 + 1e10), &opts));
+        assert!(!json!(1.0).approx_eq(&json!(1.001), &opts));
+        assert!(!json!(1e-6).approx_eq(&json!(2e-6), &opts));
+        assert!(!json!(f64::NAN).approx_eq(&json!(f64::NAN), &opts));
+    }
+
+    #[test]
+    fn integers_equal_floats_only_when_enabled() {
+        let mut opts = ApproxOptions::default();
+        assert!(json!(1).approx_eq(&json!(1.0), &opts));
+        assert!(json!(1.0).approx_eq(&json!(1), &opts));
+        assert!(!json!(1).approx_eq(&json!(1.5), &opts));
+
+        opts.integer_equals_float = false;
+        assert!(!json!(1).approx_eq(&json!(1.0), &opts));
+        assert!(json!(1).approx_eq(&json!(1), &opts));
+    }
+
+    #[test]
+    fn containers_compare_member_by_member() {
+        let opts = ApproxOptions {
+            absolute_epsilon: 0.01,
+            relative_epsilon: 0.0,
+            integer_equals_float: true,
+        };
+
+        assert!(
+            json!({"a": [1.001, 2], "b": "x"}).approx_eq(&json!({"b": "x", "a": [1, 2.0]}), &opts)
+        );
+        assert!(!json!({"a": [1.1]}).approx_eq(&json!({"a": [1]}), &opts));
+        assert!(!json!([1, 2]).approx_eq(&json!([1]), &opts));
+        assert!(!json!({"a": 1}).approx_eq(&json!({"b": 1}), &opts));
+        assert!(!json!({"a": 1}).approx_eq(&json!({"a": 1, "b": 1}), &opts));
+        assert!(!json!("1").approx_eq(&json!(1), &opts));
+        assert!(json!(null).approx_eq(&json!(null), &opts));
+    }
+}
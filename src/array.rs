@@ -0,0 +1,381 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::hash::contains_nan;
+use crate::json::{JsonValue, WrongKindError};
+
+impl JsonValue {
+    fn as_array_mut_or_err(&mut self) -> Result<&mut Vec<JsonValue>, WrongKindError> {
+        match self {
+            JsonValue::Array(array) => {
+                return Ok(array);
+            }
+            _ => {
+                return Err(self.wrong_kind("array"));
+            }
+        }
+    }
+
+    pub fn push(&mut self, value: impl Into<JsonValue>) -> Result<(), WrongKindError> {
+        self.as_array_mut_or_err()?.push(value.into());
+        return Ok(());
+    }
+
+    pub fn pop(&mut self) -> Result<Option<JsonValue>, WrongKindError> {
+        return Ok(self.as_array_mut_or_err()?.pop());
+    }
+
+    /// Inserts `value` at `index`, shifting later elements. An index past the
+    /// end appends instead of panicking. Named `insert_at` so that `insert`
+    /// is free for object members.
+    pub fn insert_at(
+        &mut self,
+        index: usize,
+        value: impl Into<JsonValue>,
+    ) -> Result<(), WrongKindError> {
+        let array = self.as_array_mut_or_err()?;
+        let index = index.min(array.len());
+        array.insert(index, value.into());
+
+        return Ok(());
+    }
+
+    /// Removes and returns the element at `index`, or `None` if the index is
+    /// out of range.
+    pub fn remove_at(&mut self, index: usize) -> Result<Option<JsonValue>, WrongKindError> {
+        let array = self.as_array_mut_or_err()?;
+
+        if index >= array.len() {
+            return Ok(None);
+        }
+
+        return Ok(Some(array.remove(index)));
+    }
+
+    pub fn clear(&mut self) -> Result<(), WrongKindError> {
+        self.as_array_mut_or_err()?.clear();
+        return Ok(());
+    }
+}
+
+// Array counterparts of the object views: named `elements` because `iter`
+// already yields object members. Non-arrays yield nothing.
+impl JsonValue {
+    pub fn elements(&self) -> impl Iterator<Item = &JsonValue> {
+        let array = match self {
+            JsonValue::Array(array) => Some(array),
+            _ => None,
+        };
+
+        return array.into_iter().flat_map(|array| array.iter());
+    }
+
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut JsonValue> {
+        let array = match self {
+            JsonValue::Array(array) => Some(array),
+            _ => None,
+        };
+
+        return array.into_iter().flat_map(|array| array.iter_mut());
+    }
+}
+
+// Writes a string that is equal for two values exactly when they are equal
+// under `==`, with object members in sorted key order. Floats are written
+// with their full round-trip representation and tagged apart from integers,
+// and `-0.0` is written as `0.0` since the two compare equal. NaNs give
+// matching keys, so values holding one must not be compared this way.
+fn write_canonical_key(value: &JsonValue, key: &mut String) {
+    match value {
+        JsonValue::Null => key.push('n'),
+        JsonValue::Boolean(val) => key.push(if *val { 't' } else { 'f' }),
+        JsonValue::Integer(val) => key.push_str(&format!("i{};", val)),
+        JsonValue::Float(val) if *val == 0.0 => key.push_str("d0.0;"),
+        JsonValue::Float(val) => key.push_str(&format!("d{:?};", val)),
+        JsonValue::String(val) => key.push_str(&format!("s{}:{}", val.len(), val)),
+        JsonValue::Array(array) => {
+            key.push_str(&format!("a{}[", array.len()));
+            for element in array {
+                write_canonical_key(element, key);
+            }
+            key.push(']');
+        }
+        JsonValue::Object(object) => {
+            let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+
+            key.push_str(&format!("o{}{{", members.len()));
+            for (member_key, member) in members {
+                key.push_str(&format!("{}:{}", member_key.len(), member_key));
+                write_canonical_key(member, key);
+            }
+            key.push('}');
+        }
+    }
+}
+
+impl JsonValue {
+    /// Removes consecutive repeated elements, like `Vec::dedup`. Elements
+    /// are compared with `==`, as in `unique_array`. Returns the number of
+    /// elements removed.
+    pub fn dedup_array(&mut self) -> Result<usize, WrongKindError> {
+        let array = self.as_array_mut_or_err()?;
+        let before = array.len();
+        array.dedup();
+
+        return Ok(before - array.len());
+    }
+
+    /// Removes every element equal to an earlier one, keeping the first
+    /// occurrence. Elements are keyed by a canonical string form, so this
+    /// runs in time linear in the size of the array's contents rather than
+    /// comparing every pair. Returns the number of elements removed.
+    ///
+    /// Equality is `==`'s: `-0.0` equals `0.0`, integers never equal
+    /// floats, and an element holding a NaN equals nothing, so it's always
+    /// kept.
+    pub fn unique_array(&mut self) -> Result<usize, WrongKindError> {
+        let array = self.as_array_mut_or_err()?;
+        let before = array.len();
+        let mut seen = HashSet::new();
+
+        array.retain(|element| {
+            if contains_nan(element) {
+                return true;
+            }
+
+            let mut key = String::new();
+            write_canonical_key(element, &mut key);
+            return seen.insert(key);
+        });
+
+        return Ok(before - array.len());
+    }
+}
+
+// Copies of parts of an array, for paging through it. Non-arrays give
+// `None`.
+impl JsonValue {
+    fn as_slice(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// A new array of the elements in `range`. Ends past the last element
+    /// are clamped to it, and a range that's empty after that gives an
+    /// empty array rather than panicking.
+    pub fn array_slice(&self, range: Range<usize>) -> Option<JsonValue> {
+        let array = self.as_slice()?;
+        let end = range.end.min(array.len());
+        let start = range.start.min(end);
+
+        return Some(JsonValue::Array(array[start..end].to_vec()));
+    }
+
+    /// Splits the array into arrays of `size` consecutive elements, the last
+    /// holding whatever remains. An empty array gives no chunks, and a
+    /// `size` of 0 gives `None`.
+    pub fn array_chunks(&self, size: usize) -> Option<Vec<JsonValue>> {
+        let array = self.as_slice()?;
+        if size == 0 {
+            return None;
+        }
+
+        return Some(
+            array
+                .chunks(size)
+                .map(|chunk| JsonValue::Array(chunk.to_vec()))
+                .collect(),
+        );
+    }
+
+    /// Every run of `size` consecutive elements as an array, overlapping
+    /// like `slice::windows`. There are none if the array is shorter than
+    /// `size`, and a `size` of 0 gives `None`.
+    pub fn array_windows(&self, size: usize) -> Option<Vec<JsonValue>> {
+        let array = self.as_slice()?;
+        if size == 0 {
+            return None;
+        }
+
+        return Some(
+            array
+                .windows(size)
+                .map(|window| JsonValue::Array(window.to_vec()))
+                .collect(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{JsonValue, WrongKindError};
+
+    fn not_an_array(found: &'static str) -> WrongKindError {
+        return WrongKindError {
+            expected: "array",
+            found,
+        };
+    }
+
+    #[test]
+    fn pushes_pops_inserts_and_removes() {
+        let mut array = json!([1]);
+
+        array.push(2).unwrap();
+        array.push("three").unwrap();
+        array.insert_at(0, 0).unwrap();
+        array.insert_at(99, json!([4])).unwrap();
+        assert_eq!(array, json!([0, 1, 2, "three", [4]]));
+
+        assert_eq!(array.remove_at(3), Ok(Some(json!("three"))));
+        assert_eq!(array.remove_at(4), Ok(None));
+        assert_eq!(array.pop(), Ok(Some(json!([4]))));
+        assert_eq!(array, json!([0, 1, 2]));
+
+        array.clear().unwrap();
+        assert_eq!(array.pop(), Ok(None));
+        assert_eq!(array, json!([]));
+    }
+
+    #[test]
+    fn non_arrays_are_errors_and_left_alone() {
+        let mut object = json!({"a": 1});
+
+        assert_eq!(object.push(1), Err(not_an_array("object")));
+        assert_eq!(object.pop(), Err(not_an_array("object")));
+        assert_eq!(object.insert_at(0, 1), Err(not_an_array("object")));
+        assert_eq!(object.remove_at(0), Err(not_an_array("object")));
+        assert_eq!(json!(null).clear(), Err(not_an_array("null")));
+        assert_eq!(object, json!({"a": 1}));
+        assert_eq!(
+            not_an_array("string").to_string(),
+            "expected array, found string"
+        );
+    }
+
+    #[test]
+    fn elements_yield_array_elements_only() {
+        let mut array = json!([1, "two", [3]]);
+
+        let elements: Vec<&JsonValue> = array.elements().collect();
+        assert_eq!(elements, [&json!(1), &json!("two"), &json!([3])]);
+
+        for element in array.elements_mut() {
+            *element = json!(0);
+        }
+        assert_eq!(array, json!([0, 0, 0]));
+
+        assert_eq!(json!({"a": 1}).elements().count(), 0);
+        assert_eq!(json!("abc").elements_mut().count(), 0);
+    }
+
+    #[test]
+    fn dedup_removes_consecutive_repeats_only() {
+        let mut array = json!([1, 1, {"a": 1}, {"a": 1}, 2, 1, 1.0]);
+
+        assert_eq!(array.dedup_array(), Ok(2));
+        assert_eq!(array, json!([1, {"a": 1}, 2, 1, 1.0]));
+        assert_eq!(json!("a").dedup_array(), Err(not_an_array("string")));
+    }
+
+    #[test]
+    fn unique_keeps_first_occurrences_of_nested_duplicates() {
+        let mut array = json!([
+            {"a": [1, {"b": null}], "c": "x"},
+            2,
+            {"c": "x", "a": [1, {"b": null}]},
+            [2],
+            2,
+            {"a": [1, {"b": false}], "c": "x"},
+            [2]
+        ]);
+
+        assert_eq!(array.unique_array(), Ok(3));
+        assert_eq!(
+            array,
+            json!([{"a": [1, {"b": null}], "c": "x"}, 2, [2], {"a": [1, {"b": false}], "c": "x"}])
+        );
+        assert_eq!(json!({}).unique_array(), Err(not_an_array("object")));
+    }
+
+    #[test]
+    fn unique_keeps_near_equal_floats_and_distinct_kinds_apart() {
+        let mut array = json!([0.1 + 0.2, 0.3, 1, 1.0, "1", [1], 0.3, "a:", "a", ":"]);
+
+        assert_eq!(array.unique_array(), Ok(1));
+        assert_eq!(
+            array,
+            json!([0.1 + 0.2, 0.3, 1, 1.0, "1", [1], "a:", "a", ":"])
+        );
+    }
+
+    #[test]
+    fn dedup_and_unique_agree_on_zeroes_and_nans() {
+        let mut deduped = json!([0.0, -0.0, 1, 1.0, [-0.0], [0.0], f64::NAN, f64::NAN]);
+        let mut uniqued = deduped.clone();
+
+        assert_eq!(deduped.dedup_array(), Ok(2));
+        assert_eq!(uniqued.unique_array(), Ok(2));
+        // NaN != NaN, so the results are compared through `Debug`.
+        let expected =
+            "[Float(0.0), Integer(1), Float(1.0), Array([Float(-0.0)]), Float(NaN), Float(NaN)]";
+        assert_eq!(
+            format!("{:?}", deduped.elements().collect::<Vec<_>>()),
+            expected
+        );
+        assert_eq!(
+            format!("{:?}", uniqued.elements().collect::<Vec<_>>()),
+            expected
+        );
+
+        let mut array = json!([-0.0, {"a": [f64::NAN]}, 0.0, {"a": [f64::NAN]}, {"a": [0.0]}]);
+        assert_eq!(array.unique_array(), Ok(1));
+        assert_eq!(array.len(), Some(4));
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn slices_clamp_out_of_range_ends() {
+        let array = json!([0, 1, 2, 3, 4]);
+
+        assert_eq!(array.array_slice(1..3), Some(json!([1, 2])));
+        assert_eq!(array.array_slice(3..99), Some(json!([3, 4])));
+        assert_eq!(array.array_slice(7..9), Some(json!([])));
+        assert_eq!(array.array_slice(4..2), Some(json!([])));
+        assert_eq!(json!([]).array_slice(0..1), Some(json!([])));
+        assert_eq!(json!({"a": 1}).array_slice(0..1), None);
+    }
+
+    #[test]
+    fn chunks_split_exactly_or_with_a_remainder() {
+        let six = json!([1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            six.array_chunks(3),
+            Some(vec![json!([1, 2, 3]), json!([4, 5, 6])])
+        );
+        assert_eq!(
+            six.array_chunks(4),
+            Some(vec![json!([1, 2, 3, 4]), json!([5, 6])])
+        );
+        assert_eq!(six.array_chunks(10), Some(vec![six.clone()]));
+        assert_eq!(six.array_chunks(0), None);
+
+        assert_eq!(json!([]).array_chunks(2), Some(vec![]));
+        assert_eq!(json!("abc").array_chunks(2), None);
+    }
+
+    #[test]
+    fn windows_overlap() {
+        let array = json!(["a", "b", "c"]);
+        assert_eq!(
+            array.array_windows(2),
+            Some(vec![json!(["a", "b"]), json!(["b", "c"])])
+        );
+        assert_eq!(array.array_windows(4), Some(vec![]));
+        assert_eq!(array.array_windows(0), None);
+        assert_eq!(JsonValue::Null.array_windows(1), None);
+    }
+}
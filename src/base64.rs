@@ -0,0 +1,181 @@
+use crate::json::JsonValue;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let group = (b0 << 16) | (b1 << 8) | b2;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                output.push(STANDARD[index as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    return output;
+}
+
+fn sextet(c: u8, url_safe: &mut Option<bool>) -> Option<u32> {
+    let (value, is_url_safe) = match c {
+        b'A'..=b'Z' => (c - b'A', None),
+        b'a'..=b'z' => (c - b'a' + 26, None),
+        b'0'..=b'9' => (c - b'0' + 52, None),
+        b'+' => (62, Some(false)),
+        b'/' => (63, Some(false)),
+        b'-' => (62, Some(true)),
+        b'_' => (63, Some(true)),
+        _ => return None,
+    };
+
+    // The two alphabets can't be mixed within one string.
+    if let Some(is_url_safe) = is_url_safe {
+        if *url_safe.get_or_insert(is_url_safe) != is_url_safe {
+            return None;
+        }
+    }
+
+    return Some(value as u32);
+}
+
+fn decode(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+
+    let data = match bytes.iter().position(|&c| c == b'=') {
+        Some(padding_start) => {
+            let padding = &bytes[padding_start..];
+            if !bytes.len().is_multiple_of(4)
+                || padding.len() > 2
+                || padding.iter().any(|&c| c != b'=')
+            {
+                return None;
+            }
+            &bytes[..padding_start]
+        }
+        None => bytes,
+    };
+
+    if data.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut url_safe = None;
+    let mut output = Vec::with_capacity(data.len() * 3 / 4);
+
+    for chunk in data.chunks(4) {
+        let mut group = 0;
+        for (i, &c) in chunk.iter().enumerate() {
+            group |= sextet(c, &mut url_safe)? << (18 - 6 * i);
+        }
+
+        let decoded = [(group >> 16) as u8, (group >> 8) as u8, group as u8];
+        let length = chunk.len() - 1;
+
+        // Bits past the last whole byte must be zero, so every byte string
+        // has exactly one encoding per alphabet.
+        if decoded[length..].iter().any(|&byte| byte != 0) {
+            return None;
+        }
+
+        output.extend_from_slice(&decoded[..length]);
+    }
+
+    return Some(output);
+}
+
+impl JsonValue {
+    /// Decodes a base64 string using either the standard (`+/`) or the
+    /// URL-safe (`-_`) alphabet, with or without `=` padding. Returns `None`
+    /// for non-strings and for anything that isn't exactly valid base64:
+    /// whitespace, mixed alphabets, wrong padding or non-zero trailing bits.
+    pub fn as_base64_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            JsonValue::String(text) => decode(text),
+            _ => None,
+        }
+    }
+
+    /// Encodes `bytes` as a padded base64 string in the standard alphabet.
+    pub fn from_bytes_base64(bytes: &[u8]) -> JsonValue {
+        return JsonValue::String(encode(bytes));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::JsonValue;
+
+    fn decode(text: &str) -> Option<Vec<u8>> {
+        return JsonValue::from(text).as_base64_bytes();
+    }
+
+    #[test]
+    fn encodes_the_rfc_4648_vectors() {
+        for (bytes, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(
+                JsonValue::from_bytes_base64(bytes.as_bytes()),
+                json!(encoded)
+            );
+            assert_eq!(decode(encoded), Some(bytes.as_bytes().to_vec()));
+        }
+    }
+
+    #[test]
+    fn round_trips_random_bytes_in_both_alphabets() {
+        let mut state: u32 = 0x2545_f491;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            return state as u8;
+        };
+
+        for len in 0..=10 {
+            for _ in 0..50 {
+                let bytes: Vec<u8> = (0..len).map(|_| next()).collect();
+                let encoded = match JsonValue::from_bytes_base64(&bytes) {
+                    JsonValue::String(encoded) => encoded,
+                    other => panic!("expected a string, found {:?}", other),
+                };
+                let url_safe = encoded.replace('+', "-").replace('/', "_");
+                let unpadded = url_safe.trim_end_matches('=');
+
+                assert_eq!(decode(&encoded).as_ref(), Some(&bytes), "{}", encoded);
+                assert_eq!(decode(&url_safe).as_ref(), Some(&bytes), "{}", url_safe);
+                assert_eq!(decode(unpadded).as_ref(), Some(&bytes), "{}", unpadded);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_anything_but_exact_base64() {
+        for text in [
+            "Zm9v!", "Zm 9v", "Zm9v\n", "Z", "Zg=", "Zg===", "Z===", "Zm=v", "=Zm9", "Zh==",
+            "Zm9=", "+/-_",
+        ] {
+            assert_eq!(decode(text), None, "{}", text);
+        }
+
+        assert_eq!(decode("+/+/"), Some(vec![0xfb, 0xff, 0xbf]));
+        assert_eq!(decode("-_-_"), Some(vec![0xfb, 0xff, 0xbf]));
+        assert_eq!(json!(12).as_base64_bytes(), None);
+        assert_eq!(json!(["Zg=="]).as_base64_bytes(), None);
+    }
+}
@@ -0,0 +1,710 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::JsonValue;
+use crate::parser::MAX_DEPTH;
+use crate::path::escape_pointer_token;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BsonError {
+    /// JSON Pointer to the value or element that failed.
+    pub pointer: String,
+    /// Set for errors in BSON input.
+    pub offset: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for BsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(
+                f,
+                "[BSON error at byte {} ('{}')]: {}",
+                offset, self.pointer, self.message
+            ),
+            None => write!(f, "[BSON error at '{}']: {}", self.pointer, self.message),
+        }
+    }
+}
+
+impl std::error::Error for BsonError {}
+
+fn encode_error(pointer: &str, message: &str) -> BsonError {
+    BsonError {
+        pointer: pointer.to_string(),
+        offset: None,
+        message: message.to_string(),
+    }
+}
+
+const DOUBLE: u8 = 0x01;
+const STRING: u8 = 0x02;
+const DOCUMENT: u8 = 0x03;
+const ARRAY: u8 = 0x04;
+const BINARY: u8 = 0x05;
+const UNDEFINED: u8 = 0x06;
+const OBJECT_ID: u8 = 0x07;
+const BOOLEAN: u8 = 0x08;
+const DATE_TIME: u8 = 0x09;
+const NULL: u8 = 0x0a;
+const REGEX: u8 = 0x0b;
+const CODE: u8 = 0x0d;
+const SYMBOL: u8 = 0x0e;
+const INT32: u8 = 0x10;
+const TIMESTAMP: u8 = 0x11;
+const INT64: u8 = 0x12;
+const MIN_KEY: u8 = 0xff;
+const MAX_KEY: u8 = 0x7f;
+
+fn single_member<'a>(object: &'a HashMap<String, JsonValue>, key: &str) -> Option<&'a JsonValue> {
+    if object.len() != 1 {
+        return None;
+    }
+
+    return object.get(key);
+}
+
+fn string_member<'a>(object: &'a HashMap<String, JsonValue>, key: &str) -> Option<&'a str> {
+    match object.get(key) {
+        Some(JsonValue::String(value)) => Some(value),
+        _ => None,
+    }
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+
+    return (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect();
+}
+
+fn write_cstring(out: &mut Vec<u8>, text: &str, pointer: &str) -> Result<(), BsonError> {
+    if text.contains('\0') {
+        return Err(encode_error(
+            pointer,
+            "Keys and regular expressions can't contain NUL",
+        ));
+    }
+
+    out.extend_from_slice(text.as_bytes());
+    out.push(0);
+    return Ok(());
+}
+
+fn write_string(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(&(text.len() as i32 + 1).to_le_bytes());
+    out.extend_from_slice(text.as_bytes());
+    out.push(0);
+}
+
+// Recognizes the canonical Extended JSON wrappers `from_bson_document`
+// produces, so BSON-only types survive a round trip. Returns the element
+// type and writes the value's bytes.
+fn write_extended(out: &mut Vec<u8>, object: &HashMap<String, JsonValue>) -> Option<u8> {
+    if let Some(JsonValue::String(hex)) = single_member(object, "$oid") {
+        let bytes = parse_hex(hex).filter(|bytes| bytes.len() == 12)?;
+        out.extend_from_slice(&bytes);
+        return Some(OBJECT_ID);
+    }
+
+    if let Some(JsonValue::Object(date)) = single_member(object, "$date") {
+        let millis = single_member(date, "$numberLong").and_then(|millis| match millis {
+            JsonValue::String(millis) => millis.parse::<i64>().ok(),
+            _ => None,
+        })?;
+        out.extend_from_slice(&millis.to_le_bytes());
+        return Some(DATE_TIME);
+    }
+
+    if let Some(JsonValue::Object(binary)) = single_member(object, "$binary") {
+        if binary.len() != 2 {
+            return None;
+        }
+        let bytes = binary.get("base64")?.as_base64_bytes()?;
+        let subtype = parse_hex(string_member(binary, "subType")?).filter(|s| s.len() == 1)?;
+        out.extend_from_slice(&(bytes.len() as i32).to_le_bytes());
+        out.push(subtype[0]);
+        out.extend_from_slice(&bytes);
+        return Some(BINARY);
+    }
+
+    if let Some(JsonValue::Object(timestamp)) = single_member(object, "$timestamp") {
+        let part = |key: &str| match timestamp.get(key) {
+            Some(JsonValue::Integer(value)) => u32::try_from(*value).ok(),
+            _ => None,
+        };
+        if timestamp.len() != 2 {
+            return None;
+        }
+        let (time, increment) = (part("t")?, part("i")?);
+        out.extend_from_slice(&((time as u64) << 32 | increment as u64).to_le_bytes());
+        return Some(TIMESTAMP);
+    }
+
+    if let Some(JsonValue::Object(regex)) = single_member(object, "$regularExpression") {
+        let pattern = string_member(regex, "pattern")?;
+        let options = string_member(regex, "options")?;
+        if regex.len() != 2 || pattern.contains('\0') || options.contains('\0') {
+            return None;
+        }
+        for text in [pattern, options] {
+            out.extend_from_slice(text.as_bytes());
+            out.push(0);
+        }
+        return Some(REGEX);
+    }
+
+    if let Some(JsonValue::String(code)) = single_member(object, "$code") {
+        write_string(out, code);
+        return Some(CODE);
+    }
+    if let Some(JsonValue::String(symbol)) = single_member(object, "$symbol") {
+        write_string(out, symbol);
+        return Some(SYMBOL);
+    }
+
+    match object.iter().next() {
+        Some((key, JsonValue::Integer(1))) if object.len() == 1 && key == "$minKey" => {
+            return Some(MIN_KEY);
+        }
+        Some((key, JsonValue::Integer(1))) if object.len() == 1 && key == "$maxKey" => {
+            return Some(MAX_KEY);
+        }
+        Some((key, JsonValue::Boolean(true))) if object.len() == 1 && key == "$undefined" => {
+            return Some(UNDEFINED);
+        }
+        _ => {
+            return None;
+        }
+    }
+}
+
+// Writes an element's value and returns its type.
+fn write_element_value(
+    out: &mut Vec<u8>,
+    value: &JsonValue,
+    pointer: &mut String,
+) -> Result<u8, BsonError> {
+    match value {
+        JsonValue::Null => {
+            return Ok(NULL);
+        }
+        JsonValue::Boolean(value) => {
+            out.push(*value as u8);
+            return Ok(BOOLEAN);
+        }
+        JsonValue::Integer(value) => {
+            out.extend_from_slice(&(*value as i64).to_le_bytes());
+            return Ok(INT64);
+        }
+        JsonValue::Float(value) => {
+            out.extend_from_slice(&value.to_le_bytes());
+            return Ok(DOUBLE);
+        }
+        JsonValue::String(value) => {
+            write_string(out, value);
+            return Ok(STRING);
+        }
+        JsonValue::Array(array) => {
+            write_array(out, array, pointer)?;
+            return Ok(ARRAY);
+        }
+        JsonValue::Object(object) => {
+            if let Some(kind) = write_extended(out, object) {
+                return Ok(kind);
+            }
+
+            write_document(out, object, pointer)?;
+            return Ok(DOCUMENT);
+        }
+    }
+}
+
+fn write_elements<'a>(
+    out: &mut Vec<u8>,
+    elements: impl Iterator<Item = (String, &'a JsonValue)>,
+    pointer: &mut String,
+) -> Result<(), BsonError> {
+    let start = out.len();
+    out.extend_from_slice(&[0; 4]);
+
+    for (key, value) in elements {
+        let len = pointer.len();
+        pointer.push('/');
+        pointer.push_str(&escape_pointer_token(&key));
+
+        // The type byte comes first but is only known once the value is
+        // written, so reserve it and fill it in afterwards.
+        let type_index = out.len();
+        out.push(0);
+        write_cstring(out, &key, pointer)?;
+        out[type_index] = write_element_value(out, value, pointer)?;
+
+        pointer.truncate(len);
+    }
+
+    out.push(0);
+
+    let length = i32::try_from(out.len() - start)
+        .map_err(|_| encode_error(pointer, "Document is larger than BSON allows"))?;
+    out[start..start + 4].copy_from_slice(&length.to_le_bytes());
+
+    return Ok(());
+}
+
+fn write_document(
+    out: &mut Vec<u8>,
+    object: &HashMap<String, JsonValue>,
+    pointer: &mut String,
+) -> Result<(), BsonError> {
+    let mut keys: Vec<&String> = object.keys().collect();
+    keys.sort();
+
+    return write_elements(
+        out,
+        keys.into_iter().map(|key| (key.clone(), &object[key])),
+        pointer,
+    );
+}
+
+fn write_array(
+    out: &mut Vec<u8>,
+    array: &[JsonValue],
+    pointer: &mut String,
+) -> Result<(), BsonError> {
+    return write_elements(
+        out,
+        array
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (index.to_string(), element)),
+        pointer,
+    );
+}
+
+/// Encodes an object as a BSON document with its keys sorted. Integers are
+/// written as int64 and floats as doubles. A top-level array becomes a
+/// document keyed `"0"`, `"1"` and so on, as BSON arrays are. Objects in the
+/// canonical Extended JSON forms `from_bson_document` produces, such as
+/// `{"$oid": "..."}`, are written as the BSON types they stand for.
+pub fn to_bson_document(value: &JsonValue) -> Result<Vec<u8>, BsonError> {
+    let mut output = Vec::new();
+
+    match value {
+        JsonValue::Object(object) => write_document(&mut output, object, &mut String::new())?,
+        JsonValue::Array(array) => write_array(&mut output, array, &mut String::new())?,
+        _ => {
+            return Err(encode_error(
+                "",
+                &format!("Expected an object or array, found {}", value.kind_name()),
+            ));
+        }
+    }
+
+    return Ok(output);
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    pointer: String,
+    depth: usize,
+}
+
+fn wrap(key: &str, value: JsonValue) -> JsonValue {
+    let mut object = HashMap::new();
+    object.insert(key.to_string(), value);
+
+    return JsonValue::Object(object);
+}
+
+impl<'a> Decoder<'a> {
+    fn error(&self, message: &str, offset: usize) -> BsonError {
+        return BsonError {
+            pointer: self.pointer.clone(),
+            offset: Some(offset),
+            message: message.to_string(),
+        };
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], BsonError> {
+        if self.bytes.len() - self.pos < count {
+            return Err(self.error("Unexpected end of input", self.bytes.len()));
+        }
+
+        let slice = &self.bytes[self.pos..self.pos + count];
+        self.pos += count;
+        return Ok(slice);
+    }
+
+    fn i32(&mut self) -> Result<i32, BsonError> {
+        return Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()));
+    }
+
+    fn i64(&mut self) -> Result<i64, BsonError> {
+        return Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()));
+    }
+
+    fn utf8(&self, bytes: &[u8], offset: usize) -> Result<String, BsonError> {
+        return String::from_utf8(bytes.to_vec())
+            .map_err(|_| self.error("Invalid UTF-8 in string", offset));
+    }
+
+    fn cstring(&mut self) -> Result<String, BsonError> {
+        let start = self.pos;
+        let length = match self.bytes[start..].iter().position(|&byte| byte == 0) {
+            Some(length) => length,
+            None => return Err(self.error("Unterminated C string", start)),
+        };
+
+        let bytes = self.take(length + 1)?;
+        return self.utf8(&bytes[..length], start);
+    }
+
+    fn string(&mut self) -> Result<String, BsonError> {
+        let start = self.pos;
+        let length = self.i32()?;
+        if length < 1 {
+            return Err(self.error("Invalid string length", start));
+        }
+
+        let bytes = self.take(length as usize)?;
+        if bytes[bytes.len() - 1] != 0 {
+            return Err(self.error("String is missing its NUL terminator", start));
+        }
+        return self.utf8(&bytes[..bytes.len() - 1], start);
+    }
+
+    // Reads a document's elements as key/value pairs in order.
+    fn elements(&mut self) -> Result<Vec<(String, JsonValue)>, BsonError> {
+        let start = self.pos;
+        let length = self.i32()?;
+        if length < 5 || (length as usize) > self.bytes.len() - start {
+            return Err(self.error("Invalid document length", start));
+        }
+        let end = start + length as usize;
+
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            let message = format!("Nested more than {} levels deep", MAX_DEPTH);
+            return Err(self.error(&message, start));
+        }
+
+        let mut elements = Vec::new();
+        loop {
+            let type_offset = self.pos;
+            if type_offset >= end {
+                return Err(self.error("Document is missing its terminator", type_offset));
+            }
+
+            let kind = self.take(1)?[0];
+            if kind == 0 {
+                break;
+            }
+
+            let key = self.cstring()?;
+            let len = self.pointer.len();
+            self.pointer.push('/');
+            self.pointer.push_str(&escape_pointer_token(&key));
+
+            let value = self.value(kind, type_offset)?;
+            elements.push((key, value));
+
+            self.pointer.truncate(len);
+        }
+
+        if self.pos != end {
+            return Err(self.error("Document length doesn't match its contents", start));
+        }
+
+        self.depth -= 1;
+        return Ok(elements);
+    }
+
+    fn document(&mut self) -> Result<JsonValue, BsonError> {
+        let start = self.pos;
+        let mut object = HashMap::new();
+
+        for (key, value) in self.elements()? {
+            if object.contains_key(&key) {
+                return Err(self.error(&format!("Duplicate key '{}'", key), start));
+            }
+            object.insert(key, value);
+        }
+
+        return Ok(JsonValue::Object(object));
+    }
+
+    fn value(&mut self, kind: u8, offset: usize) -> Result<JsonValue, BsonError> {
+        match kind {
+            DOUBLE => {
+                let bytes = self.take(8)?.try_into().unwrap();
+                return Ok(JsonValue::Float(f64::from_le_bytes(bytes)));
+            }
+            STRING => {
+                return Ok(JsonValue::String(self.string()?));
+            }
+            DOCUMENT => {
+                return self.document();
+            }
+            ARRAY => {
+                // Array keys are meant to be "0", "1", ...; only their order
+                // is used.
+                let elements = self.elements()?;
+                return Ok(JsonValue::Array(
+                    elements.into_iter().map(|(_, value)| value).collect(),
+                ));
+            }
+            BINARY => {
+                let length = self.i32()?;
+                if length < 0 {
+                    return Err(self.error("Invalid binary length", offset));
+                }
+                let subtype = self.take(1)?[0];
+                let bytes = self.take(length as usize)?;
+
+                let mut binary = HashMap::new();
+                binary.insert("base64".to_string(), JsonValue::from_bytes_base64(bytes));
+                binary.insert(
+                    "subType".to_string(),
+                    JsonValue::String(format!("{:02x}", subtype)),
+                );
+                return Ok(wrap("$binary", JsonValue::Object(binary)));
+            }
+            UNDEFINED => {
+                return Ok(wrap("$undefined", JsonValue::Boolean(true)));
+            }
+            OBJECT_ID => {
+                let bytes = self.take(12)?;
+                let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+                return Ok(wrap("$oid", JsonValue::String(hex)));
+            }
+            BOOLEAN => match self.take(1)?[0] {
+                0 => {
+                    return Ok(JsonValue::Boolean(false));
+                }
+                1 => {
+                    return Ok(JsonValue::Boolean(true));
+                }
+                _ => {
+                    return Err(self.error("Invalid boolean", offset));
+                }
+            },
+            DATE_TIME => {
+                let millis = self.i64()?;
+                let date = wrap("$numberLong", JsonValue::String(millis.to_string()));
+                return Ok(wrap("$date", date));
+            }
+            NULL => {
+                return Ok(JsonValue::Null);
+            }
+            REGEX => {
+                let mut regex = HashMap::new();
+                regex.insert("pattern".to_string(), JsonValue::String(self.cstring()?));
+                regex.insert("options".to_string(), JsonValue::String(self.cstring()?));
+                return Ok(wrap("$regularExpression", JsonValue::Object(regex)));
+            }
+            CODE => {
+                return Ok(wrap("$code", JsonValue::String(self.string()?)));
+            }
+            SYMBOL => {
+                return Ok(wrap("$symbol", JsonValue::String(self.string()?)));
+            }
+            INT32 => {
+                return Ok(JsonValue::Integer(self.i32()? as isize));
+            }
+            TIMESTAMP => {
+                let value = self.i64()? as u64;
+                let mut timestamp = HashMap::new();
+                timestamp.insert("t".to_string(), JsonValue::Integer((value >> 32) as isize));
+                timestamp.insert(
+                    "i".to_string(),
+                    JsonValue::Integer((value & 0xffff_ffff) as isize),
+                );
+                return Ok(wrap("$timestamp", JsonValue::Object(timestamp)));
+            }
+            INT64 => {
+                let value = self.i64()?;
+                match isize::try_from(value) {
+                    Ok(value) => return Ok(JsonValue::Integer(value)),
+                    Err(_) => return Ok(JsonValue::Float(value as f64)),
+                }
+            }
+            MIN_KEY => {
+                return Ok(wrap("$minKey", JsonValue::Integer(1)));
+            }
+            MAX_KEY => {
+                return Ok(wrap("$maxKey", JsonValue::Integer(1)));
+            }
+            _ => {
+                return Err(self.error(&format!("Unsupported BSON type 0x{:02x}", kind), offset));
+            }
+        }
+    }
+}
+
+/// Decodes a BSON document into an object. The JSON-compatible types map
+/// directly, with int32 and int64 both becoming integers. BSON-only types
+/// become canonical Extended JSON wrappers: `{"$oid": hex}`,
+/// `{"$date": {"$numberLong": millis}}`,
+/// `{"$binary": {"base64": ..., "subType": hex}}`,
+/// `{"$timestamp": {"t": ..., "i": ...}}`,
+/// `{"$regularExpression": {"pattern": ..., "options": ...}}`, `{"$code": ...}`,
+/// `{"$symbol": ...}`, `{"$minKey": 1}`, `{"$maxKey": 1}` and
+/// `{"$undefined": true}`. Decimal128, DBPointer and code with scope are
+/// errors, as are duplicate keys and trailing bytes.
+pub fn from_bson_document(bytes: &[u8]) -> Result<JsonValue, BsonError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        pointer: String::new(),
+        depth: 0,
+    };
+    let value = decoder.document()?;
+
+    if decoder.pos < bytes.len() {
+        return Err(decoder.error("Trailing bytes after document", decoder.pos));
+    }
+
+    return Ok(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bson::{from_bson_document, to_bson_document};
+    use crate::json;
+
+    // A user record as a MongoDB dump holds it: fields in insertion order,
+    // small numbers as int32, and an ObjectId and a date.
+    const DUMPED_USER: &str = "\
+        91000000075f6964005f1d7c3c9d1e8a3b4c5d6e7f026e616d65000400000061\
+        646100106167650024000000096a6f696e65640060ea3c8b7301000004746167\
+        73001d0000000230000600000061646d696e00023100040000006f7073000001\
+        73636f726500000000000000f83f127669736974730000f2052a010000000861\
+        637469766500010a6d616e616765720000";
+
+    fn hex(text: &str) -> Vec<u8> {
+        return (0..text.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&text[index..index + 2], 16).unwrap())
+            .collect();
+    }
+
+    fn error(text: &str) -> String {
+        return from_bson_document(&hex(text)).unwrap_err().to_string();
+    }
+
+    #[test]
+    fn spec_examples_decode_and_encode() {
+        let hello = "160000000268656c6c6f0006000000776f726c640000";
+        assert_eq!(
+            from_bson_document(&hex(hello)),
+            Ok(json!({"hello": "world"}))
+        );
+        assert_eq!(to_bson_document(&json!({"hello": "world"})), Ok(hex(hello)));
+
+        // 1986 is an int32 here, and comes back as an integer.
+        let awesome = "310000000442534f4e002600000002300008000000617765736f6d65000131003333333333331440103200c20700000000";
+        assert_eq!(
+            from_bson_document(&hex(awesome)),
+            Ok(json!({"BSON": ["awesome", 5.05, 1986]}))
+        );
+    }
+
+    #[test]
+    fn dumped_documents_decode_to_extended_json() {
+        let user = json!({
+            "_id": {"$oid": "5f1d7c3c9d1e8a3b4c5d6e7f"},
+            "name": "ada",
+            "age": 36,
+            "joined": {"$date": {"$numberLong": "1595768892000"}},
+            "tags": ["admin", "ops"],
+            "score": 1.5,
+            "visits": 5000000000_isize,
+            "active": true,
+            "manager": null
+        });
+
+        assert_eq!(from_bson_document(&hex(DUMPED_USER)), Ok(user.clone()));
+
+        // Encoding sorts the keys and widens int32 to int64, so the bytes
+        // differ but decode to the same document.
+        let encoded = to_bson_document(&user).unwrap();
+        assert_eq!(from_bson_document(&encoded), Ok(user));
+    }
+
+    #[test]
+    fn bson_only_types_round_trip_as_wrappers() {
+        let value = json!({
+            "oid": {"$oid": "000102030405060708090a0b"},
+            "date": {"$date": {"$numberLong": "-1"}},
+            "bin": {"$binary": {"base64": "AQID", "subType": "04"}},
+            "ts": {"$timestamp": {"t": 7, "i": 2}},
+            "re": {"$regularExpression": {"pattern": "^a.*", "options": "i"}},
+            "code": {"$code": "x = 1"},
+            "sym": {"$symbol": "s"},
+            "min": {"$minKey": 1},
+            "max": {"$maxKey": 1},
+            "undef": {"$undefined": true},
+            // Not quite a wrapper, so written as a plain document.
+            "fake": {"$oid": "xyz"},
+            "nested": [{"a": [1, [2.5]]}, {}]
+        });
+
+        let encoded = to_bson_document(&value).unwrap();
+        assert_eq!(from_bson_document(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn top_level_arrays_become_index_keyed_documents() {
+        let encoded = to_bson_document(&json!(["a", 1])).unwrap();
+
+        assert_eq!(
+            encoded,
+            hex("19000000023000020000006100123100010000000000000000")
+        );
+        assert_eq!(from_bson_document(&encoded), Ok(json!({"0": "a", "1": 1})));
+    }
+
+    #[test]
+    fn invalid_values_and_documents_are_rejected() {
+        assert_eq!(
+            to_bson_document(&json!("x")).unwrap_err().to_string(),
+            "[BSON error at '']: Expected an object or array, found string"
+        );
+        assert_eq!(
+            to_bson_document(&json!({"a": [{"b\u{0}": 1}]}))
+                .unwrap_err()
+                .to_string(),
+            "[BSON error at '/a/0/b\u{0}']: Keys and regular expressions can't contain NUL"
+        );
+
+        assert_eq!(
+            error("0400"),
+            "[BSON error at byte 2 ('')]: Unexpected end of input"
+        );
+        assert_eq!(
+            error("0300000000"),
+            "[BSON error at byte 0 ('')]: Invalid document length"
+        );
+        assert_eq!(
+            error("090000000861000200"),
+            "[BSON error at byte 4 ('/a')]: Invalid boolean"
+        );
+        assert_eq!(
+            error("0d000000136100000000000000"),
+            "[BSON error at byte 4 ('/a')]: Unsupported BSON type 0x13"
+        );
+        assert_eq!(
+            error("0b0000000a61000a610000"),
+            "[BSON error at byte 0 ('')]: Duplicate key 'a'"
+        );
+        assert_eq!(
+            error("050000000000"),
+            "[BSON error at byte 5 ('')]: Trailing bytes after document"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use crate::json::JsonValue;
+
+/// Assembles a `JsonValue::Object` one member at a time. Inserting a key
+/// that is already present replaces its value.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectBuilder {
+    members: HashMap<String, JsonValue>,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> ObjectBuilder {
+        return ObjectBuilder::default();
+    }
+
+    pub fn insert(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> ObjectBuilder {
+        self.members.insert(key.into(), value.into());
+        return self;
+    }
+
+    /// Inserts `value` only if it is `Some`, leaving the key absent
+    /// otherwise. Use `insert` with an `Option` to get `null` instead.
+    pub fn insert_if<T: Into<JsonValue>>(
+        self,
+        key: impl Into<String>,
+        value: Option<T>,
+    ) -> ObjectBuilder {
+        match value {
+            Some(value) => {
+                return self.insert(key, value);
+            }
+            None => {
+                return self;
+            }
+        }
+    }
+
+    pub fn insert_when(
+        self,
+        condition: bool,
+        key: impl Into<String>,
+        value: impl Into<JsonValue>,
+    ) -> ObjectBuilder {
+        if condition {
+            return self.insert(key, value);
+        }
+
+        return self;
+    }
+
+    pub fn nested_object(
+        self,
+        key: impl Into<String>,
+        f: impl FnOnce(ObjectBuilder) -> ObjectBuilder,
+    ) -> ObjectBuilder {
+        let object = f(ObjectBuilder::new()).build();
+        return self.insert(key, object);
+    }
+
+    pub fn nested_array(
+        self,
+        key: impl Into<String>,
+        f: impl FnOnce(ArrayBuilder) -> ArrayBuilder,
+    ) -> ObjectBuilder {
+        let array = f(ArrayBuilder::new()).build();
+        return self.insert(key, array);
+    }
+
+    pub fn build(self) -> JsonValue {
+        return JsonValue::Object(self.members);
+    }
+}
+
+/// Assembles a `JsonValue::Array` one element at a time.
+#[derive(Debug, Clone, Default)]
+pub struct ArrayBuilder {
+    elements: Vec<JsonValue>,
+}
+
+impl ArrayBuilder {
+    pub fn new() -> ArrayBuilder {
+        return ArrayBuilder::default();
+    }
+
+    pub fn push(mut self, value: impl Into<JsonValue>) -> ArrayBuilder {
+        self.elements.push(value.into());
+        return self;
+    }
+
+    /// Pushes `value` only if it is `Some`.
+    pub fn push_if<T: Into<JsonValue>>(self, value: Option<T>) -> ArrayBuilder {
+        match value {
+            Some(value) => {
+                return self.push(value);
+            }
+            None => {
+                return self;
+            }
+        }
+    }
+
+    pub fn push_when(self, condition: bool, value: impl Into<JsonValue>) -> ArrayBuilder {
+        if condition {
+            return self.push(value);
+        }
+
+        return self;
+    }
+
+    pub fn extend<T: Into<JsonValue>>(
+        mut self,
+        values: impl IntoIterator<Item = T>,
+    ) -> ArrayBuilder {
+        self.elements.extend(values.into_iter().map(Into::into));
+        return self;
+    }
+
+    pub fn nested_object(self, f: impl FnOnce(ObjectBuilder) -> ObjectBuilder) -> ArrayBuilder {
+        let object = f(ObjectBuilder::new()).build();
+        return self.push(object);
+    }
+
+    pub fn nested_array(self, f: impl FnOnce(ArrayBuilder) -> ArrayBuilder) -> ArrayBuilder {
+        let array = f(ArrayBuilder::new()).build();
+        return self.push(array);
+    }
+
+    pub fn build(self) -> JsonValue {
+        return JsonValue::Array(self.elements);
+    }
+}
+
+impl From<ObjectBuilder> for JsonValue {
+    fn from(builder: ObjectBuilder) -> JsonValue {
+        return builder.build();
+    }
+}
+
+impl From<ArrayBuilder> for JsonValue {
+    fn from(builder: ArrayBuilder) -> JsonValue {
+        return builder.build();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::{ArrayBuilder, ObjectBuilder};
+    use crate::json::{parse_from_str, JsonValue};
+
+    #[test]
+    fn builds_a_nested_document() {
+        let nickname: Option<&str> = None;
+        let email = Some("ada@example.com");
+        let admin = true;
+
+        let value = ObjectBuilder::new()
+            .insert("id", 7)
+            .insert("name", "Ada")
+            .insert_if("nickname", nickname)
+            .insert_if("email", email)
+            .insert_when(admin, "role", "admin")
+            .insert_when(!admin, "guest", true)
+            .insert("manager", None::<i32>)
+            .nested_object("settings", |settings| {
+                settings
+                    .insert("theme", "dark")
+                    .nested_array("shortcuts", |shortcuts| shortcuts.push("ctrl+k"))
+            })
+            .nested_array("orders", |orders| {
+                orders
+                    .nested_object(|order| order.insert("id", 1).insert("total", 9.5))
+                    .nested_array(|empty| empty)
+                    .push_if(Some(2))
+                    .push_if(None::<i32>)
+                    .push_when(false, 3)
+                    .extend([4, 5])
+            })
+            .build();
+
+        let expected = parse_from_str(
+            r#"{"id": 7, "name": "Ada", "email": "ada@example.com", "role": "admin",
+                "manager": null, "settings": {"theme": "dark", "shortcuts": ["ctrl+k"]},
+                "orders": [{"id": 1, "total": 9.5}, [], 2, 4, 5]}"#,
+        )
+        .unwrap();
+        assert_eq!(value, JsonValue::from(expected));
+    }
+
+    #[test]
+    fn builders_convert_and_replace_repeated_keys() {
+        let object: JsonValue = ObjectBuilder::new().insert("a", 1).insert("a", 2).into();
+        assert_eq!(object, crate::json!({"a": 2}));
+
+        let array: JsonValue = ArrayBuilder::new().push(ObjectBuilder::new()).into();
+        assert_eq!(array, crate::json!([{}]));
+        assert_eq!(ArrayBuilder::new().build(), crate::json!([]));
+    }
+}
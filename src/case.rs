@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use crate::json::JsonValue;
+use crate::path::escape_pointer_token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCase {
+    SnakeCase,
+    CamelCase,
+    PascalCase,
+    KebabCase,
+}
+
+fn is_convertible(key: &str) -> bool {
+    return !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+}
+
+// Splits on `_` and `-`, before an uppercase letter that follows a lowercase
+// letter or digit, and before the last capital of an acronym followed by a
+// lowercase letter, so `HTTPServerId` becomes `HTTP`, `Server`, `Id`.
+fn split_words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' {
+            if !word.is_empty() {
+                words.push(word);
+                word = String::new();
+            }
+            continue;
+        }
+
+        if c.is_ascii_uppercase() && !word.is_empty() {
+            let previous = chars[i - 1];
+            let next_is_lower = i + 1 < chars.len() && chars[i + 1].is_ascii_lowercase();
+
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower)
+            {
+                words.push(word);
+                word = String::new();
+            }
+        }
+
+        word.push(c);
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    return words;
+}
+
+fn capitalize(word: &str) -> String {
+    let lower = word.to_ascii_lowercase();
+    let mut chars = lower.chars();
+
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn convert_key(key: &str, style: KeyCase) -> String {
+    if !is_convertible(key) {
+        return key.to_string();
+    }
+
+    let words = split_words(key);
+
+    match style {
+        KeyCase::SnakeCase => words
+            .iter()
+            .map(|word| word.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        KeyCase::KebabCase => words
+            .iter()
+            .map(|word| word.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+            .join("-"),
+        KeyCase::PascalCase => words.iter().map(|word| capitalize(word)).collect(),
+        KeyCase::CamelCase => words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == 0 {
+                    word.to_ascii_lowercase()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+    }
+}
+
+fn rename_value(
+    value: &mut JsonValue,
+    style: KeyCase,
+    pointer: &mut String,
+    conflicts: &mut Vec<String>,
+) {
+    match value {
+        JsonValue::Object(object) => {
+            let mut keys: Vec<String> = object.keys().cloned().collect();
+            keys.sort();
+
+            // Keys already in the target style claim their name first, so a
+            // collision never moves a value that didn't need renaming.
+            let mut renamed: HashMap<String, JsonValue> = HashMap::with_capacity(keys.len());
+            let mut pending = Vec::new();
+
+            for key in keys {
+                let new_key = convert_key(&key, style);
+                let member = object.remove(&key).unwrap_or_default();
+
+                if new_key == key {
+                    renamed.insert(key, member);
+                } else {
+                    pending.push((key, new_key, member));
+                }
+            }
+
+            for (key, new_key, member) in pending {
+                if renamed.contains_key(&new_key) {
+                    conflicts.push(format!("{}/{}", pointer, escape_pointer_token(&new_key)));
+                    renamed.insert(key, member);
+                } else {
+                    renamed.insert(new_key, member);
+                }
+            }
+
+            *object = renamed;
+
+            for (key, member) in object.iter_mut() {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer_token(key));
+
+                rename_value(member, style, pointer, conflicts);
+
+                pointer.truncate(len);
+            }
+        }
+        JsonValue::Array(array) => {
+            for (index, element) in array.iter_mut().enumerate() {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+
+                rename_value(element, style, pointer, conflicts);
+
+                pointer.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl JsonValue {
+    /// Renames every object key at every depth to `style`. Keys containing
+    /// characters other than ASCII letters, digits, `_` and `-` are left as
+    /// they are. When a renamed key would collide with an existing one, it
+    /// keeps its original name and the pointer of the contested name is
+    /// returned.
+    pub fn rename_keys(&mut self, style: KeyCase) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        rename_value(self, style, &mut String::new(), &mut conflicts);
+
+        return conflicts;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::case::{convert_key, KeyCase};
+    use crate::json;
+
+    #[test]
+    fn converts_between_every_style() {
+        for (key, snake, camel, pascal, kebab) in [
+            ("user_id", "user_id", "userId", "UserId", "user-id"),
+            ("userId", "user_id", "userId", "UserId", "user-id"),
+            (
+                "HTTPServerId",
+                "http_server_id",
+                "httpServerId",
+                "HttpServerId",
+                "http-server-id",
+            ),
+            (
+                "ipv4-address",
+                "ipv4_address",
+                "ipv4Address",
+                "Ipv4Address",
+                "ipv4-address",
+            ),
+            (
+                "line2Total",
+                "line2_total",
+                "line2Total",
+                "Line2Total",
+                "line2-total",
+            ),
+            ("__private", "private", "private", "Private", "private"),
+        ] {
+            assert_eq!(convert_key(key, KeyCase::SnakeCase), snake, "{}", key);
+            assert_eq!(convert_key(key, KeyCase::CamelCase), camel, "{}", key);
+            assert_eq!(convert_key(key, KeyCase::PascalCase), pascal, "{}", key);
+            assert_eq!(convert_key(key, KeyCase::KebabCase), kebab, "{}", key);
+        }
+    }
+
+    #[test]
+    fn keys_with_other_characters_are_left_alone() {
+        for key in ["", "first name", "a.b", "café_au_lait", "$ref"] {
+            assert_eq!(convert_key(key, KeyCase::CamelCase), key);
+        }
+    }
+
+    #[test]
+    fn renames_an_api_payload_both_ways() {
+        let snake = json!({
+            "user_id": 7,
+            "display_name": "Ada",
+            "created_at": "2024-01-01",
+            "recent_orders": [
+                {"order_id": 1, "line_items": [{"unit_price": 2.5}]},
+                {"order_id": 2, "line_items": []}
+            ],
+            "metadata": {"x-request-id": "abc", "utm source": "mail"}
+        });
+        let camel = json!({
+            "userId": 7,
+            "displayName": "Ada",
+            "createdAt": "2024-01-01",
+            "recentOrders": [
+                {"orderId": 1, "lineItems": [{"unitPrice": 2.5}]},
+                {"orderId": 2, "lineItems": []}
+            ],
+            "metadata": {"xRequestId": "abc", "utm source": "mail"}
+        });
+
+        let mut value = snake.clone();
+        assert!(value.rename_keys(KeyCase::CamelCase).is_empty());
+        assert_eq!(value, camel);
+
+        assert!(value.rename_keys(KeyCase::SnakeCase).is_empty());
+        let mut expected = snake;
+        expected
+            .pointer_mut("/metadata")
+            .unwrap()
+            .rename_key("x-request-id", "x_request_id");
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn collisions_keep_the_original_key_and_are_reported() {
+        let mut value = json!({
+            "user_id": 1,
+            "userId": 2,
+            "items": [{"item~name": 0, "a_b": 3, "aB": 4}]
+        });
+
+        let mut conflicts = value.rename_keys(KeyCase::CamelCase);
+        assert_eq!(
+            value,
+            json!({
+                "user_id": 1,
+                "userId": 2,
+                "items": [{"item~name": 0, "a_b": 3, "aB": 4}]
+            })
+        );
+
+        conflicts.sort();
+        assert_eq!(conflicts, ["/items/0/aB", "/userId"]);
+    }
+}
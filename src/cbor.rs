@@ -0,0 +1,650 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::JsonValue;
+use crate::parser::MAX_DEPTH;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CborOptions {
+    /// Sorts map keys by their encoded bytes, as RFC 8949 section 4.2.1
+    /// requires for deterministic encoding. Everything else is always
+    /// written in preferred form: the shortest integer and length headers,
+    /// definite lengths, and the shortest float that keeps the value.
+    pub deterministic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CborError {
+    pub message: String,
+    /// Byte offset of the item that failed to decode.
+    pub offset: usize,
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[CBOR error at byte {}]: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for CborError {}
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+const BREAK: u8 = 0xff;
+
+fn write_header(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+
+    if argument < 24 {
+        out.push(major | argument as u8);
+    } else if argument <= u8::MAX as u64 {
+        out.push(major | 24);
+        out.push(argument as u8);
+    } else if argument <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(argument as u16).to_be_bytes());
+    } else if argument <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(argument as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&argument.to_be_bytes());
+    }
+}
+
+fn decode_half(bits: u16) -> f64 {
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as f64;
+
+    let magnitude = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2f64.powi(exponent as i32 - 25),
+    };
+
+    if bits & 0x8000 != 0 {
+        return -magnitude;
+    }
+    return magnitude;
+}
+
+// Returns the half-precision bits for `value` if it converts exactly.
+fn encode_half(value: f64) -> Option<u16> {
+    let single = value as f32;
+    if single as f64 != value {
+        return None;
+    }
+
+    let bits = single.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    let half = match exponent {
+        0 if mantissa == 0 => sign,
+        255 if mantissa == 0 => sign | 0x7c00,
+        0 | 255 => return None,
+        _ => {
+            let unbiased = exponent - 127;
+            if (-14..=15).contains(&unbiased) {
+                sign | ((unbiased + 15) as u16) << 10 | (mantissa >> 13) as u16
+            } else if (-24..-14).contains(&unbiased) {
+                let shift = -1 - unbiased;
+                sign | ((mantissa | 0x80_0000) >> shift) as u16
+            } else {
+                return None;
+            }
+        }
+    };
+
+    // Checking the round trip catches mantissa bits shifted out above.
+    if decode_half(half) == value {
+        return Some(half);
+    }
+    return None;
+}
+
+fn write_float(out: &mut Vec<u8>, value: f64) {
+    let simple = SIMPLE << 5;
+
+    // All NaNs share the canonical quiet NaN.
+    if value.is_nan() {
+        out.extend_from_slice(&[simple | 25, 0x7e, 0x00]);
+        return;
+    }
+
+    if let Some(half) = encode_half(value) {
+        out.push(simple | 25);
+        out.extend_from_slice(&half.to_be_bytes());
+    } else if (value as f32) as f64 == value {
+        out.push(simple | 26);
+        out.extend_from_slice(&(value as f32).to_be_bytes());
+    } else {
+        out.push(simple | 27);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn write_text(out: &mut Vec<u8>, text: &str) {
+    write_header(out, TEXT, text.len() as u64);
+    out.extend_from_slice(text.as_bytes());
+}
+
+fn write_value(out: &mut Vec<u8>, value: &JsonValue, options: &CborOptions) {
+    match value {
+        JsonValue::Null => out.push(SIMPLE << 5 | 22),
+        JsonValue::Boolean(false) => out.push(SIMPLE << 5 | 20),
+        JsonValue::Boolean(true) => out.push(SIMPLE << 5 | 21),
+        JsonValue::Integer(value) if *value >= 0 => write_header(out, UNSIGNED, *value as u64),
+        JsonValue::Integer(value) => write_header(out, NEGATIVE, !(*value as i64) as u64),
+        JsonValue::Float(value) => write_float(out, *value),
+        JsonValue::String(value) => write_text(out, value),
+        JsonValue::Array(array) => {
+            write_header(out, ARRAY, array.len() as u64);
+            for element in array {
+                write_value(out, element, options);
+            }
+        }
+        JsonValue::Object(object) => {
+            write_header(out, MAP, object.len() as u64);
+
+            let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+            if options.deterministic {
+                // For text keys, comparing encoded bytes means shorter keys
+                // first, then bytewise.
+                members.sort_by(|(a, _), (b, _)| {
+                    a.len().cmp(&b.len()).then(a.as_bytes().cmp(b.as_bytes()))
+                });
+            }
+
+            for (key, member) in members {
+                write_text(out, key);
+                write_value(out, member, options);
+            }
+        }
+    }
+}
+
+/// Encodes `value` as CBOR (RFC 8949). Objects become maps with text keys;
+/// integers use major types 0 and 1, and floats the shortest of half,
+/// single and double precision that keeps their value.
+pub fn to_cbor(value: &JsonValue, options: &CborOptions) -> Vec<u8> {
+    let mut output = Vec::new();
+    write_value(&mut output, value, options);
+
+    return output;
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+// A length or a marker for an indefinite-length item.
+enum Length {
+    Definite(u64),
+    Indefinite,
+}
+
+impl<'a> Decoder<'a> {
+    fn error(&self, message: &str, offset: usize) -> CborError {
+        return CborError {
+            message: message.to_string(),
+            offset,
+        };
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], CborError> {
+        if self.bytes.len() - self.pos < count {
+            return Err(self.error("Unexpected end of input", self.bytes.len()));
+        }
+
+        let slice = &self.bytes[self.pos..self.pos + count];
+        self.pos += count;
+        return Ok(slice);
+    }
+
+    fn argument(&mut self, info: u8, start: usize) -> Result<Length, CborError> {
+        let value = match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+            31 => return Ok(Length::Indefinite),
+            _ => return Err(self.error("Reserved additional information", start)),
+        };
+
+        return Ok(Length::Definite(value));
+    }
+
+    fn at_break(&mut self) -> Result<bool, CborError> {
+        match self.bytes.get(self.pos) {
+            Some(&BREAK) => {
+                self.pos += 1;
+                return Ok(true);
+            }
+            Some(_) => {
+                return Ok(false);
+            }
+            None => {
+                return Err(self.error("Missing break in indefinite-length item", self.pos));
+            }
+        }
+    }
+
+    fn length(&self, length: u64, start: usize) -> Result<usize, CborError> {
+        return usize::try_from(length).map_err(|_| self.error("Length too large", start));
+    }
+
+    // Reads a byte or text string, joining the chunks of an
+    // indefinite-length one.
+    fn string_bytes(&mut self, major: u8, info: u8, start: usize) -> Result<Vec<u8>, CborError> {
+        match self.argument(info, start)? {
+            Length::Definite(length) => {
+                let length = self.length(length, start)?;
+                return Ok(self.take(length)?.to_vec());
+            }
+            Length::Indefinite => {
+                let mut bytes = Vec::new();
+
+                while !self.at_break()? {
+                    let chunk_start = self.pos;
+                    let initial = self.take(1)?[0];
+                    if initial >> 5 != major || initial & 0x1f == 31 {
+                        return Err(self.error(
+                            "Chunks of an indefinite-length string must be definite strings of the same type",
+                            chunk_start,
+                        ));
+                    }
+                    bytes.extend(self.string_bytes(major, initial & 0x1f, chunk_start)?);
+                }
+
+                return Ok(bytes);
+            }
+        }
+    }
+
+    fn text(&mut self, info: u8, start: usize) -> Result<String, CborError> {
+        let bytes = self.string_bytes(TEXT, info, start)?;
+
+        return String::from_utf8(bytes)
+            .map_err(|_| self.error("Invalid UTF-8 in text string", start));
+    }
+
+    fn integer(&self, major: u8, value: u64) -> JsonValue {
+        let signed = match major {
+            UNSIGNED => i128::from(value),
+            _ => -1 - i128::from(value),
+        };
+
+        match isize::try_from(signed) {
+            Ok(integer) => {
+                return JsonValue::Integer(integer);
+            }
+            Err(_) => {
+                return JsonValue::Float(signed as f64);
+            }
+        }
+    }
+
+    // Text keys are used as they are and integer keys as their decimal
+    // spelling; any other key has no JSON equivalent.
+    fn key(&mut self) -> Result<String, CborError> {
+        let start = self.pos;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        match major {
+            TEXT => {
+                return self.text(info, start);
+            }
+            UNSIGNED | NEGATIVE => match self.argument(info, start)? {
+                Length::Definite(value) => {
+                    let signed = match major {
+                        UNSIGNED => i128::from(value),
+                        _ => -1 - i128::from(value),
+                    };
+                    return Ok(signed.to_string());
+                }
+                Length::Indefinite => {
+                    return Err(self.error("Integers can't have indefinite length", start));
+                }
+            },
+            _ => {
+                return Err(self.error("Map keys must be text strings or integers", start));
+            }
+        }
+    }
+
+    // Called on entering an array or map, which must decrement `depth` on
+    // leaving it.
+    fn enter(&mut self, start: usize) -> Result<(), CborError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            let message = format!("Nested more than {} levels deep", MAX_DEPTH);
+            return Err(self.error(&message, start));
+        }
+
+        return Ok(());
+    }
+
+    fn value(&mut self) -> Result<JsonValue, CborError> {
+        let start = self.pos;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        match major {
+            UNSIGNED | NEGATIVE => match self.argument(info, start)? {
+                Length::Definite(value) => {
+                    return Ok(self.integer(major, value));
+                }
+                Length::Indefinite => {
+                    return Err(self.error("Integers can't have indefinite length", start));
+                }
+            },
+            BYTES => {
+                let bytes = self.string_bytes(BYTES, info, start)?;
+                return Ok(JsonValue::from_bytes_base64(&bytes));
+            }
+            TEXT => {
+                return Ok(JsonValue::String(self.text(info, start)?));
+            }
+            ARRAY => {
+                self.enter(start)?;
+                let mut array = Vec::new();
+
+                match self.argument(info, start)? {
+                    Length::Definite(length) => {
+                        for _ in 0..length {
+                            array.push(self.value()?);
+                        }
+                    }
+                    Length::Indefinite => {
+                        while !self.at_break()? {
+                            array.push(self.value()?);
+                        }
+                    }
+                }
+
+                self.depth -= 1;
+                return Ok(JsonValue::Array(array));
+            }
+            MAP => {
+                self.enter(start)?;
+                let mut object = HashMap::new();
+                let length = self.argument(info, start)?;
+                let mut remaining = match length {
+                    Length::Definite(length) => length,
+                    Length::Indefinite => u64::MAX,
+                };
+
+                while remaining > 0 {
+                    if matches!(length, Length::Indefinite) && self.at_break()? {
+                        break;
+                    }
+
+                    let key_start = self.pos;
+                    let key = self.key()?;
+                    let member = self.value()?;
+                    if object.insert(key, member).is_some() {
+                        return Err(self.error("Duplicate map key", key_start));
+                    }
+
+                    remaining -= 1;
+                }
+
+                self.depth -= 1;
+                return Ok(JsonValue::Object(object));
+            }
+            TAG => {
+                return Err(self.error("Tags are not supported", start));
+            }
+            _ => match info {
+                20 => {
+                    return Ok(JsonValue::Boolean(false));
+                }
+                21 => {
+                    return Ok(JsonValue::Boolean(true));
+                }
+                22 | 23 => {
+                    return Ok(JsonValue::Null);
+                }
+                25 => {
+                    let bits = u16::from_be_bytes(self.take(2)?.try_into().unwrap());
+                    return Ok(JsonValue::Float(decode_half(bits)));
+                }
+                26 => {
+                    let bytes = self.take(4)?.try_into().unwrap();
+                    return Ok(JsonValue::Float(f32::from_be_bytes(bytes) as f64));
+                }
+                27 => {
+                    let bytes = self.take(8)?.try_into().unwrap();
+                    return Ok(JsonValue::Float(f64::from_be_bytes(bytes)));
+                }
+                31 => {
+                    return Err(self.error("Unexpected break", start));
+                }
+                _ => {
+                    return Err(self.error("Unsupported simple value", start));
+                }
+            },
+        }
+    }
+}
+
+/// Decodes a single CBOR data item, accepting both definite and
+/// indefinite lengths. CBOR without a JSON equivalent is mapped where it
+/// can be: byte strings become standard base64 strings, integer map keys
+/// become their decimal spelling, `undefined` becomes null, and integers
+/// outside `isize` become floats. Tags, other map keys, simple values
+/// besides `false`, `true`, `null` and `undefined`, duplicate keys and
+/// trailing bytes are errors.
+pub fn from_cbor(bytes: &[u8]) -> Result<JsonValue, CborError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        depth: 0,
+    };
+    let value = decoder.value()?;
+
+    if decoder.pos < bytes.len() {
+        return Err(decoder.error("Trailing bytes after data item", decoder.pos));
+    }
+
+    return Ok(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbor::{from_cbor, to_cbor, CborOptions};
+    use crate::json;
+    use crate::json::JsonValue;
+
+    const DETERMINISTIC: CborOptions = CborOptions {
+        deterministic: true,
+    };
+
+    fn hex(text: &str) -> Vec<u8> {
+        return (0..text.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&text[index..index + 2], 16).unwrap())
+            .collect();
+    }
+
+    fn error(text: &str) -> String {
+        return from_cbor(&hex(text)).unwrap_err().to_string();
+    }
+
+    // RFC 8949 appendix A, for the items JSON can hold. Each encodes to and
+    // decodes from exactly these bytes.
+    fn vectors() -> Vec<(&'static str, JsonValue)> {
+        return vec![
+            ("00", json!(0)),
+            ("01", json!(1)),
+            ("0a", json!(10)),
+            ("17", json!(23)),
+            ("1818", json!(24)),
+            ("1819", json!(25)),
+            ("1864", json!(100)),
+            ("1903e8", json!(1000)),
+            ("1a000f4240", json!(1000000)),
+            ("1b000000e8d4a51000", json!(1000000000000_isize)),
+            ("20", json!(-1)),
+            ("29", json!(-10)),
+            ("3863", json!(-100)),
+            ("3903e7", json!(-1000)),
+            ("f90000", json!(0.0)),
+            ("f98000", json!(-0.0)),
+            ("f93c00", json!(1.0)),
+            ("fb3ff199999999999a", json!(1.1)),
+            ("f93e00", json!(1.5)),
+            ("f97bff", json!(65504.0)),
+            ("fa47c35000", json!(100000.0)),
+            ("fa7f7fffff", json!(3.4028234663852886e38)),
+            ("fb7e37e43c8800759c", json!(1.0e300)),
+            ("f90001", json!(5.960464477539063e-8)),
+            ("f90400", json!(0.00006103515625)),
+            ("f9c400", json!(-4.0)),
+            ("fbc010666666666666", json!(-4.1)),
+            ("f97c00", json!(f64::INFINITY)),
+            ("f9fc00", json!(f64::NEG_INFINITY)),
+            ("f4", json!(false)),
+            ("f5", json!(true)),
+            ("f6", json!(null)),
+            ("60", json!("")),
+            ("6161", json!("a")),
+            ("6449455446", json!("IETF")),
+            ("62225c", json!("\"\\")),
+            ("62c3bc", json!("\u{fc}")),
+            ("63e6b0b4", json!("\u{6c34}")),
+            ("64f0908591", json!("\u{10151}")),
+            ("80", json!([])),
+            ("83010203", json!([1, 2, 3])),
+            ("8301820203820405", json!([1, [2, 3], [4, 5]])),
+            (
+                "98190102030405060708090a0b0c0d0e0f101112131415161718181819",
+                JsonValue::Array((1..=25).map(JsonValue::Integer).collect()),
+            ),
+            ("a0", json!({})),
+            ("a26161016162820203", json!({"a": 1, "b": [2, 3]})),
+            ("826161a161626163", json!(["a", {"b": "c"}])),
+            (
+                "a56161614161626142616361436164614461656145",
+                json!({"a": "A", "b": "B", "c": "C", "d": "D", "e": "E"}),
+            ),
+        ];
+    }
+
+    #[test]
+    fn rfc_vectors_encode_and_decode() {
+        for (bytes, value) in vectors() {
+            assert_eq!(to_cbor(&value, &DETERMINISTIC), hex(bytes), "{:?}", value);
+            assert_eq!(from_cbor(&hex(bytes)), Ok(value), "{}", bytes);
+        }
+
+        assert_eq!(to_cbor(&json!(f64::NAN), &DETERMINISTIC), hex("f97e00"));
+        assert!(matches!(from_cbor(&hex("f97e00")), Ok(JsonValue::Float(nan)) if nan.is_nan()));
+    }
+
+    #[test]
+    fn rfc_vectors_in_other_forms_decode() {
+        for (bytes, value) in [
+            ("1bffffffffffffffff", json!(18446744073709551615.0)),
+            ("3bffffffffffffffff", json!(-18446744073709551616.0)),
+            ("fa7f800000", json!(f64::INFINITY)),
+            ("fbfff0000000000000", json!(f64::NEG_INFINITY)),
+            ("f7", json!(null)),
+            ("40", json!("")),
+            ("4401020304", json!("AQIDBA==")),
+            ("5f42010243030405ff", json!("AQIDBAU=")),
+            ("7f657374726561646d696e67ff", json!("streaming")),
+            ("9fff", json!([])),
+            ("9f018202039f0405ffff", json!([1, [2, 3], [4, 5]])),
+            ("83019f0203ff820405", json!([1, [2, 3], [4, 5]])),
+            ("bf61610161629f0203ffff", json!({"a": 1, "b": [2, 3]})),
+            ("826161bf61626163ff", json!(["a", {"b": "c"}])),
+            ("bf6346756ef563416d7421ff", json!({"Fun": true, "Amt": -2})),
+            ("a201020304", json!({"1": 2, "3": 4})),
+        ] {
+            assert_eq!(from_cbor(&hex(bytes)), Ok(value), "{}", bytes);
+        }
+    }
+
+    #[test]
+    fn deterministic_maps_sort_shorter_keys_first() {
+        let value = json!({"bb": 1, "a": 2, "ab": 3, "": 4});
+
+        assert_eq!(
+            to_cbor(&value, &DETERMINISTIC),
+            hex("a460046161026261620362626201")
+        );
+        assert_eq!(
+            from_cbor(&to_cbor(&value, &CborOptions::default())),
+            Ok(value)
+        );
+    }
+
+    #[test]
+    fn unsupported_items_are_rejected_with_their_offset() {
+        assert_eq!(
+            error("c074323031332d30332d32315432303a30343a30305a"),
+            "[CBOR error at byte 0]: Tags are not supported"
+        );
+        assert_eq!(
+            error("8201a1f401"),
+            "[CBOR error at byte 3]: Map keys must be text strings or integers"
+        );
+        assert_eq!(
+            error("a2616101616102"),
+            "[CBOR error at byte 4]: Duplicate map key"
+        );
+        assert_eq!(
+            error("f0"),
+            "[CBOR error at byte 0]: Unsupported simple value"
+        );
+        assert_eq!(error("ff"), "[CBOR error at byte 0]: Unexpected break");
+        assert_eq!(
+            error("1c"),
+            "[CBOR error at byte 0]: Reserved additional information"
+        );
+        assert_eq!(
+            error("62c3"),
+            "[CBOR error at byte 2]: Unexpected end of input"
+        );
+        assert_eq!(
+            error("9f01"),
+            "[CBOR error at byte 2]: Missing break in indefinite-length item"
+        );
+        assert_eq!(
+            error("0102"),
+            "[CBOR error at byte 1]: Trailing bytes after data item"
+        );
+        assert_eq!(
+            error("62c328"),
+            "[CBOR error at byte 0]: Invalid UTF-8 in text string"
+        );
+        assert_eq!(
+            error("5f6161ff"),
+            "[CBOR error at byte 1]: Chunks of an indefinite-length string must be definite strings of the same type"
+        );
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        let bytes = vec![0x81; 1000];
+
+        assert!(from_cbor(&bytes)
+            .unwrap_err()
+            .message
+            .starts_with("Nested more than"));
+    }
+}
@@ -0,0 +1,560 @@
+use crate::json::JsonValue;
+
+fn remove_nulls_in(value: &mut JsonValue, include_array_elements: bool) -> usize {
+    let mut removed = 0;
+
+    match value {
+        JsonValue::Object(object) => {
+            let before = object.len();
+            object.retain(|_, member| *member != JsonValue::Null);
+            removed += before - object.len();
+
+            for member in object.values_mut() {
+                removed += remove_nulls_in(member, include_array_elements);
+            }
+        }
+        JsonValue::Array(array) => {
+            if include_array_elements {
+                let before = array.len();
+                array.retain(|element| *element != JsonValue::Null);
+                removed += before - array.len();
+            }
+
+            for element in array.iter_mut() {
+                removed += remove_nulls_in(element, include_array_elements);
+            }
+        }
+        _ => {}
+    }
+
+    return removed;
+}
+
+impl JsonValue {
+    /// Recursively removes object members whose value is `null`, and null
+    /// array elements too when `include_array_elements` is set. Returns the
+    /// number of values removed.
+    pub fn remove_nulls(&mut self, include_array_elements: bool) -> usize {
+        return remove_nulls_in(self, include_array_elements);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneOptions {
+    pub empty_strings: bool,
+}
+
+fn is_prunable(value: &JsonValue, opts: &PruneOptions) -> bool {
+    match value {
+        JsonValue::Object(object) => object.is_empty(),
+        JsonValue::Array(array) => array.is_empty(),
+        JsonValue::String(string) => opts.empty_strings && string.is_empty(),
+        _ => false,
+    }
+}
+
+// Children are pruned before their parent is checked, so a single post-order
+// pass also removes containers that only become empty after pruning.
+fn prune_empty_in(value: &mut JsonValue, opts: &PruneOptions) -> usize {
+    let mut removed = 0;
+
+    match value {
+        JsonValue::Object(object) => {
+            for member in object.values_mut() {
+                removed += prune_empty_in(member, opts);
+            }
+
+            let before = object.len();
+            object.retain(|_, member| !is_prunable(member, opts));
+            removed += before - object.len();
+        }
+        JsonValue::Array(array) => {
+            for element in array.iter_mut() {
+                removed += prune_empty_in(element, opts);
+            }
+
+            let before = array.len();
+            array.retain(|element| !is_prunable(element, opts));
+            removed += before - array.len();
+        }
+        _ => {}
+    }
+
+    return removed;
+}
+
+impl JsonValue {
+    /// Recursively removes members and elements that are empty objects or
+    /// arrays (and empty strings if `opts.empty_strings` is set), including
+    /// containers that become empty as a result. The value itself is never
+    /// removed. Returns the number of values removed.
+    pub fn prune_empty(&mut self, opts: PruneOptions) -> usize {
+        return prune_empty_in(self, &opts);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedactOptions {
+    /// Matches keys to names ignoring case, so `password` also redacts
+    /// `Password`.
+    pub case_insensitive: bool,
+    /// Lets `*` in a name stand for any run of characters, as in
+    /// `*_secret`.
+    pub wildcards: bool,
+    pub replacement: JsonValue,
+}
+
+impl Default for RedactOptions {
+    fn default() -> RedactOptions {
+        RedactOptions {
+            case_insensitive: true,
+            wildcards: false,
+            replacement: JsonValue::String("***".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GlobToken {
+    // `*`: any run of characters, including none.
+    Star,
+    // `?`: any one character.
+    Question,
+    Literal(char),
+}
+
+// Reads a glob in which `*` and `?` are wildcards and a backslash makes the
+// character after it literal, as in `\*`. A trailing backslash is literal.
+fn parse_glob(pattern: &str) -> Vec<GlobToken> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '*' => GlobToken::Star,
+            '?' => GlobToken::Question,
+            '\\' => GlobToken::Literal(chars.next().unwrap_or('\\')),
+            c => GlobToken::Literal(c),
+        };
+        tokens.push(token);
+    }
+
+    return tokens;
+}
+
+// Whether all of `text` matches `pattern`. Backtracks only to the most
+// recent `*`, which is enough since a later `*` can absorb anything an
+// earlier one would have.
+fn glob_match(pattern: &[GlobToken], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(GlobToken::Star) => {
+                star = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some(GlobToken::Question) => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            Some(GlobToken::Literal(c)) if *c == text[t] => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        match star {
+            Some((star_p, star_t)) => {
+                p = star_p + 1;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            }
+            None => {
+                return false;
+            }
+        }
+    }
+
+    return pattern[p..].iter().all(|token| *token == GlobToken::Star);
+}
+
+// Redaction names have no `?` or escapes: with `wildcards`, `*` is the
+// only special character.
+fn name_tokens(name: &[char], wildcards: bool) -> Vec<GlobToken> {
+    return name
+        .iter()
+        .map(|c| match c {
+            '*' if wildcards => GlobToken::Star,
+            c => GlobToken::Literal(*c),
+        })
+        .collect();
+}
+
+fn fold_case(text: &str, case_insensitive: bool) -> Vec<char> {
+    match case_insensitive {
+        true => text.to_lowercase().chars().collect(),
+        false => text.chars().collect(),
+    }
+}
+
+struct Redactor<'a> {
+    names: Vec<Vec<GlobToken>>,
+    opts: &'a RedactOptions,
+}
+
+impl Redactor<'_> {
+    fn matches(&self, key: &str) -> bool {
+        let key = fold_case(key, self.opts.case_insensitive);
+
+        return self.names.iter().any(|name| glob_match(name, &key));
+    }
+
+    fn redact(&self, value: &mut JsonValue) -> usize {
+        let mut replaced = 0;
+
+        match value {
+            JsonValue::Object(object) => {
+                for (key, member) in object.iter_mut() {
+                    if self.matches(key) {
+                        *member = self.opts.replacement.clone();
+                        replaced += 1;
+                    } else {
+                        replaced += self.redact(member);
+                    }
+                }
+            }
+            JsonValue::Array(array) => {
+                for element in array.iter_mut() {
+                    replaced += self.redact(element);
+                }
+            }
+            _ => {}
+        }
+
+        return replaced;
+    }
+}
+
+impl JsonValue {
+    /// Replaces the value of every object member, at any depth, whose key
+    /// is one of `keys` ignoring case, so secrets can be masked before a
+    /// document is logged. Keys are left as they are. Returns the number
+    /// of values replaced.
+    pub fn redact(&mut self, keys: &[&str], replacement: JsonValue) -> usize {
+        let opts = RedactOptions {
+            replacement,
+            ..RedactOptions::default()
+        };
+
+        return self.redact_with(keys, &opts);
+    }
+
+    /// Like `redact`, matching keys and replacing values as `opts` says.
+    /// A redacted member's value isn't searched any further.
+    pub fn redact_with(&mut self, keys: &[&str], opts: &RedactOptions) -> usize {
+        let redactor = Redactor {
+            names: keys
+                .iter()
+                .map(|key| name_tokens(&fold_case(key, opts.case_insensitive), opts.wildcards))
+                .collect(),
+            opts,
+        };
+
+        return redactor.redact(self);
+    }
+}
+
+// Removes the members of every object, at any depth, for which `remove`
+// holds of the key, searching only the members that are kept.
+fn remove_keys_in(value: &mut JsonValue, remove: &impl Fn(&str) -> bool) -> usize {
+    let mut removed = 0;
+
+    match value {
+        JsonValue::Object(object) => {
+            let before = object.len();
+            object.retain(|key, _| !remove(key));
+            removed += before - object.len();
+
+            for member in object.values_mut() {
+                removed += remove_keys_in(member, remove);
+            }
+        }
+        JsonValue::Array(array) => {
+            for element in array.iter_mut() {
+                removed += remove_keys_in(element, remove);
+            }
+        }
+        _ => {}
+    }
+
+    return removed;
+}
+
+impl JsonValue {
+    /// Removes every object member, at any depth and including objects
+    /// inside arrays, whose whole key matches the glob `pattern`. In the
+    /// pattern `*` matches any run of characters, `?` any one character,
+    /// and a backslash matches the character after it literally, so
+    /// `\*` matches a `*`. Matching is case-sensitive. Returns the number
+    /// of members removed.
+    ///
+    /// ```
+    /// use mini_json::json;
+    ///
+    /// let mut payload = json!({"id": 1, "_internal_id": 9, "rows": [{"v": 1, "v_deprecated": 0}]});
+    /// assert_eq!(payload.remove_keys_matching("_internal*"), 1);
+    /// assert_eq!(payload.remove_keys_matching("*_deprecated"), 1);
+    /// assert_eq!(payload, json!({"id": 1, "rows": [{"v": 1}]}));
+    /// ```
+    pub fn remove_keys_matching(&mut self, pattern: &str) -> usize {
+        let pattern = parse_glob(pattern);
+        let matches = |key: &str| glob_match(&pattern, &key.chars().collect::<Vec<char>>());
+
+        return remove_keys_in(self, &matches);
+    }
+
+    /// The inverse of `remove_keys_matching`: removes every object member,
+    /// at any depth, whose key doesn't match `pattern`, so nested objects
+    /// are filtered too. Returns the number of members removed.
+    pub fn retain_keys_matching(&mut self, pattern: &str) -> usize {
+        let pattern = parse_glob(pattern);
+        let matches = |key: &str| glob_match(&pattern, &key.chars().collect::<Vec<char>>());
+
+        return remove_keys_in(self, &|key| !matches(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clean::{PruneOptions, RedactOptions};
+    use crate::json;
+
+    #[test]
+    fn remove_nulls_strips_only_null_members() {
+        let mut value = json!({
+            "id": 1,
+            "name": null,
+            "empty": "",
+            "zero": 0,
+            "flag": false,
+            "items": [null, {"a": null, "b": [null, 2]}, {"c": {"d": null}}]
+        });
+
+        assert_eq!(value.remove_nulls(false), 3);
+        assert_eq!(
+            value,
+            json!({
+                "id": 1,
+                "empty": "",
+                "zero": 0,
+                "flag": false,
+                "items": [null, {"b": [null, 2]}, {"c": {}}]
+            })
+        );
+    }
+
+    #[test]
+    fn remove_nulls_can_include_array_elements() {
+        let mut value = json!([null, {"a": null, "b": [null, [null], 2]}, 0]);
+
+        assert_eq!(value.remove_nulls(true), 4);
+        assert_eq!(value, json!([{"b": [[], 2]}, 0]));
+        assert_eq!(value.remove_nulls(true), 0);
+
+        let mut scalar = json!(null);
+        assert_eq!(scalar.remove_nulls(true), 0);
+        assert_eq!(scalar, json!(null));
+    }
+
+    #[test]
+    fn prune_empty_cascades_through_levels() {
+        let mut value = json!({
+            "id": 1,
+            "meta": {},
+            "tags": [],
+            "nested": {"a": {"b": [{}, []]}, "c": [[[]]]},
+            "items": [{"x": {}}, 2, ""],
+            "name": ""
+        });
+
+        // "meta", "tags", "nested" and the seven containers inside it, and
+        // "x" and the object it leaves empty.
+        assert_eq!(value.prune_empty(PruneOptions::default()), 12);
+        assert_eq!(value, json!({"id": 1, "items": [2, ""], "name": ""}));
+
+        let opts = PruneOptions {
+            empty_strings: true,
+        };
+        assert_eq!(value.prune_empty(opts), 2);
+        assert_eq!(value, json!({"id": 1, "items": [2]}));
+    }
+
+    #[test]
+    fn prune_empty_leaves_full_documents_and_the_root_alone() {
+        let original = json!({"a": [0, false, null, " "], "b": {"c": "x"}});
+        let mut value = original.clone();
+        let opts = PruneOptions {
+            empty_strings: true,
+        };
+
+        assert_eq!(value.prune_empty(opts), 0);
+        assert_eq!(value, original);
+
+        let mut root = json!({"a": {"b": {}}});
+        assert_eq!(root.prune_empty(opts), 2);
+        assert_eq!(root, json!({}));
+    }
+
+    #[test]
+    fn redact_masks_secrets_at_every_depth() {
+        let mut payload = json!({
+            "user": "ada",
+            "Password": "hunter2",
+            "session": {"TOKEN": "abc", "expires": 60},
+            "requests": [
+                {"headers": {"Authorization": "Bearer x", "Accept": "*/*"}},
+                {"headers": {}}
+            ]
+        });
+
+        assert_eq!(
+            payload.redact(&["password", "token", "authorization"], json!("***")),
+            3
+        );
+        assert_eq!(
+            payload,
+            json!({
+                "user": "ada",
+                "Password": "***",
+                "session": {"TOKEN": "***", "expires": 60},
+                "requests": [
+                    {"headers": {"Authorization": "***", "Accept": "*/*"}},
+                    {"headers": {}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn redact_replaces_whole_subtrees_without_searching_them() {
+        let mut value = json!({"token": {"token": "a", "b": [1]}, "keep": [{"token": null}]});
+
+        assert_eq!(value.redact(&["token"], json!(null)), 2);
+        assert_eq!(value, json!({"token": null, "keep": [{"token": null}]}));
+        assert_eq!(json!([1, "token"]).redact(&["token"], json!(null)), 0);
+    }
+
+    #[test]
+    fn redact_with_can_match_case_and_wildcards() {
+        let payload = json!({"Token": 1, "token": 2, "api_secret": 3, "secret": 4, "a*b": 5});
+
+        let mut value = payload.clone();
+        let opts = RedactOptions {
+            case_insensitive: false,
+            ..RedactOptions::default()
+        };
+        assert_eq!(value.redact_with(&["token", "*_secret"], &opts), 1);
+        assert_eq!(value.pointer("/Token"), Some(&json!(1)));
+        assert_eq!(value.pointer("/token"), Some(&json!("***")));
+        assert_eq!(value.pointer("/api_secret"), Some(&json!(3)));
+
+        let mut value = payload.clone();
+        let opts = RedactOptions {
+            wildcards: true,
+            replacement: json!(0),
+            ..RedactOptions::default()
+        };
+        assert_eq!(value.redact_with(&["*_SECRET", "a*b"], &opts), 2);
+        assert_eq!(
+            value,
+            json!({"Token": 1, "token": 2, "api_secret": 0, "secret": 4, "a*b": 0})
+        );
+    }
+
+    #[test]
+    fn remove_keys_matching_searches_nested_objects_and_arrays() {
+        let mut value = json!({
+            "id": 1,
+            "tmp_a": 2,
+            "rows": [{"tmp_b": 3, "v": 4}, [{"tmp_": 5}], "tmp_c"],
+            "meta": {"tmp": 6, "nested": {"tmp_d": {"tmp_e": 7}}}
+        });
+
+        assert_eq!(value.remove_keys_matching("tmp_*"), 4);
+        assert_eq!(
+            value,
+            json!({
+                "id": 1,
+                "rows": [{"v": 4}, [{}], "tmp_c"],
+                "meta": {"tmp": 6, "nested": {}}
+            })
+        );
+    }
+
+    #[test]
+    fn glob_patterns_match_whole_keys() {
+        let original = json!({"ab": 1, "abc": 2, "xabc": 3, "aXc": 4, "ac": 5, "ABC": 6});
+        let cases = [
+            ("abc", vec!["abc"]),
+            ("a?c", vec!["aXc", "abc"]),
+            ("a*c", vec!["aXc", "abc", "ac"]),
+            ("*b*", vec!["ab", "abc", "xabc"]),
+            ("*", vec!["ABC", "aXc", "ab", "abc", "ac", "xabc"]),
+            ("b", vec![]),
+            ("a??c", vec![]),
+        ];
+
+        for (pattern, expected) in cases {
+            let mut value = original.clone();
+            let removed = value.remove_keys_matching(pattern);
+
+            let mut gone: Vec<&str> = original
+                .keys()
+                .filter(|key| !value.contains_key(key))
+                .collect();
+            gone.sort();
+            assert_eq!(gone, expected, "{}", pattern);
+            assert_eq!(removed, expected.len());
+        }
+    }
+
+    #[test]
+    fn no_matches_leave_the_value_alone() {
+        let original = json!({"a": [{"b": 1}], "c": {"d": null}});
+        let mut value = original.clone();
+
+        assert_eq!(value.remove_keys_matching("x*"), 0);
+        assert_eq!(value, original);
+        assert_eq!(json!([1, "a"]).remove_keys_matching("*"), 0);
+    }
+
+    #[test]
+    fn backslashes_escape_wildcards() {
+        let mut value = json!({"a*": 1, "ab": 2, "a?": 3, "a\\": 4, "*": 5});
+
+        assert_eq!(value.remove_keys_matching("a\\*"), 1);
+        assert_eq!(value.remove_keys_matching("a\\?"), 1);
+        assert_eq!(value.remove_keys_matching("\\*"), 1);
+        assert_eq!(value.remove_keys_matching("a\\"), 1);
+        assert_eq!(value, json!({"ab": 2}));
+    }
+
+    #[test]
+    fn retain_keys_matching_removes_the_rest() {
+        let mut value = json!({
+            "id": 1,
+            "name": "x",
+            "items": [{"id": 2, "price": 3}],
+            "ids": {"id": 4, "other": 5}
+        });
+
+        assert_eq!(value.retain_keys_matching("id*"), 3);
+        assert_eq!(value, json!({"id": 1, "ids": {"id": 4}}));
+    }
+}
@@ -0,0 +1,509 @@
+use std::fmt;
+
+use crate::json::{JsonValue, WrongKindError};
+use crate::number::JsonNumber;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoerceError {
+    /// The value is neither the target type nor a string.
+    WrongKind(WrongKindError),
+    /// The value is a string that doesn't parse as the target type.
+    Invalid(String),
+}
+
+impl fmt::Display for CoerceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoerceError::WrongKind(error) => write!(f, "{}", error),
+            CoerceError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CoerceError {}
+
+impl From<WrongKindError> for CoerceError {
+    fn from(error: WrongKindError) -> CoerceError {
+        CoerceError::WrongKind(error)
+    }
+}
+
+fn invalid(string: &str, target: &str) -> CoerceError {
+    return CoerceError::Invalid(format!("'{}' is not a valid {}", string, target));
+}
+
+fn parse_f64(string: &str) -> Option<f64> {
+    match string.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() => Some(value),
+        _ => None,
+    }
+}
+
+// Accepts plain integers and any float syntax with an integral value, so
+// "1e3" parses as 1000.
+fn parse_i64(string: &str) -> Option<i64> {
+    let trimmed = string.trim();
+
+    if let Ok(value) = trimmed.parse::<i64>() {
+        return Some(value);
+    }
+
+    return JsonValue::Float(parse_f64(trimmed)?).as_number()?.as_i64();
+}
+
+// The number a trimmed string spells: an integer if it's written as one
+// and fits, a finite float otherwise.
+fn string_number(string: &str) -> Option<JsonValue> {
+    let trimmed = string.trim();
+
+    if let Some(integer) = trimmed
+        .parse::<i64>()
+        .ok()
+        .and_then(|integer| isize::try_from(integer).ok())
+    {
+        return Some(JsonValue::Integer(integer));
+    }
+
+    return parse_f64(trimmed).map(JsonValue::Float);
+}
+
+fn coerce_value(value: &mut JsonValue) -> usize {
+    match value {
+        JsonValue::String(string) => match string_number(string) {
+            Some(number) => {
+                *value = number;
+                return 1;
+            }
+            None => {
+                return 0;
+            }
+        },
+        JsonValue::Object(object) => object.values_mut().map(coerce_value).sum(),
+        JsonValue::Array(array) => array.iter_mut().map(coerce_value).sum(),
+        _ => 0,
+    }
+}
+
+// Strings are trimmed before parsing in all of these.
+impl JsonValue {
+    /// Returns an integral number, or a string holding one. Floats and
+    /// float syntax such as `"1e3"` are accepted when the value is integral
+    /// and fits in an `i64`.
+    pub fn parse_string_as_i64(&self) -> Result<i64, CoerceError> {
+        match self {
+            JsonValue::String(string) => {
+                return parse_i64(string).ok_or_else(|| invalid(string, "integer"));
+            }
+            JsonValue::Integer(_) | JsonValue::Float(_) => {
+                return self
+                    .as_number()
+                    .and_then(|number| number.as_i64())
+                    .ok_or_else(|| CoerceError::Invalid("Number is not an integer".to_string()));
+            }
+            _ => {
+                return Err(self.wrong_kind("integer").into());
+            }
+        }
+    }
+
+    /// Returns a number, or a string holding a finite one.
+    pub fn parse_string_as_f64(&self) -> Result<f64, CoerceError> {
+        match self {
+            JsonValue::String(string) => {
+                return parse_f64(string).ok_or_else(|| invalid(string, "number"));
+            }
+            JsonValue::Integer(value) => {
+                return Ok(*value as f64);
+            }
+            JsonValue::Float(value) => {
+                return Ok(*value);
+            }
+            _ => {
+                return Err(self.wrong_kind("number").into());
+            }
+        }
+    }
+
+    /// Returns a boolean, or a string spelling `true` or `false` in any
+    /// case.
+    pub fn parse_string_as_bool(&self) -> Result<bool, CoerceError> {
+        return self.parse_bool(false);
+    }
+
+    /// Like `parse_string_as_bool`, but also accepts `yes`/`no`, `on`/`off`
+    /// and `1`/`0` in strings.
+    pub fn parse_string_as_bool_lenient(&self) -> Result<bool, CoerceError> {
+        return self.parse_bool(true);
+    }
+
+    fn parse_bool(&self, lenient: bool) -> Result<bool, CoerceError> {
+        let string = match self {
+            JsonValue::Boolean(value) => return Ok(*value),
+            JsonValue::String(string) => string,
+            _ => return Err(self.wrong_kind("boolean").into()),
+        };
+
+        match string.trim().to_ascii_lowercase().as_str() {
+            "true" => {
+                return Ok(true);
+            }
+            "false" => {
+                return Ok(false);
+            }
+            "yes" | "on" | "1" if lenient => {
+                return Ok(true);
+            }
+            "no" | "off" | "0" if lenient => {
+                return Ok(false);
+            }
+            _ => {
+                return Err(invalid(string, "boolean"));
+            }
+        }
+    }
+
+    /// Replaces every string in the tree that parses as a number with that
+    /// number: an integer if it is written as one and fits, a float
+    /// otherwise. Non-finite values like `"inf"` stay strings. Returns the
+    /// number of strings converted.
+    pub fn coerce_string_numbers(&mut self) -> usize {
+        return coerce_value(self);
+    }
+}
+
+/// Which strings `coerce_in_place` converts. Nothing is converted by
+/// default; each rule has to be turned on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoercionRules {
+    /// Converts the spellings `coerce_bool` accepts to booleans.
+    pub booleans: bool,
+    /// Converts strings holding a finite number, as `coerce_number` does.
+    pub numbers: bool,
+    /// Strings that mean `null`, such as `""`, `"null"` or `"N/A"`.
+    /// They're compared after trimming and ignoring ASCII case.
+    pub null_strings: Vec<String>,
+}
+
+/// Reads a boolean, or a string spelling one: `true`/`false`, `yes`/`no`,
+/// `on`/`off` or `1`/`0`, ignoring surrounding whitespace and ASCII case.
+/// Anything else, such as `"y"` or `"2"`, gives `None`.
+pub fn coerce_bool(value: &JsonValue) -> Option<bool> {
+    return value.parse_string_as_bool_lenient().ok();
+}
+
+/// Reads a number, or a string holding a finite one. A string written as
+/// an integer, like `"42"`, gives an integer, and any other number syntax,
+/// like `"3.0"` or `"1e3"`, a float. `"inf"` and `"NaN"` give `None`.
+pub fn coerce_number(value: &JsonValue) -> Option<JsonNumber> {
+    match value {
+        JsonValue::String(string) => string_number(string)?.as_number(),
+        _ => value.as_number(),
+    }
+}
+
+/// Whether `value` is `null` or one of `rules.null_strings`.
+pub fn coerce_null(value: &JsonValue, rules: &CoercionRules) -> bool {
+    match value {
+        JsonValue::Null => true,
+        JsonValue::String(string) => rules
+            .null_strings
+            .iter()
+            .any(|null| null.trim().eq_ignore_ascii_case(string.trim())),
+        _ => false,
+    }
+}
+
+// What the string `value` becomes under `rules`, if anything.
+fn coerced_string(value: &JsonValue, rules: &CoercionRules) -> Option<JsonValue> {
+    if coerce_null(value, rules) {
+        return Some(JsonValue::Null);
+    }
+    if let Some(boolean) = coerce_bool(value).filter(|_| rules.booleans) {
+        return Some(JsonValue::Boolean(boolean));
+    }
+
+    match value {
+        JsonValue::String(string) if rules.numbers => string_number(string),
+        _ => None,
+    }
+}
+
+fn coerce_string(value: &mut JsonValue, rules: &CoercionRules) -> usize {
+    match value {
+        JsonValue::String(_) => match coerced_string(value, rules) {
+            Some(coerced) => {
+                *value = coerced;
+                return 1;
+            }
+            None => {
+                return 0;
+            }
+        },
+        JsonValue::Object(object) => {
+            return object
+                .values_mut()
+                .map(|member| coerce_string(member, rules))
+                .sum();
+        }
+        JsonValue::Array(array) => {
+            return array
+                .iter_mut()
+                .map(|element| coerce_string(element, rules))
+                .sum();
+        }
+        _ => {
+            return 0;
+        }
+    }
+}
+
+impl JsonValue {
+    /// Converts every string in the tree, at any depth, that `rules` turns
+    /// on a conversion for. Null strings are checked first, then booleans,
+    /// then numbers, so with both of the latter on `"1"` becomes `true`.
+    /// Keys are never converted. Returns the number of strings converted.
+    ///
+    /// ```
+    /// use mini_json::{json, CoercionRules};
+    ///
+    /// let mut row = json!({"active": "TRUE", "score": "3.0", "note": "", "id": "A7"});
+    /// let rules = CoercionRules {
+    ///     booleans: true,
+    ///     numbers: true,
+    ///     null_strings: vec!["".to_string(), "N/A".to_string()],
+    /// };
+    ///
+    /// assert_eq!(row.coerce_in_place(&rules), 3);
+    /// assert_eq!(row, json!({"active": true, "score": 3.0, "note": null, "id": "A7"}));
+    /// ```
+    pub fn coerce_in_place(&mut self, rules: &CoercionRules) -> usize {
+        return coerce_string(self, rules);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coerce::{coerce_bool, coerce_null, coerce_number, CoerceError, CoercionRules};
+    use crate::json;
+    use crate::json::{JsonValue, WrongKindError};
+    use crate::number::JsonNumber;
+
+    fn invalid(message: &str) -> CoerceError {
+        return CoerceError::Invalid(message.to_string());
+    }
+
+    #[test]
+    fn parses_integers_from_numbers_and_trimmed_strings() {
+        assert_eq!(json!(42).parse_string_as_i64(), Ok(42));
+        assert_eq!(json!(3.0).parse_string_as_i64(), Ok(3));
+        assert_eq!(json!(" 42 ").parse_string_as_i64(), Ok(42));
+        assert_eq!(json!("-7\n").parse_string_as_i64(), Ok(-7));
+        assert_eq!(json!("1e3").parse_string_as_i64(), Ok(1000));
+        assert_eq!(
+            json!("9223372036854775807").parse_string_as_i64(),
+            Ok(i64::MAX)
+        );
+
+        assert_eq!(
+            json!("3.14").parse_string_as_i64(),
+            Err(invalid("'3.14' is not a valid integer"))
+        );
+        assert_eq!(
+            json!("").parse_string_as_i64(),
+            Err(invalid("'' is not a valid integer"))
+        );
+        assert_eq!(
+            json!(2.5).parse_string_as_i64(),
+            Err(invalid("Number is not an integer"))
+        );
+        assert_eq!(
+            json!(true).parse_string_as_i64(),
+            Err(CoerceError::WrongKind(WrongKindError {
+                expected: "integer",
+                found: "boolean"
+            }))
+        );
+    }
+
+    #[test]
+    fn parses_finite_floats() {
+        assert_eq!(json!(" 2.75 ").parse_string_as_f64(), Ok(2.75));
+        assert_eq!(json!("1e3").parse_string_as_f64(), Ok(1000.0));
+        assert_eq!(json!(2).parse_string_as_f64(), Ok(2.0));
+        assert_eq!(json!(0.5).parse_string_as_f64(), Ok(0.5));
+        assert!(json!("inf").parse_string_as_f64().is_err());
+        assert!(json!("NaN").parse_string_as_f64().is_err());
+        assert!(json!("1,5").parse_string_as_f64().is_err());
+        assert!(json!(null).parse_string_as_f64().is_err());
+    }
+
+    #[test]
+    fn yes_is_a_boolean_only_when_lenient() {
+        assert_eq!(json!(" TRUE ").parse_string_as_bool(), Ok(true));
+        assert_eq!(json!("False").parse_string_as_bool(), Ok(false));
+        assert_eq!(json!(false).parse_string_as_bool(), Ok(false));
+        assert_eq!(
+            json!("yes").parse_string_as_bool(),
+            Err(invalid("'yes' is not a valid boolean"))
+        );
+        assert!(json!("1").parse_string_as_bool().is_err());
+        assert!(json!(1).parse_string_as_bool().is_err());
+
+        for (text, expected) in [
+            ("yes", true),
+            ("ON", true),
+            ("1", true),
+            ("No", false),
+            ("off", false),
+            ("0", false),
+        ] {
+            assert_eq!(
+                json!(text).parse_string_as_bool_lenient(),
+                Ok(expected),
+                "{}",
+                text
+            );
+        }
+        assert!(json!("y").parse_string_as_bool_lenient().is_err());
+    }
+
+    #[test]
+    fn coerces_numeric_strings_throughout_a_tree() {
+        let mut feed = json!({
+            "id": "42",
+            "price": " 3.50 ",
+            "big": "1e3",
+            "huge": "99999999999999999999",
+            "name": "4 apples",
+            "flag": "true",
+            "missing": "inf",
+            "rows": [{"n": "-1"}, "x", 7],
+            "12": "key stays"
+        });
+
+        assert_eq!(feed.coerce_string_numbers(), 5);
+        assert_eq!(
+            feed,
+            json!({
+                "id": 42,
+                "price": 3.5,
+                "big": 1000.0,
+                "huge": 1e20,
+                "name": "4 apples",
+                "flag": "true",
+                "missing": "inf",
+                "rows": [{"n": -1}, "x", 7],
+                "12": "key stays"
+            })
+        );
+    }
+
+    #[test]
+    fn coerce_bool_accepts_only_the_listed_spellings() {
+        for text in ["true", "TRUE", " yes ", "Yes", "on", "ON", "1"] {
+            assert_eq!(coerce_bool(&json!(text)), Some(true), "{}", text);
+        }
+        for text in ["false", "False", "no", "NO", "off", "Off", " 0\t"] {
+            assert_eq!(coerce_bool(&json!(text)), Some(false), "{}", text);
+        }
+        for text in [
+            "", "y", "n", "t", "f", "2", "-1", "01", "1.0", "yes!", "nope",
+        ] {
+            assert_eq!(coerce_bool(&json!(text)), None, "{}", text);
+        }
+
+        assert_eq!(coerce_bool(&json!(true)), Some(true));
+        assert_eq!(coerce_bool(&json!(1)), None);
+        assert_eq!(coerce_bool(&json!(null)), None);
+    }
+
+    #[test]
+    fn coerce_number_keeps_integers_apart_from_floats() {
+        assert_eq!(coerce_number(&json!("42")), Some(JsonNumber::Integer(42)));
+        assert_eq!(coerce_number(&json!(" -7 ")), Some(JsonNumber::Integer(-7)));
+        assert_eq!(coerce_number(&json!("3.0")), Some(JsonNumber::Float(3.0)));
+        assert_eq!(
+            coerce_number(&json!("1e3")),
+            Some(JsonNumber::Float(1000.0))
+        );
+        assert_eq!(coerce_number(&json!(5)), Some(JsonNumber::Integer(5)));
+        assert_eq!(coerce_number(&json!(0.5)), Some(JsonNumber::Float(0.5)));
+
+        for text in ["", "inf", "-Infinity", "NaN", "1,5", "0x10", "1 2", "abc"] {
+            assert_eq!(coerce_number(&json!(text)), None, "{}", text);
+        }
+        assert_eq!(coerce_number(&json!(true)), None);
+    }
+
+    #[test]
+    fn coerce_null_uses_only_the_configured_strings() {
+        let rules = CoercionRules {
+            null_strings: vec!["".to_string(), "null".to_string(), "N/A".to_string()],
+            ..CoercionRules::default()
+        };
+
+        for text in ["", "  ", "null", "NULL", " n/a "] {
+            assert!(coerce_null(&json!(text), &rules), "{}", text);
+        }
+        for text in ["none", "nil", "NA", "0"] {
+            assert!(!coerce_null(&json!(text), &rules), "{}", text);
+        }
+        assert!(coerce_null(&json!(null), &rules));
+        assert!(!coerce_null(&json!(0), &rules));
+        assert!(!coerce_null(&json!(""), &CoercionRules::default()));
+    }
+
+    fn messy() -> JsonValue {
+        return json!({
+            "id": "0042",
+            "active": "Yes",
+            "score": " 9.5 ",
+            "email": "",
+            "phone": "N/A",
+            "tags": ["1", "off", "x", "n/a"],
+            "address": {"zip": "01234", "notes": "null", "unit": 4},
+            "raw": true
+        });
+    }
+
+    #[test]
+    fn coerce_in_place_converts_a_messy_record() {
+        let mut record = messy();
+        let rules = CoercionRules {
+            booleans: true,
+            numbers: true,
+            null_strings: vec!["".to_string(), "null".to_string(), "N/A".to_string()],
+        };
+
+        assert_eq!(record.coerce_in_place(&rules), 10);
+        assert_eq!(
+            record,
+            json!({
+                "id": 42,
+                "active": true,
+                "score": 9.5,
+                "email": null,
+                "phone": null,
+                "tags": [true, false, "x", null],
+                "address": {"zip": 1234, "notes": null, "unit": 4},
+                "raw": true
+            })
+        );
+    }
+
+    #[test]
+    fn coerce_in_place_does_nothing_unless_asked() {
+        let mut record = messy();
+        assert_eq!(record.coerce_in_place(&CoercionRules::default()), 0);
+        assert_eq!(record, messy());
+
+        let rules = CoercionRules {
+            numbers: true,
+            ..CoercionRules::default()
+        };
+        assert_eq!(record.coerce_in_place(&rules), 4);
+        assert_eq!(record.pointer("/active"), Some(&json!("Yes")));
+        assert_eq!(record.pointer("/tags/0"), Some(&json!(1)));
+        assert_eq!(record.pointer("/email"), Some(&json!("")));
+    }
+}
@@ -0,0 +1,244 @@
+use std::{fmt, io};
+
+use crate::json::{Json, JsonValue};
+use crate::serialize::{
+    serialize_to_string, serialize_to_writer, Indent, NonFiniteFloatError, NonFiniteFloats,
+    OutputStyle, SerializeOptions, Serializer,
+};
+
+/// ANSI SGR parameters for each kind of token, such as `"0;32"` for green.
+/// Each token is wrapped in `ESC[<code>m` and a reset; an empty code leaves
+/// that kind of token uncolored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorTheme {
+    pub null: String,
+    pub boolean: String,
+    pub number: String,
+    pub string: String,
+    pub key: String,
+    pub punctuation: String,
+}
+
+// The defaults follow jq's colors.
+impl Default for ColorTheme {
+    fn default() -> ColorTheme {
+        ColorTheme {
+            null: "1;30".to_string(),
+            boolean: "0;39".to_string(),
+            number: "0;39".to_string(),
+            string: "0;32".to_string(),
+            key: "34;1".to_string(),
+            punctuation: "1;39".to_string(),
+        }
+    }
+}
+
+/// Removes ANSI escape sequences, so `strip_ansi(&doc.to_string_colored(t)?)`
+/// equals `doc.to_string_pretty()?`. Useful for measuring the visible width
+/// of colored output.
+pub fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+
+        // Skip a CSI sequence: `[`, parameter bytes, then a final byte in
+        // the range `@` to `~`.
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+
+    return plain;
+}
+
+const PRETTY: SerializeOptions = SerializeOptions {
+    style: OutputStyle::Pretty,
+    indent: Indent::Spaces(2),
+    escape_html: false,
+    line_width: 80,
+    single_quotes: false,
+    trailing_commas: false,
+    sort_keys: false,
+    non_finite_floats: NonFiniteFloats::Error,
+};
+
+impl JsonValue {
+    pub fn write_colored_fmt(&self, out: &mut impl fmt::Write, theme: &ColorTheme) -> fmt::Result {
+        return self.write_colored_fmt_with(out, &PRETTY, theme);
+    }
+
+    pub fn write_colored_fmt_with(
+        &self,
+        out: &mut impl fmt::Write,
+        options: &SerializeOptions,
+        theme: &ColorTheme,
+    ) -> fmt::Result {
+        return Serializer::new(out, options).with_theme(theme).value(self);
+    }
+
+    /// Pretty-prints with every token wrapped in the theme's ANSI colors.
+    pub fn to_string_colored(&self, theme: &ColorTheme) -> Result<String, NonFiniteFloatError> {
+        return self.to_string_colored_with(&PRETTY, theme);
+    }
+
+    /// Serializes with `options` like `to_string_with`, with every token
+    /// wrapped in the theme's ANSI colors. Stripping the colors gives the
+    /// uncolored output exactly.
+    pub fn to_string_colored_with(
+        &self,
+        options: &SerializeOptions,
+        theme: &ColorTheme,
+    ) -> Result<String, NonFiniteFloatError> {
+        return serialize_to_string(options, Some(theme), |serializer| serializer.value(self));
+    }
+
+    pub fn write_colored(&self, writer: &mut impl io::Write, theme: &ColorTheme) -> io::Result<()> {
+        return serialize_to_writer(writer, &PRETTY, Some(theme), |serializer| {
+            serializer.value(self)
+        });
+    }
+}
+
+impl Json {
+    pub fn write_colored_fmt(&self, out: &mut impl fmt::Write, theme: &ColorTheme) -> fmt::Result {
+        return self.write_colored_fmt_with(out, &PRETTY, theme);
+    }
+
+    pub fn write_colored_fmt_with(
+        &self,
+        out: &mut impl fmt::Write,
+        options: &SerializeOptions,
+        theme: &ColorTheme,
+    ) -> fmt::Result {
+        return Serializer::new(out, options).with_theme(theme).root(self);
+    }
+
+    pub fn to_string_colored(&self, theme: &ColorTheme) -> Result<String, NonFiniteFloatError> {
+        return self.to_string_colored_with(&PRETTY, theme);
+    }
+
+    pub fn to_string_colored_with(
+        &self,
+        options: &SerializeOptions,
+        theme: &ColorTheme,
+    ) -> Result<String, NonFiniteFloatError> {
+        return serialize_to_string(options, Some(theme), |serializer| serializer.root(self));
+    }
+
+    pub fn write_colored(&self, writer: &mut impl io::Write, theme: &ColorTheme) -> io::Result<()> {
+        return serialize_to_writer(writer, &PRETTY, Some(theme), |serializer| {
+            serializer.root(self)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{strip_ansi, ColorTheme};
+    use crate::json;
+    use crate::serialize::{OutputStyle, SerializeOptions};
+
+    fn theme() -> ColorTheme {
+        return ColorTheme {
+            null: "1".to_string(),
+            boolean: "2".to_string(),
+            number: "3".to_string(),
+            string: "4".to_string(),
+            key: "5".to_string(),
+            punctuation: "6".to_string(),
+        };
+    }
+
+    fn paint(code: &str, text: &str) -> String {
+        return format!("\x1b[{}m{}\x1b[0m", code, text);
+    }
+
+    #[test]
+    fn tokens_are_wrapped_in_their_theme_colors() {
+        let value = json!({"k": [null, true, 1.5, "a\"b\n"]});
+
+        let expected = [
+            paint("6", "{"),
+            "\n  ".to_string(),
+            paint("5", "\"k\""),
+            paint("6", ":"),
+            " ".to_string(),
+            paint("6", "["),
+            "\n    ".to_string(),
+            paint("1", "null"),
+            paint("6", ","),
+            "\n    ".to_string(),
+            paint("2", "true"),
+            paint("6", ","),
+            "\n    ".to_string(),
+            paint("3", "1.5"),
+            paint("6", ","),
+            "\n    ".to_string(),
+            paint("4", "\"a\\\"b\\n\""),
+            "\n  ".to_string(),
+            paint("6", "]"),
+            "\n".to_string(),
+            paint("6", "}"),
+        ]
+        .concat();
+        assert_eq!(value.to_string_colored(&theme()).unwrap(), expected);
+    }
+
+    #[test]
+    fn empty_codes_leave_tokens_uncolored() {
+        let theme = ColorTheme {
+            punctuation: String::new(),
+            ..theme()
+        };
+
+        assert_eq!(
+            json!([1]).to_string_colored(&theme).unwrap(),
+            format!("[\n  {}\n]", paint("3", "1"))
+        );
+    }
+
+    #[test]
+    fn stripping_colors_gives_the_plain_output() {
+        let value = json!({
+            "name": "\u{1b}[31m not a color \u{1b}",
+            "list": [1, -2.5e-8, false, null, {"x": "é\t"}],
+            "empty": [],
+            "nested": {"inner": {}}
+        });
+
+        for theme in [theme(), ColorTheme::default()] {
+            let colored = value.to_string_colored(&theme).unwrap();
+            assert_eq!(strip_ansi(&colored), value.to_string_pretty().unwrap());
+
+            let options = SerializeOptions::new(OutputStyle::Minified);
+            let colored = value.to_string_colored_with(&options, &theme).unwrap();
+            assert_eq!(
+                strip_ansi(&colored),
+                value.to_string_with(&options).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn writer_variants_match_the_string_output() {
+        let value = json!([{"a": "b"}, 2]);
+        let expected = value.to_string_colored(&theme()).unwrap();
+
+        let mut bytes = Vec::new();
+        value.write_colored(&mut bytes, &theme()).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+
+        let mut text = String::new();
+        value.write_colored_fmt(&mut text, &theme()).unwrap();
+        assert_eq!(text, expected);
+    }
+}
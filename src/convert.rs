@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use crate::json::{Json, JsonValue, WrongKindError};
+
+macro_rules! from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for JsonValue {
+                fn from(value: $t) -> JsonValue {
+                    JsonValue::Integer(value as isize)
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8, i16, i32, isize, u8, u16);
+
+#[cfg(target_pointer_width = "64")]
+from_integer!(i64, u32);
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> JsonValue {
+        JsonValue::Float(value)
+    }
+}
+
+impl From<f32> for JsonValue {
+    fn from(value: f32) -> JsonValue {
+        JsonValue::Float(value as f64)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> JsonValue {
+        JsonValue::Boolean(value)
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> JsonValue {
+        JsonValue::String(value)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> JsonValue {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(value: Vec<JsonValue>) -> JsonValue {
+        JsonValue::Array(value)
+    }
+}
+
+impl From<HashMap<String, JsonValue>> for JsonValue {
+    fn from(value: HashMap<String, JsonValue>) -> JsonValue {
+        JsonValue::Object(value)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> JsonValue {
+        match value {
+            Some(value) => value.into(),
+            None => JsonValue::Null,
+        }
+    }
+}
+
+impl From<Json> for JsonValue {
+    fn from(value: Json) -> JsonValue {
+        match value {
+            Json::Object(object) => JsonValue::Object(object),
+            Json::Array(array) => JsonValue::Array(array),
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for Json {
+    type Error = WrongKindError;
+
+    fn try_from(value: JsonValue) -> Result<Json, WrongKindError> {
+        match value {
+            JsonValue::Object(object) => Ok(Json::Object(object)),
+            JsonValue::Array(array) => Ok(Json::Array(array)),
+            _ => Err(value.wrong_kind("object or array")),
+        }
+    }
+}
+
+// `Json` owns its container directly rather than wrapping a `JsonValue`, so
+// there is no borrowed `as_value`; `to_value` clones instead. Collapsing the
+// two types into one would remove the need for either.
+impl Json {
+    pub fn into_value(self) -> JsonValue {
+        return self.into();
+    }
+
+    pub fn to_value(&self) -> JsonValue {
+        return self.clone().into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::json;
+    use crate::json::{parse_from_str, Json, JsonValue, WrongKindError};
+
+    #[test]
+    fn rust_values_convert_to_their_variants() {
+        assert_eq!(JsonValue::from(-3i8), JsonValue::Integer(-3));
+        assert_eq!(JsonValue::from(65_535u16), JsonValue::Integer(65_535));
+        assert_eq!(JsonValue::from(1.5f32), JsonValue::Float(1.5));
+        assert_eq!(JsonValue::from(true), JsonValue::Boolean(true));
+        assert_eq!(JsonValue::from("a"), JsonValue::String("a".to_string()));
+        assert_eq!(JsonValue::from(Some(2)), JsonValue::Integer(2));
+        assert_eq!(JsonValue::from(None::<&str>), JsonValue::Null);
+        assert_eq!(JsonValue::from(vec![json!(1)]), json!([1]));
+        assert_eq!(
+            JsonValue::from(HashMap::from([("a".to_string(), json!(null))])),
+            json!({"a": null})
+        );
+    }
+
+    #[test]
+    fn a_parsed_document_moves_into_a_larger_tree_and_back() {
+        let document = parse_from_str(r#"{"id": 1, "tags": ["a"]}"#).unwrap();
+        let copy = document.to_value();
+
+        let mut envelope = json!({"version": 2, "items": []});
+        envelope.insert("payload", document.into_value()).unwrap();
+        envelope
+            .pointer_mut("/items")
+            .unwrap()
+            .push(parse_from_str("[1, 2]").unwrap())
+            .unwrap();
+        assert_eq!(
+            envelope,
+            json!({"version": 2, "items": [[1, 2]], "payload": {"id": 1, "tags": ["a"]}})
+        );
+
+        let payload = envelope.remove("payload").unwrap().unwrap();
+        assert_eq!(payload, copy);
+        let document = Json::try_from(payload).unwrap();
+        assert_eq!(
+            document,
+            parse_from_str(r#"{"tags": ["a"], "id": 1}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn scalars_are_not_documents() {
+        assert_eq!(
+            Json::try_from(json!("text")),
+            Err(WrongKindError {
+                expected: "object or array",
+                found: "string"
+            })
+        );
+        assert!(Json::try_from(JsonValue::Null).is_err());
+        assert_eq!(Json::try_from(json!([])), Ok(Json::Array(Vec::new())));
+    }
+}
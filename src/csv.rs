@@ -0,0 +1,589 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::{Json, JsonValue};
+use crate::path::escape_pointer_token;
+use crate::serialize::{format_float, OutputStyle, SerializeOptions};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderOrder {
+    /// Columns appear in the order their keys are first seen, row by row.
+    /// Keys new to the same row are sorted among themselves, since objects
+    /// don't keep insertion order.
+    #[default]
+    FirstSeen,
+    Sorted,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NestedCells {
+    /// Objects and arrays in cells are an error.
+    #[default]
+    Error,
+    /// Objects and arrays are written as minified JSON, and cells holding
+    /// JSON objects or arrays are parsed back.
+    EmbedJson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub header_order: HeaderOrder,
+    pub nested: NestedCells,
+    /// Reads a column as numbers or booleans when every non-empty cell in it
+    /// is one, with empty cells becoming null. Otherwise every cell is a
+    /// string.
+    pub infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions {
+            delimiter: ',',
+            header_order: HeaderOrder::FirstSeen,
+            nested: NestedCells::Error,
+            infer_types: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvError {
+    /// JSON Pointer to the offending row or cell, such as `/2/name`.
+    pub pointer: String,
+    /// Set for errors in CSV input.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(
+                f,
+                "[CSV error at line {} ('{}')]: {}",
+                line, self.pointer, self.message
+            ),
+            None => write!(f, "[CSV error at '{}']: {}", self.pointer, self.message),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+fn csv_error(pointer: String, line: Option<usize>, message: &str) -> CsvError {
+    CsvError {
+        pointer,
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn cell_pointer(row: usize, column: &str) -> String {
+    return format!("/{}/{}", row, escape_pointer_token(column));
+}
+
+// Quotes a field per RFC 4180 when it holds the delimiter, a quote or a
+// line break, doubling any quotes inside.
+fn write_field(out: &mut String, field: &str, delimiter: char) {
+    if !field.contains([delimiter, '"', '\n', '\r']) {
+        out.push_str(field);
+        return;
+    }
+
+    out.push('"');
+    out.push_str(&field.replace('"', "\"\""));
+    out.push('"');
+}
+
+fn write_record<'a>(out: &mut String, fields: impl Iterator<Item = &'a str>, delimiter: char) {
+    for (index, field) in fields.enumerate() {
+        if index > 0 {
+            out.push(delimiter);
+        }
+        write_field(out, field, delimiter);
+    }
+    out.push('\n');
+}
+
+fn cell_text(
+    value: Option<&JsonValue>,
+    pointer: impl FnOnce() -> String,
+    options: &CsvOptions,
+) -> Result<String, CsvError> {
+    match value {
+        None | Some(JsonValue::Null) => {
+            return Ok(String::new());
+        }
+        Some(JsonValue::Boolean(value)) => {
+            return Ok(value.to_string());
+        }
+        Some(JsonValue::Integer(value)) => {
+            return Ok(value.to_string());
+        }
+        Some(JsonValue::Float(value)) if !value.is_finite() => {
+            return Ok(String::new());
+        }
+        Some(JsonValue::Float(value)) => {
+            return Ok(format_float(*value));
+        }
+        Some(JsonValue::String(value)) => {
+            return Ok(value.clone());
+        }
+        Some(nested) => {
+            if options.nested == NestedCells::EmbedJson {
+                return Ok(nested.to_string_lossy(&SerializeOptions::new(OutputStyle::Minified)));
+            }
+            return Err(csv_error(
+                pointer(),
+                None,
+                &format!("Can't write a nested {} to a cell", nested.kind_name()),
+            ));
+        }
+    }
+}
+
+/// Writes an array of objects as CSV with a header row made from the union
+/// of their keys. Missing members and nulls become empty cells. Fails for
+/// anything but an array of objects, and for nested values unless
+/// `options.nested` allows embedding them as JSON. Records end with `\n`.
+pub fn to_csv(value: &JsonValue, options: &CsvOptions) -> Result<String, CsvError> {
+    let rows = match value {
+        JsonValue::Array(rows) => rows,
+        _ => {
+            return Err(csv_error(
+                String::new(),
+                None,
+                &format!("Expected an array of objects, found {}", value.kind_name()),
+            ));
+        }
+    };
+
+    let mut objects = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        match row {
+            JsonValue::Object(object) => objects.push(object),
+            _ => {
+                return Err(csv_error(
+                    format!("/{}", index),
+                    None,
+                    &format!("Expected an object, found {}", row.kind_name()),
+                ));
+            }
+        }
+    }
+
+    let mut columns: Vec<&String> = Vec::new();
+    for object in &objects {
+        let mut new_keys: Vec<&String> =
+            object.keys().filter(|key| !columns.contains(key)).collect();
+        new_keys.sort();
+        columns.extend(new_keys);
+    }
+    if options.header_order == HeaderOrder::Sorted {
+        columns.sort();
+    }
+
+    if columns.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut output = String::new();
+    write_record(
+        &mut output,
+        columns.iter().map(|column| column.as_str()),
+        options.delimiter,
+    );
+
+    for (index, object) in objects.iter().enumerate() {
+        let mut cells = Vec::with_capacity(columns.len());
+        for column in &columns {
+            cells.push(cell_text(
+                object.get(*column),
+                || cell_pointer(index, column),
+                options,
+            )?);
+        }
+        write_record(
+            &mut output,
+            cells.iter().map(|cell| cell.as_str()),
+            options.delimiter,
+        );
+    }
+
+    return Ok(output);
+}
+
+// Records are counted with the header, which has no row of its own.
+fn row_pointer(record: usize) -> String {
+    match record {
+        0 => String::new(),
+        _ => format!("/{}", record - 1),
+    }
+}
+
+struct Record {
+    line: usize,
+    fields: Vec<String>,
+}
+
+fn parse_records(source: &str, delimiter: char) -> Result<Vec<Record>, CsvError> {
+    let mut records = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let mut record = Record {
+            line,
+            fields: Vec::new(),
+        };
+        let mut field = String::new();
+
+        loop {
+            match chars.next() {
+                None => {
+                    record.fields.push(field);
+                    break;
+                }
+                Some('"') if field.is_empty() => {
+                    let start = line;
+
+                    loop {
+                        match chars.next() {
+                            None => {
+                                return Err(csv_error(
+                                    row_pointer(records.len()),
+                                    Some(start),
+                                    "Unterminated quoted field",
+                                ));
+                            }
+                            Some('"') if chars.peek() == Some(&'"') => {
+                                chars.next();
+                                field.push('"');
+                            }
+                            Some('"') => {
+                                break;
+                            }
+                            Some(c) => {
+                                if c == '\n' {
+                                    line += 1;
+                                }
+                                field.push(c);
+                            }
+                        }
+                    }
+
+                    // A closing quote must end the field.
+                    match chars.peek() {
+                        None | Some('\n') | Some('\r') => {}
+                        Some(c) if *c == delimiter => {}
+                        Some(_) => {
+                            return Err(csv_error(
+                                row_pointer(records.len()),
+                                Some(line),
+                                "Unexpected character after closing quote",
+                            ));
+                        }
+                    }
+                }
+                Some('"') => {
+                    return Err(csv_error(
+                        row_pointer(records.len()),
+                        Some(line),
+                        "Unexpected quote in unquoted field",
+                    ));
+                }
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some('\n') => {
+                    line += 1;
+                    record.fields.push(field);
+                    break;
+                }
+                Some(c) if c == delimiter => {
+                    record.fields.push(std::mem::take(&mut field));
+                }
+                Some(c) => {
+                    field.push(c);
+                }
+            }
+        }
+
+        records.push(record);
+    }
+
+    return Ok(records);
+}
+
+// Matches JSON number syntax, so cells like `007`, `+1` and `inf` stay
+// strings.
+fn is_json_number(text: &str) -> bool {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (&unsigned[..index], Some(&unsigned[index + 1..])),
+        None => (unsigned, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    return digits(whole)
+        && (whole == "0" || !whole.starts_with('0'))
+        && fraction.is_none_or(digits)
+        && exponent
+            .is_none_or(|exponent| digits(exponent.strip_prefix(['-', '+']).unwrap_or(exponent)));
+}
+
+fn parse_number(text: &str) -> JsonValue {
+    if let Ok(integer) = text.parse::<isize>() {
+        return JsonValue::Integer(integer);
+    }
+
+    return JsonValue::Float(text.parse::<f64>().unwrap_or(f64::NAN));
+}
+
+fn parse_embedded(text: &str) -> Option<JsonValue> {
+    if !text.starts_with(['{', '[']) {
+        return None;
+    }
+
+    return text.parse::<Json>().ok().map(JsonValue::from);
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnType {
+    Number,
+    Boolean,
+    String,
+}
+
+fn column_type(records: &[Record], column: usize) -> ColumnType {
+    let mut cells = records
+        .iter()
+        .map(|record| record.fields[column].as_str())
+        .filter(|cell| !cell.is_empty())
+        .peekable();
+
+    if cells.peek().is_none() {
+        return ColumnType::String;
+    }
+
+    let cells: Vec<&str> = cells.collect();
+    if cells.iter().all(|cell| is_json_number(cell)) {
+        return ColumnType::Number;
+    }
+    if cells.iter().all(|cell| *cell == "true" || *cell == "false") {
+        return ColumnType::Boolean;
+    }
+
+    return ColumnType::String;
+}
+
+/// Reads CSV with a header row into an array of objects keyed by the
+/// header. Both `\n` and `\r\n` end records, and quoted fields may hold
+/// delimiters, doubled quotes and line breaks. Every record must have as
+/// many fields as the header, and header names must be unique.
+pub fn from_csv(source: &str, options: &CsvOptions) -> Result<JsonValue, CsvError> {
+    let mut records = parse_records(source, options.delimiter)?.into_iter();
+
+    let header = match records.next() {
+        Some(header) => header,
+        None => return Ok(JsonValue::Array(Vec::new())),
+    };
+    for (index, name) in header.fields.iter().enumerate() {
+        if header.fields[..index].contains(name) {
+            return Err(csv_error(
+                String::new(),
+                Some(header.line),
+                &format!("Duplicate column '{}'", name),
+            ));
+        }
+    }
+
+    let records: Vec<Record> = records.collect();
+    for (index, record) in records.iter().enumerate() {
+        if record.fields.len() != header.fields.len() {
+            return Err(csv_error(
+                format!("/{}", index),
+                Some(record.line),
+                &format!(
+                    "Expected {} fields, found {}",
+                    header.fields.len(),
+                    record.fields.len()
+                ),
+            ));
+        }
+    }
+
+    let types: Vec<ColumnType> = (0..header.fields.len())
+        .map(|column| match options.infer_types {
+            true => column_type(&records, column),
+            false => ColumnType::String,
+        })
+        .collect();
+
+    let mut rows = Vec::with_capacity(records.len());
+    for record in records {
+        let mut object = HashMap::new();
+
+        for (column, cell) in record.fields.into_iter().enumerate() {
+            let embedded = match options.nested {
+                NestedCells::EmbedJson => parse_embedded(&cell),
+                NestedCells::Error => None,
+            };
+
+            let value = match (embedded, types[column]) {
+                (Some(value), _) => value,
+                (None, ColumnType::String) => JsonValue::String(cell),
+                (None, _) if cell.is_empty() => JsonValue::Null,
+                (None, ColumnType::Number) => parse_number(&cell),
+                (None, ColumnType::Boolean) => JsonValue::Boolean(cell == "true"),
+            };
+
+            object.insert(header.fields[column].clone(), value);
+        }
+
+        rows.push(JsonValue::Object(object));
+    }
+
+    return Ok(JsonValue::Array(rows));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::{from_csv, to_csv, CsvOptions, HeaderOrder, NestedCells};
+    use crate::json;
+
+    fn error(source: &str) -> String {
+        return from_csv(source, &CsvOptions::default())
+            .unwrap_err()
+            .to_string();
+    }
+
+    #[test]
+    fn tables_round_trip_with_inferred_types() {
+        let rows = json!([
+            {"name": "ada", "age": 36, "admin": true, "score": 1.5},
+            {"name": "bo", "age": 7, "admin": false, "score": null},
+            {"name": "cy", "age": -1, "admin": null, "score": 2e-9}
+        ]);
+
+        let csv = to_csv(&rows, &CsvOptions::default()).unwrap();
+        assert_eq!(
+            csv,
+            "admin,age,name,score\ntrue,36,ada,1.5\nfalse,7,bo,\n,-1,cy,2e-9\n"
+        );
+        assert_eq!(from_csv(&csv, &CsvOptions::default()), Ok(rows));
+    }
+
+    #[test]
+    fn fields_with_commas_quotes_and_newlines_are_quoted() {
+        let rows = json!([{"text": "a, \"b\"\nc", "id": "1"}, {"text": "\r\n", "id": "x"}]);
+
+        let csv = to_csv(&rows, &CsvOptions::default()).unwrap();
+        assert_eq!(csv, "id,text\n1,\"a, \"\"b\"\"\nc\"\nx,\"\r\n\"\n");
+        assert_eq!(from_csv(&csv, &CsvOptions::default()), Ok(rows));
+    }
+
+    #[test]
+    fn headers_are_the_union_of_keys_in_first_seen_order() {
+        let rows = json!([{"b": 1, "a": 2}, {"d": 3}, {"c": 4, "a": 5}]);
+
+        assert_eq!(
+            to_csv(&rows, &CsvOptions::default()).unwrap(),
+            "a,b,d,c\n2,1,,\n,,3,\n5,,,4\n"
+        );
+
+        let sorted = CsvOptions {
+            header_order: HeaderOrder::Sorted,
+            ..CsvOptions::default()
+        };
+        assert_eq!(
+            to_csv(&rows, &sorted).unwrap(),
+            "a,b,c,d\n2,1,,\n,,,3\n5,,4,\n"
+        );
+    }
+
+    #[test]
+    fn columns_are_typed_only_when_every_cell_agrees() {
+        let csv = "a;b;c;d\n1;true;007;x\n2.5;;1;\n";
+        let options = CsvOptions {
+            delimiter: ';',
+            ..CsvOptions::default()
+        };
+
+        assert_eq!(
+            from_csv(csv, &options),
+            Ok(json!([
+                {"a": 1, "b": true, "c": "007", "d": "x"},
+                {"a": 2.5, "b": null, "c": "1", "d": ""}
+            ]))
+        );
+
+        let untyped = CsvOptions {
+            infer_types: false,
+            ..options
+        };
+        assert_eq!(
+            from_csv(csv, &untyped).unwrap().pointer("/0/a"),
+            Some(&json!("1"))
+        );
+    }
+
+    #[test]
+    fn nested_cells_error_or_embed_json() {
+        let rows = json!([{"id": 1, "tags": ["a", "b"], "meta": {"k": null}}]);
+
+        assert_eq!(
+            to_csv(&rows, &CsvOptions::default())
+                .unwrap_err()
+                .to_string(),
+            "[CSV error at '/0/meta']: Can't write a nested object to a cell"
+        );
+
+        let embed = CsvOptions {
+            nested: NestedCells::EmbedJson,
+            ..CsvOptions::default()
+        };
+        let csv = to_csv(&rows, &embed).unwrap();
+        assert_eq!(
+            csv,
+            "id,meta,tags\n1,\"{\"\"k\"\":null}\",\"[\"\"a\"\",\"\"b\"\"]\"\n"
+        );
+        assert_eq!(from_csv(&csv, &embed), Ok(rows));
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert_eq!(
+            to_csv(&json!({"a": 1}), &CsvOptions::default())
+                .unwrap_err()
+                .to_string(),
+            "[CSV error at '']: Expected an array of objects, found object"
+        );
+        assert_eq!(
+            to_csv(&json!([{}, 1]), &CsvOptions::default())
+                .unwrap_err()
+                .to_string(),
+            "[CSV error at '/1']: Expected an object, found integer"
+        );
+        assert_eq!(
+            error("a,b\n1\n"),
+            "[CSV error at line 2 ('/0')]: Expected 2 fields, found 1"
+        );
+        assert_eq!(
+            error("a,a\n1,2\n"),
+            "[CSV error at line 1 ('')]: Duplicate column 'a'"
+        );
+        assert_eq!(
+            error("a\n\"open\n"),
+            "[CSV error at line 2 ('/0')]: Unterminated quoted field"
+        );
+        assert_eq!(
+            error("a\nx\"y\n"),
+            "[CSV error at line 2 ('/0')]: Unexpected quote in unquoted field"
+        );
+        assert_eq!(from_csv("", &CsvOptions::default()), Ok(json!([])));
+    }
+}
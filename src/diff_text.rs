@@ -0,0 +1,262 @@
+use crate::json::JsonValue;
+use crate::path::escape_pointer_token;
+use crate::preview::PreviewOptions;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffTextOptions {
+    /// Also prints unchanged values beside the changes, as
+    /// `  POINTER: VALUE`.
+    pub context: bool,
+    /// The most lines printed before the rest are counted in a final
+    /// `… N more lines`.
+    pub max_lines: Option<usize>,
+    /// How values are abbreviated; see `JsonValue::preview`.
+    pub values: PreviewOptions,
+}
+
+struct TextDiff<'a> {
+    lines: Vec<String>,
+    options: &'a DiffTextOptions,
+}
+
+impl TextDiff<'_> {
+    fn text(&self, value: &JsonValue) -> String {
+        return value.preview(&self.options.values);
+    }
+
+    fn child(&mut self, old: Option<&JsonValue>, new: Option<&JsonValue>, pointer: &mut String) {
+        match (old, new) {
+            (Some(old_value), Some(new_value)) => {
+                self.value(old_value, new_value, pointer);
+            }
+            (Some(old_value), None) => {
+                let line = format!("- {}: {}", pointer, self.text(old_value));
+                self.lines.push(line);
+            }
+            (None, Some(new_value)) => {
+                let line = format!("+ {}: {}", pointer, self.text(new_value));
+                self.lines.push(line);
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn value(&mut self, old: &JsonValue, new: &JsonValue, pointer: &mut String) {
+        if old == new {
+            if self.options.context {
+                let line = format!("  {}: {}", pointer, self.text(old));
+                self.lines.push(line);
+            }
+            return;
+        }
+
+        match (old, new) {
+            (JsonValue::Object(old_object), JsonValue::Object(new_object)) => {
+                let mut keys: Vec<&String> = old_object.keys().chain(new_object.keys()).collect();
+                keys.sort();
+                keys.dedup();
+
+                for key in keys {
+                    let len = pointer.len();
+                    pointer.push('/');
+                    pointer.push_str(&escape_pointer_token(key));
+
+                    self.child(old_object.get(key), new_object.get(key), pointer);
+
+                    pointer.truncate(len);
+                }
+            }
+            (JsonValue::Array(old_array), JsonValue::Array(new_array)) => {
+                for index in 0..old_array.len().max(new_array.len()) {
+                    let len = pointer.len();
+                    pointer.push('/');
+                    pointer.push_str(&index.to_string());
+
+                    self.child(old_array.get(index), new_array.get(index), pointer);
+
+                    pointer.truncate(len);
+                }
+            }
+            _ => {
+                let line = format!("~ {}: {} -> {}", pointer, self.text(old), self.text(new));
+                self.lines.push(line);
+            }
+        }
+    }
+}
+
+/// Describes how `old` became `new` for people, one line per difference:
+/// `+ POINTER: VALUE` for an addition, `- POINTER: VALUE` for a removal and
+/// `~ POINTER: OLD -> NEW` for a change. Lines come in pointer order, with
+/// object keys sorted and array indices ascending. As with `diff`, arrays
+/// are compared index by index. Every line ends in a newline, so equal
+/// documents give an empty string.
+///
+/// ```
+/// use mini_json::{diff_text, json, DiffTextOptions};
+///
+/// let old = json!({"user": {"age": 41, "nickname": "al", "tags": ["a", "b"]}});
+/// let new = json!({"user": {"age": 42, "tags": ["a", "b", "new"]}});
+///
+/// assert_eq!(
+///     diff_text(&old, &new, &DiffTextOptions::default()),
+///     "~ /user/age: 41 -> 42\n- /user/nickname: \"al\"\n+ /user/tags/2: \"new\"\n"
+/// );
+/// ```
+pub fn diff_text(old: &JsonValue, new: &JsonValue, options: &DiffTextOptions) -> String {
+    let mut diff = TextDiff {
+        lines: Vec::new(),
+        options,
+    };
+    diff.value(old, new, &mut String::new());
+
+    let mut lines = diff.lines;
+    if let Some(max_lines) = options.max_lines {
+        if lines.len() > max_lines {
+            let more = lines.len() - max_lines;
+            lines.truncate(max_lines);
+            lines.push(format!("… {} more lines", more));
+        }
+    }
+
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(&line);
+        text.push('\n');
+    }
+
+    return text;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff_text::{diff_text, DiffTextOptions};
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::parse_from_str;
+    use crate::preview::PreviewOptions;
+
+    const OLD: &str = r#"{
+        "name": "service",
+        "version": 3,
+        "owner": {"team": "core", "pager": "x1"},
+        "hosts": ["a", "b", "c"],
+        "limits": [{"cpu": 1}, {"cpu": 2}],
+        "notes": "short"
+    }"#;
+
+    const NEW: &str = r##"{
+        "name": "service",
+        "version": 4,
+        "owner": {"team": "core", "slack": "#core"},
+        "hosts": ["a", "B"],
+        "limits": [{"cpu": 1}, {"cpu": 2, "mem": "1G"}, {"cpu": 4}],
+        "notes": ["now", "a", "list"],
+        "a/b": true
+    }"##;
+
+    fn fixtures() -> (JsonValue, JsonValue) {
+        let old = JsonValue::from(parse_from_str(OLD).unwrap());
+        let new = JsonValue::from(parse_from_str(NEW).unwrap());
+        return (old, new);
+    }
+
+    #[test]
+    fn changes_are_listed_in_pointer_order() {
+        let (old, new) = fixtures();
+
+        assert_eq!(
+            diff_text(&old, &new, &DiffTextOptions::default()),
+            concat!(
+                "+ /a~1b: true\n",
+                "~ /hosts/1: \"b\" -> \"B\"\n",
+                "- /hosts/2: \"c\"\n",
+                "+ /limits/1/mem: \"1G\"\n",
+                "+ /limits/2: {\"cpu\": 4}\n",
+                "~ /notes: \"short\" -> [\"now\", \"a\", \"list\"]\n",
+                "- /owner/pager: \"x1\"\n",
+                "+ /owner/slack: \"#core\"\n",
+                "~ /version: 3 -> 4\n",
+            )
+        );
+        assert_eq!(
+            diff_text(&new, &old, &DiffTextOptions::default())
+                .lines()
+                .next(),
+            Some("- /a~1b: true")
+        );
+    }
+
+    #[test]
+    fn context_shows_unchanged_values_too() {
+        let (old, new) = fixtures();
+        let options = DiffTextOptions {
+            context: true,
+            ..DiffTextOptions::default()
+        };
+
+        assert_eq!(
+            diff_text(&old, &new, &options),
+            concat!(
+                "+ /a~1b: true\n",
+                "  /hosts/0: \"a\"\n",
+                "~ /hosts/1: \"b\" -> \"B\"\n",
+                "- /hosts/2: \"c\"\n",
+                "  /limits/0: {\"cpu\": 1}\n",
+                "  /limits/1/cpu: 2\n",
+                "+ /limits/1/mem: \"1G\"\n",
+                "+ /limits/2: {\"cpu\": 4}\n",
+                "  /name: \"service\"\n",
+                "~ /notes: \"short\" -> [\"now\", \"a\", \"list\"]\n",
+                "- /owner/pager: \"x1\"\n",
+                "+ /owner/slack: \"#core\"\n",
+                "  /owner/team: \"core\"\n",
+                "~ /version: 3 -> 4\n",
+            )
+        );
+        assert_eq!(
+            diff_text(&old, &old, &options),
+            format!("  : {}\n", old.preview(&PreviewOptions::default()))
+        );
+    }
+
+    #[test]
+    fn long_diffs_and_values_are_cut_short() {
+        let ids: JsonValue = (0..50).map(JsonValue::from).collect();
+        let old = json!({"log": "x".repeat(100), "ids": ids});
+        let new = json!({"log": "y", "ids": []});
+        let options = DiffTextOptions {
+            max_lines: Some(2),
+            values: PreviewOptions {
+                max_string_chars: 3,
+                ..PreviewOptions::default()
+            },
+            ..DiffTextOptions::default()
+        };
+
+        assert_eq!(
+            diff_text(&old, &new, &options),
+            "- /ids/0: 0\n- /ids/1: 1\n… 49 more lines\n"
+        );
+
+        let options = DiffTextOptions {
+            max_lines: None,
+            ..options
+        };
+        assert!(diff_text(&old, &new, &options).ends_with("~ /log: \"xxx…\" -> \"y\"\n"));
+    }
+
+    #[test]
+    fn equal_documents_give_no_lines() {
+        let (old, _) = fixtures();
+
+        assert_eq!(
+            diff_text(&old, &old.clone(), &DiffTextOptions::default()),
+            ""
+        );
+        assert_eq!(
+            diff_text(&json!(1), &json!(1.0), &DiffTextOptions::default()),
+            "~ : 1 -> 1.0\n"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+
+use crate::json::JsonValue;
+use crate::path::escape_pointer_token;
+
+/// A `${NAME}` placeholder with no default whose variable `lookup` didn't
+/// find.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandError {
+    /// The JSON Pointer to the string holding the placeholder.
+    pub pointer: String,
+    pub variable: String,
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[Expand error at '{}']: Variable '{}' isn't defined",
+            self.pointer, self.variable
+        )
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+// Expands the placeholders in `text`, returning the new text and how many
+// were substituted, or the name of an undefined variable.
+fn expand_string(
+    text: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<(String, usize), String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        if let Some(after) = tail.strip_prefix("$${") {
+            expanded.push_str("${");
+            rest = after;
+            continue;
+        }
+
+        // A `$` that doesn't open a complete `${...}` is kept as it is.
+        let body_end = match tail.strip_prefix("${").and_then(|body| body.find('}')) {
+            Some(end) => end,
+            None => {
+                expanded.push('$');
+                rest = &tail[1..];
+                continue;
+            }
+        };
+        let body = &tail[2..2 + body_end];
+
+        // As in the shell, `:-` also applies the default to a variable
+        // that's set but empty.
+        let value = match body.split_once(":-") {
+            Some((name, default)) => match lookup(name) {
+                Some(value) if !value.is_empty() => value,
+                _ => default.to_string(),
+            },
+            None => match lookup(body) {
+                Some(value) => value,
+                None => return Err(body.to_string()),
+            },
+        };
+
+        expanded.push_str(&value);
+        count += 1;
+        rest = &tail[2 + body_end + 1..];
+    }
+
+    expanded.push_str(rest);
+    return Ok((expanded, count));
+}
+
+fn expand_value(
+    value: &mut JsonValue,
+    lookup: &impl Fn(&str) -> Option<String>,
+    pointer: &mut String,
+) -> Result<usize, ExpandError> {
+    match value {
+        JsonValue::String(text) => {
+            if !text.contains('$') {
+                return Ok(0);
+            }
+
+            match expand_string(text, lookup) {
+                Ok((expanded, count)) => {
+                    *text = expanded;
+                    return Ok(count);
+                }
+                Err(variable) => {
+                    return Err(ExpandError {
+                        pointer: pointer.clone(),
+                        variable,
+                    });
+                }
+            }
+        }
+        JsonValue::Array(array) => {
+            let mut count = 0;
+            for (index, element) in array.iter_mut().enumerate() {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+
+                count += expand_value(element, lookup, pointer)?;
+
+                pointer.truncate(len);
+            }
+
+            return Ok(count);
+        }
+        JsonValue::Object(object) => {
+            // Members are visited in key order so that, of several
+            // undefined variables, the same one is always reported.
+            let mut members: Vec<(&String, &mut JsonValue)> = object.iter_mut().collect();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+
+            let mut count = 0;
+            for (key, member) in members {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer_token(key));
+
+                count += expand_value(member, lookup, pointer)?;
+
+                pointer.truncate(len);
+            }
+
+            return Ok(count);
+        }
+        _ => {
+            return Ok(0);
+        }
+    }
+}
+
+impl JsonValue {
+    /// Substitutes `${NAME}` placeholders in every string value at every
+    /// depth with `lookup(NAME)`, returning how many were substituted.
+    /// `${NAME:-default}` falls back to `default` when `NAME` is undefined
+    /// or empty, and `$${` writes a literal `${`. Keys aren't expanded, and
+    /// a `$` that doesn't start a closed `${...}` is left alone.
+    ///
+    /// An undefined variable without a default fails with the pointer to
+    /// its string, leaving the value unchanged.
+    ///
+    /// ```
+    /// use mini_json::json;
+    ///
+    /// let mut config = json!({
+    ///     "url": "https://${API_HOST}/v1",
+    ///     "retries": "${RETRIES:-3}",
+    ///     "price": "$${not_a_variable}"
+    /// });
+    /// let lookup = |name: &str| match name {
+    ///     "API_HOST" => Some("api.example.com".to_string()),
+    ///     _ => None,
+    /// };
+    ///
+    /// assert_eq!(config.expand_env(lookup), Ok(2));
+    /// assert_eq!(config.pointer("/url"), Some(&json!("https://api.example.com/v1")));
+    /// assert_eq!(config.pointer("/retries"), Some(&json!("3")));
+    /// assert_eq!(config.pointer("/price"), Some(&json!("${not_a_variable}")));
+    /// ```
+    pub fn expand_env(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<usize, ExpandError> {
+        let mut expanded = self.clone();
+        let count = expand_value(&mut expanded, &lookup, &mut String::new())?;
+        *self = expanded;
+
+        return Ok(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expand::ExpandError;
+    use crate::json;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("db.local".to_string()),
+            "PORT" => Some("5432".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn defaults_apply_to_undefined_and_empty_variables() {
+        let mut value = json!({
+            "a": "${HOST:-localhost}",
+            "b": "${MISSING:-localhost}",
+            "c": "${EMPTY:-fallback}",
+            "d": "${MISSING:-}",
+            "e": "${EMPTY}"
+        });
+
+        assert_eq!(value.expand_env(lookup), Ok(5));
+        assert_eq!(
+            value,
+            json!({"a": "db.local", "b": "localhost", "c": "fallback", "d": "", "e": ""})
+        );
+    }
+
+    #[test]
+    fn escapes_and_stray_dollars_are_kept() {
+        let mut value = json!({
+            "escaped": "$${HOST} costs $5",
+            "unclosed": "${HOST",
+            "mixed": "$$${HOST}",
+            "plain": "no placeholders"
+        });
+
+        // Read left to right, `$$${` is a kept `$` and then an escape.
+        assert_eq!(value.expand_env(lookup), Ok(0));
+        assert_eq!(
+            value,
+            json!({
+                "escaped": "${HOST} costs $5",
+                "unclosed": "${HOST",
+                "mixed": "$${HOST}",
+                "plain": "no placeholders"
+            })
+        );
+    }
+
+    #[test]
+    fn nested_arrays_are_expanded_and_counted() {
+        let mut value = json!({
+            "servers": [["${HOST}:${PORT}", 1], {"url": "http://${HOST}"}],
+            "${HOST}": true
+        });
+
+        assert_eq!(value.expand_env(lookup), Ok(3));
+        assert_eq!(
+            value,
+            json!({
+                "servers": [["db.local:5432", 1], {"url": "http://db.local"}],
+                "${HOST}": true
+            })
+        );
+    }
+
+    #[test]
+    fn undefined_variables_fail_with_their_pointer() {
+        let original = json!({"ok": "${HOST}", "list": [1, {"a/b": "x ${USER} y"}]});
+        let mut value = original.clone();
+
+        let error = value.expand_env(lookup).unwrap_err();
+        assert_eq!(
+            error,
+            ExpandError {
+                pointer: "/list/1/a~1b".to_string(),
+                variable: "USER".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "[Expand error at '/list/1/a~1b']: Variable 'USER' isn't defined"
+        );
+        assert_eq!(value, original);
+
+        let error = json!("${A} ${B}").expand_env(lookup).unwrap_err();
+        assert_eq!(error.pointer, "");
+        assert_eq!(error.variable, "A");
+    }
+}
@@ -0,0 +1,358 @@
+use crate::json::{Json, JsonValue};
+use crate::parser::{column_at, unexpected_at, ParseError, ParseErrorKind, Parser};
+use crate::path::{parse_array_index, parse_pointer, PathSegment};
+
+// Scans raw JSON text without building values, so that subtrees off the
+// path to the target can be skipped at the cost of a byte scan.
+struct Scanner<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    current: usize,
+    line: usize,
+}
+
+impl Scanner<'_> {
+    fn unexpected(&self, expected: &'static str) -> ParseError {
+        return unexpected_at(self.source, self.current, self.line, expected);
+    }
+
+    fn peek(&self) -> Option<u8> {
+        return self.bytes.get(self.current).copied();
+    }
+
+    fn advance(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.current += 1;
+
+        if byte == b'\n' {
+            self.line += 1;
+        }
+
+        return Some(byte);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.peek() {
+            self.advance();
+        }
+    }
+
+    fn expect(&mut self, byte: u8, expected: &'static str) -> Result<(), ParseError> {
+        if self.peek() != Some(byte) {
+            return Err(self.unexpected(expected));
+        }
+
+        self.advance();
+        return Ok(());
+    }
+
+    // Consumes a string whose opening quote was already read and returns
+    // the raw bytes between the quotes.
+    fn scan_string(&mut self) -> Result<&[u8], ParseError> {
+        let start = self.current;
+        let start_line = self.line;
+
+        loop {
+            match self.advance() {
+                Some(b'"') => {
+                    return Ok(&self.bytes[start..self.current - 1]);
+                }
+                Some(b'\\') => {
+                    self.advance();
+                }
+                Some(_) => {}
+                None => {
+                    return Err(ParseError {
+                        kind: ParseErrorKind::UnterminatedString {
+                            start_line,
+                            start_column: column_at(self.source, start - 1),
+                        },
+                        line: self.line,
+                        column: column_at(self.source, self.current),
+                    });
+                }
+            }
+        }
+    }
+
+    // Consumes a key whose opening quote was already read and returns it
+    // with its escapes decoded, as the parser would.
+    fn scan_key(&mut self) -> Result<String, ParseError> {
+        let start = self.current - 1;
+        let raw = self.scan_string()?;
+
+        if !raw.contains(&b'\\') {
+            return Ok(self.source[start + 1..self.current - 1].to_string());
+        }
+
+        match parse_span(self.source, start, self.current)? {
+            Some(JsonValue::String(key)) => {
+                return Ok(key);
+            }
+            _ => {
+                return Err(self.unexpected("a key"));
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Result<(), ParseError> {
+        if matches!(self.peek(), Some(b',' | b'}' | b']' | b':') | None) {
+            return Err(self.unexpected("a value"));
+        }
+
+        match self.advance() {
+            Some(b'"') => {
+                self.scan_string()?;
+            }
+            Some(b'{' | b'[') => {
+                let mut depth = 1;
+
+                while depth > 0 {
+                    match self.advance() {
+                        Some(b'{' | b'[') => depth += 1,
+                        Some(b'}' | b']') => depth -= 1,
+                        Some(b'"') => {
+                            self.scan_string()?;
+                        }
+                        Some(_) => {}
+                        None => return Err(self.unexpected("']' or '}'")),
+                    }
+                }
+            }
+            _ => {
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n') {
+                        break;
+                    }
+                    self.advance();
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    // After a member or element: consumes the `,` before the next one and
+    // returns true, or returns false at the closing `close`.
+    fn next_member(&mut self, close: u8) -> Result<bool, ParseError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b',') => {
+                self.advance();
+                return Ok(true);
+            }
+            Some(byte) if byte == close => {
+                self.advance();
+                return Ok(false);
+            }
+            _ => {
+                return Err(self.unexpected("',' or the end of the container"));
+            }
+        }
+    }
+
+    // Returns the byte span of the value at `segments` below the current
+    // position, or `None` once it's clear the value doesn't exist.
+    fn find(&mut self, segments: &[String]) -> Result<Option<(usize, usize)>, ParseError> {
+        self.skip_whitespace();
+
+        let (segment, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                let start = self.current;
+                self.skip_value()?;
+                return Ok(Some((start, self.current)));
+            }
+        };
+
+        match self.peek() {
+            Some(b'{') => {
+                self.advance();
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    return Ok(None);
+                }
+
+                // The parser keeps the last of duplicate keys, so the rest
+                // of the object is scanned for a later match.
+                let mut found = None;
+
+                loop {
+                    self.skip_whitespace();
+                    self.expect(b'"', "a key")?;
+                    let matched = self.scan_key()? == *segment;
+                    self.skip_whitespace();
+                    self.expect(b':', "':' after a key")?;
+                    self.skip_whitespace();
+
+                    if matched {
+                        found = Some((self.current, self.line));
+                    }
+                    self.skip_value()?;
+
+                    if !self.next_member(b'}')? {
+                        break;
+                    }
+                }
+
+                match found {
+                    Some((current, line)) => {
+                        self.current = current;
+                        self.line = line;
+                        return self.find(rest);
+                    }
+                    None => {
+                        return Ok(None);
+                    }
+                }
+            }
+            Some(b'[') => {
+                let index = match parse_array_index(segment) {
+                    Some(index) => index,
+                    None => return Ok(None),
+                };
+
+                self.advance();
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    return Ok(None);
+                }
+
+                for position in 0.. {
+                    if position == index {
+                        return self.find(rest);
+                    }
+
+                    self.skip_whitespace();
+                    self.skip_value()?;
+
+                    if !self.next_member(b']')? {
+                        break;
+                    }
+                }
+
+                return Ok(None);
+            }
+            _ => {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+// Parses the value at `source[start..end]`, with errors positioned in
+// `source`.
+fn parse_span(source: &str, start: usize, end: usize) -> Result<Option<JsonValue>, ParseError> {
+    // The parser only accepts a container at the top level, so the value
+    // is parsed as the single element of an array.
+    let mut parser = Parser::new(format!("[{}]", &source[start..end]));
+    match parser.parse() {
+        Ok(Json::Array(mut values)) => {
+            return Ok(values.pop());
+        }
+        Ok(Json::Object(_)) => {
+            return Ok(None);
+        }
+        Err(mut error) => {
+            error.relocate(source, start);
+            return Err(error);
+        }
+    }
+}
+
+/// Finds the value at a JSON Pointer in `source` without parsing the rest
+/// of the document. Subtrees off the path are only scanned for their end
+/// and only the matched value is built. Keys are compared with their
+/// escapes decoded, and where an object repeats a key the last one wins,
+/// as in a full parse, so each object on the path is scanned to its end.
+/// Arrays on the path aren't read past the matched element, so input after
+/// it is never checked for errors.
+pub fn extract_pointer(source: &str, pointer: &str) -> Result<Option<JsonValue>, ParseError> {
+    let segments: Vec<String> = match parse_pointer(pointer) {
+        Some(segments) => segments
+            .into_iter()
+            .map(|segment| match segment {
+                PathSegment::Key(key) => key,
+                PathSegment::Index(index) => index.to_string(),
+            })
+            .collect(),
+        None => {
+            return Err(ParseError {
+                kind: ParseErrorKind::InvalidPointer {
+                    pointer: pointer.to_string(),
+                },
+                line: 1,
+                column: 1,
+            });
+        }
+    };
+
+    let mut scanner = Scanner {
+        source,
+        bytes: source.as_bytes(),
+        current: 0,
+        line: 1,
+    };
+
+    let (start, end) = match scanner.find(&segments)? {
+        Some(span) => span,
+        None => return Ok(None),
+    };
+
+    return parse_span(source, start, end);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::extract::extract_pointer;
+    use crate::json;
+    use crate::parser::ParseErrorKind;
+
+    #[test]
+    fn escaped_keys_match_their_decoded_form() {
+        let source = r#"{"a\"b": 1, "café": {"\/x": [2]}, "tab\t": 3, "😀": 4}"#;
+
+        assert_eq!(extract_pointer(source, "/a\"b"), Ok(Some(json!(1))));
+        assert_eq!(extract_pointer(source, "/café/~1x/0"), Ok(Some(json!(2))));
+        assert_eq!(extract_pointer(source, "/tab\t"), Ok(Some(json!(3))));
+        assert_eq!(extract_pointer(source, "/😀"), Ok(Some(json!(4))));
+        assert_eq!(extract_pointer(source, "/caf\\u00e9"), Ok(None));
+    }
+
+    #[test]
+    fn the_last_duplicate_key_wins() {
+        let source = r#"{"a": 1, "b": {"c": 2}, "a": {"x": 3}, "b": {"c": 4}}"#;
+
+        assert_eq!(extract_pointer(source, "/a"), Ok(Some(json!({"x": 3}))));
+        assert_eq!(extract_pointer(source, "/a/x"), Ok(Some(json!(3))));
+        assert_eq!(extract_pointer(source, "/b/c"), Ok(Some(json!(4))));
+        // An escaped spelling of the same key is the same key.
+        assert_eq!(
+            extract_pointer(r#"{"ab": 1, "a\u0062": 2}"#, "/ab"),
+            Ok(Some(json!(2)))
+        );
+    }
+
+    #[test]
+    fn input_after_the_matching_element_is_not_read() {
+        let source = r#"[{"a": {"b": 1, "c": 2}}, {"rest": [1, 2 garbage"#;
+        assert_eq!(extract_pointer(source, "/0/a/b"), Ok(Some(json!(1))));
+
+        // Looking for a later duplicate reads the rest of the object.
+        let source = r#"[{"a": {"b": 1, "c": 2 garbage}}]"#;
+        assert!(extract_pointer(source, "/0/a/b").is_err());
+    }
+
+    #[test]
+    fn bad_escapes_in_keys_are_errors() {
+        let error = extract_pointer("{\n  \"a\\x\": 1}", "/a").unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::InvalidEscape {
+                escape: "x".to_string()
+            }
+        );
+        assert_eq!(error.line, 2);
+    }
+}
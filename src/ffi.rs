@@ -0,0 +1,527 @@
+// C bindings, declared in include/mini_json.h. Values are borrowed from
+// their document and stay valid until it's freed. No entry point lets a
+// panic unwind into C: each one catches it and returns its failure value.
+use std::{
+    ffi::{c_char, c_int, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use crate::json::{Json, JsonValue};
+use crate::serialize::{OutputStyle, SerializeOptions};
+
+pub const MINI_JSON_NULL: c_int = 0;
+pub const MINI_JSON_BOOLEAN: c_int = 1;
+pub const MINI_JSON_INTEGER: c_int = 2;
+pub const MINI_JSON_FLOAT: c_int = 3;
+pub const MINI_JSON_STRING: c_int = 4;
+pub const MINI_JSON_ARRAY: c_int = 5;
+pub const MINI_JSON_OBJECT: c_int = 6;
+
+const OK: c_int = 0;
+const FAILED: c_int = -1;
+
+/// A parsed document, owned by C until `mini_json_free`.
+pub struct MiniJsonDoc {
+    root: JsonValue,
+}
+
+/// Where and why parsing failed. `message` is owned by the error and freed
+/// by `mini_json_error_free`; `line` and `column` are 0 when the input
+/// couldn't be read as text at all.
+#[repr(C)]
+pub struct MiniJsonError {
+    pub line: usize,
+    pub column: usize,
+    pub message: *mut c_char,
+}
+
+fn guard<T>(failed: T, f: impl FnOnce() -> T) -> T {
+    return panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(failed);
+}
+
+unsafe fn set_error(err: *mut MiniJsonError, line: usize, column: usize, message: String) {
+    if err.is_null() {
+        return;
+    }
+
+    *err = MiniJsonError {
+        line,
+        column,
+        message: CString::new(message).map_or(ptr::null_mut(), CString::into_raw),
+    };
+}
+
+/// Parses `len` bytes of UTF-8 at `input`, which needn't be NUL-terminated.
+/// On success stores a new document in `out` and returns 0; otherwise fills
+/// in `err`, if it isn't null, and returns -1.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes, `out` must be writable and
+/// `err` must be writable or null.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_parse(
+    input: *const c_char,
+    len: usize,
+    out: *mut *mut MiniJsonDoc,
+    err: *mut MiniJsonError,
+) -> c_int {
+    return guard(FAILED, || {
+        if input.is_null() || out.is_null() {
+            set_error(err, 0, 0, "input and out can't be null".to_string());
+            return FAILED;
+        }
+
+        let bytes = slice::from_raw_parts(input.cast::<u8>(), len);
+        let source = match std::str::from_utf8(bytes) {
+            Ok(source) => source,
+            Err(error) => {
+                set_error(err, 0, 0, format!("Input isn't UTF-8: {}", error));
+                return FAILED;
+            }
+        };
+
+        match source.parse::<Json>() {
+            Ok(json) => {
+                let root = JsonValue::from(json);
+                *out = Box::into_raw(Box::new(MiniJsonDoc { root }));
+                return OK;
+            }
+            Err(error) => {
+                set_error(err, error.line, error.column, error.kind.to_string());
+                return FAILED;
+            }
+        }
+    });
+}
+
+/// Frees a document from `mini_json_parse`. Null is ignored.
+///
+/// # Safety
+///
+/// `doc` must come from `mini_json_parse` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_free(doc: *mut MiniJsonDoc) {
+    guard((), || {
+        if !doc.is_null() {
+            drop(Box::from_raw(doc));
+        }
+    });
+}
+
+/// Frees the message of an error filled in by `mini_json_parse`, leaving
+/// it null.
+///
+/// # Safety
+///
+/// `err` must be null or point to an error filled in by `mini_json_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_error_free(err: *mut MiniJsonError) {
+    guard((), || {
+        if !err.is_null() && !(*err).message.is_null() {
+            drop(CString::from_raw((*err).message));
+            (*err).message = ptr::null_mut();
+        }
+    });
+}
+
+/// The document's root value, which lives as long as the document.
+///
+/// # Safety
+///
+/// `doc` must be null or a live document.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_root(doc: *const MiniJsonDoc) -> *const JsonValue {
+    return guard(ptr::null(), || match doc.as_ref() {
+        Some(doc) => &doc.root,
+        None => ptr::null(),
+    });
+}
+
+/// One of the `MINI_JSON_*` kind constants, or -1 for a null pointer.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_type(value: *const JsonValue) -> c_int {
+    return guard(FAILED, || match value.as_ref() {
+        Some(JsonValue::Null) => MINI_JSON_NULL,
+        Some(JsonValue::Boolean(_)) => MINI_JSON_BOOLEAN,
+        Some(JsonValue::Integer(_)) => MINI_JSON_INTEGER,
+        Some(JsonValue::Float(_)) => MINI_JSON_FLOAT,
+        Some(JsonValue::String(_)) => MINI_JSON_STRING,
+        Some(JsonValue::Array(_)) => MINI_JSON_ARRAY,
+        Some(JsonValue::Object(_)) => MINI_JSON_OBJECT,
+        None => FAILED,
+    });
+}
+
+/// The member of an object at the `key_len` bytes of UTF-8 at `key`, or
+/// null if there's no such member or `value` isn't an object.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document, and `key` must
+/// point to `key_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_object_get(
+    value: *const JsonValue,
+    key: *const c_char,
+    key_len: usize,
+) -> *const JsonValue {
+    return guard(ptr::null(), || {
+        if key.is_null() {
+            return ptr::null();
+        }
+        let key = match std::str::from_utf8(slice::from_raw_parts(key.cast::<u8>(), key_len)) {
+            Ok(key) => key,
+            Err(_) => return ptr::null(),
+        };
+
+        match value.as_ref() {
+            Some(JsonValue::Object(object)) => object.get(key).map_or(ptr::null(), ptr::from_ref),
+            _ => ptr::null(),
+        }
+    });
+}
+
+/// The number of elements in an array or members in an object, or 0 for
+/// anything else.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_len(value: *const JsonValue) -> usize {
+    return guard(0, || match value.as_ref() {
+        Some(JsonValue::Array(array)) => array.len(),
+        Some(JsonValue::Object(object)) => object.len(),
+        _ => 0,
+    });
+}
+
+/// The element of an array at `index`, or null if it's out of bounds or
+/// `value` isn't an array.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_array_get(
+    value: *const JsonValue,
+    index: usize,
+) -> *const JsonValue {
+    return guard(ptr::null(), || match value.as_ref() {
+        Some(JsonValue::Array(array)) => array.get(index).map_or(ptr::null(), ptr::from_ref),
+        _ => ptr::null(),
+    });
+}
+
+/// Stores a boolean in `out` and returns 0, or returns -1 if `value` isn't
+/// a boolean.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document, and `out` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_get_bool(value: *const JsonValue, out: *mut bool) -> c_int {
+    return guard(FAILED, || match value.as_ref() {
+        Some(JsonValue::Boolean(boolean)) if !out.is_null() => {
+            *out = *boolean;
+            OK
+        }
+        _ => FAILED,
+    });
+}
+
+/// Stores an integer in `out` and returns 0, or returns -1 if `value` isn't
+/// an integer.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document, and `out` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_get_integer(value: *const JsonValue, out: *mut i64) -> c_int {
+    return guard(FAILED, || match value.as_ref() {
+        Some(JsonValue::Integer(integer)) if !out.is_null() => {
+            *out = *integer as i64;
+            OK
+        }
+        _ => FAILED,
+    });
+}
+
+/// Stores a number in `out` and returns 0, or returns -1 if `value` isn't
+/// a number. Integers are converted, so large ones may lose precision.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document, and `out` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_get_double(value: *const JsonValue, out: *mut f64) -> c_int {
+    return guard(FAILED, || {
+        let number = match value.as_ref() {
+            Some(JsonValue::Float(float)) => *float,
+            Some(JsonValue::Integer(integer)) => *integer as f64,
+            _ => return FAILED,
+        };
+        if out.is_null() {
+            return FAILED;
+        }
+
+        *out = number;
+        return OK;
+    });
+}
+
+/// Points `out` at a string's UTF-8 bytes and stores their count in
+/// `out_len`, returning 0, or returns -1 if `value` isn't a string. The
+/// bytes aren't NUL-terminated and may contain NULs; they belong to the
+/// document.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document, and `out` and
+/// `out_len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_get_string(
+    value: *const JsonValue,
+    out: *mut *const c_char,
+    out_len: *mut usize,
+) -> c_int {
+    return guard(FAILED, || match value.as_ref() {
+        Some(JsonValue::String(string)) if !out.is_null() && !out_len.is_null() => {
+            *out = string.as_ptr().cast::<c_char>();
+            *out_len = string.len();
+            OK
+        }
+        _ => FAILED,
+    });
+}
+
+/// Serializes a value as a new NUL-terminated string, indented when
+/// `pretty` isn't 0. Free it with `mini_json_string_free`. Returns null
+/// if `value` is null.
+///
+/// # Safety
+///
+/// `value` must be null or a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_to_string(
+    value: *const JsonValue,
+    pretty: c_int,
+) -> *mut c_char {
+    return guard(ptr::null_mut(), || {
+        let value = match value.as_ref() {
+            Some(value) => value,
+            None => return ptr::null_mut(),
+        };
+        let style = match pretty {
+            0 => OutputStyle::Minified,
+            _ => OutputStyle::Pretty,
+        };
+
+        // Parsed documents hold no NaN or infinite floats, and serialized
+        // JSON escapes NUL, so neither step can fail.
+        let text = match value.to_string_with(&SerializeOptions::new(style)) {
+            Ok(text) => text,
+            Err(_) => return ptr::null_mut(),
+        };
+        return CString::new(text).map_or(ptr::null_mut(), CString::into_raw);
+    });
+}
+
+/// Frees a string from `mini_json_to_string`. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must come from `mini_json_to_string` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn mini_json_string_free(string: *mut c_char) {
+    guard((), || {
+        if !string.is_null() {
+            drop(CString::from_raw(string));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, CStr};
+    use std::{ptr, slice};
+
+    use crate::ffi::*;
+    use crate::json::JsonValue;
+
+    // Parses `source`, returning the document or the error's position and
+    // message.
+    fn parse(source: &[u8]) -> Result<*mut MiniJsonDoc, (usize, usize, String)> {
+        let mut doc = ptr::null_mut();
+        let mut err = MiniJsonError {
+            line: 0,
+            column: 0,
+            message: ptr::null_mut(),
+        };
+
+        unsafe {
+            if mini_json_parse(source.as_ptr().cast(), source.len(), &mut doc, &mut err) == 0 {
+                return Ok(doc);
+            }
+
+            let message = CStr::from_ptr(err.message).to_string_lossy().into_owned();
+            mini_json_error_free(&mut err);
+            assert!(err.message.is_null());
+            return Err((err.line, err.column, message));
+        }
+    }
+
+    unsafe fn get(value: *const JsonValue, key: &str) -> *const JsonValue {
+        return mini_json_object_get(value, key.as_ptr().cast(), key.len());
+    }
+
+    unsafe fn string(value: *const JsonValue) -> Option<Vec<u8>> {
+        let mut bytes: *const c_char = ptr::null();
+        let mut len = 0;
+        if mini_json_get_string(value, &mut bytes, &mut len) != 0 {
+            return None;
+        }
+        return Some(slice::from_raw_parts(bytes.cast::<u8>(), len).to_vec());
+    }
+
+    #[test]
+    fn parses_navigates_and_frees_a_document() {
+        let doc =
+            parse(br#"{"name": "a\u0000b", "items": [true, -3, 2.5, null], "nested": {"k": []}}"#)
+                .unwrap();
+
+        unsafe {
+            let root = mini_json_root(doc);
+            assert_eq!(mini_json_type(root), MINI_JSON_OBJECT);
+            assert_eq!(mini_json_len(root), 3);
+
+            let name = get(root, "name");
+            assert_eq!(mini_json_type(name), MINI_JSON_STRING);
+            assert_eq!(string(name), Some(b"a\0b".to_vec()));
+
+            let items = get(root, "items");
+            assert_eq!(mini_json_type(items), MINI_JSON_ARRAY);
+            assert_eq!(mini_json_len(items), 4);
+
+            let mut boolean = false;
+            assert_eq!(
+                mini_json_get_bool(mini_json_array_get(items, 0), &mut boolean),
+                0
+            );
+            assert!(boolean);
+
+            let mut integer = 0;
+            let mut double = 0.0;
+            let negative = mini_json_array_get(items, 1);
+            assert_eq!(mini_json_type(negative), MINI_JSON_INTEGER);
+            assert_eq!(mini_json_get_integer(negative, &mut integer), 0);
+            assert_eq!(integer, -3);
+            assert_eq!(mini_json_get_double(negative, &mut double), 0);
+            assert_eq!(double, -3.0);
+
+            let float = mini_json_array_get(items, 2);
+            assert_eq!(mini_json_type(float), MINI_JSON_FLOAT);
+            assert_eq!(mini_json_get_double(float, &mut double), 0);
+            assert_eq!(double, 2.5);
+            assert_eq!(mini_json_get_integer(float, &mut integer), -1);
+
+            assert_eq!(
+                mini_json_type(mini_json_array_get(items, 3)),
+                MINI_JSON_NULL
+            );
+            assert!(mini_json_array_get(items, 4).is_null());
+
+            let nested = get(get(root, "nested"), "k");
+            assert_eq!(mini_json_type(nested), MINI_JSON_ARRAY);
+            assert_eq!(mini_json_len(nested), 0);
+
+            mini_json_free(doc);
+        }
+    }
+
+    #[test]
+    fn wrong_kinds_and_null_pointers_fail() {
+        let doc = parse(b"[1, \"s\"]").unwrap();
+
+        unsafe {
+            let root = mini_json_root(doc);
+            let one = mini_json_array_get(root, 0);
+            let mut boolean = false;
+
+            assert!(get(root, "k").is_null());
+            assert!(mini_json_array_get(one, 0).is_null());
+            assert_eq!(mini_json_len(one), 0);
+            assert_eq!(mini_json_get_bool(one, &mut boolean), -1);
+            assert_eq!(string(one), None);
+            assert_eq!(string(mini_json_array_get(root, 1)), Some(b"s".to_vec()));
+            assert_eq!(mini_json_get_bool(one, ptr::null_mut()), -1);
+
+            assert_eq!(mini_json_type(ptr::null()), -1);
+            assert!(mini_json_root(ptr::null()).is_null());
+            assert!(get(ptr::null(), "k").is_null());
+            assert!(mini_json_object_get(root, ptr::null(), 1).is_null());
+            assert!(mini_json_to_string(ptr::null(), 0).is_null());
+
+            mini_json_free(doc);
+            mini_json_free(ptr::null_mut());
+            mini_json_string_free(ptr::null_mut());
+            mini_json_error_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn errors_give_the_position_and_message() {
+        assert_eq!(
+            parse(b"{\n  \"a\": tru\n}").unwrap_err(),
+            (2, 11, "Unexpected '\\n', expected 'true'".to_string())
+        );
+        assert_eq!(
+            parse(b"").unwrap_err(),
+            (1, 1, "Empty document".to_string())
+        );
+
+        let (line, column, message) = parse(b"[\"\xff\"]").unwrap_err();
+        assert_eq!((line, column), (0, 0));
+        assert!(message.starts_with("Input isn't UTF-8: "), "{}", message);
+
+        unsafe {
+            let mut doc = ptr::null_mut();
+            assert_eq!(
+                mini_json_parse(ptr::null(), 0, &mut doc, ptr::null_mut()),
+                -1
+            );
+            assert!(doc.is_null());
+        }
+    }
+
+    #[test]
+    fn serializes_minified_or_pretty() {
+        let doc = parse(br#"[{"a": [1, "x"]}]"#).unwrap();
+
+        unsafe {
+            let root = mini_json_root(doc);
+            for (pretty, expected) in [
+                (0, "[{\"a\":[1,\"x\"]}]"),
+                (
+                    1,
+                    "[\n  {\n    \"a\": [\n      1,\n      \"x\"\n    ]\n  }\n]",
+                ),
+            ] {
+                let text = mini_json_to_string(root, pretty);
+                assert_eq!(CStr::from_ptr(text).to_str(), Ok(expected));
+                mini_json_string_free(text);
+            }
+
+            mini_json_free(doc);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::json::JsonValue;
+use crate::path::{parse_array_index, PathSegment};
+
+// Flattened keys join object keys with the separator and append array
+// indices as `[i]`. Inside a key, a backslash, `[`, `]` and the separator are
+// escaped with a backslash so the result can be split unambiguously. An
+// empty key is written `[]`, which can't be an index, so it still takes up
+// a segment of its own.
+fn escape_key(key: &str, sep: &str) -> String {
+    if key.is_empty() {
+        return "[]".to_string();
+    }
+
+    let mut escaped = String::with_capacity(key.len());
+    let mut rest = key;
+
+    while let Some(c) = rest.chars().next() {
+        if !sep.is_empty() && rest.starts_with(sep) {
+            escaped.push('\\');
+            escaped.push_str(sep);
+            rest = &rest[sep.len()..];
+            continue;
+        }
+
+        if c == '\\' || c == '[' || c == ']' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    return escaped;
+}
+
+fn flatten_value(
+    value: &JsonValue,
+    sep: &str,
+    prefix: &mut String,
+    entries: &mut HashMap<String, JsonValue>,
+) {
+    match value {
+        JsonValue::Object(object) if !object.is_empty() => {
+            for (key, member) in object {
+                let len = prefix.len();
+                if len > 0 {
+                    prefix.push_str(sep);
+                }
+                prefix.push_str(&escape_key(key, sep));
+
+                flatten_value(member, sep, prefix, entries);
+
+                prefix.truncate(len);
+            }
+        }
+        JsonValue::Array(array) if !array.is_empty() => {
+            for (index, element) in array.iter().enumerate() {
+                let len = prefix.len();
+                prefix.push_str(&format!("[{}]", index));
+
+                flatten_value(element, sep, prefix, entries);
+
+                prefix.truncate(len);
+            }
+        }
+        _ => {
+            entries.insert(prefix.clone(), value.clone());
+        }
+    }
+}
+
+impl JsonValue {
+    /// Flattens a tree into a single map, so `{"a": {"b": [1, 2]}}` becomes
+    /// `{"a.b[0]": 1, "a.b[1]": 2}` with `"."` as the separator. Empty
+    /// objects and arrays are kept as values, and a non-container root
+    /// produces a single entry with an empty key. Backslash escapes the
+    /// separator and brackets inside keys, and an empty key is written `[]`,
+    /// so `{"": {"a.b": 1}}` becomes `{"[].a\.b": 1}`.
+    pub fn flatten(&self, sep: &str) -> HashMap<String, JsonValue> {
+        let mut entries = HashMap::new();
+        flatten_value(self, sep, &mut String::new(), &mut entries);
+
+        return entries;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnflattenError {
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for UnflattenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[Unflatten error at '{}']: {}", self.key, self.message)
+    }
+}
+
+impl std::error::Error for UnflattenError {}
+
+enum Node {
+    Empty,
+    Leaf(JsonValue),
+    Object(HashMap<String, Node>),
+    Array(BTreeMap<usize, Node>),
+}
+
+fn unflatten_error(key: &str, message: &str) -> UnflattenError {
+    UnflattenError {
+        key: key.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn parse_flat_key(key: &str, sep: &str) -> Result<Vec<PathSegment>, UnflattenError> {
+    let mut segments = Vec::new();
+    let mut rest = key;
+    let mut expect_key = !key.starts_with('[') || key.starts_with("[]");
+
+    while expect_key || !rest.is_empty() {
+        if expect_key && rest.starts_with("[]") {
+            segments.push(PathSegment::Key(String::new()));
+            rest = &rest[2..];
+            expect_key = false;
+        } else if expect_key {
+            let mut name = String::new();
+
+            while let Some(c) = rest.chars().next() {
+                if c == '\\' {
+                    rest = &rest[1..];
+                    if !sep.is_empty() && rest.starts_with(sep) {
+                        name.push_str(sep);
+                        rest = &rest[sep.len()..];
+                    } else if let Some(escaped) = rest.chars().next() {
+                        name.push(escaped);
+                        rest = &rest[escaped.len_utf8()..];
+                    } else {
+                        return Err(unflatten_error(key, "Dangling escape"));
+                    }
+                } else if c == '[' || (!sep.is_empty() && rest.starts_with(sep)) {
+                    break;
+                } else {
+                    name.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+
+            if name.is_empty() {
+                return Err(unflatten_error(key, "Empty key, which is written '[]'"));
+            }
+
+            segments.push(PathSegment::Key(name));
+            expect_key = false;
+        } else if rest.starts_with('[') {
+            let end = match rest.find(']') {
+                Some(end) => end,
+                None => return Err(unflatten_error(key, "Unclosed '['")),
+            };
+
+            match parse_array_index(&rest[1..end]) {
+                Some(index) => segments.push(PathSegment::Index(index)),
+                None => return Err(unflatten_error(key, "Invalid array index")),
+            }
+
+            rest = &rest[end + 1..];
+        } else if !sep.is_empty() && rest.starts_with(sep) {
+            rest = &rest[sep.len()..];
+            expect_key = true;
+        } else {
+            return Err(unflatten_error(key, "Expect separator or '['"));
+        }
+    }
+
+    return Ok(segments);
+}
+
+fn insert_node(
+    node: &mut Node,
+    segments: &[PathSegment],
+    value: JsonValue,
+    key: &str,
+) -> Result<(), UnflattenError> {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return Err(unflatten_error(key, "Value is also a parent of other keys")),
+    };
+
+    let child = match (node, segment) {
+        (Node::Object(object), PathSegment::Key(name)) => {
+            object.entry(name.clone()).or_insert_with(|| new_node(rest))
+        }
+        (Node::Array(array), PathSegment::Index(index)) => {
+            array.entry(*index).or_insert_with(|| new_node(rest))
+        }
+        _ => {
+            return Err(unflatten_error(key, "Used as both an object and an array"));
+        }
+    };
+
+    if rest.is_empty() {
+        if !matches!(child, Node::Empty) {
+            return Err(unflatten_error(key, "Value is also a parent of other keys"));
+        }
+
+        *child = Node::Leaf(value);
+        return Ok(());
+    }
+
+    if let Node::Empty | Node::Leaf(_) = child {
+        return Err(unflatten_error(key, "Value is also a parent of other keys"));
+    }
+
+    return insert_node(child, rest, value, key);
+}
+
+fn new_node(rest: &[PathSegment]) -> Node {
+    match rest.first() {
+        Some(PathSegment::Key(_)) => Node::Object(HashMap::new()),
+        Some(PathSegment::Index(_)) => Node::Array(BTreeMap::new()),
+        None => Node::Empty,
+    }
+}
+
+fn build_value(node: Node, sep: &str, key: &mut String) -> Result<JsonValue, UnflattenError> {
+    match node {
+        Node::Empty => {
+            return Ok(JsonValue::Null);
+        }
+        Node::Leaf(value) => {
+            return Ok(value);
+        }
+        Node::Object(object) => {
+            let mut result = HashMap::with_capacity(object.len());
+
+            for (name, child) in object {
+                let len = key.len();
+                if len > 0 {
+                    key.push_str(sep);
+                }
+                key.push_str(&escape_key(&name, sep));
+
+                let value = build_value(child, sep, key)?;
+                result.insert(name, value);
+
+                key.truncate(len);
+            }
+
+            return Ok(JsonValue::Object(result));
+        }
+        Node::Array(array) => {
+            let mut result = Vec::with_capacity(array.len());
+
+            for (index, child) in array {
+                let len = key.len();
+                key.push_str(&format!("[{}]", result.len()));
+
+                if index != result.len() {
+                    return Err(unflatten_error(key, "Missing array index"));
+                }
+                result.push(build_value(child, sep, key)?);
+
+                key.truncate(len);
+            }
+
+            return Ok(JsonValue::Array(result));
+        }
+    }
+}
+
+/// Rebuilds a nested tree from keys produced by `JsonValue::flatten`. Keys
+/// that put a value where another key expects a container, or that use a
+/// path as both an object and an array, are errors. Array indices must be
+/// contiguous from zero; a gap is reported as a missing index rather than
+/// filled with null.
+pub fn unflatten(
+    entries: &HashMap<String, JsonValue>,
+    sep: &str,
+) -> Result<JsonValue, UnflattenError> {
+    if let Some(root) = entries.get("") {
+        if entries.len() > 1 {
+            return Err(unflatten_error(
+                "",
+                "Root value is also a parent of other keys",
+            ));
+        }
+
+        return Ok(root.clone());
+    }
+
+    let mut keys: Vec<&String> = entries.keys().collect();
+    keys.sort();
+
+    let mut root: Option<Node> = None;
+
+    for key in keys {
+        let segments = parse_flat_key(key, sep)?;
+        let node = root.get_or_insert_with(|| new_node(&segments));
+        insert_node(node, &segments, entries[key].clone(), key)?;
+    }
+
+    match root {
+        Some(root) => {
+            return build_value(root, sep, &mut String::new());
+        }
+        None => {
+            return Ok(JsonValue::Object(HashMap::new()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::flatten::unflatten;
+    use crate::json;
+    use crate::json::JsonValue;
+
+    fn entries(pairs: &[(&str, JsonValue)]) -> HashMap<String, JsonValue> {
+        return pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+    }
+
+    #[test]
+    fn nests_through_arrays_of_objects() {
+        let value = json!({
+            "a": {"b": [1, 2]},
+            "rows": [{"id": 1, "tags": ["x"]}, {"id": 2, "tags": []}],
+            "empty": {}
+        });
+
+        assert_eq!(
+            value.flatten("."),
+            entries(&[
+                ("a.b[0]", json!(1)),
+                ("a.b[1]", json!(2)),
+                ("rows[0].id", json!(1)),
+                ("rows[0].tags[0]", json!("x")),
+                ("rows[1].id", json!(2)),
+                ("rows[1].tags", json!([])),
+                ("empty", json!({})),
+            ])
+        );
+        assert_eq!(
+            json!([[1], {"a": null}]).flatten("/"),
+            entries(&[("[0][0]", json!(1)), ("[1]/a", json!(null))])
+        );
+    }
+
+    #[test]
+    fn scalar_roots_have_an_empty_key() {
+        assert_eq!(json!(5).flatten("."), entries(&[("", json!(5))]));
+        assert_eq!(json!([]).flatten("."), entries(&[("", json!([]))]));
+    }
+
+    #[test]
+    fn escapes_separators_brackets_and_backslashes() {
+        let value = json!({"a.b": {"c[0]": 1, "d\\": 2}, "x::y": 3});
+
+        assert_eq!(
+            value.flatten("."),
+            entries(&[
+                ("a\\.b.c\\[0\\]", json!(1)),
+                ("a\\.b.d\\\\", json!(2)),
+                ("x::y", json!(3)),
+            ])
+        );
+        assert_eq!(
+            value.flatten("::"),
+            entries(&[
+                ("a.b::c\\[0\\]", json!(1)),
+                ("a.b::d\\\\", json!(2)),
+                ("x\\::y", json!(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_keys_keep_their_segment() {
+        assert_eq!(json!({"": 1}).flatten("."), entries(&[("[]", json!(1))]));
+        assert_eq!(
+            json!({"": {"b": 1}, "b": 2}).flatten("."),
+            entries(&[("[].b", json!(1)), ("b", json!(2))])
+        );
+        assert_eq!(
+            json!({"a": {"": [{"": 1}]}, "[]": 2}).flatten("."),
+            entries(&[("a.[][0].[]", json!(1)), ("\\[\\]", json!(2))])
+        );
+    }
+
+    #[test]
+    fn empty_key_segments_must_be_written_as_brackets() {
+        for key in ["a..b", "a.", ".a"] {
+            let error = unflatten(&entries(&[(key, json!(1))]), ".").unwrap_err();
+            assert_eq!(error.key, key);
+        }
+        assert_eq!(
+            unflatten(&entries(&[("a.[].b", json!(1))]), ".").unwrap(),
+            json!({"a": {"": {"b": 1}}})
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+use crate::json::{Json, JsonValue};
+use crate::serialize::{NonFiniteFloats, OutputStyle, SerializeOptions};
+use crate::sha256::Sha256;
+
+// Floats hash by bit pattern, with `-0.0` folded into `0.0` since the two
+// compare equal and every NaN folded into one canonical NaN.
+fn float_bits(value: f64) -> u64 {
+    if value == 0.0 {
+        return 0.0f64.to_bits();
+    }
+    if value.is_nan() {
+        return f64::NAN.to_bits();
+    }
+
+    return value.to_bits();
+}
+
+pub(crate) fn contains_nan(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Float(value) => value.is_nan(),
+        JsonValue::Array(array) => array.iter().any(contains_nan),
+        JsonValue::Object(object) => object.values().any(contains_nan),
+        _ => false,
+    }
+}
+
+fn contains_negative_zero(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Float(value) => *value == 0.0 && value.is_sign_negative(),
+        JsonValue::Array(array) => array.iter().any(contains_negative_zero),
+        JsonValue::Object(object) => object.values().any(contains_negative_zero),
+        _ => false,
+    }
+}
+
+fn fold_negative_zero(value: &mut JsonValue) {
+    match value {
+        JsonValue::Float(float) if *float == 0.0 => {
+            *float = 0.0;
+        }
+        JsonValue::Array(array) => {
+            array.iter_mut().for_each(fold_negative_zero);
+        }
+        JsonValue::Object(object) => {
+            object.values_mut().for_each(fold_negative_zero);
+        }
+        _ => {}
+    }
+}
+
+impl JsonValue {
+    /// Feeds the tree into `hasher` so that values equal under `==` hash
+    /// equally. Object members are hashed in sorted key order, so insertion
+    /// order never matters. `JsonValue` doesn't implement `Hash` itself
+    /// because NaN makes `==` non-reflexive; use `HashableJson` for set and
+    /// map keys.
+    pub fn structural_hash(&self, hasher: &mut impl Hasher) {
+        match self {
+            JsonValue::Null => {
+                hasher.write_u8(0);
+            }
+            JsonValue::Boolean(value) => {
+                hasher.write_u8(1);
+                value.hash(hasher);
+            }
+            JsonValue::Integer(value) => {
+                hasher.write_u8(2);
+                value.hash(hasher);
+            }
+            JsonValue::Float(value) => {
+                hasher.write_u8(3);
+                hasher.write_u64(float_bits(*value));
+            }
+            JsonValue::String(value) => {
+                hasher.write_u8(4);
+                value.hash(hasher);
+            }
+            JsonValue::Array(array) => {
+                hasher.write_u8(5);
+                hasher.write_usize(array.len());
+                for element in array {
+                    element.structural_hash(hasher);
+                }
+            }
+            JsonValue::Object(object) => {
+                let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+                members.sort_by(|a, b| a.0.cmp(b.0));
+
+                hasher.write_u8(6);
+                hasher.write_usize(members.len());
+                for (key, member) in members {
+                    key.hash(hasher);
+                    member.structural_hash(hasher);
+                }
+            }
+        }
+    }
+}
+
+// The canonical form fingerprints are taken over: minified, with members
+// sorted by key and floats in their shortest round-tripping form.
+fn canonical_options() -> SerializeOptions {
+    let mut options = SerializeOptions::new(OutputStyle::Minified);
+    options.sort_keys = true;
+    options.non_finite_floats = NonFiniteFloats::Literal;
+
+    return options;
+}
+
+impl JsonValue {
+    /// The SHA-256 of the value's canonical serialization: minified, with
+    /// object members sorted by key and each float in the shortest form
+    /// that reads back as the same float. Unlike `structural_hash`, it's
+    /// stable across platforms and releases, so it can be stored.
+    ///
+    /// Documents that parse to equal values share a fingerprint however
+    /// they were spelled, so `1e2` and `100.0` match, and so do `-0.0` and
+    /// `0.0`, which are written as `0.0`. Integers and floats stay
+    /// distinct, as they do under `==`, so `100` doesn't match `100.0`.
+    /// NaN and the infinities are written as `NaN`, `Infinity` and
+    /// `-Infinity`.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        // The tree is only copied when it has a `-0.0` to fold.
+        if contains_negative_zero(self) {
+            let mut folded = self.clone();
+            fold_negative_zero(&mut folded);
+            return folded.fingerprint();
+        }
+
+        let mut sha = Sha256::new();
+        // Writing to the hash can't fail, and neither can `Literal` floats.
+        let _ = self.write_fmt_with(&mut sha, &canonical_options());
+
+        return sha.finish();
+    }
+}
+
+impl Json {
+    /// The same as the root value's `JsonValue::fingerprint`.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        return JsonValue::from(self.clone()).fingerprint();
+    }
+}
+
+/// A `JsonValue` known to contain no NaN, which makes `==` an equivalence
+/// relation and lets the value implement `Eq` and `Hash`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashableJson(JsonValue);
+
+impl HashableJson {
+    /// Wraps `value`, or returns `None` if a NaN appears anywhere in it.
+    pub fn new(value: JsonValue) -> Option<HashableJson> {
+        if contains_nan(&value) {
+            return None;
+        }
+
+        return Some(HashableJson(value));
+    }
+
+    pub fn into_inner(self) -> JsonValue {
+        return self.0;
+    }
+}
+
+impl Eq for HashableJson {}
+
+impl Hash for HashableJson {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.structural_hash(state);
+    }
+}
+
+impl Deref for HashableJson {
+    type Target = JsonValue;
+
+    fn deref(&self) -> &JsonValue {
+        return &self.0;
+    }
+}
+
+impl From<HashableJson> for JsonValue {
+    fn from(value: HashableJson) -> JsonValue {
+        return value.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{HashMap, HashSet};
+    use std::hash::Hasher;
+
+    use crate::hash::HashableJson;
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::parse_from_str;
+
+    fn hash(value: &JsonValue) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.structural_hash(&mut hasher);
+        return hasher.finish();
+    }
+
+    // Rebuilds every object with its members inserted in a different order
+    // and into a map of a different capacity, so they iterate differently.
+    fn permute(value: &JsonValue, seed: usize) -> JsonValue {
+        match value {
+            JsonValue::Object(object) => {
+                let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+                members.sort_by(|a, b| a.0.cmp(b.0));
+                if !members.is_empty() {
+                    let len = members.len();
+                    members.rotate_left(seed % len);
+                }
+                if seed % 2 == 1 {
+                    members.reverse();
+                }
+
+                let mut permuted = HashMap::with_capacity(seed * 7);
+                for (key, member) in members {
+                    permuted.insert(key.clone(), permute(member, seed));
+                }
+                return JsonValue::Object(permuted);
+            }
+            JsonValue::Array(array) => {
+                return JsonValue::Array(
+                    array.iter().map(|element| permute(element, seed)).collect(),
+                );
+            }
+            _ => {
+                return value.clone();
+            }
+        }
+    }
+
+    #[test]
+    fn equal_values_hash_equally_whatever_the_key_order() {
+        let values = [
+            json!({"a": 1, "b": [true, null, {"x": 1.5, "y": "s"}], "c": {}, "d": {"e": {"f": -0.0}}}),
+            json!([{"k1": 1, "k2": 2, "k3": 3, "k4": 4, "k5": 5, "k6": 6, "k7": 7, "k8": 8}]),
+            json!({"": "", "é": [], "日本": {"a": [1, 2], "b": [2, 1]}}),
+        ];
+
+        for value in &values {
+            for seed in 0..20 {
+                let permuted = permute(value, seed);
+                assert_eq!(&permuted, value);
+                assert_eq!(hash(&permuted), hash(value), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn different_values_usually_hash_differently() {
+        let values = [
+            json!(null),
+            json!(false),
+            json!(0),
+            json!(0.0),
+            json!(""),
+            json!([]),
+            json!({}),
+            json!([[]]),
+            json!([null]),
+            json!({"a": 1}),
+            json!({"a": 2}),
+            json!({"b": 1}),
+            json!([1, 2]),
+            json!([2, 1]),
+            json!(["ab", "c"]),
+            json!(["a", "bc"]),
+        ];
+        let hashes: HashSet<u64> = values.iter().map(hash).collect();
+
+        assert_eq!(hashes.len(), values.len());
+    }
+
+    #[test]
+    fn zeroes_share_a_hash_and_nans_are_rejected() {
+        assert_eq!(json!(0.0), json!(-0.0));
+        assert_eq!(hash(&json!(0.0)), hash(&json!(-0.0)));
+
+        assert_eq!(HashableJson::new(json!({"a": [1, f64::NAN]})), None);
+        assert_eq!(HashableJson::new(json!(f64::NAN)), None);
+        assert!(HashableJson::new(json!([f64::INFINITY])).is_some());
+    }
+
+    #[test]
+    fn hashable_values_deduplicate_in_a_set() {
+        let documents = [
+            json!({"id": 1, "tags": ["a"]}),
+            permute(&json!({"id": 1, "tags": ["a"]}), 1),
+            json!({"id": 1, "tags": ["a", "a"]}),
+            json!({"id": 1.0, "tags": ["a"]}),
+        ];
+
+        let set: HashSet<HashableJson> = documents
+            .iter()
+            .map(|document| HashableJson::new(document.clone()).unwrap())
+            .collect();
+        assert_eq!(set.len(), 3);
+
+        let wrapped = HashableJson::new(json!([1])).unwrap();
+        assert_eq!(wrapped.len(), Some(1));
+        assert_eq!(wrapped.into_inner(), json!([1]));
+    }
+
+    fn fingerprint(value: &JsonValue) -> String {
+        return value
+            .fingerprint()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+    }
+
+    #[test]
+    fn fingerprints_hash_the_canonical_text() {
+        assert_eq!(
+            fingerprint(&json!({})),
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        // SHA-256 of `{"a":[1,1e2],"b":{"c":null}}`.
+        assert_eq!(
+            fingerprint(&json!({"b": {"c": null}, "a": [1, 100.0]})),
+            "ec36b0ba292613da0734543278050e9c395863bb71919df084963b82f695f541"
+        );
+
+        let document = parse_from_str(r#"{"b": {"c": null}, "a": [1, 1e2]}"#).unwrap();
+        assert_eq!(
+            document.fingerprint(),
+            JsonValue::from(document.clone()).fingerprint()
+        );
+    }
+
+    #[test]
+    fn permuted_keys_share_a_fingerprint() {
+        let value = json!({
+            "id": 7,
+            "tags": ["a", "b"],
+            "owner": {"name": "ada", "k1": 1, "k2": 2, "k3": 3, "k4": 4, "k5": 5}
+        });
+
+        for seed in 0..20 {
+            assert_eq!(fingerprint(&permute(&value, seed)), fingerprint(&value));
+        }
+    }
+
+    #[test]
+    fn negative_zero_shares_the_fingerprint_of_zero() {
+        let negative = parse_from_str("[-0.0, {\"a\": [-0.0]}]").unwrap();
+        let positive = parse_from_str("[0.0, {\"a\": [0e5]}]").unwrap();
+
+        assert_eq!(negative, positive);
+        assert_eq!(negative.fingerprint(), positive.fingerprint());
+        assert_eq!(fingerprint(&json!(-0.0)), fingerprint(&json!(0.0)));
+        assert_ne!(fingerprint(&json!(-0.0)), fingerprint(&json!(0)));
+        assert_ne!(fingerprint(&json!(-1e-300)), fingerprint(&json!(0.0)));
+    }
+
+    #[test]
+    fn near_misses_get_different_fingerprints() {
+        let value = json!({"id": 7, "tags": ["a", "b"], "price": 100.0});
+        let near_misses = [
+            json!({"id": 8, "tags": ["a", "b"], "price": 100.0}),
+            json!({"id": 7, "tags": ["b", "a"], "price": 100.0}),
+            json!({"id": 7, "tags": ["a", "b"], "price": 100}),
+            json!({"id": 7, "tags": ["a", "b"], "price": 100.00000000000001}),
+            json!({"id": 7, "tags": ["a", "b"], "Price": 100.0}),
+            json!({"id": 7, "tags": ["a", "b"]}),
+        ];
+
+        for near_miss in &near_misses {
+            assert_ne!(fingerprint(near_miss), fingerprint(&value), "{}", near_miss);
+        }
+        assert_eq!(
+            fingerprint(&json!([f64::NAN])),
+            fingerprint(&json!([-f64::NAN]))
+        );
+    }
+}
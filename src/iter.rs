@@ -0,0 +1,292 @@
+use std::{collections::hash_map, slice, vec};
+
+use crate::json::{Json, JsonValue};
+
+// Iterating a value yields the elements of an array or the values of an
+// object, and nothing for scalars. Use `iter` or `into_entries` to get an
+// object's keys alongside its values.
+
+enum IntoIterKind {
+    Array(vec::IntoIter<JsonValue>),
+    Object(hash_map::IntoValues<String, JsonValue>),
+    Empty,
+}
+
+pub struct IntoIter {
+    kind: IntoIterKind,
+}
+
+impl Iterator for IntoIter {
+    type Item = JsonValue;
+
+    fn next(&mut self) -> Option<JsonValue> {
+        match &mut self.kind {
+            IntoIterKind::Array(iter) => iter.next(),
+            IntoIterKind::Object(iter) => iter.next(),
+            IntoIterKind::Empty => None,
+        }
+    }
+}
+
+enum IterKind<'a> {
+    Array(slice::Iter<'a, JsonValue>),
+    Object(hash_map::Values<'a, String, JsonValue>),
+    Empty,
+}
+
+pub struct Iter<'a> {
+    kind: IterKind<'a>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a JsonValue;
+
+    fn next(&mut self) -> Option<&'a JsonValue> {
+        match &mut self.kind {
+            IterKind::Array(iter) => iter.next(),
+            IterKind::Object(iter) => iter.next(),
+            IterKind::Empty => None,
+        }
+    }
+}
+
+impl IntoIterator for JsonValue {
+    type Item = JsonValue;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        let kind = match self {
+            JsonValue::Array(array) => IntoIterKind::Array(array.into_iter()),
+            JsonValue::Object(object) => IntoIterKind::Object(object.into_values()),
+            _ => IntoIterKind::Empty,
+        };
+
+        return IntoIter { kind };
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonValue {
+    type Item = &'a JsonValue;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        let kind = match self {
+            JsonValue::Array(array) => IterKind::Array(array.iter()),
+            JsonValue::Object(object) => IterKind::Object(object.values()),
+            _ => IterKind::Empty,
+        };
+
+        return Iter { kind };
+    }
+}
+
+impl IntoIterator for Json {
+    type Item = JsonValue;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        let kind = match self {
+            Json::Array(array) => IntoIterKind::Array(array.into_iter()),
+            Json::Object(object) => IntoIterKind::Object(object.into_values()),
+        };
+
+        return IntoIter { kind };
+    }
+}
+
+impl<'a> IntoIterator for &'a Json {
+    type Item = &'a JsonValue;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        let kind = match self {
+            Json::Array(array) => IterKind::Array(array.iter()),
+            Json::Object(object) => IterKind::Object(object.values()),
+        };
+
+        return Iter { kind };
+    }
+}
+
+impl JsonValue {
+    /// Moves the members out of an object as `(key, value)` pairs. Yields
+    /// nothing for non-objects.
+    pub fn into_entries(self) -> impl Iterator<Item = (String, JsonValue)> {
+        let object = match self {
+            JsonValue::Object(object) => Some(object),
+            _ => None,
+        };
+
+        return object.into_iter().flatten();
+    }
+}
+
+impl FromIterator<JsonValue> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = JsonValue>>(iter: I) -> JsonValue {
+        return JsonValue::Array(iter.into_iter().collect());
+    }
+}
+
+// A later pair replaces an earlier one with the same key, as in `insert`.
+impl FromIterator<(String, JsonValue)> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = (String, JsonValue)>>(iter: I) -> JsonValue {
+        return JsonValue::Object(iter.into_iter().collect());
+    }
+}
+
+// `Extend` can't report an error, so extending the wrong kind of value
+// panics; `push` and `insert` return a `WrongKindError` instead.
+impl Extend<JsonValue> for JsonValue {
+    /// Appends to an array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value isn't an array.
+    fn extend<I: IntoIterator<Item = JsonValue>>(&mut self, iter: I) {
+        match self {
+            JsonValue::Array(array) => array.extend(iter),
+            _ => panic!("{}", self.wrong_kind("array")),
+        }
+    }
+}
+
+impl Extend<(String, JsonValue)> for JsonValue {
+    /// Inserts members into an object, replacing any with the same key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value isn't an object.
+    fn extend<I: IntoIterator<Item = (String, JsonValue)>>(&mut self, iter: I) {
+        match self {
+            JsonValue::Object(object) => object.extend(iter),
+            _ => panic!("{}", self.wrong_kind("object")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::json;
+    use crate::json::{parse_from_str, JsonValue};
+
+    #[test]
+    fn a_for_loop_sums_an_array() {
+        let mut sum = 0;
+        for element in &json!([1, 2, 3, 4]) {
+            if let JsonValue::Integer(integer) = element {
+                sum += integer;
+            }
+        }
+        assert_eq!(sum, 10);
+
+        let parsed = parse_from_str("[5, 6]").unwrap();
+        let mut sum = 0;
+        for element in &parsed {
+            if let JsonValue::Integer(integer) = element {
+                sum += integer;
+            }
+        }
+        for element in parsed {
+            if let JsonValue::Integer(integer) = element {
+                sum += integer;
+            }
+        }
+        assert_eq!(sum, 22);
+    }
+
+    #[test]
+    fn objects_yield_values_and_scalars_nothing() {
+        let object = json!({"a": 1, "b": 2});
+
+        let mut values: Vec<&JsonValue> = (&object).into_iter().collect();
+        values.sort_by_key(|value| value.to_string());
+        assert_eq!(values, [&json!(1), &json!(2)]);
+        assert_eq!(object.into_iter().count(), 2);
+
+        assert_eq!(json!(null).into_iter().count(), 0);
+        assert_eq!((&json!("abc")).into_iter().count(), 0);
+        assert_eq!(parse_from_str("{}").unwrap().into_iter().count(), 0);
+    }
+
+    #[test]
+    fn entries_collect_into_a_btree_map() {
+        let object = json!({"b": [2], "a": 1});
+
+        let map: BTreeMap<String, JsonValue> = object.into_entries().collect();
+        assert_eq!(
+            map.into_iter().collect::<Vec<_>>(),
+            [("a".to_string(), json!(1)), ("b".to_string(), json!([2]))]
+        );
+        assert_eq!(json!([1]).into_entries().count(), 0);
+    }
+
+    #[test]
+    fn owned_iteration_moves_values_out() {
+        let array = json!(["a long enough string"]);
+        let address = match &array {
+            JsonValue::Array(array) => match &array[0] {
+                JsonValue::String(string) => string.as_ptr(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        match array.into_iter().next() {
+            Some(JsonValue::String(string)) => assert_eq!(string.as_ptr(), address),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn collects_an_object_from_zipped_keys_and_values() {
+        let keys = ["a", "b", "a"].map(String::from);
+        let values = [json!(1), json!([true]), json!(3)];
+
+        let object: JsonValue = keys.into_iter().zip(values).collect();
+        assert_eq!(object, json!({"a": 3, "b": [true]}));
+    }
+
+    #[test]
+    fn maps_and_collects_an_array() {
+        let parsed = JsonValue::from(parse_from_str("[1, 2, 3]").unwrap());
+
+        let doubled: JsonValue = parsed
+            .elements()
+            .map(|element| match element {
+                JsonValue::Integer(integer) => JsonValue::Integer(integer * 2),
+                other => other.clone(),
+            })
+            .collect();
+        assert_eq!(doubled, json!([2, 4, 6]));
+        assert_eq!(
+            std::iter::empty::<JsonValue>().collect::<JsonValue>(),
+            json!([])
+        );
+    }
+
+    #[test]
+    fn extends_a_parsed_array_and_object() {
+        let mut array = JsonValue::from(parse_from_str(r#"["a", {"b": null}]"#).unwrap());
+        array.extend([json!(1), json!("c")]);
+        array.extend(Vec::<JsonValue>::new());
+        assert_eq!(array, json!(["a", {"b": null}, 1, "c"]));
+
+        let mut object = JsonValue::from(parse_from_str(r#"{"a": 1, "b": 2}"#).unwrap());
+        object.extend([("b".to_string(), json!(20)), ("c".to_string(), json!(30))]);
+        assert_eq!(object, json!({"a": 1, "b": 20, "c": 30}));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected array, found object")]
+    fn extending_an_object_with_elements_panics() {
+        json!({}).extend([json!(1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected object, found null")]
+    fn extending_a_scalar_with_members_panics() {
+        JsonValue::Null.extend([("k".to_string(), json!(1))]);
+    }
+}
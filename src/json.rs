@@ -1,9 +1,21 @@
-use std::{collections::HashMap, fmt, fs};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Read},
+    mem,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
 
-use crate::parser::Parser;
+use crate::parser::{column_at, ParseError, ParseErrorKind, ParseStats, Parser, ParserOptions};
 
-#[derive(Debug)]
+/// Any JSON value. Integers that fit in an `isize` are kept apart from
+/// floats, and objects don't keep their members' order.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum JsonValue {
+    #[default]
+    Null,
     String(String),
     Integer(isize),
     Float(f64),
@@ -12,105 +24,449 @@ pub enum JsonValue {
     Array(Vec<JsonValue>),
 }
 
-#[derive(Debug)]
+/// A parsed document, whose root is always an object or an array. Convert
+/// it with `JsonValue::from` to treat the root like any other value.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Json {
     Object(HashMap<String, JsonValue>),
     Array(Vec<JsonValue>),
 }
 
-pub fn parse_from_file(file_path: &str) -> std::io::Result<Json> {
-    let source = fs::read_to_string(file_path)?;
-    return Ok(parse_from_string(source));
+/// Returned when a value is used as a kind it isn't, such as indexing a
+/// string as an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrongKindError {
+    pub expected: &'static str,
+    pub found: &'static str,
 }
 
-pub fn parse_from_string(source: String) -> Json {
-    let mut parser = Parser::new(source);
-    return parser.parse();
+impl fmt::Display for WrongKindError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
 }
 
-fn print_value(depth: i32, json_value: &JsonValue, f: &mut fmt::Formatter) -> fmt::Result {
-    match json_value {
-        JsonValue::Boolean(val) => {
-            write!(f, "{val}")?;
+impl std::error::Error for WrongKindError {}
+
+/// Why a document couldn't be read: either reading failed or what was read
+/// isn't valid JSON. Each carries the file's path when there is one.
+#[derive(Debug)]
+pub enum JsonError {
+    Io {
+        path: Option<PathBuf>,
+        error: io::Error,
+    },
+    Parse {
+        path: Option<PathBuf>,
+        error: ParseError,
+    },
+}
+
+impl JsonError {
+    fn with_path(self, file_path: &Path) -> JsonError {
+        let path = Some(file_path.to_path_buf());
+
+        match self {
+            JsonError::Io { error, .. } => JsonError::Io { path, error },
+            JsonError::Parse { error, .. } => JsonError::Parse { path, error },
         }
-        JsonValue::Float(val) => {
-            write!(f, "{val}")?;
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonError::Io {
+                path: Some(path),
+                error,
+            } => write!(f, "{}: {}", path.display(), error),
+            JsonError::Io { path: None, error } => write!(f, "{}", error),
+            JsonError::Parse {
+                path: Some(path),
+                error,
+            } => write!(f, "{}: {}", path.display(), error),
+            JsonError::Parse { path: None, error } => write!(f, "{}", error),
         }
-        JsonValue::Integer(val) => {
-            write!(f, "{val}")?;
+    }
+}
+
+impl std::error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JsonError::Io { error, .. } => Some(error),
+            JsonError::Parse { error, .. } => Some(error),
         }
-        JsonValue::String(val) => {
-            write!(f, "\"{val}\"")?;
+    }
+}
+
+impl From<io::Error> for JsonError {
+    fn from(error: io::Error) -> JsonError {
+        return JsonError::Io { path: None, error };
+    }
+}
+
+impl From<ParseError> for JsonError {
+    fn from(error: ParseError) -> JsonError {
+        return JsonError::Parse { path: None, error };
+    }
+}
+
+impl JsonValue {
+    pub(crate) fn kind_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::String(_) => "string",
+            JsonValue::Integer(_) => "integer",
+            JsonValue::Float(_) => "float",
+            JsonValue::Boolean(_) => "boolean",
+            JsonValue::Object(_) => "object",
+            JsonValue::Array(_) => "array",
         }
-        JsonValue::Array(array) => {
-            writeln!(f, "[")?;
+    }
 
-            for val in array {
-                for _ in 0..depth {
-                    write!(f, "  ")?;
-                }
+    pub(crate) fn wrong_kind(&self, expected: &'static str) -> WrongKindError {
+        WrongKindError {
+            expected,
+            found: self.kind_name(),
+        }
+    }
 
-                print_value(depth + 1, val, f)?;
+    /// Returns the number of elements in an array, members in an object, or
+    /// characters (not bytes) in a string. Other values have no length.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            JsonValue::Array(array) => Some(array.len()),
+            JsonValue::Object(object) => Some(object.len()),
+            JsonValue::String(string) => Some(string.chars().count()),
+            _ => None,
+        }
+    }
 
-                writeln!(f, ",")?;
-            }
+    /// Returns `true` for empty arrays, objects and strings, and `false` for
+    /// everything else, including values without a length.
+    pub fn is_empty(&self) -> bool {
+        return self.len() == Some(0);
+    }
 
-            for _ in 0..depth - 1 {
-                write!(f, "  ")?;
-            }
-            write!(f, "]")?;
+    /// Moves the value out, leaving `Null` in its place.
+    pub fn take(&mut self) -> JsonValue {
+        return mem::take(self);
+    }
+
+    /// Stores `value` in place of the current value and returns the old one.
+    pub fn replace(&mut self, value: JsonValue) -> JsonValue {
+        return mem::replace(self, value);
+    }
+}
+
+/// Reads and parses the file at `file_path`. Errors carry the path.
+pub fn parse_from_file(file_path: impl AsRef<Path>) -> Result<Json, JsonError> {
+    let file_path = file_path.as_ref();
+
+    return fs::File::open(file_path)
+        .map_err(JsonError::from)
+        .and_then(parse_from_reader)
+        .map_err(|error| error.with_path(file_path));
+}
+
+/// Reads `reader` to the end and parses it. Input that isn't UTF-8 is an
+/// `io::ErrorKind::InvalidData` error.
+pub fn parse_from_reader(mut reader: impl Read) -> Result<Json, JsonError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    return Ok(source.parse::<Json>()?);
+}
+
+/// Parses `source`, which must hold an object or an array.
+///
+/// # Panics
+///
+/// Panics if `source` isn't valid JSON. Use `parse_from_str` to get the
+/// `ParseError` instead.
+pub fn parse_from_string(source: String) -> Json {
+    match Parser::new(source).parse() {
+        Ok(json) => {
+            return json;
+        }
+        Err(error) => {
+            panic!("{}", error);
         }
-        JsonValue::Object(object) => {
-            writeln!(f, "{{")?;
+    }
+}
 
-            for (key, val) in object {
-                for _ in 0..depth {
-                    write!(f, "  ")?;
-                }
-                write!(f, "\"{key}\": ")?;
+/// Parses `source`, which must hold an object or an array. The same as
+/// `source.parse::<Json>()`.
+pub fn parse_from_str(source: &str) -> Result<Json, ParseError> {
+    return source.parse();
+}
 
-                print_value(depth + 1, val, f)?;
+/// Parses `source`, which must hold an object or an array, following
+/// `options`.
+pub fn parse_from_str_with(source: &str, options: &ParserOptions) -> Result<Json, ParseError> {
+    return Parser::with_options(source.to_string(), *options).parse();
+}
 
-                writeln!(f, ",")?;
-            }
+/// Parses `source` like `parse_from_str`, also returning counts gathered
+/// along the way, so describing a document doesn't need a second pass over
+/// it.
+pub fn parse_with_stats(source: &str) -> Result<(Json, ParseStats), ParseError> {
+    let start = Instant::now();
+    let mut parser = Parser::new(source.to_string());
+    let json = parser.parse()?;
 
-            for _ in 0..depth - 1 {
-                write!(f, "  ")?;
-            }
-            write!(f, "}}")?;
+    let mut stats = parser.stats();
+    stats.elapsed = start.elapsed();
+    return Ok((json, stats));
+}
+
+/// Parses UTF-8 `bytes`, which must hold an object or an array. Bytes that
+/// aren't UTF-8 are an `InvalidUtf8` error at the first bad one.
+pub fn parse_from_bytes(bytes: &[u8]) -> Result<Json, ParseError> {
+    match std::str::from_utf8(bytes) {
+        Ok(source) => {
+            return source.parse();
+        }
+        Err(error) => {
+            // Everything before the bad byte is valid, so it can be used to
+            // find the line and column.
+            let valid = std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default();
+            return Err(ParseError {
+                kind: ParseErrorKind::InvalidUtf8 { error },
+                line: valid.matches('\n').count() + 1,
+                column: column_at(valid, valid.len()),
+            });
         }
     }
+}
+
+impl FromStr for Json {
+    type Err = ParseError;
 
-    return Ok(());
+    fn from_str(source: &str) -> Result<Json, ParseError> {
+        return Parser::new(source.to_string()).parse();
+    }
 }
 
-impl fmt::Display for Json {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &self {
-            Json::Object(object) => {
-                writeln!(f, "{{")?;
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, fs, io, path::Path};
+
+    use crate::json;
+    use crate::json::{
+        parse_from_file, parse_from_reader, parse_from_str, parse_with_stats, Json, JsonError,
+        JsonValue,
+    };
+    use crate::parser::{ParseError, ParseErrorKind, ParseStats};
+
+    #[test]
+    fn take_moves_a_subtree_and_leaves_null() {
+        let mut source = json!({"outer": {"inner": {"a": [1, 2]}}, "keep": true});
+
+        let inner = source.pointer_mut("/outer/inner").unwrap().take();
+        let mut moved = json!({});
+        moved.insert("inner", inner).unwrap();
+
+        assert_eq!(moved, json!({"inner": {"a": [1, 2]}}));
+        assert_eq!(source, json!({"outer": {"inner": null}, "keep": true}));
+        assert_eq!(JsonValue::Null.take(), JsonValue::Null);
+    }
+
+    #[test]
+    fn replace_returns_the_old_value() {
+        let mut source = json!({"a": [1], "b": "x"});
+
+        let old = source.pointer_mut("/a").unwrap().replace(json!({"new": 1}));
+        assert_eq!(old, json!([1]));
+        assert_eq!(source, json!({"a": {"new": 1}, "b": "x"}));
+
+        let root = source.replace(json!([]));
+        assert_eq!(root, json!({"a": {"new": 1}, "b": "x"}));
+        assert_eq!(source, json!([]));
+    }
+
+    #[test]
+    fn len_counts_elements_members_and_characters() {
+        assert_eq!(json!([1, [2, 3]]).len(), Some(2));
+        assert_eq!(json!({"a": 1}).len(), Some(1));
+        assert_eq!(json!("héllo").len(), Some(5));
+
+        for scalar in [json!(null), json!(true), json!(0), json!(1.5)] {
+            assert_eq!(scalar.len(), None, "{:?}", scalar);
+            assert!(!scalar.is_empty(), "{:?}", scalar);
+        }
+    }
+
+    #[test]
+    fn empty_strings_arrays_and_objects_are_empty() {
+        assert_eq!(json!("").len(), Some(0));
+        assert_eq!(json!([]).len(), Some(0));
+        assert!(json!("").is_empty());
+        assert!(json!([]).is_empty());
+        assert!(json!({}).is_empty());
+        assert!(!json!([""]).is_empty());
+        assert!(!json!(" ").is_empty());
+    }
+
+    #[test]
+    fn documents_parse_with_from_str() {
+        let document: Json = r#"{"a": [1, 2.5, "x"]}"#.parse().unwrap();
+        assert_eq!(JsonValue::from(document), json!({"a": [1, 2.5, "x"]}));
 
-                for (key, val) in object {
-                    write!(f, "  \"{key}\": ")?;
-                    print_value(2, val, f)?;
-                    writeln!(f, ",")?;
-                }
+        let error = "[1, 2".parse::<Json>().unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::UnexpectedEnd {
+                expected: "',' or ']'"
+            }
+        );
+        assert_eq!(parse_from_str("[1, 2").unwrap_err(), error);
+        assert!(matches!(
+            "".parse::<Json>(),
+            Err(ParseError {
+                kind: ParseErrorKind::EmptyDocument,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_errors_are_std_errors() {
+        fn parse(text: &str) -> Result<Json, Box<dyn std::error::Error>> {
+            return Ok(text.parse::<Json>()?);
+        }
+
+        assert!(parse("{}").is_ok());
+        let error = parse("{} x").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[Error at line 1, column 4]: Unexpected text after the document"
+        );
+    }
+
+    // A reader that fails the way a file without read permission does.
+    struct Denied;
 
-                writeln!(f, "}}")?;
+    impl io::Read for Denied {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+        }
+    }
+
+    #[test]
+    fn missing_files_are_io_errors_with_the_path() {
+        let path = std::env::temp_dir().join("mini-json-no-such-file.json");
+
+        match parse_from_file(&path) {
+            Err(JsonError::Io {
+                path: Some(found),
+                error,
+            }) => {
+                assert_eq!(found, path);
+                assert_eq!(error.kind(), io::ErrorKind::NotFound);
             }
-            Json::Array(array) => {
-                writeln!(f, "[")?;
+            other => panic!("expected an IO error, got {:?}", other),
+        }
+    }
 
-                for val in array {
-                    write!(f, "  ")?;
-                    print_value(2, val, f)?;
-                    writeln!(f, ",")?;
-                }
+    #[test]
+    fn malformed_files_are_parse_errors_with_the_path() {
+        let path = std::env::temp_dir().join(format!("mini-json-{}-bad.json", std::process::id()));
+        fs::write(&path, "{\n  \"a\": tru\n}").unwrap();
+        let result = parse_from_file(&path);
+        fs::remove_file(&path).unwrap();
 
-                writeln!(f, "]")?;
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}: [Error at line 2, column 11]: Unexpected '\\n', expected 'true'",
+                path.display()
+            )
+        );
+        match error {
+            JsonError::Parse {
+                path: Some(found),
+                error,
+            } => {
+                assert_eq!(found, path);
+                assert_eq!((error.line, error.column), (2, 11));
             }
+            other => panic!("expected a parse error, got {:?}", other),
         }
+    }
+
+    #[test]
+    fn reader_errors_have_no_path() {
+        let error = parse_from_reader(Denied).unwrap_err();
+        assert!(matches!(
+            &error,
+            JsonError::Io { path: None, error } if error.kind() == io::ErrorKind::PermissionDenied
+        ));
+        assert_eq!(error.to_string(), "permission denied");
+
+        let error = parse_from_reader("[1, 2".as_bytes()).unwrap_err();
+        assert!(matches!(&error, JsonError::Parse { path: None, .. }));
+        assert_eq!(
+            error.to_string(),
+            "[Error at line 1, column 5]: Unexpected end of input, expected ',' or ']'"
+        );
+    }
+
+    #[test]
+    fn json_errors_convert_and_have_sources() {
+        let parse_error = parse_from_str("[").unwrap_err();
+        let error = JsonError::from(parse_error.clone());
+        let source = error.source().unwrap();
+        assert_eq!(source.downcast_ref::<ParseError>(), Some(&parse_error));
+
+        let error = JsonError::from(io::Error::from(io::ErrorKind::NotFound));
+        let source = error.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<io::Error>().map(io::Error::kind),
+            Some(io::ErrorKind::NotFound)
+        );
+        assert!(!Path::new(&error.to_string()).is_absolute());
+    }
+
+    #[test]
+    fn parse_with_stats_counts_a_fixture_exactly() {
+        let source = r#"{"a": [1, 2.5, "x\n", true, null], "a": {"k\u00e9": "plain", "b": [[]]}, "c": false}"#;
+
+        let (json, stats) = parse_with_stats(source).unwrap();
+        assert_eq!(json, parse_from_str(source).unwrap());
+        assert_eq!(
+            ParseStats {
+                elapsed: Default::default(),
+                ..stats
+            },
+            ParseStats {
+                objects: 2,
+                arrays: 3,
+                strings: 2,
+                integers: 1,
+                floats: 1,
+                booleans: 2,
+                nulls: 1,
+                max_depth: 4,
+                // "x\n" and the key "ké", which both held escapes.
+                unescaped_string_bytes: 5,
+                duplicate_keys: 1,
+                elapsed: Default::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_with_stats_counts_every_replaced_duplicate() {
+        let (json, stats) = parse_with_stats(r#"[{"k": 1, "k": 2, "k": 3}, {"k": 4}]"#).unwrap();
+        assert_eq!(JsonValue::from(json), json!([{"k": 3}, {"k": 4}]));
+        assert_eq!(stats.duplicate_keys, 2);
+        assert_eq!(stats.integers, 4);
 
-        return Ok(());
+        assert_eq!(
+            parse_with_stats("[1,]").unwrap_err(),
+            parse_from_str("[1,]").unwrap_err()
+        );
     }
 }
@@ -0,0 +1,698 @@
+use std::collections::HashMap;
+
+use crate::json::{Json, JsonValue};
+use crate::parser::{column_at, unexpected_at, ParseError, ParseErrorKind, Parser, MAX_DEPTH};
+use crate::path::escape_pointer_token;
+
+/// Comments attached to one node, kept verbatim with their `//` or `/* */`
+/// delimiters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeComments {
+    /// Comments on the lines before the node.
+    pub leading: Vec<String>,
+    /// A comment after the node on the same line.
+    pub trailing: Option<String>,
+    /// Comments after a container's last element, before its closing
+    /// bracket.
+    pub dangling: Vec<String>,
+}
+
+/// A JSON document parsed with its comments, for editing files like
+/// `settings.json` without losing them. Edit `value`, then write it back
+/// with `to_string_pretty`.
+///
+/// Comments are keyed by the JSON Pointer of the node they belong to, so
+/// they follow a value as long as it stays at the same pointer. Objects
+/// keep their original key order, with added keys written after it in
+/// sorted order. Comments whose node was removed are written before the
+/// closing bracket of the nearest container that still exists, so none is
+/// ever dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsoncDocument {
+    pub value: JsonValue,
+    comments: HashMap<String, NodeComments>,
+    key_order: HashMap<String, Vec<String>>,
+    end: Vec<String>,
+}
+
+struct JsoncParser<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    current: usize,
+    line: usize,
+    depth: usize,
+    pending: Vec<String>,
+    comments: HashMap<String, NodeComments>,
+    key_order: HashMap<String, Vec<String>>,
+}
+
+impl<'a> JsoncParser<'a> {
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        return ParseError {
+            kind,
+            line: self.line,
+            column: column_at(self.source, self.current.min(self.bytes.len())),
+        };
+    }
+
+    fn unexpected(&self, expected: &'static str) -> ParseError {
+        return unexpected_at(self.source, self.current, self.line, expected);
+    }
+
+    fn peek(&self) -> Option<u8> {
+        return self.bytes.get(self.current).copied();
+    }
+
+    fn comment(&mut self) -> Result<String, ParseError> {
+        let start = self.current;
+
+        if self.bytes[self.current + 1] == b'/' {
+            while self.peek().is_some_and(|byte| byte != b'\n') {
+                self.current += 1;
+            }
+            return Ok(self.source[start..self.current].trim_end().to_string());
+        }
+
+        match self.source[start + 2..].find("*/") {
+            Some(end) => {
+                self.current = start + 2 + end + 2;
+                let comment = &self.source[start..self.current];
+                self.line += comment.matches('\n').count();
+                return Ok(comment.to_string());
+            }
+            None => {
+                self.current = self.bytes.len();
+                return Err(self.unexpected("'*/' to close the comment"));
+            }
+        }
+    }
+
+    fn at_comment(&self) -> bool {
+        return self.peek() == Some(b'/')
+            && matches!(self.bytes.get(self.current + 1), Some(b'/') | Some(b'*'));
+    }
+
+    // Skips whitespace, collecting comments as pending until a node claims
+    // them.
+    fn skip_whitespace(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                Some(b'\n') => {
+                    self.line += 1;
+                    self.current += 1;
+                }
+                Some(b' ') | Some(b'\t') | Some(b'\r') => {
+                    self.current += 1;
+                }
+                Some(b'/') if self.at_comment() => {
+                    let comment = self.comment()?;
+                    self.pending.push(comment);
+                }
+                _ => {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // After a value and its optional comma, claims a comment that starts on
+    // the same line as the value's trailing comment.
+    fn trailing(&mut self, pointer: &str) -> Result<bool, ParseError> {
+        let mut comma = false;
+
+        loop {
+            match self.peek() {
+                Some(b' ') | Some(b'\t') | Some(b'\r') => {
+                    self.current += 1;
+                }
+                Some(b',') if !comma => {
+                    comma = true;
+                    self.current += 1;
+                }
+                Some(b'/') if self.at_comment() => {
+                    let comment = self.comment()?;
+                    self.node(pointer).trailing = Some(comment);
+                    break;
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+
+        if !comma {
+            self.skip_whitespace()?;
+            if self.peek() == Some(b',') {
+                self.current += 1;
+                comma = true;
+            }
+        }
+
+        return Ok(comma);
+    }
+
+    fn node(&mut self, pointer: &str) -> &mut NodeComments {
+        return self.comments.entry(pointer.to_string()).or_default();
+    }
+
+    fn claim_leading(&mut self, pointer: &str) {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.node(pointer).leading.extend(pending);
+        }
+    }
+
+    fn claim_dangling(&mut self, pointer: &str) {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.node(pointer).dangling.extend(pending);
+        }
+    }
+
+    // Parses a scalar by handing its text to the regular parser, wrapped in
+    // an array since that only accepts containers at the top level.
+    fn scalar(&mut self) -> Result<JsonValue, ParseError> {
+        let start = self.current;
+
+        if self.peek() == Some(b'"') {
+            self.current += 1;
+            loop {
+                match self.peek() {
+                    None | Some(b'\n') => {
+                        return Err(self.error(ParseErrorKind::UnterminatedString {
+                            start_line: self.line,
+                            start_column: column_at(self.source, start),
+                        }));
+                    }
+                    Some(b'\\') => self.current += 2,
+                    Some(b'"') => {
+                        self.current += 1;
+                        break;
+                    }
+                    Some(_) => self.current += 1,
+                }
+            }
+        } else {
+            while self.peek().is_some_and(|byte| {
+                byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'+' | b'.')
+            }) {
+                self.current += 1;
+            }
+            if self.current == start {
+                return Err(self.unexpected("a value"));
+            }
+        }
+
+        let text = &self.source[start..self.current.min(self.bytes.len())];
+        match Parser::new(format!("[{}]", text)).parse() {
+            Ok(Json::Array(mut values)) if values.len() == 1 => {
+                return Ok(values.pop().unwrap_or_default());
+            }
+            Ok(_) => {
+                self.current = start;
+                return Err(self.unexpected("a value"));
+            }
+            Err(mut error) => {
+                error.relocate(self.source, start);
+                return Err(error);
+            }
+        }
+    }
+
+    // Called on entering an array or object, which must decrement `depth`
+    // on leaving it.
+    fn enter(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(ParseErrorKind::DepthLimitExceeded { limit: MAX_DEPTH }));
+        }
+
+        return Ok(());
+    }
+
+    fn value(&mut self, pointer: &str) -> Result<JsonValue, ParseError> {
+        match self.peek() {
+            Some(b'{') => {
+                self.current += 1;
+                self.enter()?;
+                let object = self.object(pointer);
+                self.depth -= 1;
+                return object;
+            }
+            Some(b'[') => {
+                self.current += 1;
+                self.enter()?;
+                let array = self.array(pointer);
+                self.depth -= 1;
+                return array;
+            }
+            Some(_) => {
+                return self.scalar();
+            }
+            None => {
+                return Err(self.unexpected("a value"));
+            }
+        }
+    }
+
+    fn object(&mut self, pointer: &str) -> Result<JsonValue, ParseError> {
+        let mut object = HashMap::new();
+        let mut order = Vec::new();
+
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(b'}') {
+                self.current += 1;
+                break;
+            }
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected("a key"));
+            }
+
+            let key = match self.scalar()? {
+                JsonValue::String(key) => key,
+                _ => unreachable!("a value starting with '\"' is a string"),
+            };
+            let child = format!("{}/{}", pointer, escape_pointer_token(&key));
+            self.claim_leading(&child);
+
+            self.skip_whitespace()?;
+            if self.peek() != Some(b':') {
+                return Err(self.unexpected("':' after a key"));
+            }
+            self.current += 1;
+            self.skip_whitespace()?;
+            // Comments between the colon and the value lead the member.
+            self.claim_leading(&child);
+
+            let member = self.value(&child)?;
+            if object.insert(key.clone(), member).is_some() {
+                return Err(self.error(ParseErrorKind::DuplicateKey { key }));
+            }
+            order.push(key);
+
+            if !self.trailing(&child)? {
+                self.skip_whitespace()?;
+                if self.peek() != Some(b'}') {
+                    return Err(self.unexpected("',' or '}' after a member"));
+                }
+            }
+        }
+
+        self.claim_dangling(pointer);
+        self.key_order.insert(pointer.to_string(), order);
+        return Ok(JsonValue::Object(object));
+    }
+
+    fn array(&mut self, pointer: &str) -> Result<JsonValue, ParseError> {
+        let mut array = Vec::new();
+
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(b']') {
+                self.current += 1;
+                break;
+            }
+
+            let child = format!("{}/{}", pointer, array.len());
+            self.claim_leading(&child);
+            array.push(self.value(&child)?);
+
+            if !self.trailing(&child)? {
+                self.skip_whitespace()?;
+                if self.peek() != Some(b']') {
+                    return Err(self.unexpected("',' or ']' after an element"));
+                }
+            }
+        }
+
+        self.claim_dangling(pointer);
+        return Ok(JsonValue::Array(array));
+    }
+}
+
+impl JsoncDocument {
+    /// Parses JSON with `//` and `/* */` comments and trailing commas.
+    /// Each comment is attached to the following member or element, to
+    /// the preceding one if it's on the same line, or to the enclosing
+    /// container if nothing follows it.
+    pub fn parse(source: &str) -> Result<JsoncDocument, ParseError> {
+        let mut parser = JsoncParser {
+            source,
+            bytes: source.as_bytes(),
+            current: 0,
+            line: 1,
+            depth: 0,
+            pending: Vec::new(),
+            comments: HashMap::new(),
+            key_order: HashMap::new(),
+        };
+
+        parser.skip_whitespace()?;
+        parser.claim_leading("");
+        let value = parser.value("")?;
+
+        parser.trailing("")?;
+        parser.skip_whitespace()?;
+        if parser.current < parser.bytes.len() {
+            return Err(parser.error(ParseErrorKind::TrailingCharacters));
+        }
+
+        return Ok(JsoncDocument {
+            value,
+            comments: parser.comments,
+            key_order: parser.key_order,
+            end: parser.pending,
+        });
+    }
+
+    pub fn comments(&self, pointer: &str) -> Option<&NodeComments> {
+        return self.comments.get(pointer);
+    }
+
+    /// Pretty-prints `value` with two-space indentation, each comment back
+    /// in place: leading comments on their own lines above their node and
+    /// trailing ones after it. NaN and infinite floats, which only edits
+    /// can introduce, are written as `null`.
+    pub fn to_string_pretty(&self) -> String {
+        let mut writer = JsoncWriter {
+            document: self,
+            out: String::new(),
+        };
+
+        let root = self.comments.get("");
+        if let Some(root) = root {
+            for comment in &root.leading {
+                writer.out.push_str(comment);
+                writer.out.push('\n');
+            }
+        }
+
+        writer.value(&self.value, "", 0);
+        if let Some(trailing) = root.and_then(|root| root.trailing.as_ref()) {
+            writer.out.push(' ');
+            writer.out.push_str(trailing);
+        }
+        writer.out.push('\n');
+
+        // A container root prints these itself.
+        if !matches!(self.value, JsonValue::Object(_) | JsonValue::Array(_)) {
+            let orphans = writer.orphans("", &self.value);
+            writer.comment_lines(&orphans, 0);
+            if let Some(root) = root {
+                writer.comment_lines(&root.dangling, 0);
+            }
+        }
+
+        for comment in &self.end {
+            writer.out.push_str(comment);
+            writer.out.push('\n');
+        }
+
+        return writer.out;
+    }
+}
+
+struct JsoncWriter<'a> {
+    document: &'a JsoncDocument,
+    out: String,
+}
+
+impl JsoncWriter<'_> {
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+    }
+
+    fn comment_lines(&mut self, comments: &[String], depth: usize) {
+        for comment in comments {
+            self.indent(depth);
+            self.out.push_str(comment);
+            self.out.push('\n');
+        }
+    }
+
+    // Comments under `pointer` whose node no longer exists in `value`, in
+    // pointer order. A child that exists but is no longer a container
+    // orphans the comments below it.
+    fn orphans(&self, pointer: &str, value: &JsonValue) -> Vec<String> {
+        let prefix = format!("{}/", pointer);
+        let mut orphaned: Vec<(&String, &NodeComments)> = self
+            .document
+            .comments
+            .iter()
+            .filter(|(child, _)| {
+                let rest = match child.strip_prefix(&prefix) {
+                    Some(rest) => rest,
+                    None => return false,
+                };
+                let (token, deeper) = match rest.split_once('/') {
+                    Some((token, _)) => (token, true),
+                    None => (rest, false),
+                };
+                match value.pointer(&format!("/{}", token)) {
+                    Some(JsonValue::Object(_)) | Some(JsonValue::Array(_)) => false,
+                    Some(_) => deeper,
+                    None => true,
+                }
+            })
+            .collect();
+        orphaned.sort_by_key(|(child, _)| *child);
+
+        let mut lines = Vec::new();
+        for (_, comments) in orphaned {
+            lines.extend(comments.leading.iter().cloned());
+            lines.extend(comments.trailing.iter().cloned());
+            lines.extend(comments.dangling.iter().cloned());
+        }
+
+        return lines;
+    }
+
+    fn value(&mut self, value: &JsonValue, pointer: &str, depth: usize) {
+        match value {
+            JsonValue::Object(object) => {
+                let mut keys: Vec<&String> = Vec::new();
+                if let Some(order) = self.document.key_order.get(pointer) {
+                    keys.extend(order.iter().filter(|key| object.contains_key(*key)));
+                }
+                let mut added: Vec<&String> =
+                    object.keys().filter(|key| !keys.contains(key)).collect();
+                added.sort();
+                keys.extend(added);
+
+                let children: Vec<(String, String, &JsonValue)> = keys
+                    .into_iter()
+                    .map(|key| {
+                        let child = format!("{}/{}", pointer, escape_pointer_token(key));
+                        let label = JsonValue::String(key.clone()).to_string();
+                        (child, label + ": ", &object[key])
+                    })
+                    .collect();
+
+                let orphans = self.orphans(pointer, value);
+                self.container(('{', '}'), children, orphans, pointer, depth);
+            }
+            JsonValue::Array(array) => {
+                let children: Vec<(String, String, &JsonValue)> = array
+                    .iter()
+                    .enumerate()
+                    .map(|(index, element)| {
+                        (format!("{}/{}", pointer, index), String::new(), element)
+                    })
+                    .collect();
+
+                let orphans = self.orphans(pointer, value);
+                self.container(('[', ']'), children, orphans, pointer, depth);
+            }
+            scalar => {
+                self.out.push_str(&scalar.to_string());
+            }
+        }
+    }
+
+    fn container(
+        &mut self,
+        (open, close): (char, char),
+        children: Vec<(String, String, &JsonValue)>,
+        orphans: Vec<String>,
+        pointer: &str,
+        depth: usize,
+    ) {
+        let document = self.document;
+        let dangling = document
+            .comments
+            .get(pointer)
+            .map(|comments| comments.dangling.as_slice())
+            .unwrap_or_default();
+
+        self.out.push(open);
+        if children.is_empty() && dangling.is_empty() && orphans.is_empty() {
+            self.out.push(close);
+            return;
+        }
+        self.out.push('\n');
+
+        let count = children.len();
+        for (index, (child, label, member)) in children.into_iter().enumerate() {
+            let comments = document.comments.get(&child);
+            if let Some(comments) = comments {
+                self.comment_lines(&comments.leading, depth + 1);
+            }
+
+            self.indent(depth + 1);
+            self.out.push_str(&label);
+            self.value(member, &child, depth + 1);
+            if index + 1 < count {
+                self.out.push(',');
+            }
+            if let Some(trailing) = comments.and_then(|comments| comments.trailing.as_ref()) {
+                self.out.push(' ');
+                self.out.push_str(trailing);
+            }
+            self.out.push('\n');
+        }
+
+        self.comment_lines(dangling, depth + 1);
+        self.comment_lines(&orphans, depth + 1);
+
+        self.indent(depth);
+        self.out.push(close);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::jsonc::JsoncDocument;
+
+    const SETTINGS: &str = r#"// Editor settings.
+{
+  // Font size in points.
+  "fontSize": 14, // was 12
+  /* Files to hide. */
+  "exclude": [
+    "target", // build output
+    "*.tmp",
+    // more to come
+  ],
+  "theme": {
+    "name": "dark" /* default */
+  }
+}
+// end of file
+"#;
+
+    #[test]
+    fn comments_attach_to_the_nearest_node() {
+        let document = JsoncDocument::parse(SETTINGS).unwrap();
+
+        assert_eq!(
+            document.value,
+            json!({"fontSize": 14, "exclude": ["target", "*.tmp"], "theme": {"name": "dark"}})
+        );
+        assert_eq!(
+            document.comments("").unwrap().leading,
+            ["// Editor settings."]
+        );
+
+        let font_size = document.comments("/fontSize").unwrap();
+        assert_eq!(font_size.leading, ["// Font size in points."]);
+        assert_eq!(font_size.trailing.as_deref(), Some("// was 12"));
+
+        let exclude = document.comments("/exclude").unwrap();
+        assert_eq!(exclude.leading, ["/* Files to hide. */"]);
+        assert_eq!(exclude.dangling, ["// more to come"]);
+        assert_eq!(
+            document.comments("/exclude/0").unwrap().trailing.as_deref(),
+            Some("// build output")
+        );
+        assert_eq!(
+            document
+                .comments("/theme/name")
+                .unwrap()
+                .trailing
+                .as_deref(),
+            Some("/* default */")
+        );
+        assert_eq!(document.comments("/exclude/1"), None);
+    }
+
+    #[test]
+    fn edited_documents_keep_every_comment_in_place() {
+        let mut document = JsoncDocument::parse(SETTINGS).unwrap();
+        *document.value.pointer_mut("/fontSize").unwrap() = json!(16);
+
+        assert_eq!(
+            document.to_string_pretty(),
+            r#"// Editor settings.
+{
+  // Font size in points.
+  "fontSize": 16, // was 12
+  /* Files to hide. */
+  "exclude": [
+    "target", // build output
+    "*.tmp"
+    // more to come
+  ],
+  "theme": {
+    "name": "dark" /* default */
+  }
+}
+// end of file
+"#
+        );
+    }
+
+    #[test]
+    fn comments_of_removed_nodes_move_to_their_container() {
+        let mut document = JsoncDocument::parse(SETTINGS).unwrap();
+        document
+            .value
+            .pointer_mut("/exclude")
+            .unwrap()
+            .pop()
+            .unwrap();
+        document
+            .value
+            .pointer_mut("")
+            .unwrap()
+            .remove("theme")
+            .unwrap();
+        document
+            .value
+            .pointer_mut("")
+            .unwrap()
+            .insert("added", json!(true))
+            .unwrap();
+
+        let written = document.to_string_pretty();
+        for comment in [
+            "// Editor settings.",
+            "// Font size in points.",
+            "// was 12",
+            "/* Files to hide. */",
+            "// build output",
+            "// more to come",
+            "/* default */",
+            "// end of file",
+        ] {
+            assert!(written.contains(comment), "{} in\n{}", comment, written);
+        }
+        assert!(written.contains("\"added\": true"));
+
+        let reparsed = JsoncDocument::parse(&written).unwrap();
+        assert_eq!(reparsed.value, document.value);
+    }
+
+    #[test]
+    fn scalar_roots_and_errors() {
+        let document = JsoncDocument::parse("/* a */ 1 // b\n// c\n").unwrap();
+
+        assert_eq!(document.value, json!(1));
+        assert_eq!(document.to_string_pretty(), "/* a */\n1 // b\n// c\n");
+        assert!(JsoncDocument::parse("{\"a\": 1 /* open").is_err());
+        assert!(JsoncDocument::parse("{} {}").is_err());
+    }
+}
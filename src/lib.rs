@@ -1,5 +1,184 @@
+//! A small JSON library: parsing into `Json` and `JsonValue`, serializing
+//! with `SerializeOptions`, and tools for querying, patching, merging and
+//! converting documents to and from other formats.
+//!
+//! ```
+//! use mini_json::{parse_from_str, JsonValue, OutputStyle, SerializeOptions};
+//!
+//! let json = parse_from_str(r#"{"name": "mini-json", "tags": ["json"]}"#).unwrap();
+//! let value = JsonValue::from(json);
+//!
+//! assert_eq!(
+//!     value.pointer("/tags/0"),
+//!     Some(&JsonValue::String("json".to_string()))
+//! );
+//!
+//! let options = SerializeOptions::new(OutputStyle::Minified);
+//! let tags = value.pointer("/tags").unwrap().to_string_with(&options);
+//! assert_eq!(tags, Ok(r#"["json"]"#.to_string()));
+//! ```
+//!
+//! The `mini-json` binary is built on this crate's public API alone.
+
+#![allow(clippy::needless_return)]
+
+mod aggregate;
+mod approx;
+mod array;
+mod base64;
+mod bson;
+mod builder;
+mod case;
+mod cbor;
+mod clean;
+mod coerce;
+mod color;
+mod convert;
+mod csv;
+mod diff_text;
+mod expand;
+mod extract;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod flatten;
+mod hash;
+mod iter;
 mod json;
+mod jsonc;
+mod macros;
+mod mapping;
+mod merge;
+mod number;
+mod object;
 mod parser;
+mod patch;
+mod path;
+mod preview;
+mod project;
+mod query;
+mod query_string;
+mod schema;
+mod search;
+mod serialize;
+mod sha256;
+mod sort;
+mod stats;
+#[cfg(feature = "toml")]
+mod toml;
+mod transform;
+mod walk;
+mod xml;
+#[cfg(feature = "yaml")]
+mod yaml;
 
+pub use aggregate::NumberSummary;
+pub use approx::ApproxOptions;
+pub use bson::from_bson_document;
+pub use bson::to_bson_document;
+pub use bson::BsonError;
+pub use builder::ArrayBuilder;
+pub use builder::ObjectBuilder;
+pub use case::KeyCase;
+pub use cbor::from_cbor;
+pub use cbor::to_cbor;
+pub use cbor::CborError;
+pub use cbor::CborOptions;
+pub use clean::PruneOptions;
+pub use clean::RedactOptions;
+pub use coerce::coerce_bool;
+pub use coerce::coerce_null;
+pub use coerce::coerce_number;
+pub use coerce::CoerceError;
+pub use coerce::CoercionRules;
+pub use color::strip_ansi;
+pub use color::ColorTheme;
+pub use csv::from_csv;
+pub use csv::to_csv;
+pub use csv::CsvError;
+pub use csv::CsvOptions;
+pub use csv::HeaderOrder;
+pub use csv::NestedCells;
+pub use diff_text::diff_text;
+pub use diff_text::DiffTextOptions;
+pub use expand::ExpandError;
+pub use extract::extract_pointer;
+pub use flatten::unflatten;
+pub use flatten::UnflattenError;
+pub use hash::HashableJson;
+pub use iter::IntoIter;
+pub use iter::Iter;
+pub use json::parse_from_bytes;
 pub use json::parse_from_file;
+pub use json::parse_from_reader;
+pub use json::parse_from_str;
+pub use json::parse_from_str_with;
 pub use json::parse_from_string;
+pub use json::parse_with_stats;
+pub use json::Json;
+pub use json::JsonError;
+pub use json::JsonValue;
+pub use json::WrongKindError;
+pub use jsonc::JsoncDocument;
+pub use jsonc::NodeComments;
+pub use mapping::FromJson;
+pub use mapping::FromJsonError;
+pub use mapping::ToJson;
+pub use merge::MergeStrategy;
+pub use number::JsonNumber;
+pub use object::CaseInsensitiveView;
+pub use parser::ParseError;
+pub use parser::ParseErrorKind;
+pub use parser::ParseStats;
+pub use parser::ParserOptions;
+pub use patch::diff;
+pub use patch::PatchError;
+pub use path::parse_path;
+pub use path::PathError;
+pub use path::PathSegment;
+pub use preview::PreviewOptions;
+pub use project::ProjectOptions;
+pub use query::QueryError;
+pub use query_string::from_query_string;
+pub use query_string::to_query_string;
+pub use query_string::DeepNesting;
+pub use query_string::QueryStringError;
+pub use query_string::QueryStringOptions;
+pub use schema::infer_schema;
+pub use schema::infer_schema_with;
+pub use schema::InferOptions;
+pub use schema::Schema;
+pub use schema::SchemaError;
+pub use schema::ValidationError;
+pub use serialize::write_atomically;
+pub use serialize::Indent;
+pub use serialize::NonFiniteFloatError;
+pub use serialize::NonFiniteFloats;
+pub use serialize::OutputStyle;
+pub use serialize::SerializeOptions;
+pub use sort::SortError;
+pub use sort::SortOrder;
+pub use stats::JsonStats;
+pub use walk::JsonPath;
+pub use walk::PathStep;
+pub use walk::WalkControl;
+pub use xml::from_xml;
+pub use xml::to_xml;
+pub use xml::XmlError;
+pub use xml::XmlOptions;
+
+#[cfg(feature = "derive")]
+pub use mini_json_derive::FromJson;
+#[cfg(feature = "derive")]
+pub use mini_json_derive::ToJson;
+#[cfg(feature = "toml")]
+pub use toml::from_toml_str;
+#[cfg(feature = "toml")]
+pub use toml::to_toml_string;
+#[cfg(feature = "toml")]
+pub use toml::TomlConvertError;
+#[cfg(feature = "yaml")]
+pub use yaml::from_yaml_str;
+#[cfg(feature = "yaml")]
+pub use yaml::to_yaml_string;
+#[cfg(feature = "yaml")]
+pub use yaml::YamlError;
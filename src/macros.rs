@@ -0,0 +1,123 @@
+/// Builds a `JsonValue` from JSON-like syntax.
+///
+/// Object keys are string literals or parenthesized expressions, `null`
+/// produces `JsonValue::Null`, and any other value is a Rust expression
+/// converted with `JsonValue::from`. Trailing commas are allowed.
+///
+/// ```
+/// use mini_json::json;
+///
+/// let name = "mini-json";
+/// let doc = json!({
+///     "name": name,
+///     "tags": ["a", "b"],
+///     "count": 1 + 2,
+///     "nested": {"ok": true, "missing": null},
+/// });
+/// ```
+#[macro_export]
+macro_rules! json {
+    (@array [$($elements:expr,)*]) => {
+        ::std::vec![$($elements,)*]
+    };
+    (@array [$($elements:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elements,)* $crate::JsonValue::Null,] $($($rest)*)?)
+    };
+    (@array [$($elements:expr,)*] [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elements,)* $crate::json!([$($array)*]),] $($($rest)*)?)
+    };
+    (@array [$($elements:expr,)*] {$($object:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elements,)* $crate::json!({$($object)*}),] $($($rest)*)?)
+    };
+    (@array [$($elements:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elements,)* $crate::JsonValue::from($value),] $($($rest)*)?)
+    };
+
+    (@object $object:ident) => {};
+    (@object $object:ident $key:tt : null $(, $($rest:tt)*)?) => {
+        $object.insert(::std::string::ToString::to_string(&$key), $crate::JsonValue::Null);
+        $crate::json!(@object $object $($($rest)*)?);
+    };
+    (@object $object:ident $key:tt : [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $object.insert(::std::string::ToString::to_string(&$key), $crate::json!([$($array)*]));
+        $crate::json!(@object $object $($($rest)*)?);
+    };
+    (@object $object:ident $key:tt : {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $object.insert(::std::string::ToString::to_string(&$key), $crate::json!({$($inner)*}));
+        $crate::json!(@object $object $($($rest)*)?);
+    };
+    (@object $object:ident $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $object.insert(::std::string::ToString::to_string(&$key), $crate::JsonValue::from($value));
+        $crate::json!(@object $object $($($rest)*)?);
+    };
+
+    (null) => {
+        $crate::JsonValue::Null
+    };
+    ([$($array:tt)*]) => {
+        $crate::JsonValue::Array($crate::json!(@array [] $($array)*))
+    };
+    ({$($object:tt)*}) => {
+        $crate::JsonValue::Object({
+            #[allow(unused_mut)]
+            let mut object = ::std::collections::HashMap::new();
+            $crate::json!(@object object $($object)*);
+            object
+        })
+    };
+    ($value:expr) => {
+        $crate::JsonValue::from($value)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::json::{parse_from_str, JsonValue};
+
+    fn parse(text: &str) -> JsonValue {
+        return JsonValue::from(parse_from_str(text).unwrap());
+    }
+
+    #[test]
+    fn builds_the_same_tree_as_parsing() {
+        let name = "mini-json";
+        let tags = vec![JsonValue::from("a"), JsonValue::from("b")];
+        let key = "dynamic";
+
+        let value = json!({
+            "name": name,
+            "tags": tags,
+            "count": 1 + 2,
+            "ratio": 0.5,
+            "nested": {"ok": true, "missing": null, "deeper": [[], {}, [null, -1]]},
+            (key): Some("x"),
+            "none": None::<i32>,
+        });
+        assert_eq!(
+            value,
+            parse(
+                r#"{"name": "mini-json", "tags": ["a", "b"], "count": 3, "ratio": 0.5,
+                    "nested": {"ok": true, "missing": null, "deeper": [[], {}, [null, -1]]},
+                    "dynamic": "x", "none": null}"#
+            )
+        );
+    }
+
+    #[test]
+    fn accepts_trailing_commas_and_bare_values() {
+        assert_eq!(json!([1, "two", null,]), parse(r#"[1, "two", null]"#));
+        assert_eq!(json!({"a": [1,],}), parse(r#"{"a": [1]}"#));
+        assert_eq!(json!([]), JsonValue::Array(Vec::new()));
+        assert_eq!(json!({}), JsonValue::Object(HashMap::new()));
+        assert_eq!(json!(null), JsonValue::Null);
+        assert_eq!(json!("s"), JsonValue::String("s".to_string()));
+        assert_eq!(json!(-2 * 3), JsonValue::Integer(-6));
+    }
+
+    #[test]
+    fn later_duplicate_keys_win() {
+        assert_eq!(json!({"a": 1, "a": 2}), parse(r#"{"a": 2}"#));
+    }
+}
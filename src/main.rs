@@ -0,0 +1,981 @@
+#![allow(clippy::needless_return)]
+
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    env, fs,
+    io::{self, IsTerminal},
+    path::Path,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use mini_json::{
+    diff, diff_text, write_atomically, ColorTheme, DiffTextOptions, Indent, Json, JsonValue,
+    MergeStrategy, ObjectBuilder, OutputStyle, ParseErrorKind, PreviewOptions, SerializeOptions,
+};
+
+const USAGE: &str = "Usage: mini-json [OPTIONS] [FILE]...
+       mini-json diff [--patch] OLD NEW
+       mini-json merge [--arrays replace|concat|index | --merge-patch] FILE...
+       mini-json stats [--json] [FILE]
+
+Parses each JSON file in order and prints it to stdout. With no FILE, or
+when FILE is -, reads standard input. A directory is searched recursively
+for .json files, in sorted order and skipping hidden entries. With several
+files, each document printed is prefixed by its file name. Errors are
+reported on stderr as FILE:LINE:COLUMN: MESSAGE.
+
+Options:
+  --pretty           Indent the output, one member or element per line
+                     (default)
+  --compact          Print each document on a single line
+  --indent <N|tab>   Indent pretty output by N spaces, at most 16, or by a
+                     tab (default: 2)
+  --sort-keys        Print object members sorted by key
+  --validate         Only check that each file parses, printing nothing else
+  --query <POINTER>  Print the value at a JSON Pointer instead of the whole
+                     document; repeat to print several, one per line
+  --write            Reformat each file in place, with keys sorted, leaving
+                     files that fail to parse untouched
+  -o, --output <FILE>
+                     Write the output to FILE instead of stdout, replacing
+                     it in one step once every input has parsed; FILE may
+                     be one of the inputs
+  --create-dirs      With --output, create FILE's missing parent
+                     directories
+  --ndjson           Read one document per line, skipping blank lines, and
+                     print one compact document or query result per line;
+                     errors give the line number
+  --skip-missing     With --query, skip pointers that match nothing instead
+                     of reporting them
+  --ext <EXT>        Also search directories for files ending in .EXT;
+                     repeat to add several
+  --jobs <N>         Process N files at a time (default: 1); output stays
+                     in order
+  --no-filename      Don't prefix output with file names
+  --color <WHEN>     Color the output: always, never or auto (default),
+                     which colors it when stdout is a terminal and NO_COLOR
+                     isn't set
+  -h, --help         Print this message
+
+Subcommands:
+  diff  Compare two files structurally, ignoring formatting and key order.
+        Each difference is printed as '+ POINTER: VALUE' when added,
+        '- POINTER: VALUE' when removed or '~ POINTER: OLD -> NEW' when
+        changed; --patch prints an RFC 6902 JSON Patch instead. Exits 0
+        if the files are equal, 1 if they differ and 2 on errors.
+  merge Deep-merge the files left to right and print the result, taking
+        --pretty, --compact, --indent and --sort-keys. Objects merge key by
+        key and arrays are replaced unless --arrays says otherwise; a
+        warning is printed where a value replaces one of a different kind.
+        --merge-patch applies each file as an RFC 7386 merge patch
+        instead, so null members delete keys.
+  stats Summarize a document: nodes of each kind, the deepest nesting,
+        the longest array, string sizes and the most common object keys.
+        --json prints the summary as JSON.
+
+Exit status:
+  0  Success
+  1  A file couldn't be read, parsed or written
+  2  Invalid arguments
+  3  A queried pointer matched nothing";
+
+const EXIT_FAILED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_NOT_FOUND: u8 = 3;
+
+#[derive(PartialEq)]
+enum Mode {
+    Print,
+    Validate,
+    Query(Vec<String>),
+    Write,
+}
+
+impl Mode {
+    fn flag(&self) -> &'static str {
+        match self {
+            Mode::Print => "",
+            Mode::Validate => "--validate",
+            Mode::Query(_) => "--query",
+            Mode::Write => "--write",
+        }
+    }
+}
+
+const MAX_INDENT: u8 = 16;
+
+struct Args {
+    style: OutputStyle,
+    indent: Indent,
+    sort_keys: bool,
+    ndjson: bool,
+    skip_missing: bool,
+    color: bool,
+    extensions: Vec<String>,
+    jobs: usize,
+    filenames: bool,
+    output: Option<String>,
+    create_dirs: bool,
+    mode: Mode,
+    paths: Vec<String>,
+}
+
+fn set_mode(mode: &mut Mode, new: Mode) -> Result<(), String> {
+    match (&mut *mode, new) {
+        (Mode::Query(queries), Mode::Query(new)) => {
+            queries.extend(new);
+        }
+        (Mode::Print, new) => {
+            *mode = new;
+        }
+        (current, new) if *current == new => {}
+        (current, new) => {
+            return Err(format!(
+                "{} and {} can't be used together",
+                current.flag(),
+                new.flag()
+            ));
+        }
+    }
+
+    return Ok(());
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut style = None;
+    let mut indent = Indent::default();
+    let mut sort_keys = false;
+    let mut ndjson = false;
+    let mut skip_missing = false;
+    let mut color = None;
+    let mut extensions = vec!["json".to_string()];
+    let mut jobs = 1;
+    let mut filenames = true;
+    let mut output = None;
+    let mut create_dirs = false;
+    let mut mode = Mode::Print;
+    let mut paths = Vec::new();
+    let mut flags = true;
+    let mut args = args;
+
+    while let Some(arg) = args.next() {
+        let flag_style = match arg.as_str() {
+            _ if !flags || arg == "-" || !arg.starts_with('-') => {
+                paths.push(arg);
+                continue;
+            }
+            "--" => {
+                flags = false;
+                continue;
+            }
+            "-h" | "--help" => return Ok(None),
+            "--sort-keys" => {
+                sort_keys = true;
+                continue;
+            }
+            "--indent" => {
+                indent = parse_indent(args.next().as_deref())?;
+                continue;
+            }
+            "--ndjson" => {
+                ndjson = true;
+                continue;
+            }
+            "--skip-missing" => {
+                skip_missing = true;
+                continue;
+            }
+            "--ext" => {
+                match args.next() {
+                    Some(extension) => {
+                        extensions.push(extension.trim_start_matches('.').to_string());
+                    }
+                    None => return Err("--ext needs an extension".to_string()),
+                }
+                continue;
+            }
+            "--jobs" => {
+                jobs = match args.next().map(|jobs| jobs.parse::<usize>()) {
+                    Some(Ok(jobs)) if jobs > 0 => jobs,
+                    _ => return Err("--jobs takes a number of at least 1".to_string()),
+                };
+                continue;
+            }
+            "--no-filename" => {
+                filenames = false;
+                continue;
+            }
+            _ if arg == "-o" || arg == "--output" || arg.starts_with("--output=") => {
+                output = match arg.strip_prefix("--output=") {
+                    Some(path) => Some(path.to_string()),
+                    None => args.next(),
+                };
+                if output.as_deref().is_none_or(str::is_empty) {
+                    return Err(format!("{} needs a file", arg));
+                }
+                continue;
+            }
+            "--create-dirs" => {
+                create_dirs = true;
+                continue;
+            }
+            _ if arg == "--color" || arg.starts_with("--color=") => {
+                let when = match arg.strip_prefix("--color=") {
+                    Some(when) => Some(when.to_string()),
+                    None => args.next(),
+                };
+                color = match when.as_deref() {
+                    Some("always") => Some(true),
+                    Some("never") => Some(false),
+                    Some("auto") => None,
+                    _ => return Err("--color takes always, never or auto".to_string()),
+                };
+                continue;
+            }
+            "--validate" => {
+                set_mode(&mut mode, Mode::Validate)?;
+                continue;
+            }
+            "--write" => {
+                set_mode(&mut mode, Mode::Write)?;
+                continue;
+            }
+            "--query" => {
+                let pointer = match args.next() {
+                    Some(pointer) => pointer,
+                    None => return Err("--query needs a JSON Pointer".to_string()),
+                };
+                if !pointer.is_empty() && !pointer.starts_with('/') {
+                    return Err(format!(
+                        "'{}' isn't a JSON Pointer; it should start with '/'",
+                        pointer
+                    ));
+                }
+                set_mode(&mut mode, Mode::Query(vec![pointer]))?;
+                continue;
+            }
+            "--pretty" => OutputStyle::Pretty,
+            "--compact" => OutputStyle::Minified,
+            _ => return Err(format!("unknown option '{}'", arg)),
+        };
+
+        if style.is_some_and(|style| style != flag_style) {
+            return Err("--pretty and --compact can't be used together".to_string());
+        }
+        style = Some(flag_style);
+    }
+
+    if skip_missing && !matches!(mode, Mode::Query(_)) {
+        return Err("--skip-missing needs --query".to_string());
+    }
+    if create_dirs && output.is_none() {
+        return Err("--create-dirs needs --output".to_string());
+    }
+    if output.is_some() && matches!(mode, Mode::Validate | Mode::Write) {
+        return Err(format!("--output can't be used with {}", mode.flag()));
+    }
+    if mode == Mode::Write {
+        if ndjson {
+            return Err("--write can't be used with --ndjson".to_string());
+        }
+        if paths.is_empty() {
+            return Err("--write needs at least one file".to_string());
+        }
+        if paths.iter().any(|path| path == "-") {
+            return Err("--write can't write to standard input".to_string());
+        }
+    }
+    if paths.is_empty() {
+        paths.push("-".to_string());
+    }
+
+    // NO_COLOR only counts when it's set to something.
+    let color = color.unwrap_or_else(|| {
+        output.is_none()
+            && io::stdout().is_terminal()
+            && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+    });
+
+    return Ok(Some(Args {
+        style: style.unwrap_or_default(),
+        indent,
+        sort_keys,
+        ndjson,
+        skip_missing,
+        color,
+        extensions,
+        jobs,
+        filenames,
+        output,
+        create_dirs,
+        mode,
+        paths,
+    }));
+}
+
+fn display_name(path: &str) -> &str {
+    if path == "-" {
+        return "<stdin>";
+    }
+
+    return path;
+}
+
+// Reads a file, or stdin for `-`, with errors formatted for printing.
+fn read_file(path: &str) -> Result<String, String> {
+    let source = match path {
+        "-" => io::read_to_string(io::stdin().lock()),
+        _ => fs::read_to_string(path),
+    };
+
+    return source.map_err(|error| format!("{}: {}", display_name(path), error));
+}
+
+fn parse(path: &str, source: &str) -> Result<Json, String> {
+    return source.parse::<Json>().map_err(|error| {
+        format!(
+            "{}:{}:{}: {}",
+            display_name(path),
+            error.line,
+            error.column,
+            error.kind
+        )
+    });
+}
+
+// What processing one file produced. Files may be processed on several
+// threads, so output is collected here and printed in order afterwards.
+#[derive(Default)]
+struct Report {
+    out: String,
+    err: String,
+    failed: bool,
+    not_found: bool,
+    formatted: usize,
+    unchanged: usize,
+}
+
+impl Report {
+    // Prints `text`, prefixed by `name` when `args.filenames` is set.
+    fn print(&mut self, args: &Args, name: &str, text: &str) {
+        if args.filenames {
+            self.out.push_str(name);
+            self.out.push_str(": ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn fail(&mut self, error: &str) {
+        self.err.push_str(error);
+        self.err.push('\n');
+        self.failed = true;
+    }
+
+    // Prints `value` serialized with `options`, in color if asked for.
+    fn print_value(
+        &mut self,
+        args: &Args,
+        name: &str,
+        value: &JsonValue,
+        options: &SerializeOptions,
+    ) {
+        let text = match args.color {
+            true => value.to_string_colored_with(options, &ColorTheme::default()),
+            false => value.to_string_with(options),
+        };
+
+        match text {
+            Ok(text) => self.print(args, name, &text),
+            Err(error) => self.fail(&format!("{}: {}", name, error)),
+        }
+    }
+}
+
+// Prints the values at `queries` in `value`. `name` says where the value
+// came from.
+fn query(
+    report: &mut Report,
+    value: &JsonValue,
+    queries: &[String],
+    name: &str,
+    args: &Args,
+    options: &SerializeOptions,
+) {
+    for pointer in queries {
+        match value.pointer(pointer) {
+            Some(found) => {
+                report.print_value(args, name, found, options);
+            }
+            None if args.skip_missing => {}
+            None => {
+                report
+                    .err
+                    .push_str(&format!("{}: nothing at '{}'\n", name, pointer));
+                report.not_found = true;
+            }
+        }
+    }
+}
+
+// Any value may be a record, but the parser only reads containers at the
+// top level, so each line is read as a one-element array.
+fn parse_record(name: &str, number: usize, line: &str) -> Result<JsonValue, String> {
+    match format!("[{}]", line).parse::<Json>() {
+        Ok(Json::Array(mut values)) if values.len() == 1 => {
+            return Ok(values.pop().unwrap_or_default());
+        }
+        Ok(_) => {
+            return Err(format!("{}:{}:1: Expected one document", name, number));
+        }
+        Err(mut error) => {
+            // Undo the `[` wrapper's shift of the columns.
+            if let ParseErrorKind::UnterminatedString {
+                start_line,
+                start_column,
+            } = &mut error.kind
+            {
+                *start_line = number;
+                *start_column = start_column.saturating_sub(1).max(1);
+            }
+            let column = error.column.saturating_sub(1).max(1);
+            return Err(format!("{}:{}:{}: {}", name, number, column, error.kind));
+        }
+    }
+}
+
+// Handles a file in `--ndjson` mode.
+fn run_ndjson(report: &mut Report, path: &str, source: &str, args: &Args) {
+    let mut options = SerializeOptions::new(OutputStyle::Minified);
+    options.sort_keys = args.sort_keys;
+
+    for (index, line) in source.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let name = display_name(path);
+        let record = match parse_record(name, index + 1, line) {
+            Ok(record) => record,
+            Err(error) => {
+                report.fail(&error);
+                continue;
+            }
+        };
+
+        let name = format!("{}:{}", name, index + 1);
+        match &args.mode {
+            Mode::Print => {
+                report.print_value(args, &name, &record, &options);
+            }
+            Mode::Query(queries) => {
+                query(report, &record, queries, &name, args, &options);
+            }
+            Mode::Validate | Mode::Write => {}
+        }
+    }
+}
+
+fn process(path: &str, args: &Args, options: &SerializeOptions) -> Report {
+    let mut report = Report::default();
+    let source = match read_file(path) {
+        Ok(source) => source,
+        Err(error) => {
+            report.fail(&error);
+            return report;
+        }
+    };
+
+    if args.ndjson {
+        run_ndjson(&mut report, path, &source, args);
+        return report;
+    }
+
+    let json = match parse(path, &source) {
+        Ok(json) => json,
+        Err(error) => {
+            report.fail(&error);
+            return report;
+        }
+    };
+
+    let name = display_name(path);
+    match &args.mode {
+        Mode::Print => {
+            report.print_value(args, name, &JsonValue::from(json), options);
+        }
+        Mode::Validate => {}
+        Mode::Query(queries) => {
+            query(
+                &mut report,
+                &JsonValue::from(json),
+                queries,
+                name,
+                args,
+                options,
+            );
+        }
+        Mode::Write => {
+            if json
+                .to_string_with(options)
+                .is_ok_and(|text| text + "\n" == source)
+            {
+                report.unchanged += 1;
+            } else if let Err(error) = json.write_to_file(path, options) {
+                report.fail(&format!("{}: {}", path, error));
+            } else {
+                report.formatted += 1;
+            }
+        }
+    }
+
+    return report;
+}
+
+// Processes `paths` on `args.jobs` threads, handing each report to `done`
+// in the order of `paths` as soon as it and every one before it are ready.
+fn process_all(
+    paths: &[String],
+    args: &Args,
+    options: &SerializeOptions,
+    mut done: impl FnMut(Report),
+) {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..args.jobs.min(paths.len()) {
+            let (next, sender) = (&next, sender.clone());
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let path = match paths.get(index) {
+                    Some(path) => path,
+                    None => return,
+                };
+                if sender.send((index, process(path, args, options))).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut ready = 0;
+        for (index, report) in receiver {
+            pending.insert(index, report);
+            while let Some(report) = pending.remove(&ready) {
+                done(report);
+                ready += 1;
+            }
+        }
+    });
+}
+
+// Adds the files under `dir` ending in one of `extensions` to `files`, sorted
+// by name within each directory. Hidden entries are skipped and symlinked
+// directories aren't followed, so links can't loop. Returns false if any
+// directory couldn't be read.
+fn find_files(dir: &Path, extensions: &[String], files: &mut Vec<String>) -> bool {
+    let entries =
+        match fs::read_dir(dir).and_then(|entries| entries.collect::<io::Result<Vec<_>>>()) {
+            Ok(entries) => entries,
+            Err(error) => {
+                eprintln!("{}: {}", dir.display(), error);
+                return false;
+            }
+        };
+
+    let mut entries: Vec<_> = entries
+        .into_iter()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut read_all = true;
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            read_all &= find_files(&path, extensions, files);
+        } else if path.is_file()
+            && path.extension().is_some_and(|extension| {
+                extensions.iter().any(|wanted| extension == wanted.as_str())
+            })
+        {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+
+    return read_all;
+}
+
+fn usage_error(error: &str) -> ExitCode {
+    eprintln!("mini-json: {}\n\n{}", error, USAGE);
+    return ExitCode::from(EXIT_USAGE);
+}
+
+fn read_json(path: &str) -> Result<JsonValue, String> {
+    let source = read_file(path)?;
+    return parse(path, &source).map(JsonValue::from);
+}
+
+// Exit statuses follow diff(1): 0 if equal, 1 if different, 2 on errors.
+fn run_diff(args: impl Iterator<Item = String>) -> ExitCode {
+    let mut as_patch = false;
+    let mut paths = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "--patch" => as_patch = true,
+            _ if arg.starts_with('-') && arg != "-" => {
+                return usage_error(&format!("unknown diff option '{}'", arg));
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    if paths.len() != 2 {
+        return usage_error("diff needs exactly two files");
+    }
+
+    let (old, new) = match (read_json(&paths[0]), read_json(&paths[1])) {
+        (Ok(old), Ok(new)) => (old, new),
+        (old, new) => {
+            for error in [old.err(), new.err()].into_iter().flatten() {
+                eprintln!("{}", error);
+            }
+            return ExitCode::from(2);
+        }
+    };
+
+    if old == new {
+        return ExitCode::SUCCESS;
+    }
+
+    if as_patch {
+        let patch = diff(&old, &new);
+        let mut options = SerializeOptions::new(OutputStyle::Pretty);
+        options.sort_keys = true;
+        println!("{}", patch.to_string_lossy(&options));
+        return ExitCode::from(1);
+    }
+
+    // The full values are printed, however long.
+    let options = DiffTextOptions {
+        values: PreviewOptions {
+            max_elements: usize::MAX,
+            max_members: usize::MAX,
+            max_string_chars: usize::MAX,
+            max_depth: usize::MAX,
+        },
+        ..DiffTextOptions::default()
+    };
+    print!("{}", diff_text(&old, &new, &options));
+
+    return ExitCode::from(1);
+}
+
+fn parse_indent(width: Option<&str>) -> Result<Indent, String> {
+    return match width {
+        Some("tab") => Ok(Indent::Tab),
+        Some(width) => match width.parse::<u8>() {
+            Ok(width) if width <= MAX_INDENT => Ok(Indent::Spaces(width)),
+            _ => Err(format!(
+                "--indent takes a number of spaces up to {} or 'tab'",
+                MAX_INDENT
+            )),
+        },
+        None => Err("--indent needs a width".to_string()),
+    };
+}
+
+fn run_merge(args: impl Iterator<Item = String>) -> ExitCode {
+    let mut strategy = None;
+    let mut merge_patch = false;
+    let mut options = SerializeOptions::default();
+    let mut style = None;
+    let mut paths = Vec::new();
+    let mut args = args;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--arrays" => {
+                strategy = match args.next().as_deref() {
+                    Some("replace") => Some(MergeStrategy::Replace),
+                    Some("concat") => Some(MergeStrategy::Concat),
+                    Some("index") => Some(MergeStrategy::MergeByIndex),
+                    _ => return usage_error("--arrays takes replace, concat or index"),
+                };
+            }
+            "--merge-patch" => merge_patch = true,
+            "--pretty" | "--compact" => {
+                let flag_style = match arg.as_str() {
+                    "--pretty" => OutputStyle::Pretty,
+                    _ => OutputStyle::Minified,
+                };
+                if style.is_some_and(|style| style != flag_style) {
+                    return usage_error("--pretty and --compact can't be used together");
+                }
+                style = Some(flag_style);
+            }
+            "--sort-keys" => options.sort_keys = true,
+            "--indent" => match parse_indent(args.next().as_deref()) {
+                Ok(indent) => options.indent = indent,
+                Err(message) => return usage_error(&message),
+            },
+            _ if arg.starts_with('-') && arg != "-" => {
+                return usage_error(&format!("unknown merge option '{}'", arg));
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    if merge_patch && strategy.is_some() {
+        return usage_error("--merge-patch and --arrays can't be used together");
+    }
+    if paths.is_empty() {
+        return usage_error("merge needs at least one file");
+    }
+    options.style = style.unwrap_or(options.style);
+
+    let mut merged: Option<JsonValue> = None;
+    for path in &paths {
+        let layer = match read_json(path) {
+            Ok(layer) => layer,
+            Err(error) => {
+                eprintln!("{}", error);
+                return ExitCode::from(EXIT_FAILED);
+            }
+        };
+
+        let merged = match &mut merged {
+            Some(merged) => merged,
+            None => {
+                merged = Some(layer);
+                continue;
+            }
+        };
+
+        if merge_patch {
+            merged.merge_patch(&layer);
+            continue;
+        }
+
+        let conflicts = merged.merge(layer, strategy.unwrap_or(MergeStrategy::Replace));
+        for pointer in conflicts {
+            eprintln!(
+                "warning: {}: '{}' replaces a value of a different kind",
+                display_name(path),
+                pointer
+            );
+        }
+    }
+
+    println!("{}", merged.unwrap_or_default().to_string_lossy(&options));
+    return ExitCode::SUCCESS;
+}
+
+fn write_output(path: &Path, output: &str, create_dirs: bool) -> io::Result<()> {
+    if create_dirs {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    return write_atomically(path, output.as_bytes());
+}
+
+const TOP_KEYS: usize = 10;
+
+fn run_stats(args: impl Iterator<Item = String>) -> ExitCode {
+    let mut as_json = false;
+    let mut paths = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "--json" => as_json = true,
+            _ if arg.starts_with('-') && arg != "-" => {
+                return usage_error(&format!("unknown stats option '{}'", arg));
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    let path = match paths.as_slice() {
+        [] => "-",
+        [path] => path.as_str(),
+        _ => return usage_error("stats takes one file"),
+    };
+    let json = match read_file(path).and_then(|source| parse(path, &source)) {
+        Ok(json) => json,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::from(EXIT_FAILED);
+        }
+    };
+
+    let stats = json.stats();
+    let key_counts = json.key_counts();
+    let mut top_keys: Vec<(&str, usize)> = key_counts
+        .iter()
+        .map(|(&key, &count)| (key, count))
+        .collect();
+    top_keys.sort_by_key(|&(key, count)| (Reverse(count), key));
+    top_keys.truncate(TOP_KEYS);
+
+    if as_json {
+        let count = |count: usize| JsonValue::Integer(count as isize);
+        let summary = ObjectBuilder::new()
+            .insert(
+                "nodes",
+                ObjectBuilder::new()
+                    .insert("total", count(stats.total_nodes()))
+                    .insert("objects", count(stats.objects))
+                    .insert("arrays", count(stats.arrays))
+                    .insert("strings", count(stats.strings))
+                    .insert("integers", count(stats.integers))
+                    .insert("floats", count(stats.floats))
+                    .insert("booleans", count(stats.booleans))
+                    .insert("nulls", count(stats.nulls)),
+            )
+            .insert("max_depth", count(stats.max_depth))
+            .insert("longest_array", count(stats.longest_array))
+            .insert("distinct_keys", count(key_counts.len()))
+            .insert("string_bytes", count(stats.string_bytes))
+            .insert("estimated_heap_bytes", count(stats.estimated_heap_bytes))
+            .insert(
+                "top_keys",
+                top_keys
+                    .iter()
+                    .map(|&(key, keys)| {
+                        ObjectBuilder::new()
+                            .insert("key", key)
+                            .insert("count", count(keys))
+                            .build()
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .build();
+
+        let mut options = SerializeOptions::new(OutputStyle::Pretty);
+        options.sort_keys = true;
+        println!("{}", summary.to_string_lossy(&options));
+        return ExitCode::SUCCESS;
+    }
+
+    println!("nodes:                {}", stats.total_nodes());
+    println!("  objects:            {}", stats.objects);
+    println!("  arrays:             {}", stats.arrays);
+    println!("  strings:            {}", stats.strings);
+    println!("  integers:           {}", stats.integers);
+    println!("  floats:             {}", stats.floats);
+    println!("  booleans:           {}", stats.booleans);
+    println!("  nulls:              {}", stats.nulls);
+    println!("max depth:            {}", stats.max_depth);
+    println!("longest array:        {}", stats.longest_array);
+    println!("distinct keys:        {}", key_counts.len());
+    println!("string bytes:         {}", stats.string_bytes);
+    println!("estimated heap bytes: {}", stats.estimated_heap_bytes);
+
+    if !top_keys.is_empty() {
+        println!("top keys:");
+        let width = top_keys.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+        for (key, count) in top_keys {
+            println!("  {:width$}  {}", key, count, width = width);
+        }
+    }
+
+    return ExitCode::SUCCESS;
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1).peekable();
+    match args.peek().map(|arg| arg.as_str()) {
+        Some("diff") => {
+            args.next();
+            return run_diff(args);
+        }
+        Some("merge") => {
+            args.next();
+            return run_merge(args);
+        }
+        Some("stats") => {
+            args.next();
+            return run_stats(args);
+        }
+        _ => {}
+    }
+
+    let mut args = match parse_args(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            return usage_error(&error);
+        }
+    };
+    let mut options = SerializeOptions::new(args.style);
+    options.indent = args.indent;
+    // Objects don't keep their members' order, so --write always sorts
+    // keys; otherwise each run could write the same file differently.
+    options.sort_keys = args.sort_keys || args.mode == Mode::Write;
+
+    let mut failed = false;
+    let mut paths = Vec::new();
+    for path in &args.paths {
+        if path != "-" && Path::new(path).is_dir() {
+            failed |= !find_files(Path::new(path), &args.extensions, &mut paths);
+        } else {
+            paths.push(path.clone());
+        }
+    }
+    args.filenames &= paths.len() > 1;
+
+    let mut not_found = false;
+    let (mut formatted, mut unchanged) = (0, 0);
+    let mut output = String::new();
+    process_all(&paths, &args, &options, |report| {
+        match args.output {
+            Some(_) => output.push_str(&report.out),
+            None => print!("{}", report.out),
+        }
+        eprint!("{}", report.err);
+        failed |= report.failed;
+        not_found |= report.not_found;
+        formatted += report.formatted;
+        unchanged += report.unchanged;
+    });
+
+    if args.mode == Mode::Write {
+        let files = if formatted == 1 { "file" } else { "files" };
+        println!("formatted {} {}, {} unchanged", formatted, files, unchanged);
+    }
+
+    // Every input has been read by now, so the output may replace one of
+    // them. It's left alone if any failed, as with --write.
+    if let Some(path) = args.output.as_deref().filter(|_| !failed) {
+        if let Err(error) = write_output(Path::new(path), &output, args.create_dirs) {
+            eprintln!("{}: {}", path, error);
+            failed = true;
+        }
+    }
+
+    if failed {
+        return ExitCode::from(EXIT_FAILED);
+    }
+    if not_found {
+        return ExitCode::from(EXIT_NOT_FOUND);
+    }
+
+    return ExitCode::SUCCESS;
+}
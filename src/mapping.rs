@@ -0,0 +1,692 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::JsonValue;
+use crate::path::PathSegment;
+
+/// A failed `FromJson` conversion. `path` leads from the value passed to
+/// `from_json` down to the value that couldn't be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+    pub path: Vec<PathSegment>,
+    pub message: String,
+}
+
+impl FromJsonError {
+    pub fn new(message: impl Into<String>) -> FromJsonError {
+        return FromJsonError {
+            path: Vec::new(),
+            message: message.into(),
+        };
+    }
+
+    /// Records that the error happened inside the member `key`. Conversions
+    /// call this while unwinding, so segments are added outermost last.
+    pub fn at_key(mut self, key: &str) -> FromJsonError {
+        self.path.insert(0, PathSegment::Key(key.to_string()));
+        return self;
+    }
+
+    pub fn at_index(mut self, index: usize) -> FromJsonError {
+        self.path.insert(0, PathSegment::Index(index));
+        return self;
+    }
+
+    fn wrong_kind(expected: &'static str, value: &JsonValue) -> FromJsonError {
+        return FromJsonError::new(value.wrong_kind(expected).to_string());
+    }
+}
+
+fn is_plain_key(key: &str) -> bool {
+    return !key.is_empty()
+        && !key
+            .chars()
+            .any(|c| matches!(c, '.' | '[' | ']' | '"' | '\'' | '\\'));
+}
+
+// Renders the path in the dotted syntax accepted by `parse_path`, quoting
+// keys that would otherwise be ambiguous.
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, segment) in self.path.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if is_plain_key(key) => {
+                    if i > 0 {
+                        write!(f, ".")?;
+                    }
+                    write!(f, "{}", key)?;
+                }
+                PathSegment::Key(key) => {
+                    write!(f, "[\"")?;
+                    for c in key.chars() {
+                        if c == '"' || c == '\\' {
+                            write!(f, "\\")?;
+                        }
+                        write!(f, "{}", c)?;
+                    }
+                    write!(f, "\"]")?;
+                }
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+
+        if !self.path.is_empty() {
+            write!(f, ": ")?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+/// Converts a `JsonValue` into a Rust type. Implementations for structs are
+/// usually a few calls to `JsonValue::field`:
+///
+/// ```
+/// use mini_json::{json, FromJson, FromJsonError, JsonValue, ToJson};
+///
+/// struct User {
+///     email: String,
+///     age: Option<u32>,
+/// }
+///
+/// struct Config {
+///     users: Vec<User>,
+/// }
+///
+/// impl FromJson for User {
+///     fn from_json(value: &JsonValue) -> Result<User, FromJsonError> {
+///         return Ok(User {
+///             email: value.field("email")?,
+///             age: value.field("age")?,
+///         });
+///     }
+/// }
+///
+/// impl ToJson for User {
+///     fn to_json(&self) -> JsonValue {
+///         return json!({"email": self.email.to_json(), "age": self.age.to_json()});
+///     }
+/// }
+///
+/// impl FromJson for Config {
+///     fn from_json(value: &JsonValue) -> Result<Config, FromJsonError> {
+///         return Ok(Config { users: value.field("users")? });
+///     }
+/// }
+///
+/// impl ToJson for Config {
+///     fn to_json(&self) -> JsonValue {
+///         return json!({"users": self.users.to_json()});
+///     }
+/// }
+///
+/// let doc = json!({"users": [{"email": "a@example.com", "age": 30}, {"email": null}]});
+/// let error = Config::from_json(&doc).err().unwrap();
+/// assert_eq!(error.to_string(), "users[1].email: expected string, found null");
+///
+/// let config = Config {
+///     users: vec![User { email: "a@example.com".to_string(), age: None }],
+/// };
+/// let round_tripped = Config::from_json(&config.to_json()).unwrap();
+/// assert_eq!(round_tripped.users[0].email, "a@example.com");
+/// ```
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError>;
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+impl JsonValue {
+    /// Converts the member `key` of an object, attaching `key` to the path
+    /// of any error. A missing member is converted from `null`, so `Option`
+    /// fields come back as `None` and anything else reports the field as
+    /// missing.
+    pub fn field<T: FromJson>(&self, key: &str) -> Result<T, FromJsonError> {
+        let object = match self {
+            JsonValue::Object(object) => object,
+            _ => return Err(FromJsonError::wrong_kind("object", self)),
+        };
+
+        match object.get(key) {
+            Some(member) => {
+                return T::from_json(member).map_err(|error| error.at_key(key));
+            }
+            None => match T::from_json(&JsonValue::Null) {
+                Ok(value) => {
+                    return Ok(value);
+                }
+                Err(_) => {
+                    return Err(FromJsonError::new("missing field").at_key(key));
+                }
+            },
+        }
+    }
+
+    /// Like `field`, but a missing or `null` member yields `default`.
+    pub fn field_or<T: FromJson>(&self, key: &str, default: T) -> Result<T, FromJsonError> {
+        match self {
+            JsonValue::Object(object) => match object.get(key) {
+                None | Some(JsonValue::Null) => {
+                    return Ok(default);
+                }
+                Some(_) => {
+                    return self.field(key);
+                }
+            },
+            _ => {
+                return Err(FromJsonError::wrong_kind("object", self));
+            }
+        }
+    }
+
+    /// Converts every element of an array, stopping at the first that
+    /// can't be converted. That error's path starts with the element's
+    /// index, as in `[3]: expected string, found integer`.
+    pub fn to_vec<T: FromJson>(&self) -> Result<Vec<T>, FromJsonError> {
+        return Vec::<T>::from_json(self);
+    }
+
+    pub fn to_string_vec(&self) -> Result<Vec<String>, FromJsonError> {
+        return self.to_vec();
+    }
+
+    /// Integral floats, like `3.0`, are accepted too.
+    pub fn to_i64_vec(&self) -> Result<Vec<i64>, FromJsonError> {
+        return self.to_vec();
+    }
+
+    pub fn to_f64_vec(&self) -> Result<Vec<f64>, FromJsonError> {
+        return self.to_vec();
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<JsonValue, FromJsonError> {
+        return Ok(value.clone());
+    }
+}
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        return self.clone();
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<bool, FromJsonError> {
+        match value {
+            JsonValue::Boolean(value) => {
+                return Ok(*value);
+            }
+            _ => {
+                return Err(FromJsonError::wrong_kind("boolean", value));
+            }
+        }
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::Boolean(*self);
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<String, FromJsonError> {
+        match value {
+            JsonValue::String(value) => {
+                return Ok(value.clone());
+            }
+            _ => {
+                return Err(FromJsonError::wrong_kind("string", value));
+            }
+        }
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::String(self.clone());
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::String(self.to_string());
+    }
+}
+
+// Integral floats such as `3.0` are accepted; anything with a fraction or
+// outside the target type's range is an error.
+macro_rules! integer_mapping {
+    ($($t:ty),*) => {
+        $(
+            impl FromJson for $t {
+                fn from_json(value: &JsonValue) -> Result<$t, FromJsonError> {
+                    let number = match value.as_number() {
+                        Some(number) => number,
+                        None => return Err(FromJsonError::wrong_kind("integer", value)),
+                    };
+
+                    match number.as_i64() {
+                        Some(integer) => <$t>::try_from(integer).map_err(|_| {
+                            FromJsonError::new(format!(
+                                "{} is out of range for {}",
+                                integer,
+                                stringify!($t)
+                            ))
+                        }),
+                        None => Err(FromJsonError::wrong_kind("integer", value)),
+                    }
+                }
+            }
+
+            // Values that don't fit in an `isize` fall back to a float.
+            impl ToJson for $t {
+                fn to_json(&self) -> JsonValue {
+                    match isize::try_from(*self) {
+                        Ok(value) => JsonValue::Integer(value),
+                        Err(_) => JsonValue::Float(*self as f64),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_mapping!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Result<f64, FromJsonError> {
+        match value.as_number() {
+            Some(number) => {
+                return Ok(number.as_f64());
+            }
+            None => {
+                return Err(FromJsonError::wrong_kind("number", value));
+            }
+        }
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::Float(*self);
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(value: &JsonValue) -> Result<f32, FromJsonError> {
+        return f64::from_json(value).map(|value| value as f32);
+    }
+}
+
+impl ToJson for f32 {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::Float(*self as f64);
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Option<T>, FromJsonError> {
+        match value {
+            JsonValue::Null => {
+                return Ok(None);
+            }
+            _ => {
+                return T::from_json(value).map(Some);
+            }
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        match self {
+            Some(value) => value.to_json(),
+            None => JsonValue::Null,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Vec<T>, FromJsonError> {
+        let array = match value {
+            JsonValue::Array(array) => array,
+            _ => return Err(FromJsonError::wrong_kind("array", value)),
+        };
+
+        let mut result = Vec::with_capacity(array.len());
+        for (index, element) in array.iter().enumerate() {
+            result.push(T::from_json(element).map_err(|error| error.at_index(index))?);
+        }
+
+        return Ok(result);
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        return self.as_slice().to_json();
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::Array(self.iter().map(ToJson::to_json).collect());
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<HashMap<String, T>, FromJsonError> {
+        let object = match value {
+            JsonValue::Object(object) => object,
+            _ => return Err(FromJsonError::wrong_kind("object", value)),
+        };
+
+        let mut result = HashMap::with_capacity(object.len());
+        for (key, member) in object {
+            let converted = T::from_json(member).map_err(|error| error.at_key(key))?;
+            result.insert(key.clone(), converted);
+        }
+
+        return Ok(result);
+    }
+}
+
+impl<T: ToJson> ToJson for HashMap<String, T> {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::Object(
+            self.iter()
+                .map(|(key, value)| (key.clone(), value.to_json()))
+                .collect(),
+        );
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        return (**self).to_json();
+    }
+}
+
+// Shapes `#[derive(FromJson, ToJson)]` must reject at compile time, checked
+// by `cargo test --doc --features derive`. Each would compile if the derive
+// accepted it, so a failure here means the error went missing.
+
+/// ```compile_fail
+/// #[derive(mini_json::FromJson)]
+/// struct Point(i32, i32);
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mini_json::ToJson)]
+/// struct Marker;
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mini_json::FromJson)]
+/// struct Wrapper<T> {
+///     value: T,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mini_json::ToJson)]
+/// enum Shape {
+///     Circle { radius: f64 },
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mini_json::FromJson)]
+/// struct User {
+///     #[json(rename)]
+///     name: String,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mini_json::FromJson)]
+/// struct User {
+///     #[json(flatten)]
+///     name: String,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mini_json::FromJson)]
+/// #[json(rename = "user")]
+/// struct User {
+///     name: String,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mini_json::FromJson)]
+/// enum Role {
+///     #[json(default)]
+///     Admin,
+/// }
+/// ```
+///
+/// The accepted shapes, for comparison:
+///
+/// ```
+/// #[derive(mini_json::FromJson, mini_json::ToJson)]
+/// struct User {
+///     #[json(rename = "userName", default)]
+///     name: String,
+///     #[json(skip)]
+///     cache: Option<Vec<u8>>,
+/// }
+///
+/// #[derive(mini_json::FromJson, mini_json::ToJson)]
+/// enum Role {
+///     #[json(rename = "admin")]
+///     Admin,
+///     Guest,
+/// }
+/// ```
+#[cfg(all(doctest, feature = "derive"))]
+pub struct UnsupportedDerives;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::mapping::{FromJson, FromJsonError, ToJson};
+    use crate::path::PathSegment;
+
+    #[derive(Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+        tags: Vec<String>,
+        timeout: Option<f64>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        name: String,
+        servers: Vec<Server>,
+        limits: HashMap<String, i64>,
+        debug: bool,
+    }
+
+    impl FromJson for Server {
+        fn from_json(value: &JsonValue) -> Result<Server, FromJsonError> {
+            return Ok(Server {
+                host: value.field("host")?,
+                port: value.field("port")?,
+                tags: value.field_or("tags", Vec::new())?,
+                timeout: value.field("timeout")?,
+            });
+        }
+    }
+
+    impl ToJson for Server {
+        fn to_json(&self) -> JsonValue {
+            return json!({
+                "host": self.host.to_json(),
+                "port": self.port.to_json(),
+                "tags": self.tags.to_json(),
+                "timeout": self.timeout.to_json(),
+            });
+        }
+    }
+
+    impl FromJson for Config {
+        fn from_json(value: &JsonValue) -> Result<Config, FromJsonError> {
+            return Ok(Config {
+                name: value.field("name")?,
+                servers: value.field("servers")?,
+                limits: value.field("limits")?,
+                debug: value.field_or("debug", false)?,
+            });
+        }
+    }
+
+    impl ToJson for Config {
+        fn to_json(&self) -> JsonValue {
+            return json!({
+                "name": self.name.to_json(),
+                "servers": self.servers.to_json(),
+                "limits": self.limits.to_json(),
+                "debug": self.debug.to_json(),
+            });
+        }
+    }
+
+    fn error(value: &JsonValue) -> String {
+        return Config::from_json(value).unwrap_err().to_string();
+    }
+
+    #[test]
+    fn converts_a_nested_struct_both_ways() {
+        let value = json!({
+            "name": "prod",
+            "servers": [
+                {"host": "a", "port": 80, "tags": ["web"], "timeout": 1.5},
+                {"host": "b", "port": 8080.0}
+            ],
+            "limits": {"rps": 100}
+        });
+
+        let config = Config::from_json(&value).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                name: "prod".to_string(),
+                servers: vec![
+                    Server {
+                        host: "a".to_string(),
+                        port: 80,
+                        tags: vec!["web".to_string()],
+                        timeout: Some(1.5),
+                    },
+                    Server {
+                        host: "b".to_string(),
+                        port: 8080,
+                        tags: Vec::new(),
+                        timeout: None,
+                    },
+                ],
+                limits: HashMap::from([("rps".to_string(), 100)]),
+                debug: false,
+            }
+        );
+        assert_eq!(Config::from_json(&config.to_json()), Ok(config));
+    }
+
+    #[test]
+    fn missing_fields_and_wrong_kinds_report_their_path() {
+        assert_eq!(error(&json!({"servers": []})), "name: missing field");
+        assert_eq!(
+            error(&json!({"name": "x", "servers": [{"host": "a", "port": 1}, {"port": 2}]})),
+            "servers[1].host: missing field"
+        );
+        assert_eq!(
+            error(&json!({"name": "x", "servers": [{"host": null, "port": 1}]})),
+            "servers[0].host: expected string, found null"
+        );
+        assert_eq!(
+            error(&json!({"name": "x", "servers": [{"host": "a", "port": 1, "tags": ["a", 2]}]})),
+            "servers[0].tags[1]: expected string, found integer"
+        );
+        assert_eq!(
+            error(&json!({"name": "x", "servers": [], "limits": {"a.b": "1"}})),
+            "limits[\"a.b\"]: expected integer, found string"
+        );
+        assert_eq!(error(&json!([])), "expected object, found array");
+    }
+
+    #[test]
+    fn integers_must_be_integral_and_in_range() {
+        assert_eq!(u16::from_json(&json!(65535.0)), Ok(65535));
+        assert_eq!(
+            u16::from_json(&json!(65536)).unwrap_err().to_string(),
+            "65536 is out of range for u16"
+        );
+        assert_eq!(
+            u8::from_json(&json!(-1)).unwrap_err().to_string(),
+            "-1 is out of range for u8"
+        );
+        assert_eq!(
+            i32::from_json(&json!(1.5)).unwrap_err().to_string(),
+            "expected integer, found float"
+        );
+        assert_eq!(f64::from_json(&json!(2)), Ok(2.0));
+        assert_eq!(u64::MAX.to_json(), JsonValue::Float(u64::MAX as f64));
+    }
+
+    #[test]
+    fn error_paths_quote_awkward_keys() {
+        let error = FromJsonError::new("bad")
+            .at_key("plain")
+            .at_index(2)
+            .at_key("say \"hi\"")
+            .at_key("");
+        assert_eq!(error.path[1], PathSegment::Key("say \"hi\"".to_string()));
+        assert_eq!(
+            error.to_string(),
+            "[\"\"][\"say \\\"hi\\\"\"][2].plain: bad"
+        );
+    }
+
+    #[test]
+    fn to_vec_converts_clean_arrays() {
+        assert_eq!(
+            json!(["a", "b"]).to_string_vec(),
+            Ok(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(json!([1, -2, 3.0]).to_i64_vec(), Ok(vec![1, -2, 3]));
+        assert_eq!(json!([1, 2.5]).to_f64_vec(), Ok(vec![1.0, 2.5]));
+        assert_eq!(
+            json!([[1], []]).to_vec::<Vec<u8>>(),
+            Ok(vec![vec![1], vec![]])
+        );
+        assert_eq!(json!([]).to_i64_vec(), Ok(vec![]));
+    }
+
+    #[test]
+    fn to_vec_fails_at_the_first_bad_element() {
+        let error = json!(["a", "b", "c", 4, null]).to_string_vec().unwrap_err();
+        assert_eq!(error.path, [PathSegment::Index(3)]);
+        assert_eq!(error.to_string(), "[3]: expected string, found integer");
+
+        assert_eq!(
+            json!([1, 2, 3, 4.5]).to_i64_vec().unwrap_err().to_string(),
+            "[3]: expected integer, found float"
+        );
+        assert_eq!(
+            json!({"a": 1}).to_i64_vec().unwrap_err().to_string(),
+            "expected array, found object"
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use std::{collections::HashMap, mem};
+
+use crate::json::JsonValue;
+use crate::path::escape_pointer_token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    Replace,
+    Concat,
+    MergeByIndex,
+}
+
+fn is_container(value: &JsonValue) -> bool {
+    matches!(value, JsonValue::Object(_) | JsonValue::Array(_))
+}
+
+fn is_type_conflict(target: &JsonValue, other: &JsonValue) -> bool {
+    if !is_container(target) && !is_container(other) {
+        return false;
+    }
+
+    return mem::discriminant(target) != mem::discriminant(other);
+}
+
+fn merge_value(
+    target: &mut JsonValue,
+    other: JsonValue,
+    strategy: MergeStrategy,
+    pointer: &mut String,
+    conflicts: &mut Vec<String>,
+) {
+    match (target, other) {
+        (JsonValue::Object(target_object), JsonValue::Object(other_object)) => {
+            for (key, other_value) in other_object {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer_token(&key));
+
+                match target_object.get_mut(&key) {
+                    Some(target_value) => {
+                        merge_value(target_value, other_value, strategy, pointer, conflicts);
+                    }
+                    None => {
+                        target_object.insert(key, other_value);
+                    }
+                }
+
+                pointer.truncate(len);
+            }
+        }
+        (JsonValue::Array(target_array), JsonValue::Array(other_array)) => match strategy {
+            MergeStrategy::Replace => {
+                *target_array = other_array;
+            }
+            MergeStrategy::Concat => {
+                target_array.extend(other_array);
+            }
+            MergeStrategy::MergeByIndex => {
+                for (index, other_value) in other_array.into_iter().enumerate() {
+                    if index < target_array.len() {
+                        let len = pointer.len();
+                        pointer.push('/');
+                        pointer.push_str(&index.to_string());
+
+                        merge_value(
+                            &mut target_array[index],
+                            other_value,
+                            strategy,
+                            pointer,
+                            conflicts,
+                        );
+
+                        pointer.truncate(len);
+                    } else {
+                        target_array.push(other_value);
+                    }
+                }
+            }
+        },
+        (target, other) => {
+            if is_type_conflict(target, &other) {
+                conflicts.push(pointer.clone());
+            }
+
+            *target = other;
+        }
+    }
+}
+
+impl JsonValue {
+    /// Deep merges `other` into `self`: objects merge key by key, arrays
+    /// follow `strategy`, and anything else from `other` replaces the current
+    /// value. Returns the pointers where an object or array was replaced by a
+    /// value of a different kind.
+    pub fn merge(&mut self, other: JsonValue, strategy: MergeStrategy) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        merge_value(self, other, strategy, &mut String::new(), &mut conflicts);
+
+        return conflicts;
+    }
+}
+
+impl JsonValue {
+    /// Applies an RFC 7386 JSON Merge Patch: `null` members delete keys,
+    /// nested objects are patched recursively, and any non-object patch
+    /// (arrays included) replaces the target wholesale.
+    pub fn merge_patch(&mut self, patch: &JsonValue) {
+        match patch {
+            JsonValue::Object(patch_object) => {
+                if !matches!(self, JsonValue::Object(_)) {
+                    *self = JsonValue::Object(HashMap::new());
+                }
+
+                if let JsonValue::Object(target_object) = self {
+                    for (key, value) in patch_object {
+                        if *value == JsonValue::Null {
+                            target_object.remove(key);
+                        } else {
+                            target_object
+                                .entry(key.clone())
+                                .or_insert(JsonValue::Null)
+                                .merge_patch(value);
+                        }
+                    }
+                }
+            }
+            _ => {
+                *self = patch.clone();
+            }
+        }
+    }
+
+    /// Produces a merge patch that turns `before` into `after` when applied
+    /// with `merge_patch`. Null values nested inside `after` can't be
+    /// expressed by a merge patch and will be dropped when it is applied.
+    pub fn merge_patch_diff(before: &JsonValue, after: &JsonValue) -> JsonValue {
+        match (before, after) {
+            (JsonValue::Object(before_object), JsonValue::Object(after_object)) => {
+                let mut patch = HashMap::new();
+
+                for key in before_object.keys() {
+                    if !after_object.contains_key(key) {
+                        patch.insert(key.clone(), JsonValue::Null);
+                    }
+                }
+
+                for (key, after_value) in after_object {
+                    match before_object.get(key) {
+                        Some(before_value) => {
+                            if before_value != after_value {
+                                patch.insert(
+                                    key.clone(),
+                                    JsonValue::merge_patch_diff(before_value, after_value),
+                                );
+                            }
+                        }
+                        None => {
+                            patch.insert(key.clone(), after_value.clone());
+                        }
+                    }
+                }
+
+                return JsonValue::Object(patch);
+            }
+            _ => {
+                return after.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{parse_from_str, Json, JsonValue};
+    use crate::merge::MergeStrategy;
+
+    fn base() -> JsonValue {
+        return json!({"a": {"b": 1, "list": [1, {"x": 1}]}, "keep": true});
+    }
+
+    #[test]
+    fn objects_merge_key_by_key() {
+        let mut doc = base();
+        let conflicts = doc.merge(
+            json!({"a": {"c": 2, "b": null}, "new": [1]}),
+            MergeStrategy::Replace,
+        );
+
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            doc,
+            json!({"a": {"b": null, "c": 2, "list": [1, {"x": 1}]}, "keep": true, "new": [1]})
+        );
+    }
+
+    #[test]
+    fn array_strategies() {
+        let other = json!({"a": {"list": [2, {"y": 2}, 3]}});
+
+        let mut replaced = base();
+        replaced.merge(other.clone(), MergeStrategy::Replace);
+        assert_eq!(replaced.pointer("/a/list"), Some(&json!([2, {"y": 2}, 3])));
+
+        let mut concatenated = base();
+        concatenated.merge(other.clone(), MergeStrategy::Concat);
+        assert_eq!(
+            concatenated.pointer("/a/list"),
+            Some(&json!([1, {"x": 1}, 2, {"y": 2}, 3]))
+        );
+
+        let mut by_index = base();
+        by_index.merge(other, MergeStrategy::MergeByIndex);
+        assert_eq!(
+            by_index.pointer("/a/list"),
+            Some(&json!([2, {"x": 1, "y": 2}, 3]))
+        );
+    }
+
+    #[test]
+    fn kind_changes_of_containers_are_reported() {
+        let mut doc = json!({"a": {"b": 1}, "c": [1], "d": 1, "e/f": {"g": [{}]}});
+        let mut conflicts = doc.merge(
+            json!({"a": 1, "c": {"x": 1}, "d": "one", "e/f": {"g": [[]]}}),
+            MergeStrategy::MergeByIndex,
+        );
+        conflicts.sort();
+
+        assert_eq!(conflicts, ["/a", "/c", "/e~1f/g/0"]);
+        assert_eq!(
+            doc,
+            json!({"a": 1, "c": {"x": 1}, "d": "one", "e/f": {"g": [[]]}})
+        );
+    }
+
+    #[test]
+    fn scalar_roots_are_replaced() {
+        let mut doc = json!(1);
+        assert!(doc.merge(json!("x"), MergeStrategy::Concat).is_empty());
+        assert_eq!(doc, json!("x"));
+
+        assert_eq!(doc.merge(json!([1]), MergeStrategy::Concat), [""]);
+        assert_eq!(doc, json!([1]));
+    }
+
+    // The examples from RFC 7386, Appendix A.
+    const RFC_7386_EXAMPLES: [(&str, &str, &str); 15] = [
+        (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+        (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+        (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+        (
+            r#"{"a":{"b":"c"}}"#,
+            r#"{"a":{"b":"d","c":null}}"#,
+            r#"{"a":{"b":"d"}}"#,
+        ),
+        (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+        (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+        (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+        (r#"{"a":"foo"}"#, "null", "null"),
+        (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+        (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+        (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+        (
+            r#"{}"#,
+            r#"{"a":{"bb":{"ccc":null}}}"#,
+            r#"{"a":{"bb":{}}}"#,
+        ),
+    ];
+
+    // Parses any value, scalars included.
+    fn value(text: &str) -> JsonValue {
+        match parse_from_str(&format!("[{}]", text)).unwrap() {
+            Json::Array(mut values) => {
+                return values.remove(0);
+            }
+            Json::Object(_) => {
+                unreachable!();
+            }
+        }
+    }
+
+    #[test]
+    fn merge_patch_matches_rfc_7386() {
+        for (original, patch, result) in RFC_7386_EXAMPLES {
+            let mut target = value(original);
+            target.merge_patch(&value(patch));
+            assert_eq!(target, value(result), "{} + {}", original, patch);
+        }
+    }
+
+    #[test]
+    fn merge_patch_diff_reproduces_rfc_7386_results() {
+        for (original, _, result) in RFC_7386_EXAMPLES {
+            let (original, result) = (value(original), value(result));
+            let patch = JsonValue::merge_patch_diff(&original, &result);
+
+            let mut target = original.clone();
+            target.merge_patch(&patch);
+            assert_eq!(target, result, "{} via {}", original, patch);
+        }
+    }
+
+    #[test]
+    fn merge_patch_diff_is_minimal() {
+        let before = json!({"a": 1, "b": {"c": 2, "d": 3}, "e": [1], "gone": true});
+        let after = json!({"a": 1, "b": {"c": 2, "d": 4}, "e": [1, 2], "new": null});
+
+        assert_eq!(
+            JsonValue::merge_patch_diff(&before, &after),
+            json!({"b": {"d": 4}, "e": [1, 2], "gone": null, "new": null})
+        );
+        assert_eq!(JsonValue::merge_patch_diff(&before, &before), json!({}));
+    }
+}
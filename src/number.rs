@@ -0,0 +1,132 @@
+use crate::json::JsonValue;
+
+/// A view over the two numeric variants of `JsonValue`.
+///
+/// Coercion rules:
+///
+/// | value                  | `as_f64`                    | `as_i64`   |
+/// |------------------------|-----------------------------|------------|
+/// | `Integer(n)`           | `n as f64`, rounded > 2^53  | `Some(n)`  |
+/// | `Float(3.0)`           | `3.0`                       | `Some(3)`  |
+/// | `Float(3.5)`           | `3.5`                       | `None`     |
+/// | NaN, infinite, >= 2^63 | the float                   | `None`     |
+///
+/// `is_exact_f64` and `is_exact_i64` report whether those conversions lose
+/// nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonNumber {
+    Integer(i64),
+    Float(f64),
+}
+
+// 2^63 as an f64; floats at or above this don't fit in an i64.
+const I64_BOUND: f64 = 9_223_372_036_854_775_808.0;
+
+impl JsonNumber {
+    /// Returns `true` when the number was stored as an integer, regardless
+    /// of whether a float happens to have an integral value.
+    pub fn is_integer(&self) -> bool {
+        return matches!(self, JsonNumber::Integer(_));
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            JsonNumber::Integer(value) => *value as f64,
+            JsonNumber::Float(value) => *value,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonNumber::Integer(value) => Some(*value),
+            JsonNumber::Float(value) => {
+                if value.fract() != 0.0 || *value < -I64_BOUND || *value >= I64_BOUND {
+                    return None;
+                }
+
+                return Some(*value as i64);
+            }
+        }
+    }
+
+    /// Returns `true` if `as_f64` represents the number exactly.
+    pub fn is_exact_f64(&self) -> bool {
+        match self {
+            JsonNumber::Integer(value) => (*value as f64) as i128 == *value as i128,
+            JsonNumber::Float(_) => true,
+        }
+    }
+
+    /// Returns `true` if `as_i64` succeeds, which for floats means the value
+    /// is finite, integral and within the `i64` range.
+    pub fn is_exact_i64(&self) -> bool {
+        return self.as_i64().is_some();
+    }
+}
+
+impl JsonValue {
+    pub fn as_number(&self) -> Option<JsonNumber> {
+        match self {
+            JsonValue::Integer(value) => Some(JsonNumber::Integer(*value as i64)),
+            JsonValue::Float(value) => Some(JsonNumber::Float(*value)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::number::JsonNumber;
+
+    const TWO_53: i64 = 1 << 53;
+
+    #[test]
+    fn follows_the_coercion_matrix() {
+        assert_eq!(JsonNumber::Integer(5).as_f64(), 5.0);
+        assert_eq!(JsonNumber::Integer(5).as_i64(), Some(5));
+        assert_eq!(JsonNumber::Float(3.0).as_i64(), Some(3));
+        assert_eq!(JsonNumber::Float(-0.0).as_i64(), Some(0));
+        assert_eq!(JsonNumber::Float(3.5).as_i64(), None);
+        assert_eq!(JsonNumber::Float(3.5).as_f64(), 3.5);
+        assert_eq!(JsonNumber::Float(f64::NAN).as_i64(), None);
+        assert_eq!(JsonNumber::Float(f64::INFINITY).as_i64(), None);
+
+        assert!(JsonNumber::Integer(3).is_integer());
+        assert!(!JsonNumber::Float(3.0).is_integer());
+    }
+
+    #[test]
+    fn integers_past_2_to_the_53_lose_precision_as_floats() {
+        assert!(JsonNumber::Integer(TWO_53).is_exact_f64());
+        assert!(JsonNumber::Integer(-TWO_53).is_exact_f64());
+        assert!(!JsonNumber::Integer(TWO_53 + 1).is_exact_f64());
+        assert_eq!(JsonNumber::Integer(TWO_53 + 1).as_f64(), TWO_53 as f64);
+        assert!(JsonNumber::Integer(TWO_53 + 2).is_exact_f64());
+        assert!(!JsonNumber::Integer(i64::MAX).is_exact_f64());
+        assert!(JsonNumber::Integer(i64::MIN).is_exact_f64());
+        assert!(JsonNumber::Float(0.1).is_exact_f64());
+    }
+
+    #[test]
+    fn floats_convert_to_i64_only_within_its_range() {
+        let two_63 = 9_223_372_036_854_775_808.0;
+
+        assert_eq!(JsonNumber::Float(TWO_53 as f64).as_i64(), Some(TWO_53));
+        assert_eq!(JsonNumber::Float(-two_63).as_i64(), Some(i64::MIN));
+        assert!(JsonNumber::Float(-two_63).is_exact_i64());
+        assert_eq!(JsonNumber::Float(two_63).as_i64(), None);
+        assert!(!JsonNumber::Float(two_63).is_exact_i64());
+        assert_eq!(JsonNumber::Float(-two_63 * 2.0).as_i64(), None);
+        assert_eq!(JsonNumber::Float(1e300).as_i64(), None);
+    }
+
+    #[test]
+    fn only_numbers_have_a_number_view() {
+        assert_eq!(json!(7).as_number(), Some(JsonNumber::Integer(7)));
+        assert_eq!(json!(7.5).as_number(), Some(JsonNumber::Float(7.5)));
+        assert_eq!(json!("7").as_number(), None);
+        assert_eq!(json!(null).as_number(), None);
+        assert_eq!(json!([7]).as_number(), None);
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::json::{JsonValue, WrongKindError};
+
+impl JsonValue {
+    fn as_object_mut_or_err(&mut self) -> Result<&mut HashMap<String, JsonValue>, WrongKindError> {
+        match self {
+            JsonValue::Object(object) => {
+                return Ok(object);
+            }
+            _ => {
+                return Err(self.wrong_kind("object"));
+            }
+        }
+    }
+
+    /// Inserts a member, returning the value previously stored under `key`.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<JsonValue>,
+    ) -> Result<Option<JsonValue>, WrongKindError> {
+        return Ok(self
+            .as_object_mut_or_err()?
+            .insert(key.into(), value.into()));
+    }
+
+    pub fn remove(&mut self, key: &str) -> Result<Option<JsonValue>, WrongKindError> {
+        return Ok(self.as_object_mut_or_err()?.remove(key));
+    }
+
+    /// Moves the member `from` to the key `to`, replacing any member
+    /// already there. Returns `false`, changing nothing, if this isn't an
+    /// object or has no member `from`.
+    pub fn rename_key(&mut self, from: &str, to: &str) -> bool {
+        let object = match self {
+            JsonValue::Object(object) => object,
+            _ => return false,
+        };
+
+        match object.remove(from) {
+            Some(value) => {
+                object.insert(to.to_string(), value);
+                return true;
+            }
+            None => {
+                return false;
+            }
+        }
+    }
+
+    /// Returns whether an object has a member named `key`. Non-objects have
+    /// no members, so this is `false` for them rather than an error.
+    pub fn contains_key(&self, key: &str) -> bool {
+        match self {
+            JsonValue::Object(object) => object.contains_key(key),
+            _ => false,
+        }
+    }
+
+    pub fn entry(
+        &mut self,
+        key: impl Into<String>,
+    ) -> Result<Entry<'_, String, JsonValue>, WrongKindError> {
+        return Ok(self.as_object_mut_or_err()?.entry(key.into()));
+    }
+
+    /// Returns the member `key`, first inserting `default()` if it is
+    /// missing. An existing member is returned as is, whatever its kind.
+    pub fn get_or_insert_with(
+        &mut self,
+        key: impl Into<String>,
+        default: impl FnOnce() -> JsonValue,
+    ) -> Result<&mut JsonValue, WrongKindError> {
+        return Ok(self.entry(key)?.or_insert_with(default));
+    }
+}
+
+// The iteration views below yield nothing for non-object values instead of
+// returning an error, so they can be used directly in `for` loops.
+impl JsonValue {
+    fn object(&self) -> Option<&HashMap<String, JsonValue>> {
+        match self {
+            JsonValue::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    fn object_mut(&mut self) -> Option<&mut HashMap<String, JsonValue>> {
+        match self {
+            JsonValue::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        return self
+            .object()
+            .into_iter()
+            .flat_map(|object| object.keys().map(|key| key.as_str()));
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        return self.object().into_iter().flat_map(|object| object.values());
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut JsonValue> {
+        return self
+            .object_mut()
+            .into_iter()
+            .flat_map(|object| object.values_mut());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &JsonValue)> {
+        return self
+            .object()
+            .into_iter()
+            .flat_map(|object| object.iter().map(|(key, value)| (key.as_str(), value)));
+    }
+}
+
+// Keys are compared with ASCII case folding only, which is all HTTP header
+// names and the like need; `ß` and `SS`, say, don't match.
+fn matches_ignore_case<'a>(
+    object: &'a HashMap<String, JsonValue>,
+    key: &str,
+) -> Vec<(&'a str, &'a JsonValue)> {
+    let mut matches: Vec<(&str, &JsonValue)> = object
+        .iter()
+        .filter(|(candidate, _)| candidate.eq_ignore_ascii_case(key))
+        .map(|(candidate, value)| (candidate.as_str(), value))
+        .collect();
+    matches.sort_by(|a, b| a.0.cmp(b.0));
+
+    return matches;
+}
+
+// An exact match wins; otherwise the first key in code point order, since
+// objects don't remember the order members were inserted in.
+fn get_ignore_case<'a>(object: &'a HashMap<String, JsonValue>, key: &str) -> Option<&'a JsonValue> {
+    if let Some(value) = object.get(key) {
+        return Some(value);
+    }
+
+    return matches_ignore_case(object, key)
+        .first()
+        .map(|(_, value)| *value);
+}
+
+impl JsonValue {
+    /// Returns the member whose key equals `key` ignoring ASCII case, as in
+    /// HTTP header lookups. When several keys differ only by case, the one
+    /// spelled exactly as `key` wins, then the first in code point order,
+    /// so looking up `CONTENT-TYPE` finds `Content-Type` before
+    /// `content-type`.
+    pub fn get_ignore_case(&self, key: &str) -> Option<&JsonValue> {
+        return get_ignore_case(self.object()?, key);
+    }
+
+    /// Every member whose key equals `key` ignoring ASCII case, sorted by
+    /// key.
+    pub fn get_all_ignore_case(&self, key: &str) -> Vec<(&str, &JsonValue)> {
+        match self.object() {
+            Some(object) => matches_ignore_case(object, key),
+            None => Vec::new(),
+        }
+    }
+
+    /// Borrows an object for lookups that ignore ASCII case, or returns
+    /// `None` for other values. Creating the view copies nothing.
+    pub fn case_insensitive(&self) -> Option<CaseInsensitiveView<'_>> {
+        return self.object().map(|object| CaseInsensitiveView { object });
+    }
+}
+
+/// An object whose members are looked up ignoring ASCII case, from
+/// `JsonValue::case_insensitive`. Each lookup scans the members, which
+/// suits the small objects, like header maps, that need it.
+///
+/// ```
+/// use mini_json::json;
+///
+/// let headers = json!({"Content-Type": "application/json", "X-Request-Id": "42"});
+/// let view = headers.case_insensitive().unwrap();
+///
+/// assert_eq!(view.get("content-type"), Some(&json!("application/json")));
+/// assert!(view.contains_key("x-request-id"));
+/// assert!(!view.contains_key("accept"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CaseInsensitiveView<'a> {
+    object: &'a HashMap<String, JsonValue>,
+}
+
+impl<'a> CaseInsensitiveView<'a> {
+    /// The same member `JsonValue::get_ignore_case` returns.
+    pub fn get(&self, key: &str) -> Option<&'a JsonValue> {
+        return get_ignore_case(self.object, key);
+    }
+
+    pub fn get_all(&self, key: &str) -> Vec<(&'a str, &'a JsonValue)> {
+        return matches_ignore_case(self.object, key);
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        return self.get(key).is_some();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.object.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.object.is_empty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{JsonValue, WrongKindError};
+
+    fn not_an_object(found: &'static str) -> WrongKindError {
+        return WrongKindError {
+            expected: "object",
+            found,
+        };
+    }
+
+    #[test]
+    fn inserts_removes_and_renames_members() {
+        let mut object = json!({"a": 1});
+
+        assert_eq!(object.insert("b", "two"), Ok(None));
+        assert_eq!(object.insert("a", json!([1])), Ok(Some(json!(1))));
+        assert!(object.contains_key("b"));
+        assert_eq!(object.remove("b"), Ok(Some(json!("two"))));
+        assert_eq!(object.remove("b"), Ok(None));
+        assert!(!object.contains_key("b"));
+        assert_eq!(object, json!({"a": [1]}));
+
+        assert!(object.rename_key("a", "c"));
+        assert!(!object.rename_key("a", "d"));
+        assert_eq!(object, json!({"c": [1]}));
+    }
+
+    #[test]
+    fn renaming_onto_an_existing_key_overwrites_it() {
+        let mut object = json!({"old": {"v": 1}, "new": 2, "other": 3});
+
+        assert!(object.rename_key("old", "new"));
+        assert_eq!(object, json!({"new": {"v": 1}, "other": 3}));
+
+        assert!(object.rename_key("other", "other"));
+        assert_eq!(object, json!({"new": {"v": 1}, "other": 3}));
+
+        let mut array = json!(["old"]);
+        assert!(!array.rename_key("old", "new"));
+        assert_eq!(array, json!(["old"]));
+    }
+
+    #[test]
+    fn entry_bumps_existing_and_missing_counters() {
+        let mut doc = json!({"stats": {"hits": 1}});
+
+        for key in ["hits", "misses", "misses"] {
+            let stats = doc.get_or_insert_with("stats", || json!({})).unwrap();
+            stats
+                .entry(key)
+                .unwrap()
+                .and_modify(|count| {
+                    if let JsonValue::Integer(count) = count {
+                        *count += 1;
+                    }
+                })
+                .or_insert(json!(1));
+        }
+        assert_eq!(doc, json!({"stats": {"hits": 2, "misses": 2}}));
+
+        let existing = doc.get_or_insert_with("stats", || json!(null)).unwrap();
+        assert_eq!(*existing, json!({"hits": 2, "misses": 2}));
+        *doc.entry("new").unwrap().or_insert_with(|| json!([])) = json!(true);
+        assert_eq!(doc.pointer("/new"), Some(&json!(true)));
+    }
+
+    #[test]
+    fn non_objects_are_errors_and_left_alone() {
+        let mut array = json!([1]);
+
+        assert_eq!(array.insert("a", 1), Err(not_an_object("array")));
+        assert_eq!(array.remove("a"), Err(not_an_object("array")));
+        assert!(array.entry("a").is_err());
+        assert!(matches!(
+            json!("s").get_or_insert_with("a", || json!(1)),
+            Err(error) if error == not_an_object("string")
+        ));
+        assert!(!array.contains_key("a"));
+        assert!(!array.rename_key("a", "b"));
+        assert_eq!(array, json!([1]));
+    }
+
+    #[test]
+    fn views_collect_object_members() {
+        let mut object = json!({"a": 1, "b": [2]});
+
+        let mut keys: Vec<&str> = object.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+
+        let mut members: Vec<(&str, &JsonValue)> = object.iter().collect();
+        members.sort_by_key(|(key, _)| *key);
+        assert_eq!(members, [("a", &json!(1)), ("b", &json!([2]))]);
+        assert_eq!(object.values().count(), 2);
+
+        for value in object.values_mut() {
+            *value = json!(null);
+        }
+        assert_eq!(object, json!({"a": null, "b": null}));
+    }
+
+    #[test]
+    fn views_of_non_objects_are_empty() {
+        let mut array = json!([1, 2]);
+
+        assert_eq!(array.keys().count(), 0);
+        assert_eq!(array.values().count(), 0);
+        assert_eq!(array.values_mut().count(), 0);
+        assert_eq!(array.iter().count(), 0);
+        assert_eq!(json!(null).iter().count(), 0);
+    }
+
+    #[test]
+    fn header_names_match_ignoring_ascii_case() {
+        let headers = json!({"Content-Type": "application/json", "Accept": "*/*"});
+
+        assert_eq!(
+            headers.get_ignore_case("content-type"),
+            Some(&json!("application/json"))
+        );
+        assert_eq!(
+            headers.get_ignore_case("CONTENT-TYPE"),
+            Some(&json!("application/json"))
+        );
+        assert_eq!(headers.get_ignore_case("content_type"), None);
+        assert_eq!(headers.get_ignore_case("accept-encoding"), None);
+        assert_eq!(json!(["Accept"]).get_ignore_case("accept"), None);
+        assert!(json!("x").get_all_ignore_case("x").is_empty());
+    }
+
+    #[test]
+    fn same_folded_keys_prefer_the_exact_spelling() {
+        let headers = json!({
+            "content-type": "lower",
+            "Content-Type": "title",
+            "CONTENT-TYPE": "upper",
+            "Content-Length": "2"
+        });
+
+        assert_eq!(
+            headers.get_ignore_case("content-type"),
+            Some(&json!("lower"))
+        );
+        assert_eq!(
+            headers.get_ignore_case("Content-Type"),
+            Some(&json!("title"))
+        );
+        assert_eq!(
+            headers.get_ignore_case("content-TYPE"),
+            Some(&json!("upper"))
+        );
+        assert_eq!(
+            headers.get_all_ignore_case("CONTENT-type"),
+            vec![
+                ("CONTENT-TYPE", &json!("upper")),
+                ("Content-Type", &json!("title")),
+                ("content-type", &json!("lower")),
+            ]
+        );
+    }
+
+    #[test]
+    fn case_folding_is_ascii_only() {
+        let value = json!({"Straße": 1, "ÉTÉ": 2});
+
+        assert_eq!(value.get_ignore_case("STRASSE"), None);
+        assert_eq!(value.get_ignore_case("STRAßE"), Some(&json!(1)));
+        assert_eq!(value.get_ignore_case("éTé"), None);
+        assert_eq!(value.get_ignore_case("Été"), None);
+        assert_eq!(value.get_ignore_case("ÉtÉ"), Some(&json!(2)));
+    }
+
+    #[test]
+    fn case_insensitive_views_borrow_objects() {
+        let headers = json!({"Content-Type": "text/plain", "content-type": "text/html"});
+        let view = headers.case_insensitive().unwrap();
+
+        assert_eq!(view.get("CONTENT-TYPE"), Some(&json!("text/plain")));
+        assert_eq!(view.get("content-type"), Some(&json!("text/html")));
+        assert_eq!(view.get_all("Content-type").len(), 2);
+        assert!(view.contains_key("CoNtEnT-TyPe"));
+        assert!(!view.contains_key("accept"));
+        assert_eq!(view.len(), 2);
+        assert!(!view.is_empty());
+
+        assert!(json!({}).case_insensitive().unwrap().is_empty());
+        assert!(json!([]).case_insensitive().is_none());
+        assert!(json!(null).case_insensitive().is_none());
+    }
+}
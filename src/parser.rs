@@ -1,7 +1,252 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, str::Utf8Error, time::Duration};
 
 use crate::json::{Json, JsonValue};
 
+// The nesting depth past which the parser gives up rather than risk
+// overflowing the stack.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// What went wrong while parsing. More kinds may be added, so matches need
+/// a wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The input was empty or only whitespace.
+    EmptyDocument,
+    /// A character that can't appear where it was. `expected` describes
+    /// what could have, as in `"':' after a key"`.
+    UnexpectedCharacter { found: char, expected: &'static str },
+    /// The input ended where `expected` should have come next.
+    UnexpectedEnd { expected: &'static str },
+    /// The input ended inside the string whose opening quote is at
+    /// `start_line` and `start_column`.
+    UnterminatedString {
+        start_line: usize,
+        start_column: usize,
+    },
+    /// A number that's malformed or doesn't fit in its type.
+    InvalidNumber { lexeme: String },
+    /// An unknown escape or a `\u` escape without four hex digits;
+    /// `escape` is the text after the backslash.
+    InvalidEscape { escape: String },
+    /// A `\u` escape of one half of a surrogate pair without the other.
+    UnpairedSurrogate { code_unit: u32 },
+    /// A key that's already in the same object, where that's an error.
+    DuplicateKey { key: String },
+    /// Arrays and objects nested more than `limit` deep.
+    DepthLimitExceeded { limit: usize },
+    /// Anything but whitespace after the end of the document.
+    TrailingCharacters,
+    /// A JSON Pointer that doesn't start with `/`.
+    InvalidPointer { pointer: String },
+    /// Bytes that aren't UTF-8, positioned at the first bad one.
+    InvalidUtf8 { error: Utf8Error },
+    /// A raw newline, carriage return or tab inside a string, which JSON
+    /// requires to be escaped. Only reported by strict parsing.
+    UnescapedControlCharacter { found: char },
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::EmptyDocument => write!(f, "Empty document"),
+            ParseErrorKind::UnexpectedCharacter { found, expected } => {
+                write!(
+                    f,
+                    "Unexpected '{}', expected {}",
+                    found.escape_debug(),
+                    expected
+                )
+            }
+            ParseErrorKind::UnexpectedEnd { expected } => {
+                write!(f, "Unexpected end of input, expected {}", expected)
+            }
+            ParseErrorKind::UnterminatedString {
+                start_line,
+                start_column,
+            } => write!(
+                f,
+                "Unterminated string starting at line {}, column {}",
+                start_line, start_column
+            ),
+            ParseErrorKind::InvalidNumber { lexeme } => write!(f, "Invalid number '{}'", lexeme),
+            ParseErrorKind::InvalidEscape { escape } => {
+                write!(f, "Invalid escape '\\{}'", escape.escape_debug())
+            }
+            ParseErrorKind::UnpairedSurrogate { code_unit } => {
+                write!(f, "Unpaired surrogate '\\u{:04X}'", code_unit)
+            }
+            ParseErrorKind::DuplicateKey { key } => write!(f, "Duplicate key '{}'", key),
+            ParseErrorKind::DepthLimitExceeded { limit } => {
+                write!(f, "Nested more than {} levels deep", limit)
+            }
+            ParseErrorKind::TrailingCharacters => write!(f, "Unexpected text after the document"),
+            ParseErrorKind::InvalidPointer { pointer } => {
+                write!(f, "Invalid JSON Pointer '{}'", pointer)
+            }
+            ParseErrorKind::InvalidUtf8 { error } => write!(f, "Invalid UTF-8: {}", error),
+            ParseErrorKind::UnescapedControlCharacter { found } => {
+                let name = match found {
+                    '\n' => "newline",
+                    '\r' => "carriage return",
+                    '\t' => "tab",
+                    _ => "control character",
+                };
+                write!(
+                    f,
+                    "Unescaped {} in a string, use '{}' instead",
+                    name,
+                    found.escape_default()
+                )
+            }
+        }
+    }
+}
+
+/// Why a document couldn't be parsed, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// The 1-based line of the offending text.
+    pub line: usize,
+    /// The 1-based column in characters, at or just past the offending
+    /// text.
+    pub column: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[Error at line {}, column {}]: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+// The 1-based column of the character at byte `offset`.
+pub(crate) fn column_at(source: &str, offset: usize) -> usize {
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    return source[line_start..offset].chars().count() + 1;
+}
+
+impl ParseError {
+    // For an error in text cut from `source` at byte `start` and parsed as
+    // the lone element of an array, moves its positions to where they are
+    // in `source`.
+    pub(crate) fn relocate(&mut self, source: &str, start: usize) {
+        let lines = source[..start].matches('\n').count();
+        let start_column = column_at(source, start);
+        let relocate = |line: &mut usize, column: &mut usize| {
+            // Columns on the first line are offset by the `[` wrapper.
+            if *line == 1 {
+                *column = (start_column + *column).saturating_sub(2);
+            }
+            *line += lines;
+        };
+
+        relocate(&mut self.line, &mut self.column);
+        if let ParseErrorKind::UnterminatedString {
+            start_line,
+            start_column,
+        } = &mut self.kind
+        {
+            relocate(start_line, start_column);
+        }
+    }
+}
+
+// An `UnexpectedCharacter` error for the character at byte `offset`, or
+// `UnexpectedEnd` at the end of `source`.
+pub(crate) fn unexpected_at(
+    source: &str,
+    offset: usize,
+    line: usize,
+    expected: &'static str,
+) -> ParseError {
+    let offset = offset.min(source.len());
+    let kind = match source[offset..].chars().next() {
+        Some(found) => ParseErrorKind::UnexpectedCharacter { found, expected },
+        None => ParseErrorKind::UnexpectedEnd { expected },
+    };
+
+    return ParseError {
+        kind,
+        line,
+        column: column_at(source, offset),
+    };
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::InvalidUtf8 { error } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// How strictly `parse_from_str_with` follows the JSON grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Rejects raw newlines, carriage returns and tabs inside strings, as
+    /// JSON requires. Otherwise they're kept in the value, with each `\r\n`
+    /// read as `\n`.
+    pub strict: bool,
+    /// Reads every number as a `JsonValue::Float`, as JavaScript does, so
+    /// `5` and `5.0` parse to the same value. Integers beyond 2^53 in
+    /// magnitude round to the nearest `f64` unless `exact_integers` is set.
+    /// `as_number().as_i64()` still gives integral floats as integers, and
+    /// serializing writes them as floats, like `5.0`.
+    pub numbers_as_f64: bool,
+    /// With `numbers_as_f64`, rejects integers beyond 2^53 in magnitude,
+    /// which an `f64` can't always hold exactly, as `InvalidNumber` instead
+    /// of rounding them.
+    pub exact_integers: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            strict: true,
+            numbers_as_f64: false,
+            exact_integers: false,
+        }
+    }
+}
+
+// Whether the integer `lexeme` is within 2^53 of zero, where every integer
+// is an exact `f64`.
+fn is_exact_f64_integer(lexeme: &str) -> bool {
+    return lexeme
+        .parse::<i128>()
+        .is_ok_and(|integer| integer.unsigned_abs() <= 1 << 53);
+}
+
+/// Counts gathered while parsing, from `parse_with_stats`. Values replaced
+/// by a duplicate key are counted too, since they were parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub integers: usize,
+    pub floats: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// The number of containers on the deepest path, counted like
+    /// `JsonStats::max_depth`.
+    pub max_depth: usize,
+    /// Bytes of UTF-8, after decoding, in string values and keys that held
+    /// escape sequences.
+    pub unescaped_string_bytes: usize,
+    /// Members replaced by a later one with the same key in the same
+    /// object.
+    pub duplicate_keys: usize,
+    /// Wall time spent parsing.
+    pub elapsed: Duration,
+}
+
 #[derive(Debug)]
 struct Token {
     start: usize,
@@ -9,183 +254,418 @@ struct Token {
 }
 
 fn is_digit(c: char) -> bool {
-    c >= '0' && c <= '9'
+    c.is_ascii_digit()
+}
+
+// Whether `lexeme` follows JSON's number grammar, which is stricter than
+// Rust's: no leading zeros, and digits on both sides of a `.`.
+fn is_json_number(lexeme: &str) -> bool {
+    let bytes = lexeme.as_bytes();
+    let mut i = 0;
+    let digits = |i: &mut usize| -> usize {
+        let start = *i;
+        while *i < bytes.len() && bytes[*i].is_ascii_digit() {
+            *i += 1;
+        }
+        return *i - start;
+    };
+
+    if bytes.first() == Some(&b'-') {
+        i += 1;
+    }
+    let integer_start = i;
+    match digits(&mut i) {
+        0 => return false,
+        1 => {}
+        _ if bytes[integer_start] == b'0' => return false,
+        _ => {}
+    }
+    if i < bytes.len() && bytes[i] == b'.' {
+        i += 1;
+        if digits(&mut i) == 0 {
+            return false;
+        }
+    }
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        i += 1;
+        if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+            i += 1;
+        }
+        if digits(&mut i) == 0 {
+            return false;
+        }
+    }
+
+    return i == bytes.len();
 }
 
 #[derive(Debug)]
-pub struct Parser {
-    source: String,
+pub(crate) struct Parser {
+    chars: Vec<char>,
     start: usize,
     current: usize,
     line: usize,
+    depth: usize,
+    options: ParserOptions,
+    stats: ParseStats,
 }
 
 impl Parser {
-    pub fn new(source: String) -> Parser {
+    pub(crate) fn new(source: String) -> Parser {
+        return Parser::with_options(source, ParserOptions::default());
+    }
+
+    pub(crate) fn with_options(source: String, options: ParserOptions) -> Parser {
         Parser {
-            source,
+            chars: source.chars().collect(),
             start: 0,
             current: 0,
             line: 1,
+            depth: 0,
+            options,
+            stats: ParseStats::default(),
         }
     }
 
-    pub fn parse(&mut self) -> Json {
+    // The counts so far, without `elapsed`.
+    pub(crate) fn stats(&self) -> ParseStats {
+        return self.stats;
+    }
+
+    pub(crate) fn parse(&mut self) -> Result<Json, ParseError> {
         self.skip_whitespace();
+        if self.is_at_end() {
+            return Err(self.error(ParseErrorKind::EmptyDocument));
+        }
 
-        let c = self.advance();
+        let json = match self.advance() {
+            '{' => Json::Object(self.parse_object()?),
+            '[' => Json::Array(self.parse_array()?),
+            found => {
+                return Err(self.error(ParseErrorKind::UnexpectedCharacter {
+                    found,
+                    expected: "'{' or '['",
+                }));
+            }
+        };
+
+        self.skip_whitespace();
+        if !self.is_at_end() {
+            self.advance();
+            return Err(self.error(ParseErrorKind::TrailingCharacters));
+        }
 
-        if c == '{' {
-            return Json::Object(self.parse_object());
-        } else if c == '[' {
-            return Json::Array(self.parse_array());
-        } else {
-            self.error("Can't parse non-object or non-array".to_string());
-            return Json::Object(HashMap::new());
+        return Ok(json);
+    }
+
+    // The 1-based column of the character before `end`. A newline there is
+    // counted as the end of its line, not the start of the next.
+    fn column(&self, end: usize) -> usize {
+        let end = end.min(self.chars.len());
+        let line_start = self.chars[..end.saturating_sub(1)]
+            .iter()
+            .rposition(|c| *c == '\n')
+            .map_or(0, |index| index + 1);
+
+        return (end - line_start).max(1);
+    }
+
+    // Points at the last character read, which is usually the offending
+    // one.
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            line: self.line,
+            column: self.column(self.current),
         }
     }
 
-    fn error(&self, message: String) {
-        panic!("[Error at line {}]: {}", self.line, message);
+    // Reads the next character and reports it as unexpected, or reports the
+    // end of input if there isn't one.
+    fn unexpected(&mut self, expected: &'static str) -> ParseError {
+        if self.is_at_end() {
+            return self.error(ParseErrorKind::UnexpectedEnd { expected });
+        }
+
+        let found = self.advance();
+        return self.error(ParseErrorKind::UnexpectedCharacter { found, expected });
     }
 
-    fn parse_array(&mut self) -> Vec<JsonValue> {
+    // Called on entering an array or object, which must decrement `depth`
+    // on leaving it.
+    fn enter(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(ParseErrorKind::DepthLimitExceeded { limit: MAX_DEPTH }));
+        }
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+
+        return Ok(());
+    }
+
+    fn parse_array(&mut self) -> Result<Vec<JsonValue>, ParseError> {
         let mut array = Vec::new();
 
+        self.enter()?;
+        self.stats.arrays += 1;
         self.skip_whitespace();
 
-        while !self.match_end_of_array() {
-            match self.parse_value() {
-                Ok(value) => {
-                    array.push(value);
+        if !self.match_char(']') {
+            loop {
+                array.push(self.parse_value()?);
+                self.skip_whitespace();
+
+                // A `]` right after a comma is caught by `parse_value`, so
+                // trailing commas are rejected.
+                if self.match_char(',') {
+                    self.skip_whitespace();
+                    continue;
                 }
-                Err(error) => {
-                    self.error(error);
+                if self.match_char(']') {
+                    break;
                 }
+                return Err(self.unexpected("',' or ']'"));
             }
-
-            self.skip_whitespace();
-            if self.peek() == ',' {
-                self.advance();
-            }
-            self.skip_whitespace();
         }
 
-        return array;
+        self.depth -= 1;
+        return Ok(array);
     }
 
-    fn parse_object(&mut self) -> HashMap<String, JsonValue> {
+    fn parse_object(&mut self) -> Result<HashMap<String, JsonValue>, ParseError> {
         let mut properties = HashMap::new();
 
+        self.enter()?;
+        self.stats.objects += 1;
         self.skip_whitespace();
 
-        while !self.match_end_of_object() {
-            let c = self.advance();
+        if !self.match_char('}') {
+            loop {
+                if !self.match_char('"') {
+                    return Err(self.unexpected("'\"' to start a key"));
+                }
+                self.parse_key(&mut properties)?;
+                self.skip_whitespace();
 
-            // TODO: should add error checking here because we always expect double quote?
-            if c == '"' {
-                self.parse_key(&mut properties);
+                if self.match_char(',') {
+                    self.skip_whitespace();
+                    continue;
+                }
+                if self.match_char('}') {
+                    break;
+                }
+                return Err(self.unexpected("',' or '}'"));
             }
-
-            self.skip_whitespace();
         }
 
-        return properties;
+        self.depth -= 1;
+        return Ok(properties);
     }
 
-    fn parse_key(&mut self, properties: &mut HashMap<String, JsonValue>) {
-        let key_token = self.parse_string();
-        let key_lexeme = self.lexeme_from_token(key_token).to_owned();
+    fn parse_key(&mut self, properties: &mut HashMap<String, JsonValue>) -> Result<(), ParseError> {
+        let key_lexeme = self.parse_string()?;
 
         self.skip_whitespace();
         if !self.match_char(':') {
-            self.error(format!("Expect colon after key: '{}'", key_lexeme));
+            return Err(self.unexpected("':' after a key"));
         }
         self.skip_whitespace();
 
-        match self.parse_value() {
-            Ok(value) => {
-                properties.insert(key_lexeme, value);
-            }
-            Err(error) => {
-                self.error(error);
-            }
+        let value = self.parse_value()?;
+        if properties.insert(key_lexeme, value).is_some() {
+            self.stats.duplicate_keys += 1;
         }
+
+        return Ok(());
     }
 
-    fn parse_value(&mut self) -> Result<JsonValue, String> {
+    fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
+        if self.is_at_end() {
+            return Err(self.unexpected("a value"));
+        }
         let c = self.advance();
 
         match c {
             '"' => {
-                let value_token = self.parse_string();
-
-                return Ok(JsonValue::String(
-                    self.lexeme_from_token(value_token).to_owned(),
-                ));
+                self.stats.strings += 1;
+                return Ok(JsonValue::String(self.parse_string()?));
             }
             '{' => {
-                let value = self.parse_object();
+                let value = self.parse_object()?;
                 return Ok(JsonValue::Object(value));
             }
             '[' => {
-                let value = self.parse_array();
+                let value = self.parse_array()?;
                 return Ok(JsonValue::Array(value));
             }
             't' => {
-                self.parse_true();
+                self.parse_literal("rue", "'true'")?;
+                self.stats.booleans += 1;
                 return Ok(JsonValue::Boolean(true));
             }
             'f' => {
-                self.parse_false();
+                self.parse_literal("alse", "'false'")?;
+                self.stats.booleans += 1;
                 return Ok(JsonValue::Boolean(false));
             }
+            'n' => {
+                self.parse_literal("ull", "'null'")?;
+                self.stats.nulls += 1;
+                return Ok(JsonValue::Null);
+            }
             _ => {
                 if !(is_digit(c) || c == '-') {
-                    return Err("Unexpected value".to_string());
+                    return Err(self.error(ParseErrorKind::UnexpectedCharacter {
+                        found: c,
+                        expected: "a value",
+                    }));
                 }
 
                 let (value_token, is_float) = self.parse_number();
+                let lexeme = self.lexeme_from_token(value_token);
+                if !is_json_number(&lexeme) {
+                    return Err(self.error(ParseErrorKind::InvalidNumber { lexeme }));
+                }
 
-                if is_float {
-                    let value = self.lexeme_from_token(value_token).parse::<f64>();
+                // Floats too large for an `f64` would read as infinity, which
+                // JSON can't represent, so they're rejected like integers
+                // too large for an `isize`.
+                let value = match (is_float, self.options.numbers_as_f64) {
+                    (false, false) => lexeme.parse::<isize>().ok().map(JsonValue::Integer),
+                    (false, true)
+                        if self.options.exact_integers && !is_exact_f64_integer(&lexeme) =>
+                    {
+                        None
+                    }
+                    _ => lexeme
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|float| float.is_finite())
+                        .map(JsonValue::Float),
+                };
+                match value {
+                    Some(JsonValue::Float(_)) => self.stats.floats += 1,
+                    Some(_) => self.stats.integers += 1,
+                    None => {}
+                }
+                return value.ok_or_else(|| self.error(ParseErrorKind::InvalidNumber { lexeme }));
+            }
+        }
+    }
+
+    // Reads the rest of a string whose opening quote was already consumed,
+    // decoding escape sequences.
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        let mut string = String::new();
+        let mut has_escapes = false;
+        // The column is only worked out on error, since finding it means
+        // scanning back to the start of the line.
+        let start = self.current;
+        let start_line = self.line;
+        let unterminated = |parser: &Parser| {
+            parser.error(ParseErrorKind::UnterminatedString {
+                start_line,
+                start_column: parser.column(start),
+            })
+        };
 
-                    match value {
-                        Ok(value) => {
-                            return Ok(JsonValue::Float(value));
-                        }
-                        Err(err) => {
-                            return Err(err.to_string());
-                        }
+        loop {
+            if self.is_at_end() {
+                return Err(unterminated(self));
+            }
+
+            match self.advance() {
+                '"' => {
+                    if has_escapes {
+                        self.stats.unescaped_string_bytes += string.len();
                     }
-                } else {
-                    let value = self.lexeme_from_token(value_token).parse::<isize>();
-
-                    match value {
-                        Ok(value) => {
-                            return Ok(JsonValue::Integer(value));
-                        }
-                        Err(err) => {
-                            return Err(err.to_string());
-                        }
+                    return Ok(string);
+                }
+                '\\' if self.is_at_end() => {
+                    return Err(unterminated(self));
+                }
+                '\\' => {
+                    let escaped = self.parse_escape()?;
+                    string.push(escaped);
+                    has_escapes = true;
+                }
+                found @ ('\n' | '\r' | '\t') if self.options.strict => {
+                    return Err(self.error(ParseErrorKind::UnescapedControlCharacter { found }));
+                }
+                '\r' if self.peek() == '\n' => {}
+                c => {
+                    if c == '\n' {
+                        self.line += 1;
                     }
+                    string.push(c);
                 }
             }
         }
     }
 
-    fn parse_string(&mut self) -> Token {
-        self.start = self.current;
-        self.advance();
+    fn parse_escape(&mut self) -> Result<char, ParseError> {
+        match self.advance() {
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            '/' => Ok('/'),
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            'u' => {
+                let high = self.parse_hex4()?;
 
-        while !self.is_at_end() && self.peek() != '"' {
-            self.advance();
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| {
+                        self.error(ParseErrorKind::UnpairedSurrogate { code_unit: high })
+                    });
+                }
+
+                if !(self.match_char('\\') && self.match_char('u')) {
+                    return Err(self.error(ParseErrorKind::UnpairedSurrogate { code_unit: high }));
+                }
+
+                let low = self.parse_hex4()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error(ParseErrorKind::UnpairedSurrogate { code_unit: high }));
+                }
+
+                let code_point = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                return Ok(char::from_u32(code_point).unwrap_or('\u{fffd}'));
+            }
+            c => Err(self.error(ParseErrorKind::InvalidEscape {
+                escape: c.to_string(),
+            })),
         }
+    }
 
-        let token = self.make_token();
-        self.advance();
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut value = 0;
+        let start = self.current;
+
+        for _ in 0..4 {
+            let digit = self.peek().to_digit(16);
+
+            match digit {
+                Some(digit) if !self.is_at_end() => {
+                    self.advance();
+                    value = value * 16 + digit;
+                }
+                _ => {
+                    // Include the offending character, if there is one.
+                    let escape = self.chars[start..(self.current + 1).min(self.chars.len())]
+                        .iter()
+                        .collect::<String>();
+                    self.advance();
+                    return Err(self.error(ParseErrorKind::InvalidEscape {
+                        escape: format!("u{}", escape),
+                    }));
+                }
+            }
+        }
 
-        return token;
+        return Ok(value);
     }
 
     fn parse_number(&mut self) -> (Token, bool) {
@@ -200,29 +680,31 @@ impl Parser {
             }
         }
 
+        if self.peek() == 'e' || self.peek() == 'E' {
+            is_float = true;
+            self.advance();
+
+            if self.peek() == '+' || self.peek() == '-' {
+                self.advance();
+            }
+            while !self.is_at_end() && is_digit(self.peek()) {
+                self.advance();
+            }
+        }
+
         let token = self.make_token();
         return (token, is_float);
     }
 
-    fn parse_true(&mut self) {
-        let r = self.advance();
-        let u = self.advance();
-        let e = self.advance();
-
-        if !(r == 'r' && u == 'u' && e == 'e') {
-            self.error("Unexpected value".to_string());
+    // Reads the rest of `true`, `false` or `null` after its first letter.
+    fn parse_literal(&mut self, rest: &str, expected: &'static str) -> Result<(), ParseError> {
+        for c in rest.chars() {
+            if !self.match_char(c) {
+                return Err(self.unexpected(expected));
+            }
         }
-    }
-
-    fn parse_false(&mut self) {
-        let a = self.advance();
-        let l = self.advance();
-        let s = self.advance();
-        let e = self.advance();
 
-        if !(a == 'a' && l == 'l' && s == 's' && e == 'e') {
-            self.error("Unexpected value".to_string());
-        }
+        return Ok(());
     }
 
     fn make_token(&self) -> Token {
@@ -232,23 +714,29 @@ impl Parser {
         }
     }
 
-    fn lexeme_from_token(&self, token: Token) -> &str {
-        return &self.source[token.start..(token.start + token.length)];
+    fn lexeme_from_token(&self, token: Token) -> String {
+        return self.chars[token.start..(token.start + token.length)]
+            .iter()
+            .collect();
     }
 
     fn is_at_end(&self) -> bool {
-        self.current == self.source.len()
+        self.current >= self.chars.len()
     }
 
     fn get_char_at_index(&self, index: usize) -> char {
-        return self
-            .source
-            .chars()
-            .nth(index)
-            .expect(format!("Couldn't get char at index {}", index).as_str());
+        return *self
+            .chars
+            .get(index)
+            .unwrap_or_else(|| panic!("Couldn't get char at index {}", index));
     }
 
+    // Returns `'\0'` at the end of input rather than moving past it.
     fn advance(&mut self) -> char {
+        if self.is_at_end() {
+            return '\0';
+        }
+
         self.current += 1;
         return self.get_char_at_index(self.current - 1);
     }
@@ -273,40 +761,6 @@ impl Parser {
         return true;
     }
 
-    fn match_end_of_array(&mut self) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-        if self.peek() != ']' {
-            return false;
-        }
-
-        self.current += 1;
-
-        if self.peek() == ',' {
-            self.current += 1;
-        }
-
-        return true;
-    }
-
-    fn match_end_of_object(&mut self) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-        if self.peek() != '}' {
-            return false;
-        }
-
-        self.current += 1;
-
-        if self.peek() == ',' {
-            self.current += 1;
-        }
-
-        return true;
-    }
-
     fn skip_whitespace(&mut self) {
         loop {
             if self.is_at_end() {
@@ -330,3 +784,360 @@ impl Parser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{parse_from_bytes, parse_from_str, parse_from_str_with, Json, JsonValue};
+    use crate::parser::{ParseErrorKind, ParserOptions, MAX_DEPTH};
+
+    fn kind(source: &str) -> ParseErrorKind {
+        return parse_from_str(source).unwrap_err().kind;
+    }
+
+    fn unexpected(source: &str) -> (char, &'static str) {
+        match kind(source) {
+            ParseErrorKind::UnexpectedCharacter { found, expected } => (found, expected),
+            other => panic!(
+                "expected UnexpectedCharacter for {:?}, got {:?}",
+                source, other
+            ),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_documents() {
+        for source in [
+            "{}",
+            "[]",
+            " { } ",
+            "[1, -2, 3.5, -0.25, 1e3, 2E-2, 0, -0, 0.5]",
+            r#"{"a": 1, "b": [true, false, null], "c": {"d": "e"}}"#,
+            "[[], {}, [[]], {\"a\": {}}]",
+        ] {
+            assert!(parse_from_str(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn empty_document() {
+        assert_eq!(kind(""), ParseErrorKind::EmptyDocument);
+        assert_eq!(kind(" \n\t "), ParseErrorKind::EmptyDocument);
+    }
+
+    #[test]
+    fn keys_must_be_strings() {
+        assert_eq!(unexpected("{garbage}"), ('g', "'\"' to start a key"));
+        assert_eq!(unexpected("{1: 2}"), ('1', "'\"' to start a key"));
+        assert_eq!(unexpected("{,,\"a\":true}"), (',', "'\"' to start a key"));
+    }
+
+    #[test]
+    fn members_and_elements_need_commas() {
+        assert_eq!(unexpected(r#"{"a":1 "b":2}"#), ('"', "',' or '}'"));
+        assert_eq!(unexpected("[1 2 3]"), ('2', "',' or ']'"));
+        assert_eq!(unexpected("[true false]"), ('f', "',' or ']'"));
+    }
+
+    #[test]
+    fn trailing_commas_are_rejected() {
+        assert_eq!(unexpected(r#"{"a":1,}"#), ('}', "'\"' to start a key"));
+        assert_eq!(unexpected("[1,]"), (']', "a value"));
+        assert_eq!(unexpected("[,]"), (',', "a value"));
+    }
+
+    #[test]
+    fn text_after_the_document() {
+        for source in ["[1],", r#"{"a":[1]},"#, "{} {}", "[] x"] {
+            assert_eq!(
+                kind(source),
+                ParseErrorKind::TrailingCharacters,
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        for (source, lexeme) in [
+            ("[01]", "01"),
+            ("[-01]", "-01"),
+            ("[1.]", "1."),
+            ("[1.e5]", "1.e5"),
+            ("[-]", "-"),
+            ("[1e]", "1e"),
+            ("[1e+]", "1e+"),
+            ("[1.2.3]", "1.2.3"),
+            ("[99999999999999999999]", "99999999999999999999"),
+            ("[1e999]", "1e999"),
+        ] {
+            assert_eq!(
+                kind(source),
+                ParseErrorKind::InvalidNumber {
+                    lexeme: lexeme.to_string()
+                },
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn unexpected_end() {
+        assert_eq!(
+            kind("{"),
+            ParseErrorKind::UnexpectedEnd {
+                expected: "'\"' to start a key"
+            }
+        );
+        assert_eq!(
+            kind("[1"),
+            ParseErrorKind::UnexpectedEnd {
+                expected: "',' or ']'"
+            }
+        );
+        assert_eq!(
+            kind("[1,"),
+            ParseErrorKind::UnexpectedEnd {
+                expected: "a value"
+            }
+        );
+        assert_eq!(
+            kind(r#"{"a""#),
+            ParseErrorKind::UnexpectedEnd {
+                expected: "':' after a key"
+            }
+        );
+    }
+
+    #[test]
+    fn unexpected_root() {
+        assert_eq!(unexpected("1"), ('1', "'{' or '['"));
+        assert_eq!(unexpected("\"a\""), ('"', "'{' or '['"));
+    }
+
+    #[test]
+    fn unterminated_string_points_at_its_start() {
+        let error = parse_from_str("[\n  \"abc").unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::UnterminatedString {
+                start_line: 2,
+                start_column: 3
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_escapes_and_surrogates() {
+        assert_eq!(
+            kind(r#"["\x"]"#),
+            ParseErrorKind::InvalidEscape {
+                escape: "x".to_string()
+            }
+        );
+        assert_eq!(
+            kind(r#"["\u12G4"]"#),
+            ParseErrorKind::InvalidEscape {
+                escape: "u12G".to_string()
+            }
+        );
+        assert_eq!(
+            kind(r#"["\ud800"]"#),
+            ParseErrorKind::UnpairedSurrogate { code_unit: 0xD800 }
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        let deep = "[".repeat(MAX_DEPTH + 1) + &"]".repeat(MAX_DEPTH + 1);
+        assert_eq!(
+            kind(&deep),
+            ParseErrorKind::DepthLimitExceeded { limit: MAX_DEPTH }
+        );
+
+        let fits = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+        assert!(parse_from_str(&fits).is_ok());
+    }
+
+    #[test]
+    fn unescaped_control_characters() {
+        assert_eq!(
+            kind("[\"a\nb\"]"),
+            ParseErrorKind::UnescapedControlCharacter { found: '\n' }
+        );
+
+        let lenient = ParserOptions {
+            strict: false,
+            ..ParserOptions::default()
+        };
+        let json = parse_from_str_with("[\"a\tb\"]", &lenient).unwrap();
+        assert_eq!(
+            json,
+            Json::Array(vec![JsonValue::String("a\tb".to_string())])
+        );
+    }
+
+    #[test]
+    fn unescaped_control_characters_suggest_the_escape() {
+        for (found, name, escape) in [
+            ('\n', "newline", "\\n"),
+            ('\r', "carriage return", "\\r"),
+            ('\t', "tab", "\\t"),
+        ] {
+            let source = format!("[\n  \"ab{}cd\"]", found);
+            let error = parse_from_str(&source).unwrap_err();
+            assert_eq!(
+                error.kind,
+                ParseErrorKind::UnescapedControlCharacter { found }
+            );
+            assert_eq!((error.line, error.column), (2, 6), "{:?}", found);
+            assert_eq!(
+                error.kind.to_string(),
+                format!("Unescaped {} in a string, use '{}' instead", name, escape)
+            );
+        }
+    }
+
+    #[test]
+    fn lenient_strings_keep_raw_characters_and_count_lines() {
+        let lenient = ParserOptions {
+            strict: false,
+            ..ParserOptions::default()
+        };
+
+        let json = parse_from_str_with("[\"a\r\nb\", \"c\rd\", \"e\nf\"]", &lenient).unwrap();
+        assert_eq!(JsonValue::from(json), json!(["a\nb", "c\rd", "e\nf"]));
+
+        let error = parse_from_str_with("[\"a\r\nb\n\", x]", &lenient).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 4));
+    }
+
+    #[test]
+    fn numbers_as_f64_reads_every_number_as_a_float() {
+        let options = ParserOptions {
+            numbers_as_f64: true,
+            ..ParserOptions::default()
+        };
+        let parse = |source: &str| JsonValue::from(parse_from_str_with(source, &options).unwrap());
+
+        let value = parse("[5, 5.0, -0, 1e2, 9007199254740993]");
+        assert_eq!(
+            value,
+            JsonValue::Array(vec![
+                JsonValue::Float(5.0),
+                JsonValue::Float(5.0),
+                JsonValue::Float(-0.0),
+                JsonValue::Float(100.0),
+                JsonValue::Float(9007199254740992.0),
+            ])
+        );
+        assert_eq!(value.pointer("/0"), value.pointer("/1"));
+        assert_eq!(
+            value.pointer("/0").unwrap().as_number().unwrap().as_i64(),
+            Some(5)
+        );
+        assert_eq!(
+            value.to_string_minified().unwrap(),
+            "[5.0,5.0,-0.0,1e2,9007199254740992.0]"
+        );
+
+        // Without the option the two fives differ.
+        let value = JsonValue::from(parse_from_str("[5, 5.0]").unwrap());
+        assert_ne!(value.pointer("/0"), value.pointer("/1"));
+    }
+
+    #[test]
+    fn exact_integers_rejects_what_an_f64_would_round() {
+        let options = ParserOptions {
+            numbers_as_f64: true,
+            exact_integers: true,
+            ..ParserOptions::default()
+        };
+
+        assert!(parse_from_str_with("[9007199254740992, -9007199254740992]", &options).is_ok());
+        for lexeme in ["9007199254740993", "-9007199254740993"] {
+            let source = format!("[{}]", lexeme);
+            assert_eq!(
+                parse_from_str_with(&source, &options).unwrap_err().kind,
+                ParseErrorKind::InvalidNumber {
+                    lexeme: lexeme.to_string()
+                }
+            );
+        }
+        // Fractions aren't integers, so they're rounded as usual.
+        assert!(parse_from_str_with("[0.1, 9007199254740993.0]", &options).is_ok());
+    }
+
+    #[test]
+    fn invalid_utf8_has_a_source() {
+        let error = parse_from_bytes(b"[\"\xff\"]").unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::InvalidUtf8 { .. }));
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!((error.line, error.column), (1, 3));
+    }
+
+    #[test]
+    fn every_kind_has_a_message() {
+        let message = |source: &str| kind(source).to_string();
+
+        assert_eq!(message(""), "Empty document");
+        assert_eq!(message("[1 2]"), "Unexpected '2', expected ',' or ']'");
+        assert_eq!(
+            message("{\"a\": 'b'}"),
+            "Unexpected '\\'', expected a value"
+        );
+        assert_eq!(message("[1,"), "Unexpected end of input, expected a value");
+        assert_eq!(
+            message("[\"a"),
+            "Unterminated string starting at line 1, column 2"
+        );
+        assert_eq!(message("[01]"), "Invalid number '01'");
+        assert_eq!(message(r#"["\x"]"#), "Invalid escape '\\x'");
+        assert_eq!(message(r#"["\udc00"]"#), "Unpaired surrogate '\\uDC00'");
+        assert_eq!(
+            message(&"[".repeat(MAX_DEPTH + 1)),
+            format!("Nested more than {} levels deep", MAX_DEPTH)
+        );
+        assert_eq!(message("[] []"), "Unexpected text after the document");
+        assert_eq!(
+            message("[\"a\tb\"]"),
+            "Unescaped tab in a string, use '\\t' instead"
+        );
+        assert_eq!(
+            ParseErrorKind::DuplicateKey {
+                key: "a".to_string()
+            }
+            .to_string(),
+            "Duplicate key 'a'"
+        );
+        assert_eq!(
+            ParseErrorKind::InvalidPointer {
+                pointer: "a/b".to_string()
+            }
+            .to_string(),
+            "Invalid JSON Pointer 'a/b'"
+        );
+        assert!(parse_from_bytes(b"[\"\xc3\"]")
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid UTF-8: "));
+    }
+
+    #[test]
+    fn positions_are_reported() {
+        let error = parse_from_str("{\n  \"a\": 1\n  \"b\": 2\n}").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+        assert_eq!(
+            error.to_string(),
+            r#"[Error at line 3, column 3]: Unexpected '\"', expected ',' or '}'"#
+        );
+
+        // A newline is at the end of its line.
+        let source = "{\n  \"a\": tru\n}";
+        assert_eq!(unexpected(source), ('\n', "'true'"));
+        let error = parse_from_str(source).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 11));
+    }
+}
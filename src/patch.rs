@@ -0,0 +1,711 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::JsonValue;
+use crate::path::{
+    escape_pointer_token, parse_array_index, parse_pointer, resolve, resolve_mut, PathError,
+    PathSegment,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchError {
+    pub index: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[Patch error at operation {}]: {}",
+            self.index, self.message
+        )
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+fn split_pointer(pointer: &str) -> Result<(Vec<PathSegment>, String), String> {
+    let mut segments = match parse_pointer(pointer) {
+        Some(segments) => segments,
+        None => return Err(format!("Invalid pointer '{}'", pointer)),
+    };
+
+    match segments.pop() {
+        Some(PathSegment::Key(last)) => {
+            return Ok((segments, last));
+        }
+        _ => {
+            return Err(format!("Pointer '{}' has no parent", pointer));
+        }
+    }
+}
+
+pub(crate) fn pointer_get<'a>(root: &'a JsonValue, pointer: &str) -> Result<&'a JsonValue, String> {
+    let segments = match parse_pointer(pointer) {
+        Some(segments) => segments,
+        None => return Err(format!("Invalid pointer '{}'", pointer)),
+    };
+
+    match resolve(root, &segments) {
+        Some(value) => {
+            return Ok(value);
+        }
+        None => {
+            return Err(format!("Path '{}' does not exist", pointer));
+        }
+    }
+}
+
+pub(crate) fn pointer_add(
+    root: &mut JsonValue,
+    pointer: &str,
+    value: JsonValue,
+) -> Result<(), String> {
+    if pointer.is_empty() {
+        *root = value;
+        return Ok(());
+    }
+
+    let (parent_segments, last) = split_pointer(pointer)?;
+    let parent = match resolve_mut(root, &parent_segments) {
+        Some(parent) => parent,
+        None => return Err(format!("Parent of '{}' does not exist", pointer)),
+    };
+
+    match parent {
+        JsonValue::Object(object) => {
+            object.insert(last, value);
+        }
+        JsonValue::Array(array) => {
+            if last == "-" {
+                array.push(value);
+            } else {
+                match parse_array_index(&last) {
+                    Some(index) if index <= array.len() => {
+                        array.insert(index, value);
+                    }
+                    _ => {
+                        return Err(format!("Invalid array index in '{}'", pointer));
+                    }
+                }
+            }
+        }
+        _ => {
+            return Err(format!("Parent of '{}' is not a container", pointer));
+        }
+    }
+
+    return Ok(());
+}
+
+pub(crate) fn pointer_remove(root: &mut JsonValue, pointer: &str) -> Result<JsonValue, String> {
+    if pointer.is_empty() {
+        return Err("Can't remove the root value".to_string());
+    }
+
+    let (parent_segments, last) = split_pointer(pointer)?;
+    let parent = match resolve_mut(root, &parent_segments) {
+        Some(parent) => parent,
+        None => return Err(format!("Path '{}' does not exist", pointer)),
+    };
+
+    let removed = match parent {
+        JsonValue::Object(object) => object.remove(&last),
+        JsonValue::Array(array) => match parse_array_index(&last) {
+            Some(index) if index < array.len() => Some(array.remove(index)),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    match removed {
+        Some(value) => {
+            return Ok(value);
+        }
+        None => {
+            return Err(format!("Path '{}' does not exist", pointer));
+        }
+    }
+}
+
+// Moves the value at `from` to `path` as JSON Patch's `move` does. If it
+// can't be added at `path`, it's put back at `from`, so a failed move leaves
+// `root` as it was.
+fn pointer_move(root: &mut JsonValue, from: &str, path: &str) -> Result<(), String> {
+    if from == path {
+        return pointer_get(root, from).map(|_| ());
+    }
+    if path.starts_with(from) && path[from.len()..].starts_with('/') {
+        return Err(format!("Can't move '{}' into its own child", from));
+    }
+
+    let value = pointer_remove(root, from)?;
+    if let Err(error) = pointer_add(root, path, value.clone()) {
+        pointer_add(root, from, value)?;
+        return Err(error);
+    }
+
+    return Ok(());
+}
+
+fn pointer_remove_or_root(root: &mut JsonValue, pointer: &str) -> Result<(), String> {
+    if pointer.is_empty() {
+        return Ok(());
+    }
+
+    pointer_remove(root, pointer)?;
+    return Ok(());
+}
+
+fn member<'a>(operation: &'a JsonValue, name: &str) -> Result<&'a JsonValue, String> {
+    match operation {
+        JsonValue::Object(object) => match object.get(name) {
+            Some(value) => {
+                return Ok(value);
+            }
+            None => {
+                return Err(format!("Missing '{}' member", name));
+            }
+        },
+        _ => {
+            return Err("Operation must be an object".to_string());
+        }
+    }
+}
+
+fn string_member<'a>(operation: &'a JsonValue, name: &str) -> Result<&'a str, String> {
+    match member(operation, name)? {
+        JsonValue::String(value) => {
+            return Ok(value);
+        }
+        _ => {
+            return Err(format!("'{}' member must be a string", name));
+        }
+    }
+}
+
+// Equality for `test`, which RFC 6902 defines on values rather than their
+// representation: numbers are equal when numerically equal, so `1` equals
+// `1.0`, and containers are compared member by member.
+fn values_equal(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Integer(integer), JsonValue::Float(float))
+        | (JsonValue::Float(float), JsonValue::Integer(integer)) => {
+            let float = JsonValue::Float(*float)
+                .as_number()
+                .and_then(|n| n.as_i64());
+            return float == Some(*integer as i64);
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            return a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b));
+        }
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            return a.len() == b.len()
+                && a.iter().all(|(key, a)| match b.get(key) {
+                    Some(b) => values_equal(a, b),
+                    None => false,
+                });
+        }
+        _ => {
+            return a == b;
+        }
+    }
+}
+
+fn apply_operation(root: &mut JsonValue, operation: &JsonValue) -> Result<(), String> {
+    let op = string_member(operation, "op")?;
+    let path = string_member(operation, "path")?;
+
+    match op {
+        "add" => {
+            let value = member(operation, "value")?.clone();
+            pointer_add(root, path, value)?;
+        }
+        "remove" => {
+            pointer_remove(root, path)?;
+        }
+        "replace" => {
+            let value = member(operation, "value")?.clone();
+            pointer_remove_or_root(root, path)?;
+            pointer_add(root, path, value)?;
+        }
+        "move" => {
+            let from = string_member(operation, "from")?;
+            pointer_move(root, from, path)?;
+        }
+        "copy" => {
+            let from = string_member(operation, "from")?;
+            let value = pointer_get(root, from)?.clone();
+            pointer_add(root, path, value)?;
+        }
+        "test" => {
+            let expected = member(operation, "value")?;
+
+            if !values_equal(pointer_get(root, path)?, expected) {
+                return Err(format!("Test failed for path '{}'", path));
+            }
+        }
+        _ => {
+            return Err(format!("Unknown operation '{}'", op));
+        }
+    }
+
+    return Ok(());
+}
+
+impl JsonValue {
+    /// Applies an RFC 6902 JSON Patch. The patch is applied to a copy, so
+    /// if any operation fails the document is left untouched.
+    pub fn apply_patch(&mut self, patch: &JsonValue) -> Result<(), PatchError> {
+        let operations = match patch {
+            JsonValue::Array(operations) => operations,
+            _ => {
+                return Err(PatchError {
+                    index: 0,
+                    message: "Patch must be an array of operations".to_string(),
+                });
+            }
+        };
+
+        let mut document = self.clone();
+
+        for (index, operation) in operations.iter().enumerate() {
+            if let Err(message) = apply_operation(&mut document, operation) {
+                return Err(PatchError { index, message });
+            }
+        }
+
+        *self = document;
+        return Ok(());
+    }
+
+    /// Moves the value at the JSON Pointer `from` to `to`, with the
+    /// semantics of a JSON Patch `move`: `to` may name a new object member
+    /// or array position, and replaces an existing member. Moving a value
+    /// into its own child is an error. On error the value is unchanged.
+    /// Errors aren't tied to one pointer, so their position is always 0.
+    pub fn move_value(&mut self, from: &str, to: &str) -> Result<(), PathError> {
+        return pointer_move(self, from, to).map_err(|message| PathError {
+            message,
+            position: 0,
+        });
+    }
+}
+
+fn operation(op: &str, path: &str, value: Option<JsonValue>) -> JsonValue {
+    let mut object = HashMap::new();
+    object.insert("op".to_string(), JsonValue::String(op.to_string()));
+    object.insert("path".to_string(), JsonValue::String(path.to_string()));
+
+    if let Some(value) = value {
+        object.insert("value".to_string(), value);
+    }
+
+    return JsonValue::Object(object);
+}
+
+fn diff_value(old: &JsonValue, new: &JsonValue, pointer: &mut String, ops: &mut Vec<JsonValue>) {
+    if old == new {
+        return;
+    }
+
+    match (old, new) {
+        (JsonValue::Object(old_object), JsonValue::Object(new_object)) => {
+            let mut keys: Vec<&String> = old_object.keys().chain(new_object.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer_token(key));
+
+                match (old_object.get(key), new_object.get(key)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_value(old_value, new_value, pointer, ops);
+                    }
+                    (Some(_), None) => {
+                        ops.push(operation("remove", pointer, None));
+                    }
+                    (None, Some(new_value)) => {
+                        ops.push(operation("add", pointer, Some(new_value.clone())));
+                    }
+                    (None, None) => {}
+                }
+
+                pointer.truncate(len);
+            }
+        }
+        (JsonValue::Array(old_array), JsonValue::Array(new_array)) => {
+            let common = old_array.len().min(new_array.len());
+
+            for index in 0..common {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+
+                diff_value(&old_array[index], &new_array[index], pointer, ops);
+
+                pointer.truncate(len);
+            }
+
+            for (index, new_value) in new_array.iter().enumerate().skip(common) {
+                let path = format!("{}/{}", pointer, index);
+                ops.push(operation("add", &path, Some(new_value.clone())));
+            }
+
+            // Remove from the end so earlier indices stay valid.
+            for index in (common..old_array.len()).rev() {
+                let path = format!("{}/{}", pointer, index);
+                ops.push(operation("remove", &path, None));
+            }
+        }
+        _ => {
+            ops.push(operation("replace", pointer, Some(new.clone())));
+        }
+    }
+}
+
+/// Computes an RFC 6902 patch that turns `old` into `new` when applied with
+/// `JsonValue::apply_patch`. Arrays are compared index by index rather than
+/// searching for a minimal edit script.
+pub fn diff(old: &JsonValue, new: &JsonValue) -> JsonValue {
+    let mut ops = Vec::new();
+    diff_value(old, new, &mut String::new(), &mut ops);
+
+    return JsonValue::Array(ops);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{parse_from_str, JsonValue};
+    use crate::patch::{diff, PatchError};
+
+    fn value(text: &str) -> JsonValue {
+        return JsonValue::from(parse_from_str(text).unwrap());
+    }
+
+    // The examples from RFC 6902, Appendix A, with `None` where the patch
+    // must fail. A.13 is left out: duplicate members can't survive parsing.
+    const RFC_6902_EXAMPLES: [(&str, &str, &str, Option<&str>); 15] = [
+        (
+            "A.1",
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/baz","value":"qux"}]"#,
+            Some(r#"{"baz":"qux","foo":"bar"}"#),
+        ),
+        (
+            "A.2",
+            r#"{"foo":["bar","baz"]}"#,
+            r#"[{"op":"add","path":"/foo/1","value":"qux"}]"#,
+            Some(r#"{"foo":["bar","qux","baz"]}"#),
+        ),
+        (
+            "A.3",
+            r#"{"baz":"qux","foo":"bar"}"#,
+            r#"[{"op":"remove","path":"/baz"}]"#,
+            Some(r#"{"foo":"bar"}"#),
+        ),
+        (
+            "A.4",
+            r#"{"foo":["bar","qux","baz"]}"#,
+            r#"[{"op":"remove","path":"/foo/1"}]"#,
+            Some(r#"{"foo":["bar","baz"]}"#),
+        ),
+        (
+            "A.5",
+            r#"{"baz":"qux","foo":"bar"}"#,
+            r#"[{"op":"replace","path":"/baz","value":"boo"}]"#,
+            Some(r#"{"baz":"boo","foo":"bar"}"#),
+        ),
+        (
+            "A.6",
+            r#"{"foo":{"bar":"baz","waldo":"fred"},"qux":{"corge":"grault"}}"#,
+            r#"[{"op":"move","from":"/foo/waldo","path":"/qux/thud"}]"#,
+            Some(r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#),
+        ),
+        (
+            "A.7",
+            r#"{"foo":["all","grass","cows","eat"]}"#,
+            r#"[{"op":"move","from":"/foo/1","path":"/foo/3"}]"#,
+            Some(r#"{"foo":["all","cows","eat","grass"]}"#),
+        ),
+        (
+            "A.8",
+            r#"{"baz":"qux","foo":["a",2,"c"]}"#,
+            r#"[{"op":"test","path":"/baz","value":"qux"},{"op":"test","path":"/foo/1","value":2}]"#,
+            Some(r#"{"baz":"qux","foo":["a",2,"c"]}"#),
+        ),
+        (
+            "A.9",
+            r#"{"baz":"qux"}"#,
+            r#"[{"op":"test","path":"/baz","value":"bar"}]"#,
+            None,
+        ),
+        (
+            "A.10",
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/child","value":{"grandchild":{}}}]"#,
+            Some(r#"{"foo":"bar","child":{"grandchild":{}}}"#),
+        ),
+        (
+            "A.11",
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/baz","value":"qux","xyz":123}]"#,
+            Some(r#"{"foo":"bar","baz":"qux"}"#),
+        ),
+        (
+            "A.12",
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/baz/bat","value":"qux"}]"#,
+            None,
+        ),
+        (
+            "A.14",
+            r#"{"/":9,"~1":10}"#,
+            r#"[{"op":"test","path":"/~01","value":10}]"#,
+            Some(r#"{"/":9,"~1":10}"#),
+        ),
+        (
+            "A.15",
+            r#"{"/":9,"~1":10}"#,
+            r#"[{"op":"test","path":"/~01","value":"10"}]"#,
+            None,
+        ),
+        (
+            "A.16",
+            r#"{"foo":["bar"]}"#,
+            r#"[{"op":"add","path":"/foo/-","value":["abc","def"]}]"#,
+            Some(r#"{"foo":["bar",["abc","def"]]}"#),
+        ),
+    ];
+
+    #[test]
+    fn apply_patch_matches_rfc_6902() {
+        for (section, document, patch, result) in RFC_6902_EXAMPLES {
+            let mut target = value(document);
+            let applied = target.apply_patch(&value(patch));
+
+            match result {
+                Some(result) => {
+                    assert_eq!(applied, Ok(()), "{}", section);
+                    assert_eq!(target, value(result), "{}", section);
+                }
+                None => {
+                    assert!(applied.is_err(), "{}", section);
+                    assert_eq!(target, value(document), "{}", section);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn failed_patches_leave_the_document_untouched() {
+        let mut doc = json!({"a": 1, "list": [1, 2]});
+        let patch = json!([
+            {"op": "add", "path": "/b", "value": 2},
+            {"op": "remove", "path": "/list/0"},
+            {"op": "remove", "path": "/missing"}
+        ]);
+
+        assert_eq!(
+            doc.apply_patch(&patch),
+            Err(PatchError {
+                index: 2,
+                message: "Path '/missing' does not exist".to_string()
+            })
+        );
+        assert_eq!(doc, json!({"a": 1, "list": [1, 2]}));
+    }
+
+    #[test]
+    fn malformed_operations_are_errors() {
+        let cases = [
+            (json!({}), "Patch must be an array of operations"),
+            (json!([1]), "Operation must be an object"),
+            (json!([{"path": "/a"}]), "Missing 'op' member"),
+            (
+                json!([{"op": "add", "path": 1}]),
+                "'path' member must be a string",
+            ),
+            (
+                json!([{"op": "add", "path": "/a"}]),
+                "Missing 'value' member",
+            ),
+            (
+                json!([{"op": "move", "path": "/a"}]),
+                "Missing 'from' member",
+            ),
+            (
+                json!([{"op": "nope", "path": "/a"}]),
+                "Unknown operation 'nope'",
+            ),
+            (
+                json!([{"op": "add", "path": "a", "value": 1}]),
+                "Invalid pointer 'a'",
+            ),
+            (
+                json!([{"op": "add", "path": "/list/5", "value": 1}]),
+                "Invalid array index in '/list/5'",
+            ),
+            (
+                json!([{"op": "add", "path": "/list/01", "value": 1}]),
+                "Invalid array index in '/list/01'",
+            ),
+            (
+                json!([{"op": "add", "path": "/a/b", "value": 1}]),
+                "Parent of '/a/b' is not a container",
+            ),
+            (
+                json!([{"op": "remove", "path": ""}]),
+                "Can't remove the root value",
+            ),
+            (
+                json!([{"op": "move", "from": "/list", "path": "/list/0"}]),
+                "Can't move '/list' into its own child",
+            ),
+        ];
+
+        for (patch, message) in cases {
+            let mut doc = json!({"a": 1, "list": [1]});
+            let error = doc.apply_patch(&patch).unwrap_err();
+            assert_eq!(error.message, message, "{}", patch);
+        }
+    }
+
+    #[test]
+    fn test_compares_numbers_by_value() {
+        let mut document = json!({"n": 1.0, "list": [2, {"x": -0.0}], "big": 9007199254740993_i64});
+        let passing = [
+            json!([{"op": "test", "path": "/n", "value": 1}]),
+            json!([{"op": "test", "path": "/list", "value": [2.0, {"x": 0}]}]),
+            json!([{"op": "test", "path": "/n", "value": 1e0}]),
+        ];
+        for patch in &passing {
+            assert_eq!(document.apply_patch(patch), Ok(()), "{}", patch);
+        }
+
+        let failing = [
+            ("/n", json!(1.5)),
+            ("/n", json!("1")),
+            ("/list", json!([2, {"x": 0, "y": 0}])),
+            // 2^53 + 1 has no float of its own, so no float equals it.
+            ("/big", json!(9007199254740992.0)),
+        ];
+        for (path, value) in failing {
+            let patch = json!([{"op": "test", "path": path, "value": value}]);
+            assert_eq!(
+                document.apply_patch(&patch),
+                Err(PatchError {
+                    index: 0,
+                    message: format!("Test failed for path '{}'", path),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn the_root_can_be_replaced() {
+        let mut doc = json!({"a": 1});
+        doc.apply_patch(&json!([{"op": "replace", "path": "", "value": [1]}]))
+            .unwrap();
+        assert_eq!(doc, json!([1]));
+
+        doc.apply_patch(&json!([{"op": "add", "path": "", "value": "x"}]))
+            .unwrap();
+        assert_eq!(doc, json!("x"));
+    }
+
+    #[test]
+    fn copy_and_move_onto_themselves() {
+        let mut doc = json!({"a": {"b": 1}});
+        doc.apply_patch(&json!([
+            {"op": "copy", "from": "/a", "path": "/c"},
+            {"op": "move", "from": "/a", "path": "/a"},
+            {"op": "move", "from": "/a/b", "path": "/ab"}
+        ]))
+        .unwrap();
+        assert_eq!(doc, json!({"a": {}, "ab": 1, "c": {"b": 1}}));
+    }
+
+    #[test]
+    fn move_value_moves_between_containers() {
+        let mut doc = json!({"from": {"x": [1, 2]}, "to": [0], "keep": {"y": 1}});
+
+        doc.move_value("/from/x", "/to/1").unwrap();
+        assert_eq!(
+            doc,
+            json!({"from": {}, "to": [0, [1, 2]], "keep": {"y": 1}})
+        );
+
+        doc.move_value("/to/0", "/keep/y").unwrap();
+        assert_eq!(doc, json!({"from": {}, "to": [[1, 2]], "keep": {"y": 0}}));
+
+        doc.move_value("/to/0/1", "/to/-").unwrap();
+        assert_eq!(doc, json!({"from": {}, "to": [[1], 2], "keep": {"y": 0}}));
+    }
+
+    #[test]
+    fn move_value_into_its_own_child_is_an_error() {
+        let mut doc = json!({"a": {"b": {"c": 1}}, "ab": 2});
+        let before = doc.clone();
+
+        let error = doc.move_value("/a", "/a/b/d").unwrap_err();
+        assert_eq!(error.position, 0);
+        assert_eq!(doc, before);
+
+        // Only whole tokens make a prefix.
+        doc.move_value("/a", "/ab").unwrap();
+        assert_eq!(doc, json!({"ab": {"b": {"c": 1}}}));
+
+        for (from, to) in [("/missing", "/x"), ("/ab", "/nope/x"), ("ab", "/x")] {
+            assert!(doc.move_value(from, to).is_err(), "{} -> {}", from, to);
+        }
+        assert_eq!(doc, json!({"ab": {"b": {"c": 1}}}));
+    }
+
+    #[test]
+    fn diff_emits_operations_in_key_order() {
+        let old = json!({"a": 1, "b": {"c": [1, 2, 3]}, "gone": null, "~/": 1});
+        let new = json!({"a": 2, "b": {"c": [1, 5]}, "added": [], "~/": 1});
+
+        assert_eq!(
+            diff(&old, &new),
+            json!([
+                {"op": "replace", "path": "/a", "value": 2},
+                {"op": "add", "path": "/added", "value": []},
+                {"op": "replace", "path": "/b/c/1", "value": 5},
+                {"op": "remove", "path": "/b/c/2"},
+                {"op": "remove", "path": "/gone"}
+            ])
+        );
+        assert_eq!(diff(&old, &old), json!([]));
+        assert_eq!(
+            diff(&json!({"~/": 1}), &json!({"~/": 2})),
+            json!([{"op": "replace", "path": "/~0~1", "value": 2}])
+        );
+    }
+
+    #[test]
+    fn diff_applies_back_to_the_new_value() {
+        let values = [
+            json!({}),
+            json!([]),
+            json!(null),
+            json!({"a": [1, {"b": 2}], "c": "d"}),
+            json!({"a": [1], "c": {"d": null}, "e": 1.5}),
+            json!([[1, 2, 3], {"x": [true]}]),
+            json!([[1], {"x": []}, 7, 8]),
+            json!("scalar"),
+        ];
+
+        for old in &values {
+            for new in &values {
+                let mut patched = old.clone();
+                patched.apply_patch(&diff(old, new)).unwrap();
+                assert_eq!(&patched, new, "{} -> {}", old, new);
+            }
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use std::fmt;
+
+use crate::json::JsonValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[Path error at {}]: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+fn path_error(message: &str, position: usize) -> PathError {
+    PathError {
+        message: message.to_string(),
+        position,
+    }
+}
+
+/// Parses a dotted path such as `server.hosts[2].port` or `a["weird.key"]`.
+/// An empty path refers to the value itself.
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>, PathError> {
+    let chars: Vec<char> = path.chars().collect();
+    let mut segments = Vec::new();
+    let mut current = 0;
+
+    if chars.is_empty() {
+        return Ok(segments);
+    }
+
+    if chars[0] != '[' {
+        current = parse_path_key(&chars, current, &mut segments)?;
+    }
+
+    while current < chars.len() {
+        match chars[current] {
+            '.' => {
+                current = parse_path_key(&chars, current + 1, &mut segments)?;
+            }
+            '[' => {
+                current = parse_path_bracket(&chars, current + 1, &mut segments)?;
+            }
+            _ => {
+                return Err(path_error("Expect '.' or '[' between segments", current));
+            }
+        }
+    }
+
+    return Ok(segments);
+}
+
+fn parse_path_key(
+    chars: &[char],
+    start: usize,
+    segments: &mut Vec<PathSegment>,
+) -> Result<usize, PathError> {
+    let mut current = start;
+
+    while current < chars.len() && chars[current] != '.' && chars[current] != '[' {
+        current += 1;
+    }
+
+    if current == start {
+        return Err(path_error("Expect key", start));
+    }
+
+    segments.push(PathSegment::Key(chars[start..current].iter().collect()));
+    return Ok(current);
+}
+
+fn parse_path_bracket(
+    chars: &[char],
+    start: usize,
+    segments: &mut Vec<PathSegment>,
+) -> Result<usize, PathError> {
+    let mut current = start;
+
+    if current < chars.len() && (chars[current] == '"' || chars[current] == '\'') {
+        let quote = chars[current];
+        let mut key = String::new();
+        current += 1;
+
+        loop {
+            if current >= chars.len() {
+                return Err(path_error("Unterminated quoted key", start));
+            }
+
+            let c = chars[current];
+            current += 1;
+
+            if c == quote {
+                break;
+            } else if c == '\\' {
+                if current >= chars.len() {
+                    return Err(path_error("Unterminated quoted key", start));
+                }
+                key.push(chars[current]);
+                current += 1;
+            } else {
+                key.push(c);
+            }
+        }
+
+        if current >= chars.len() || chars[current] != ']' {
+            return Err(path_error("Expect ']' after quoted key", current));
+        }
+
+        segments.push(PathSegment::Key(key));
+        return Ok(current + 1);
+    }
+
+    while current < chars.len() && chars[current].is_ascii_digit() {
+        current += 1;
+    }
+
+    if current == start {
+        return Err(path_error("Expect index or quoted key", start));
+    }
+    if current >= chars.len() || chars[current] != ']' {
+        return Err(path_error("Expect ']' after index", current));
+    }
+
+    let digits: String = chars[start..current].iter().collect();
+    match digits.parse::<usize>() {
+        Ok(index) => {
+            segments.push(PathSegment::Index(index));
+        }
+        Err(_) => {
+            return Err(path_error("Index out of range", start));
+        }
+    }
+
+    return Ok(current + 1);
+}
+
+/// Parses an RFC 6901 JSON Pointer into key segments, returning `None` when
+/// the pointer is malformed.
+pub(crate) fn parse_pointer(pointer: &str) -> Option<Vec<PathSegment>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return None;
+    }
+
+    let mut segments = Vec::new();
+
+    for token in pointer[1..].split('/') {
+        segments.push(PathSegment::Key(unescape_pointer_token(token)?));
+    }
+
+    return Some(segments);
+}
+
+fn unescape_pointer_token(token: &str) -> Option<String> {
+    let mut key = String::new();
+    let mut chars = token.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '~' {
+            match chars.next() {
+                Some('0') => key.push('~'),
+                Some('1') => key.push('/'),
+                _ => return None,
+            }
+        } else {
+            key.push(c);
+        }
+    }
+
+    return Some(key);
+}
+
+/// Parses an RFC 6901 array index: `0` or a number without leading zeros.
+pub(crate) fn parse_array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+
+    return token.parse::<usize>().ok();
+}
+
+fn child<'a>(value: &'a JsonValue, segment: &PathSegment) -> Option<&'a JsonValue> {
+    match (value, segment) {
+        (JsonValue::Object(object), PathSegment::Key(key)) => object.get(key),
+        (JsonValue::Array(array), PathSegment::Index(index)) => array.get(*index),
+        (JsonValue::Array(array), PathSegment::Key(key)) => array.get(parse_array_index(key)?),
+        _ => None,
+    }
+}
+
+fn child_mut<'a>(value: &'a mut JsonValue, segment: &PathSegment) -> Option<&'a mut JsonValue> {
+    match (value, segment) {
+        (JsonValue::Object(object), PathSegment::Key(key)) => object.get_mut(key),
+        (JsonValue::Array(array), PathSegment::Index(index)) => array.get_mut(*index),
+        (JsonValue::Array(array), PathSegment::Key(key)) => array.get_mut(parse_array_index(key)?),
+        _ => None,
+    }
+}
+
+pub(crate) fn resolve<'a>(value: &'a JsonValue, segments: &[PathSegment]) -> Option<&'a JsonValue> {
+    let mut current = value;
+
+    for segment in segments {
+        current = child(current, segment)?;
+    }
+
+    return Some(current);
+}
+
+pub(crate) fn resolve_mut<'a>(
+    value: &'a mut JsonValue,
+    segments: &[PathSegment],
+) -> Option<&'a mut JsonValue> {
+    let mut current = value;
+
+    for segment in segments {
+        current = child_mut(current, segment)?;
+    }
+
+    return Some(current);
+}
+
+impl JsonValue {
+    /// Looks up an RFC 6901 JSON Pointer like `/server/hosts/2/port`, where
+    /// `~1` stands for `/` and `~0` for `~`. The empty pointer is the value
+    /// itself. A missing value or a malformed pointer gives `None`.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        return resolve(self, &parse_pointer(pointer)?);
+    }
+
+    /// Like `pointer`, but borrows the value for changing in place.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        return resolve_mut(self, &parse_pointer(pointer)?);
+    }
+
+    /// Looks up a dotted path like `server.hosts[2].port`. Missing values
+    /// resolve to `Ok(None)`; only a malformed path is an error.
+    pub fn get_path(&self, path: &str) -> Result<Option<&JsonValue>, PathError> {
+        let segments = parse_path(path)?;
+        return Ok(resolve(self, &segments));
+    }
+
+    /// Like `get_path`, but borrows the value for changing in place.
+    pub fn get_path_mut(&mut self, path: &str) -> Result<Option<&mut JsonValue>, PathError> {
+        let segments = parse_path(path)?;
+        return Ok(resolve_mut(self, &segments));
+    }
+
+    /// Resolves a JSON Pointer for mutation, creating an empty object for
+    /// every missing object member along the way. Existing values are
+    /// reused and never overwritten: stepping into a scalar or `null`, or
+    /// to an array index that doesn't exist, is an error whose position is
+    /// the byte offset of the offending reference token.
+    pub fn ensure_path(&mut self, pointer: &str) -> Result<&mut JsonValue, PathError> {
+        let segments = match parse_pointer(pointer) {
+            Some(segments) => segments,
+            None => return Err(path_error("Invalid JSON Pointer", 0)),
+        };
+
+        let mut current = self;
+        let mut position = 0;
+
+        for (segment, token) in segments.into_iter().zip(pointer.split('/').skip(1)) {
+            let key = match segment {
+                PathSegment::Key(key) => key,
+                PathSegment::Index(index) => index.to_string(),
+            };
+
+            current = match current {
+                JsonValue::Object(object) => object
+                    .entry(key)
+                    .or_insert_with(|| JsonValue::Object(Default::default())),
+                JsonValue::Array(array) => match parse_array_index(&key) {
+                    Some(index) if index < array.len() => &mut array[index],
+                    _ => return Err(path_error("Array index out of bounds", position + 1)),
+                },
+                other => {
+                    let message =
+                        format!("Expected an object or array, found {}", other.kind_name());
+                    return Err(path_error(&message, position + 1));
+                }
+            };
+
+            position += 1 + token.len();
+        }
+
+        return Ok(current);
+    }
+}
+
+/// Escapes a key for use as a JSON Pointer reference token.
+pub(crate) fn escape_pointer_token(token: &str) -> String {
+    return token.replace('~', "~0").replace('/', "~1");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::path::{escape_pointer_token, parse_array_index, parse_path, parse_pointer};
+    use crate::path::{PathError, PathSegment};
+    use crate::serialize::{OutputStyle, SerializeOptions};
+
+    fn key(key: &str) -> PathSegment {
+        return PathSegment::Key(key.to_string());
+    }
+
+    fn error(message: &str, position: usize) -> PathError {
+        return PathError {
+            message: message.to_string(),
+            position,
+        };
+    }
+
+    #[test]
+    fn parses_dotted_paths() {
+        assert_eq!(parse_path(""), Ok(vec![]));
+        assert_eq!(
+            parse_path("server.hosts[2].port"),
+            Ok(vec![
+                key("server"),
+                key("hosts"),
+                PathSegment::Index(2),
+                key("port")
+            ])
+        );
+        assert_eq!(
+            parse_path("[0][1].a"),
+            Ok(vec![PathSegment::Index(0), PathSegment::Index(1), key("a")])
+        );
+        assert_eq!(
+            parse_path(r#"a["weird.key"]['it\'s'].é"#),
+            Ok(vec![key("a"), key("weird.key"), key("it's"), key("é")])
+        );
+    }
+
+    #[test]
+    fn malformed_paths_are_errors() {
+        assert_eq!(parse_path("a..b"), Err(error("Expect key", 2)));
+        assert_eq!(parse_path("a."), Err(error("Expect key", 2)));
+        assert_eq!(
+            parse_path("a[]"),
+            Err(error("Expect index or quoted key", 2))
+        );
+        assert_eq!(parse_path("a[1"), Err(error("Expect ']' after index", 3)));
+        assert_eq!(
+            parse_path("a[x]"),
+            Err(error("Expect index or quoted key", 2))
+        );
+        assert_eq!(
+            parse_path("a[\"b]"),
+            Err(error("Unterminated quoted key", 2))
+        );
+        assert_eq!(
+            parse_path("a[\"b\"x"),
+            Err(error("Expect ']' after quoted key", 5))
+        );
+        assert_eq!(
+            parse_path("a[0]b"),
+            Err(error("Expect '.' or '[' between segments", 4))
+        );
+        assert_eq!(
+            parse_path("a[99999999999999999999999]"),
+            Err(error("Index out of range", 2))
+        );
+    }
+
+    #[test]
+    fn parses_pointers() {
+        assert_eq!(parse_pointer(""), Some(vec![]));
+        assert_eq!(parse_pointer("/"), Some(vec![key("")]));
+        assert_eq!(
+            parse_pointer("/a~1b/~0c/0"),
+            Some(vec![key("a/b"), key("~c"), key("0")])
+        );
+        assert_eq!(parse_pointer("a"), None);
+        assert_eq!(parse_pointer("/a~2"), None);
+        assert_eq!(parse_pointer("/a~"), None);
+        assert_eq!(escape_pointer_token("a/b~c"), "a~1b~0c");
+    }
+
+    #[test]
+    fn array_indices_have_no_leading_zeros() {
+        assert_eq!(parse_array_index("0"), Some(0));
+        assert_eq!(parse_array_index("10"), Some(10));
+        assert_eq!(parse_array_index("01"), None);
+        assert_eq!(parse_array_index("-1"), None);
+        assert_eq!(parse_array_index("-"), None);
+        assert_eq!(parse_array_index(""), None);
+        assert_eq!(parse_array_index("1e2"), None);
+    }
+
+    #[test]
+    fn looks_up_paths_and_pointers() {
+        let doc = json!({"server": {"hosts": [{"port": 80}, {"port": 443}]}, "a/b": 1});
+
+        assert_eq!(doc.get_path("server.hosts[1].port"), Ok(Some(&json!(443))));
+        assert_eq!(doc.get_path("server.hosts[2].port"), Ok(None));
+        assert_eq!(doc.get_path("server.hosts.port"), Ok(None));
+        assert_eq!(doc.get_path(""), Ok(Some(&doc)));
+        assert!(doc.get_path("server..hosts").is_err());
+
+        assert_eq!(doc.pointer("/server/hosts/0/port"), Some(&json!(80)));
+        assert_eq!(doc.pointer("/a~1b"), Some(&json!(1)));
+        assert_eq!(doc.pointer("/server/hosts/00/port"), None);
+        assert_eq!(doc.pointer("server"), None);
+        assert_eq!(doc.pointer(""), Some(&doc));
+    }
+
+    #[test]
+    fn mutates_through_paths_and_pointers() {
+        let mut doc = json!({"a": [{"b": 1}]});
+
+        *doc.pointer_mut("/a/0/b").unwrap() = json!(2);
+        assert_eq!(doc, json!({"a": [{"b": 2}]}));
+        assert_eq!(doc.get_path_mut("a[0].c"), Ok(None));
+        if let Ok(Some(value)) = doc.get_path_mut("a[0]") {
+            *value = json!({"b": [2]});
+        }
+        assert_eq!(doc, json!({"a": [{"b": [2]}]}));
+        assert!(doc.pointer_mut("/a/1").is_none());
+    }
+
+    #[test]
+    fn mutates_deep_values_in_place_and_misses_without_side_effects() {
+        let mut doc = json!({"config": {"retries": 3, "hosts": [["a", 80], ["b", 81]]}});
+
+        *doc.pointer_mut("/config/retries").unwrap() = 5.into();
+        *doc.get_path_mut("config.hosts[1][1]").unwrap().unwrap() = 8081.into();
+        if let Some(JsonValue::Array(host)) = doc.pointer_mut("/config/hosts/0") {
+            host.push(json!(true));
+        }
+        let mut options = SerializeOptions::new(OutputStyle::Minified);
+        options.sort_keys = true;
+        assert_eq!(
+            doc.to_string_with(&options).unwrap(),
+            r#"{"config":{"hosts":[["a",80,true],["b",8081]],"retries":5}}"#
+        );
+
+        let before = doc.clone();
+        for pointer in [
+            "/config/missing",
+            "/config/hosts/2",
+            "/config/retries/x",
+            "config",
+        ] {
+            assert!(doc.pointer_mut(pointer).is_none(), "{}", pointer);
+        }
+        for path in ["config.missing", "config.hosts[9]", "nothing.here"] {
+            assert!(matches!(doc.get_path_mut(path), Ok(None)), "{}", path);
+        }
+        assert_eq!(doc, before);
+    }
+
+    #[test]
+    fn ensure_path_creates_missing_objects() {
+        let mut doc = json!({"a": {"keep": true}, "list": [{}], "n": 1});
+
+        *doc.ensure_path("/a/b/c").unwrap() = json!(1);
+        *doc.ensure_path("/list/0/x").unwrap() = json!(2);
+        assert_eq!(
+            doc,
+            json!({"a": {"keep": true, "b": {"c": 1}}, "list": [{"x": 2}], "n": 1})
+        );
+
+        // Existing values are reused rather than overwritten.
+        assert_eq!(doc.ensure_path("/a/keep").unwrap(), &json!(true));
+
+        assert_eq!(
+            doc.ensure_path("/n/x"),
+            Err(error("Expected an object or array, found integer", 3))
+        );
+        assert_eq!(
+            doc.ensure_path("/list/1"),
+            Err(error("Array index out of bounds", 6))
+        );
+        assert_eq!(doc.ensure_path("x"), Err(error("Invalid JSON Pointer", 0)));
+    }
+}
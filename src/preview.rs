@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+use crate::json::{Json, JsonValue};
+use crate::serialize::{float_text, write_string, NonFiniteFloats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewOptions {
+    /// Elements shown per array before the rest are summarized as
+    /// `"… 4321 more"`.
+    pub max_elements: usize,
+    /// Members shown per object before the rest are summarized as
+    /// `"…": "12 more"`.
+    pub max_members: usize,
+    /// Characters kept of each string, with `…` marking a cut.
+    pub max_string_chars: usize,
+    /// Levels of nesting shown; deeper non-empty arrays and objects are
+    /// written as `[…]` and `{…}`.
+    pub max_depth: usize,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> PreviewOptions {
+        PreviewOptions {
+            max_elements: 10,
+            max_members: 10,
+            max_string_chars: 80,
+            max_depth: 4,
+        }
+    }
+}
+
+struct Previewer<'a> {
+    out: String,
+    options: &'a PreviewOptions,
+}
+
+impl Previewer<'_> {
+    fn string(&mut self, string: &str) -> fmt::Result {
+        match string.char_indices().nth(self.options.max_string_chars) {
+            Some((end, _)) => {
+                let truncated = format!("{}…", &string[..end]);
+                return write_string(&mut self.out, &truncated, '"', false);
+            }
+            None => {
+                return write_string(&mut self.out, string, '"', false);
+            }
+        }
+    }
+
+    fn value(&mut self, value: &JsonValue, depth: usize) -> fmt::Result {
+        match value {
+            JsonValue::Null => self.out.write_str("null"),
+            JsonValue::Boolean(value) => write!(self.out, "{}", value),
+            JsonValue::Integer(value) => write!(self.out, "{}", value),
+            JsonValue::Float(value) => {
+                let text = float_text(*value, NonFiniteFloats::Literal).unwrap_or_default();
+                self.out.write_str(&text)
+            }
+            JsonValue::String(value) => self.string(value),
+            JsonValue::Array(array) => self.array(array, depth),
+            JsonValue::Object(object) => self.object(object, depth),
+        }
+    }
+
+    fn array(&mut self, array: &[JsonValue], depth: usize) -> fmt::Result {
+        if array.is_empty() {
+            return self.out.write_str("[]");
+        }
+        if depth >= self.options.max_depth {
+            return self.out.write_str("[…]");
+        }
+
+        self.out.write_char('[')?;
+        for (i, element) in array.iter().take(self.options.max_elements).enumerate() {
+            if i > 0 {
+                self.out.write_str(", ")?;
+            }
+            self.value(element, depth + 1)?;
+        }
+
+        let shown = array.len().min(self.options.max_elements);
+        if shown < array.len() {
+            if shown > 0 {
+                self.out.write_str(", ")?;
+            }
+            write!(self.out, "\"… {} more\"", array.len() - shown)?;
+        }
+
+        return self.out.write_char(']');
+    }
+
+    // Members are shown in sorted key order, so the same document always
+    // previews the same way.
+    fn object(&mut self, object: &HashMap<String, JsonValue>, depth: usize) -> fmt::Result {
+        if object.is_empty() {
+            return self.out.write_str("{}");
+        }
+        if depth >= self.options.max_depth {
+            return self.out.write_str("{…}");
+        }
+
+        let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+        members.sort_by(|a, b| a.0.cmp(b.0));
+
+        self.out.write_char('{')?;
+        for (i, (key, member)) in members.iter().take(self.options.max_members).enumerate() {
+            if i > 0 {
+                self.out.write_str(", ")?;
+            }
+            self.string(key)?;
+            self.out.write_str(": ")?;
+            self.value(member, depth + 1)?;
+        }
+
+        let shown = members.len().min(self.options.max_members);
+        if shown < members.len() {
+            if shown > 0 {
+                self.out.write_str(", ")?;
+            }
+            write!(self.out, "\"…\": \"{} more\"", members.len() - shown)?;
+        }
+
+        return self.out.write_char('}');
+    }
+}
+
+impl JsonValue {
+    /// Renders a short, single-line summary of the value for logs. Long
+    /// arrays, objects and strings are cut off and nesting past
+    /// `max_depth` is elided, so the text looks like JSON but may not read
+    /// back as the same value, or parse at all.
+    ///
+    /// ```
+    /// use mini_json::{json, PreviewOptions};
+    ///
+    /// let value = json!({"ids": [1, 2, 3, 4, 5], "note": "a long note"});
+    /// let options = PreviewOptions {
+    ///     max_elements: 2,
+    ///     max_string_chars: 6,
+    ///     ..PreviewOptions::default()
+    /// };
+    /// assert_eq!(
+    ///     value.preview(&options),
+    ///     r#"{"ids": [1, 2, "… 3 more"], "note": "a long…"}"#
+    /// );
+    /// ```
+    pub fn preview(&self, options: &PreviewOptions) -> String {
+        let mut previewer = Previewer {
+            out: String::new(),
+            options,
+        };
+        // Writing to a `String` can't fail.
+        let _ = previewer.value(self, 0);
+
+        return previewer.out;
+    }
+}
+
+impl Json {
+    pub fn preview(&self, options: &PreviewOptions) -> String {
+        let mut previewer = Previewer {
+            out: String::new(),
+            options,
+        };
+        let _ = match self {
+            Json::Object(object) => previewer.object(object, 0),
+            Json::Array(array) => previewer.array(array, 0),
+        };
+
+        return previewer.out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::parse_from_str;
+    use crate::preview::PreviewOptions;
+
+    // A log-sized document: 5000 records, each with a long string and a
+    // few levels of nesting.
+    fn large() -> JsonValue {
+        let records: Vec<JsonValue> = (0..5000)
+            .map(|id| {
+                json!({
+                    "id": id,
+                    "body": "x".repeat(200),
+                    "meta": {"a": {"b": {"c": {"d": 1}}}, "empty": []}
+                })
+            })
+            .collect();
+        let members: Vec<(String, JsonValue)> = (0..15)
+            .map(|n| (format!("k{:02}", n), JsonValue::from(n)))
+            .collect();
+
+        return json!({
+            "records": JsonValue::Array(records),
+            "settings": JsonValue::Object(members.into_iter().collect())
+        });
+    }
+
+    #[test]
+    fn large_documents_are_cut_down_to_one_line() {
+        let options = PreviewOptions {
+            max_elements: 2,
+            max_members: 3,
+            max_string_chars: 8,
+            max_depth: 4,
+        };
+
+        assert_eq!(
+            large().preview(&options),
+            concat!(
+                r#"{"records": ["#,
+                r#"{"body": "xxxxxxxx…", "id": 0, "meta": {"a": {…}, "empty": []}}, "#,
+                r#"{"body": "xxxxxxxx…", "id": 1, "meta": {"a": {…}, "empty": []}}, "#,
+                r#""… 4998 more"], "#,
+                r#""settings": {"k00": 0, "k01": 1, "k02": 2, "…": "12 more"}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn default_options_keep_large_previews_short() {
+        let preview = large().preview(&PreviewOptions::default());
+
+        assert!(!preview.contains('\n'));
+        assert!(preview.len() < 2000, "{} bytes", preview.len());
+        assert!(preview.contains(r#""… 4990 more""#));
+        assert!(preview.contains(r#""…": "5 more""#));
+        assert!(preview.contains(&format!("\"{}…\"", "x".repeat(80))));
+    }
+
+    #[test]
+    fn small_documents_are_shown_whole() {
+        let source = r#"{"name": "ada", "tags": ["a", "b"], "n": 1.5, "ok": true, "none": null}"#;
+        let document = parse_from_str(source).unwrap();
+        let expected = r#"{"n": 1.5, "name": "ada", "none": null, "ok": true, "tags": ["a", "b"]}"#;
+
+        assert_eq!(document.preview(&PreviewOptions::default()), expected);
+        assert_eq!(
+            JsonValue::from(document).preview(&PreviewOptions::default()),
+            expected
+        );
+        assert_eq!(json!([]).preview(&PreviewOptions::default()), "[]");
+        assert_eq!(json!("é\n").preview(&PreviewOptions::default()), r#""é\n""#);
+    }
+
+    #[test]
+    fn zero_limits_elide_everything() {
+        let options = PreviewOptions {
+            max_elements: 0,
+            max_members: 0,
+            max_string_chars: 0,
+            max_depth: 1,
+        };
+
+        assert_eq!(json!([1, 2]).preview(&options), r#"["… 2 more"]"#);
+        assert_eq!(json!({"a": 1}).preview(&options), r#"{"…": "1 more"}"#);
+        assert_eq!(json!("abc").preview(&options), r#""…""#);
+        assert_eq!(json!("").preview(&options), r#""""#);
+
+        let options = PreviewOptions {
+            max_depth: 0,
+            ..PreviewOptions::default()
+        };
+        assert_eq!(json!([[1]]).preview(&options), "[…]");
+        assert_eq!(json!({}).preview(&options), "{}");
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use crate::json::JsonValue;
+use crate::path::{parse_array_index, parse_path, parse_pointer, PathError, PathSegment};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProjectOptions {
+    /// Fails on a path that leads to nothing instead of leaving it out.
+    pub error_on_missing: bool,
+}
+
+// The paths merged into a tree. `whole` marks a node selected by a path
+// that ends there, so everything beneath it is kept.
+#[derive(Default)]
+struct Selection {
+    whole: bool,
+    children: HashMap<String, Selection>,
+}
+
+fn select(root: &mut Selection, tokens: Vec<String>) {
+    let mut node = root;
+    for token in tokens {
+        node = node.children.entry(token).or_default();
+    }
+
+    node.whole = true;
+}
+
+const WILDCARD: &str = "*";
+
+// A path's tokens, with indices as their decimal text. Paths starting with
+// `/`, and the empty path, are JSON Pointers; anything else is dotted.
+fn path_tokens(path: &str) -> Result<Vec<String>, PathError> {
+    let segments = if path.is_empty() || path.starts_with('/') {
+        match parse_pointer(path) {
+            Some(segments) => segments,
+            None => {
+                return Err(PathError {
+                    message: "Invalid JSON Pointer".to_string(),
+                    position: 0,
+                });
+            }
+        }
+    } else {
+        parse_path(path)?
+    };
+
+    let tokens = segments.into_iter().map(|segment| match segment {
+        PathSegment::Key(key) => key,
+        PathSegment::Index(index) => index.to_string(),
+    });
+    return Ok(tokens.collect());
+}
+
+// Whether `tokens` lead to anything in `value`. A wildcard only needs an
+// array or object to step into.
+fn resolves(value: &JsonValue, tokens: &[String]) -> bool {
+    let (token, rest) = match tokens.split_first() {
+        Some(split) => split,
+        None => return true,
+    };
+
+    match value {
+        JsonValue::Object(_) | JsonValue::Array(_) if token == WILDCARD => true,
+        JsonValue::Object(object) => match object.get(token) {
+            Some(member) => resolves(member, rest),
+            None => false,
+        },
+        JsonValue::Array(array) => match parse_array_index(token).and_then(|i| array.get(i)) {
+            Some(element) => resolves(element, rest),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// The part of `value` picked out by `selections`, which all apply to it:
+// one for its own key or index and one for a wildcard over its parent.
+fn project_value(value: &JsonValue, selections: &[&Selection]) -> Option<JsonValue> {
+    if selections.iter().any(|selection| selection.whole) {
+        return Some(value.clone());
+    }
+
+    let children = |token: &str| -> Vec<&Selection> {
+        return selections
+            .iter()
+            .flat_map(|selection| {
+                [
+                    selection.children.get(token),
+                    selection.children.get(WILDCARD),
+                ]
+            })
+            .flatten()
+            .collect();
+    };
+    let has_wildcard = selections
+        .iter()
+        .any(|selection| selection.children.contains_key(WILDCARD));
+
+    match value {
+        JsonValue::Object(object) => {
+            let mut projected = HashMap::new();
+            for (key, member) in object {
+                let member_selections = children(key);
+                if member_selections.is_empty() {
+                    continue;
+                }
+                if let Some(member) = project_value(member, &member_selections) {
+                    projected.insert(key.clone(), member);
+                }
+            }
+
+            if projected.is_empty() && !has_wildcard {
+                return None;
+            }
+            return Some(JsonValue::Object(projected));
+        }
+        JsonValue::Array(array) => {
+            let mut projected = Vec::new();
+            for (index, element) in array.iter().enumerate() {
+                let element_selections = children(&index.to_string());
+                if element_selections.is_empty() {
+                    continue;
+                }
+                if let Some(element) = project_value(element, &element_selections) {
+                    projected.push(element);
+                }
+            }
+
+            if projected.is_empty() && !has_wildcard {
+                return None;
+            }
+            return Some(JsonValue::Array(projected));
+        }
+        _ => {
+            return None;
+        }
+    }
+}
+
+impl JsonValue {
+    /// Copies just the values at `paths`, with the objects and arrays above
+    /// them, dropping everything else. Paths are JSON Pointers, like
+    /// `/user/name`, or dotted paths, like `user.name`, and a `*` step
+    /// selects every element or member, so `/items/*/id` keeps only the
+    /// `id` of each item. A path inside another's value, like `/a/b` beside
+    /// `/a`, adds nothing. Arrays keep only their selected elements, in
+    /// order, and a wildcard keeps the array or object it steps into even
+    /// when none of its elements match the rest of the path.
+    ///
+    /// Paths that lead nowhere, or don't parse, are left out. If nothing
+    /// matches, the result is an empty object.
+    ///
+    /// ```
+    /// use mini_json::json;
+    ///
+    /// let order = json!({
+    ///     "id": 7,
+    ///     "customer": {"name": "Ada", "email": "ada@example.com"},
+    ///     "items": [{"id": 1, "qty": 2}, {"id": 2, "qty": 1}]
+    /// });
+    ///
+    /// assert_eq!(
+    ///     order.project(&["/id", "customer.name", "/items/*/id"]),
+    ///     json!({"id": 7, "customer": {"name": "Ada"}, "items": [{"id": 1}, {"id": 2}]})
+    /// );
+    /// ```
+    pub fn project(&self, paths: &[&str]) -> JsonValue {
+        let mut root = Selection::default();
+        for path in paths {
+            if let Ok(tokens) = path_tokens(path) {
+                select(&mut root, tokens);
+            }
+        }
+
+        return project_value(self, &[&root]).unwrap_or_else(|| JsonValue::Object(HashMap::new()));
+    }
+
+    /// Like `project`, but with `error_on_missing` a path that doesn't
+    /// parse or leads to nothing is an error naming it.
+    pub fn project_with(
+        &self,
+        paths: &[&str],
+        opts: &ProjectOptions,
+    ) -> Result<JsonValue, PathError> {
+        if opts.error_on_missing {
+            for path in paths {
+                if !resolves(self, &path_tokens(path)?) {
+                    return Err(PathError {
+                        message: format!("Nothing at '{}'", path),
+                        position: 0,
+                    });
+                }
+            }
+        }
+
+        return Ok(self.project(paths));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::project::ProjectOptions;
+
+    fn order() -> JsonValue {
+        return json!({
+            "id": 7,
+            "a": {"b": {"c": 1, "d": 2}, "e": 3},
+            "items": [
+                {"id": 1, "qty": 2, "tags": ["x"]},
+                {"id": 2, "qty": 1},
+                {"qty": 5},
+                "not an object"
+            ]
+        });
+    }
+
+    #[test]
+    fn overlapping_paths_keep_the_wider_one() {
+        let expected = json!({"a": {"b": {"c": 1, "d": 2}, "e": 3}});
+
+        assert_eq!(order().project(&["/a", "/a/b"]), expected);
+        assert_eq!(order().project(&["/a/b", "/a"]), expected);
+        assert_eq!(
+            order().project(&["a.b.c", "/a/b"]),
+            json!({"a": {"b": {"c": 1, "d": 2}}})
+        );
+        assert_eq!(
+            order().project(&["/a/b/c", "/a/e"]),
+            json!({"a": {"b": {"c": 1}, "e": 3}})
+        );
+    }
+
+    #[test]
+    fn wildcards_project_every_element() {
+        // Elements that none of the paths reach are dropped.
+        assert_eq!(
+            order().project(&["/items/*/id"]),
+            json!({"items": [{"id": 1}, {"id": 2}]})
+        );
+        assert_eq!(
+            order().project(&["/items/*/tags/0", "/items/1"]),
+            json!({"items": [{"tags": ["x"]}, {"id": 2, "qty": 1}]})
+        );
+        assert_eq!(order().project(&["/a/*/c"]), json!({"a": {"b": {"c": 1}}}));
+        assert_eq!(order().project(&["/items/*/missing"]), json!({"items": []}));
+    }
+
+    #[test]
+    fn unmatched_paths_give_an_empty_object() {
+        assert_eq!(
+            order().project(&["/missing", "/id/x", "/items/9", "a..b"]),
+            json!({})
+        );
+        assert_eq!(order().project(&[]), json!({}));
+        assert_eq!(json!([1, 2]).project(&["/5"]), json!({}));
+        assert_eq!(json!([1, 2, 3]).project(&["/2", "/0"]), json!([1, 3]));
+        assert_eq!(order().project(&[""]), order());
+    }
+
+    #[test]
+    fn missing_paths_can_be_errors() {
+        let opts = ProjectOptions {
+            error_on_missing: true,
+        };
+
+        assert_eq!(
+            order().project_with(&["/id", "/items/*/qty"], &opts),
+            Ok(json!({"id": 7, "items": [{"qty": 2}, {"qty": 1}, {"qty": 5}]}))
+        );
+        assert_eq!(
+            order()
+                .project_with(&["/id", "/a/x"], &opts)
+                .unwrap_err()
+                .to_string(),
+            "[Path error at 0]: Nothing at '/a/x'"
+        );
+        assert!(order().project_with(&["/id/*"], &opts).is_err());
+        assert_eq!(
+            order().project_with(&["/a/x"], &ProjectOptions::default()),
+            Ok(json!({}))
+        );
+    }
+}
@@ -0,0 +1,497 @@
+use std::fmt;
+
+use crate::json::JsonValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[Query error at {}]: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(isize),
+    Slice {
+        start: Option<isize>,
+        end: Option<isize>,
+        step: isize,
+    },
+    Union(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Child(Selector),
+    Descendant(Selector),
+}
+
+struct QueryParser {
+    chars: Vec<char>,
+    current: usize,
+}
+
+impl QueryParser {
+    fn new(query: &str) -> QueryParser {
+        QueryParser {
+            chars: query.chars().collect(),
+            current: 0,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Vec<Step>, QueryError> {
+        let mut steps = Vec::new();
+
+        if !self.match_char('$') {
+            return Err(self.error("Expect query to start with '$'"));
+        }
+
+        while !self.is_at_end() {
+            if self.match_char('.') {
+                if self.match_char('.') {
+                    if self.match_char('[') {
+                        steps.push(Step::Descendant(self.parse_bracket()?));
+                    } else {
+                        steps.push(Step::Descendant(self.parse_dot_selector()?));
+                    }
+                } else {
+                    steps.push(Step::Child(self.parse_dot_selector()?));
+                }
+            } else if self.match_char('[') {
+                steps.push(Step::Child(self.parse_bracket()?));
+            } else {
+                return Err(self.error("Expect '.' or '['"));
+            }
+        }
+
+        return Ok(steps);
+    }
+
+    fn parse_dot_selector(&mut self) -> Result<Selector, QueryError> {
+        if self.match_char('*') {
+            return Ok(Selector::Wildcard);
+        }
+
+        let start = self.current;
+        while !self.is_at_end() && self.peek() != '.' && self.peek() != '[' {
+            self.current += 1;
+        }
+
+        if self.current == start {
+            return Err(self.error("Expect member name"));
+        }
+
+        return Ok(Selector::Name(
+            self.chars[start..self.current].iter().collect(),
+        ));
+    }
+
+    // Each comma-separated entry is parsed independently so that new entry
+    // kinds (such as `?(...)` filters) only need another branch here.
+    fn parse_bracket(&mut self) -> Result<Selector, QueryError> {
+        let mut selectors = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.parse_bracket_entry()?);
+            self.skip_whitespace();
+
+            if self.match_char(']') {
+                break;
+            }
+            if !self.match_char(',') {
+                return Err(self.error("Expect ',' or ']'"));
+            }
+        }
+
+        if selectors.len() == 1 {
+            return Ok(selectors.remove(0));
+        }
+
+        return Ok(Selector::Union(selectors));
+    }
+
+    fn parse_bracket_entry(&mut self) -> Result<Selector, QueryError> {
+        let c = self.peek();
+
+        if c == '*' {
+            self.current += 1;
+            return Ok(Selector::Wildcard);
+        }
+        if c == '\'' || c == '"' {
+            return Ok(Selector::Name(self.parse_quoted()?));
+        }
+
+        let start = self.parse_integer()?;
+
+        if !self.match_char(':') {
+            match start {
+                Some(index) => {
+                    return Ok(Selector::Index(index));
+                }
+                None => {
+                    return Err(self.error("Expect index, name, slice or '*'"));
+                }
+            }
+        }
+
+        let end = self.parse_integer()?;
+        let mut step = 1;
+
+        if self.match_char(':') {
+            if let Some(value) = self.parse_integer()? {
+                step = value;
+            }
+        }
+
+        if step == 0 {
+            return Err(self.error("Slice step can't be zero"));
+        }
+
+        return Ok(Selector::Slice { start, end, step });
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, QueryError> {
+        let start = self.current;
+        let quote = self.advance();
+        let mut name = String::new();
+
+        loop {
+            if self.is_at_end() {
+                return Err(QueryError {
+                    message: "Unterminated quoted name".to_string(),
+                    position: start,
+                });
+            }
+
+            let c = self.advance();
+
+            if c == quote {
+                return Ok(name);
+            } else if c == '\\' && !self.is_at_end() {
+                name.push(self.advance());
+            } else {
+                name.push(c);
+            }
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<Option<isize>, QueryError> {
+        let start = self.current;
+
+        if self.peek() == '-' {
+            self.current += 1;
+        }
+        while self.peek().is_ascii_digit() {
+            self.current += 1;
+        }
+
+        if self.current == start {
+            return Ok(None);
+        }
+
+        let lexeme: String = self.chars[start..self.current].iter().collect();
+        match lexeme.parse::<isize>() {
+            Ok(value) => {
+                return Ok(Some(value));
+            }
+            Err(_) => {
+                return Err(QueryError {
+                    message: format!("Invalid integer '{}'", lexeme),
+                    position: start,
+                });
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> QueryError {
+        QueryError {
+            message: message.to_string(),
+            position: self.current,
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current >= self.chars.len()
+    }
+
+    fn advance(&mut self) -> char {
+        self.current += 1;
+        return self.chars[self.current - 1];
+    }
+
+    fn peek(&self) -> char {
+        if self.is_at_end() {
+            return '\0';
+        }
+
+        return self.chars[self.current];
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.is_at_end() {
+            return false;
+        }
+        if self.peek() != expected {
+            return false;
+        }
+
+        self.current += 1;
+        return true;
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek() == ' ' {
+            self.current += 1;
+        }
+    }
+}
+
+fn normalize_index(index: isize, len: usize) -> Option<usize> {
+    let len = len as isize;
+    let index = if index < 0 { len + index } else { index };
+
+    if index < 0 || index >= len {
+        return None;
+    }
+
+    return Some(index as usize);
+}
+
+fn slice_bound(bound: isize, len: isize) -> isize {
+    if bound < 0 {
+        return (len + bound).max(0);
+    }
+
+    return bound.min(len);
+}
+
+fn reverse_slice_bound(bound: isize, len: isize) -> isize {
+    let bound = if bound < 0 { len + bound } else { bound };
+    return bound.clamp(-1, len - 1);
+}
+
+fn select<'a>(value: &'a JsonValue, selector: &Selector, results: &mut Vec<&'a JsonValue>) {
+    match (selector, value) {
+        (Selector::Name(name), JsonValue::Object(object)) => {
+            if let Some(child) = object.get(name) {
+                results.push(child);
+            }
+        }
+        (Selector::Wildcard, JsonValue::Object(object)) => {
+            results.extend(object.values());
+        }
+        (Selector::Wildcard, JsonValue::Array(array)) => {
+            results.extend(array.iter());
+        }
+        (Selector::Index(index), JsonValue::Array(array)) => {
+            if let Some(index) = normalize_index(*index, array.len()) {
+                results.push(&array[index]);
+            }
+        }
+        (Selector::Slice { start, end, step }, JsonValue::Array(array)) => {
+            let len = array.len() as isize;
+
+            if *step > 0 {
+                let mut i = slice_bound(start.unwrap_or(0), len);
+                let end = slice_bound(end.unwrap_or(len), len);
+
+                while i < end {
+                    results.push(&array[i as usize]);
+                    i = match i.checked_add(*step) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+            } else {
+                // Stepping backwards, -1 stands for "before the first
+                // element", so bounds are clamped to -1 rather than 0.
+                let mut i = match start {
+                    Some(start) => reverse_slice_bound(*start, len),
+                    None => len - 1,
+                };
+                let end = match end {
+                    Some(end) => reverse_slice_bound(*end, len),
+                    None => -1,
+                };
+
+                while i > end {
+                    results.push(&array[i as usize]);
+                    i = match i.checked_add(*step) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+            }
+        }
+        (Selector::Union(selectors), _) => {
+            for selector in selectors {
+                select(value, selector, results);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn select_descendants<'a>(
+    value: &'a JsonValue,
+    selector: &Selector,
+    results: &mut Vec<&'a JsonValue>,
+) {
+    select(value, selector, results);
+
+    match value {
+        JsonValue::Object(object) => {
+            for child in object.values() {
+                select_descendants(child, selector, results);
+            }
+        }
+        JsonValue::Array(array) => {
+            for child in array {
+                select_descendants(child, selector, results);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl JsonValue {
+    /// Evaluates a JSONPath expression such as `$.store.book[*].author`,
+    /// returning every matching value. Supports `$`, `.name`, `['name']`,
+    /// `*`, `..`, indices (negative from the end), slices and unions.
+    pub fn query(&self, query: &str) -> Result<Vec<&JsonValue>, QueryError> {
+        let steps = QueryParser::new(query).parse()?;
+        let mut current = vec![self];
+
+        for step in &steps {
+            let mut next = Vec::new();
+
+            for value in current {
+                match step {
+                    Step::Child(selector) => select(value, selector, &mut next),
+                    Step::Descendant(selector) => select_descendants(value, selector, &mut next),
+                }
+            }
+
+            current = next;
+        }
+
+        return Ok(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::query::{QueryError, QueryParser, Selector, Step};
+
+    fn name(name: &str) -> Selector {
+        return Selector::Name(name.to_string());
+    }
+
+    fn parse(query: &str) -> Result<Vec<Step>, QueryError> {
+        return QueryParser::new(query).parse();
+    }
+
+    fn error(query: &str) -> (String, usize) {
+        let error = parse(query).unwrap_err();
+        return (error.message, error.position);
+    }
+
+    #[test]
+    fn parses_each_selector() {
+        assert_eq!(parse("$"), Ok(vec![]));
+        assert_eq!(
+            parse("$.a..b.*[0]"),
+            Ok(vec![
+                Step::Child(name("a")),
+                Step::Descendant(name("b")),
+                Step::Child(Selector::Wildcard),
+                Step::Child(Selector::Index(0)),
+            ])
+        );
+        assert_eq!(
+            parse("$['a.b', \"c\\'d\"][-1]..[*]"),
+            Ok(vec![
+                Step::Child(Selector::Union(vec![name("a.b"), name("c'd")])),
+                Step::Child(Selector::Index(-1)),
+                Step::Descendant(Selector::Wildcard),
+            ])
+        );
+        assert_eq!(
+            parse("$[1:][:2][::-1][ 0 , 1:3:2 ]"),
+            Ok(vec![
+                Step::Child(Selector::Slice {
+                    start: Some(1),
+                    end: None,
+                    step: 1
+                }),
+                Step::Child(Selector::Slice {
+                    start: None,
+                    end: Some(2),
+                    step: 1
+                }),
+                Step::Child(Selector::Slice {
+                    start: None,
+                    end: None,
+                    step: -1
+                }),
+                Step::Child(Selector::Union(vec![
+                    Selector::Index(0),
+                    Selector::Slice {
+                        start: Some(1),
+                        end: Some(3),
+                        step: 2
+                    },
+                ])),
+            ])
+        );
+    }
+
+    #[test]
+    fn malformed_queries_are_errors() {
+        assert_eq!(
+            error("a"),
+            ("Expect query to start with '$'".to_string(), 0)
+        );
+        assert_eq!(error("$a"), ("Expect '.' or '['".to_string(), 1));
+        assert_eq!(error("$."), ("Expect member name".to_string(), 2));
+        assert_eq!(error("$.a."), ("Expect member name".to_string(), 4));
+        assert_eq!(error("$[0"), ("Expect ',' or ']'".to_string(), 3));
+        assert_eq!(
+            error("$[x]"),
+            ("Expect index, name, slice or '*'".to_string(), 2)
+        );
+        assert_eq!(error("$['a]"), ("Unterminated quoted name".to_string(), 2));
+        assert_eq!(error("$[::0]"), ("Slice step can't be zero".to_string(), 5));
+        assert_eq!(
+            error("$[99999999999999999999]"),
+            ("Invalid integer '99999999999999999999'".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn selectors_ignore_the_wrong_kind() {
+        let doc = json!({"a": [1, 2], "b": "text"});
+
+        assert!(doc.query("$[0]").unwrap().is_empty());
+        assert!(doc.query("$.a.b").unwrap().is_empty());
+        assert!(doc.query("$.b[0]").unwrap().is_empty());
+        assert!(doc.query("$.b.*").unwrap().is_empty());
+        assert_eq!(doc.query("$.a[-1]").unwrap(), [&json!(2)]);
+        assert!(doc.query("$.a[-3]").unwrap().is_empty());
+        assert_eq!(doc.query("$.a[5:-5:-1]").unwrap(), [&json!(2), &json!(1)]);
+        assert!(doc.query("$.a[-5::-1]").unwrap().is_empty());
+        assert_eq!(doc.query("$.a[:-2:-1]").unwrap(), [&json!(2)]);
+        assert!(json!([]).query("$[::-1]").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,483 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::JsonValue;
+use crate::path::escape_pointer_token;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeepNesting {
+    /// Nesting past `max_depth` is an error.
+    #[default]
+    Error,
+    /// Keys past `max_depth` are joined with `.` into the last bracket, so
+    /// `a[b][c][d]` with a depth of 2 becomes `a[b][c.d]`.
+    Flatten,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryStringOptions {
+    /// The most bracketed segments a key may have.
+    pub max_depth: usize,
+    pub deep_nesting: DeepNesting,
+}
+
+// Five levels is where common decoders stop parsing brackets.
+impl Default for QueryStringOptions {
+    fn default() -> QueryStringOptions {
+        QueryStringOptions {
+            max_depth: 5,
+            deep_nesting: DeepNesting::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryStringError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for QueryStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[Query string error at '{}']: {}",
+            self.pointer, self.message
+        )
+    }
+}
+
+impl std::error::Error for QueryStringError {}
+
+// Decodes `+` as a space and `%XX` escapes as bytes, reading the result as
+// UTF-8. Malformed escapes are kept as they are.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[index], hex) {
+            (b'+', _) => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    return String::from_utf8_lossy(&decoded).into_owned();
+}
+
+fn percent_encode(out: &mut String, text: &str) {
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char);
+            }
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+}
+
+enum Segment {
+    Key(String),
+    Index(usize),
+    Push,
+}
+
+// Splits `a[b][]` into its name and bracketed segments. A key whose brackets
+// don't pair up is used whole.
+fn parse_key(key: &str) -> Vec<Segment> {
+    let (name, mut rest) = match key.find('[') {
+        Some(0) | None => return vec![Segment::Key(key.to_string())],
+        Some(index) => (&key[..index], &key[index..]),
+    };
+
+    let mut segments = vec![Segment::Key(name.to_string())];
+    while !rest.is_empty() {
+        let close = match rest.strip_prefix('[').and_then(|inner| inner.find(']')) {
+            Some(close) => close + 1,
+            None => return vec![Segment::Key(key.to_string())],
+        };
+
+        let inner = &rest[1..close];
+        segments.push(match inner {
+            "" => Segment::Push,
+            _ if inner.bytes().all(|byte| byte.is_ascii_digit()) => match inner.parse() {
+                Ok(index) => Segment::Index(index),
+                Err(_) => Segment::Key(inner.to_string()),
+            },
+            _ => Segment::Key(inner.to_string()),
+        });
+        rest = &rest[close + 1..];
+    }
+
+    return segments;
+}
+
+// Stores `value` at the segments' path under `target`. A container of the
+// wrong kind is replaced, so later pairs win; a repeated plain key turns its
+// value into an array.
+fn insert(target: &mut JsonValue, segments: &[Segment], value: String) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    match segment {
+        Segment::Key(key) => {
+            if !matches!(target, JsonValue::Object(_)) {
+                *target = JsonValue::Object(HashMap::new());
+            }
+            let JsonValue::Object(object) = target else {
+                unreachable!()
+            };
+
+            if !rest.is_empty() {
+                insert(object.entry(key.clone()).or_default(), rest, value);
+                return;
+            }
+
+            match object.get_mut(key) {
+                Some(JsonValue::Array(array)) => array.push(JsonValue::String(value)),
+                Some(existing @ JsonValue::String(_)) => {
+                    let first = existing.take();
+                    *existing = JsonValue::Array(vec![first, JsonValue::String(value)]);
+                }
+                _ => {
+                    object.insert(key.clone(), JsonValue::String(value));
+                }
+            }
+        }
+        Segment::Index(_) | Segment::Push => {
+            if !matches!(target, JsonValue::Array(_)) {
+                *target = JsonValue::Array(Vec::new());
+            }
+            let JsonValue::Array(array) = target else {
+                unreachable!()
+            };
+
+            // Indices past the end append, so sparse indices don't pad the
+            // array with nulls.
+            let index = match segment {
+                Segment::Index(index) if *index < array.len() => *index,
+                _ => {
+                    array.push(JsonValue::Null);
+                    array.len() - 1
+                }
+            };
+
+            if rest.is_empty() {
+                array[index] = JsonValue::String(value);
+            } else {
+                insert(&mut array[index], rest, value);
+            }
+        }
+    }
+}
+
+/// Parses an `application/x-www-form-urlencoded` string into an object of
+/// strings. Bracketed keys nest: `b[]=x` appends to an array, `c[d]=2` sets
+/// a member and `e[0][f]=3` indexes an array. A key repeated without
+/// brackets collects its values into an array. Keys are percent-decoded
+/// before their brackets are read, so `b%5B%5D=x` is an array too.
+/// Malformed percent escapes are kept literally and invalid UTF-8 is
+/// replaced, so parsing never fails.
+pub fn from_query_string(query: &str) -> JsonValue {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let mut root = JsonValue::Object(HashMap::new());
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = percent_decode(key);
+        if key.is_empty() {
+            continue;
+        }
+
+        insert(&mut root, &parse_key(&key), percent_decode(value));
+    }
+
+    return root;
+}
+
+struct Encoder<'a> {
+    out: String,
+    options: &'a QueryStringOptions,
+}
+
+impl Encoder<'_> {
+    fn pair(&mut self, key: &[String], value: &str) {
+        if !self.out.is_empty() {
+            self.out.push('&');
+        }
+
+        // Only longer than `max_depth` when flattening: the extra keys share
+        // the last allowed bracket, or the name if no brackets are allowed.
+        let (name, brackets) = (&key[0], &key[1..]);
+        let depth = self.options.max_depth;
+
+        if depth == 0 && !brackets.is_empty() {
+            percent_encode(&mut self.out, &key.join("."));
+        } else {
+            percent_encode(&mut self.out, name);
+
+            for (index, segment) in brackets.iter().enumerate().take(depth) {
+                self.out.push('[');
+                if index + 1 == depth && brackets.len() > depth {
+                    percent_encode(&mut self.out, &brackets[index..].join("."));
+                } else {
+                    percent_encode(&mut self.out, segment);
+                }
+                self.out.push(']');
+            }
+        }
+
+        self.out.push('=');
+        percent_encode(&mut self.out, value);
+    }
+
+    fn value(
+        &mut self,
+        key: &mut Vec<String>,
+        value: &JsonValue,
+        pointer: &mut String,
+    ) -> Result<(), QueryStringError> {
+        let scalar = match value {
+            JsonValue::Null => String::new(),
+            JsonValue::Boolean(value) => value.to_string(),
+            JsonValue::Integer(value) => value.to_string(),
+            JsonValue::Float(value) => value.to_string(),
+            JsonValue::String(value) => value.clone(),
+            JsonValue::Array(array) => return self.array(key, array, pointer),
+            JsonValue::Object(object) => return self.object(key, object, pointer),
+        };
+
+        if key.len() - 1 > self.options.max_depth && self.options.deep_nesting == DeepNesting::Error
+        {
+            return Err(QueryStringError {
+                pointer: pointer.clone(),
+                message: format!(
+                    "Nested deeper than {} levels of brackets",
+                    self.options.max_depth
+                ),
+            });
+        }
+
+        self.pair(key, &scalar);
+        return Ok(());
+    }
+
+    fn array(
+        &mut self,
+        key: &mut Vec<String>,
+        array: &[JsonValue],
+        pointer: &mut String,
+    ) -> Result<(), QueryStringError> {
+        // Arrays of scalars use `key[]`; anything nested needs indices to
+        // keep elements apart.
+        let indexed = array
+            .iter()
+            .any(|element| matches!(element, JsonValue::Array(_) | JsonValue::Object(_)));
+
+        for (index, element) in array.iter().enumerate() {
+            let len = pointer.len();
+            pointer.push_str(&format!("/{}", index));
+            key.push(if indexed {
+                index.to_string()
+            } else {
+                String::new()
+            });
+
+            self.value(key, element, pointer)?;
+
+            key.pop();
+            pointer.truncate(len);
+        }
+
+        return Ok(());
+    }
+
+    fn object(
+        &mut self,
+        key: &mut Vec<String>,
+        object: &HashMap<String, JsonValue>,
+        pointer: &mut String,
+    ) -> Result<(), QueryStringError> {
+        let mut keys: Vec<&String> = object.keys().collect();
+        keys.sort();
+
+        for member in keys {
+            let len = pointer.len();
+            pointer.push('/');
+            pointer.push_str(&escape_pointer_token(member));
+            key.push(member.clone());
+
+            self.value(key, &object[member], pointer)?;
+
+            key.pop();
+            pointer.truncate(len);
+        }
+
+        return Ok(());
+    }
+}
+
+/// Writes an object as an `application/x-www-form-urlencoded` string with
+/// keys sorted. Nested objects and arrays use the bracket conventions
+/// `from_query_string` reads, up to `options.max_depth` brackets. Nulls are
+/// written as empty values, and empty objects and arrays are left out since
+/// they have no pairs. Fails if the value isn't an object.
+pub fn to_query_string(
+    value: &JsonValue,
+    options: &QueryStringOptions,
+) -> Result<String, QueryStringError> {
+    let object = match value {
+        JsonValue::Object(object) => object,
+        _ => {
+            return Err(QueryStringError {
+                pointer: String::new(),
+                message: format!("Expected an object, found {}", value.kind_name()),
+            });
+        }
+    };
+
+    let mut encoder = Encoder {
+        out: String::new(),
+        options,
+    };
+    encoder.object(&mut Vec::new(), object, &mut String::new())?;
+
+    return Ok(encoder.out);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::query_string::{
+        from_query_string, to_query_string, DeepNesting, QueryStringOptions,
+    };
+
+    fn depth(max_depth: usize, deep_nesting: DeepNesting) -> QueryStringOptions {
+        return QueryStringOptions {
+            max_depth,
+            deep_nesting,
+        };
+    }
+
+    #[test]
+    fn bracket_conventions_build_arrays_and_objects() {
+        assert_eq!(
+            from_query_string("a=1&b[]=x&b[]=y&c[d]=2"),
+            json!({"a": "1", "b": ["x", "y"], "c": {"d": "2"}})
+        );
+        assert_eq!(
+            from_query_string("?e[0][f]=3&e[0][g]=4&e[5][f]=5&tag=a&tag=b&tag=c"),
+            json!({"e": [{"f": "3", "g": "4"}, {"f": "5"}], "tag": ["a", "b", "c"]})
+        );
+        assert_eq!(
+            from_query_string("x[=1&y]=2&[z]=3"),
+            json!({"x[": "1", "y]": "2", "[z]": "3"})
+        );
+    }
+
+    #[test]
+    fn values_are_percent_decoded() {
+        assert_eq!(
+            from_query_string("name=J%C3%BCrgen+M%C3%BCller&city=%E6%9D%B1%E4%BA%AC&b%5B%5D=x"),
+            json!({"name": "Jürgen Müller", "city": "東京", "b": ["x"]})
+        );
+        assert_eq!(
+            from_query_string("bad=%zz%4&cut=%E6%9D&plus=%2B"),
+            json!({"bad": "%zz%4", "cut": "\u{fffd}", "plus": "+"})
+        );
+    }
+
+    #[test]
+    fn empty_values_and_pairs() {
+        assert_eq!(
+            from_query_string("a=&b&&=c&d[]="),
+            json!({"a": "", "b": "", "d": [""]})
+        );
+        assert_eq!(from_query_string(""), json!({}));
+
+        assert_eq!(
+            to_query_string(
+                &json!({"a": null, "b": "", "c": [], "d": {}}),
+                &QueryStringOptions::default()
+            ),
+            Ok("a=&b=".to_string())
+        );
+    }
+
+    #[test]
+    fn nested_objects_round_trip() {
+        let value = json!({
+            "user": {"name": "Zoë Ö", "email": "z@example.com"},
+            "filter": {"tags": ["a&b", "c=d"], "sort": "-date"},
+            "q": "1 + 1"
+        });
+
+        let query = to_query_string(&value, &QueryStringOptions::default()).unwrap();
+        assert_eq!(
+            query,
+            "filter[sort]=-date&filter[tags][]=a%26b&filter[tags][]=c%3Dd&q=1+%2B+1\
+             &user[email]=z%40example.com&user[name]=Zo%C3%AB+%C3%96"
+        );
+        assert_eq!(from_query_string(&query), value);
+    }
+
+    #[test]
+    fn arrays_of_containers_are_indexed() {
+        let value = json!({"items": [{"id": 1}, {"id": 2, "tags": [true]}]});
+
+        let query = to_query_string(&value, &QueryStringOptions::default()).unwrap();
+        assert_eq!(query, "items[0][id]=1&items[1][id]=2&items[1][tags][]=true");
+        assert_eq!(
+            from_query_string(&query),
+            json!({"items": [{"id": "1"}, {"id": "2", "tags": ["true"]}]})
+        );
+    }
+
+    #[test]
+    fn deep_nesting_errors_or_flattens() {
+        let value = json!({"a": {"b": {"c": {"d": 1}}}});
+
+        assert_eq!(
+            to_query_string(&value, &depth(2, DeepNesting::Error))
+                .unwrap_err()
+                .to_string(),
+            "[Query string error at '/a/b/c/d']: Nested deeper than 2 levels of brackets"
+        );
+        assert_eq!(
+            to_query_string(&value, &depth(2, DeepNesting::Flatten)),
+            Ok("a[b][c.d]=1".to_string())
+        );
+        assert_eq!(
+            to_query_string(&value, &depth(0, DeepNesting::Flatten)),
+            Ok("a.b.c.d=1".to_string())
+        );
+        assert_eq!(
+            to_query_string(&value, &depth(3, DeepNesting::Error)),
+            Ok("a[b][c][d]=1".to_string())
+        );
+        assert_eq!(
+            to_query_string(&json!([1]), &QueryStringOptions::default())
+                .unwrap_err()
+                .to_string(),
+            "[Query string error at '']: Expected an object, found array"
+        );
+    }
+}
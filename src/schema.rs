@@ -0,0 +1,748 @@
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use crate::builder::ObjectBuilder;
+use crate::json::JsonValue;
+use crate::path::escape_pointer_token;
+use crate::sort::compare_values;
+
+const TYPE_NAMES: [&str; 7] = [
+    "null", "boolean", "object", "array", "number", "string", "integer",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[Schema error at '{}']: {}", self.pointer, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// A failed check. `pointer` locates the offending value in the validated
+/// document and `keyword` names the schema keyword that rejected it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub pointer: String,
+    pub keyword: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[Validation error at '{}' ({})]: {}",
+            self.pointer, self.keyword, self.message
+        )
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Items {
+    All(Box<Node>),
+    Tuple(Vec<Node>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Keywords {
+    types: Option<Vec<String>>,
+    properties: Vec<(String, Node)>,
+    required: Vec<String>,
+    items: Option<Items>,
+    enum_values: Option<Vec<JsonValue>>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    additional_properties: Option<bool>,
+    any_of: Option<Vec<Node>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Boolean(bool),
+    Keywords(Box<Keywords>),
+}
+
+/// A compiled draft-07 schema supporting `type`, `properties`, `required`,
+/// `items`, `enum`, `minimum`, `maximum`, `minLength`, `maxLength`,
+/// `minItems`, `maxItems`, `anyOf` and the boolean form of
+/// `additionalProperties`.
+/// Other keywords, including `$ref`, are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    root: Node,
+}
+
+fn schema_error(pointer: &str, message: &str) -> SchemaError {
+    return SchemaError {
+        pointer: pointer.to_string(),
+        message: message.to_string(),
+    };
+}
+
+fn child_pointer(pointer: &str, token: &str) -> String {
+    return format!("{}/{}", pointer, escape_pointer_token(token));
+}
+
+fn parse_count(value: &JsonValue, pointer: &str) -> Result<usize, SchemaError> {
+    match value.as_number().and_then(|number| number.as_i64()) {
+        Some(count) if count >= 0 => {
+            return Ok(count as usize);
+        }
+        _ => {
+            return Err(schema_error(pointer, "Expected a non-negative integer"));
+        }
+    }
+}
+
+fn parse_bound(value: &JsonValue, pointer: &str) -> Result<f64, SchemaError> {
+    match value.as_number() {
+        Some(number) => {
+            return Ok(number.as_f64());
+        }
+        None => {
+            return Err(schema_error(pointer, "Expected a number"));
+        }
+    }
+}
+
+fn parse_type_name(value: &JsonValue, pointer: &str) -> Result<String, SchemaError> {
+    match value {
+        JsonValue::String(name) if TYPE_NAMES.contains(&name.as_str()) => {
+            return Ok(name.clone());
+        }
+        _ => {
+            return Err(schema_error(pointer, "Expected a JSON Schema type name"));
+        }
+    }
+}
+
+fn parse_node(value: &JsonValue, pointer: &str) -> Result<Node, SchemaError> {
+    let object = match value {
+        JsonValue::Boolean(accept) => return Ok(Node::Boolean(*accept)),
+        JsonValue::Object(object) => object,
+        _ => return Err(schema_error(pointer, "Schema must be an object or boolean")),
+    };
+
+    let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+    members.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut keywords = Keywords::default();
+
+    for (keyword, value) in members {
+        let pointer = child_pointer(pointer, keyword);
+
+        match keyword.as_str() {
+            "type" => match value {
+                JsonValue::Array(names) => {
+                    let mut types = Vec::new();
+                    for (index, name) in names.iter().enumerate() {
+                        types.push(parse_type_name(name, &format!("{}/{}", pointer, index))?);
+                    }
+                    keywords.types = Some(types);
+                }
+                _ => {
+                    keywords.types = Some(vec![parse_type_name(value, &pointer)?]);
+                }
+            },
+            "properties" => match value {
+                JsonValue::Object(properties) => {
+                    for (name, property) in properties {
+                        let node = parse_node(property, &child_pointer(&pointer, name))?;
+                        keywords.properties.push((name.clone(), node));
+                    }
+                    keywords.properties.sort_by(|a, b| a.0.cmp(&b.0));
+                }
+                _ => {
+                    return Err(schema_error(&pointer, "Expected an object of schemas"));
+                }
+            },
+            "required" => match value {
+                JsonValue::Array(names) => {
+                    for name in names {
+                        match name {
+                            JsonValue::String(name) => keywords.required.push(name.clone()),
+                            _ => return Err(schema_error(&pointer, "Expected property names")),
+                        }
+                    }
+                }
+                _ => {
+                    return Err(schema_error(
+                        &pointer,
+                        "Expected an array of property names",
+                    ));
+                }
+            },
+            "items" => match value {
+                JsonValue::Array(schemas) => {
+                    let mut nodes = Vec::new();
+                    for (index, schema) in schemas.iter().enumerate() {
+                        nodes.push(parse_node(schema, &format!("{}/{}", pointer, index))?);
+                    }
+                    keywords.items = Some(Items::Tuple(nodes));
+                }
+                _ => {
+                    keywords.items = Some(Items::All(Box::new(parse_node(value, &pointer)?)));
+                }
+            },
+            "anyOf" => match value {
+                JsonValue::Array(schemas) if !schemas.is_empty() => {
+                    let mut nodes = Vec::new();
+                    for (index, schema) in schemas.iter().enumerate() {
+                        nodes.push(parse_node(schema, &format!("{}/{}", pointer, index))?);
+                    }
+                    keywords.any_of = Some(nodes);
+                }
+                _ => {
+                    return Err(schema_error(
+                        &pointer,
+                        "Expected a non-empty array of schemas",
+                    ));
+                }
+            },
+            "enum" => match value {
+                JsonValue::Array(values) => {
+                    keywords.enum_values = Some(values.clone());
+                }
+                _ => {
+                    return Err(schema_error(&pointer, "Expected an array of values"));
+                }
+            },
+            "minimum" => keywords.minimum = Some(parse_bound(value, &pointer)?),
+            "maximum" => keywords.maximum = Some(parse_bound(value, &pointer)?),
+            "minLength" => keywords.min_length = Some(parse_count(value, &pointer)?),
+            "maxLength" => keywords.max_length = Some(parse_count(value, &pointer)?),
+            "minItems" => keywords.min_items = Some(parse_count(value, &pointer)?),
+            "maxItems" => keywords.max_items = Some(parse_count(value, &pointer)?),
+            "additionalProperties" => match value {
+                JsonValue::Boolean(allowed) => {
+                    keywords.additional_properties = Some(*allowed);
+                }
+                _ => {
+                    return Err(schema_error(
+                        &pointer,
+                        "Only the boolean form of additionalProperties is supported",
+                    ));
+                }
+            },
+            _ => {}
+        }
+    }
+
+    return Ok(Node::Keywords(Box::new(keywords)));
+}
+
+// Draft-07 treats a float with no fractional part as an integer.
+fn matches_type(value: &JsonValue, name: &str) -> bool {
+    match (name, value) {
+        ("null", JsonValue::Null) => true,
+        ("boolean", JsonValue::Boolean(_)) => true,
+        ("object", JsonValue::Object(_)) => true,
+        ("array", JsonValue::Array(_)) => true,
+        ("string", JsonValue::String(_)) => true,
+        ("number", JsonValue::Integer(_) | JsonValue::Float(_)) => true,
+        ("integer", JsonValue::Integer(_)) => true,
+        ("integer", JsonValue::Float(value)) => value.is_finite() && value.fract() == 0.0,
+        _ => false,
+    }
+}
+
+struct Validator {
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    fn fail(&mut self, pointer: &str, keyword: &str, message: String) {
+        self.errors.push(ValidationError {
+            pointer: pointer.to_string(),
+            keyword: keyword.to_string(),
+            message,
+        });
+    }
+
+    fn validate(&mut self, node: &Node, value: &JsonValue, pointer: &mut String) {
+        let keywords = match node {
+            Node::Boolean(true) => return,
+            Node::Boolean(false) => {
+                self.fail(pointer, "false", "No value is allowed here".to_string());
+                return;
+            }
+            Node::Keywords(keywords) => keywords,
+        };
+
+        if let Some(types) = &keywords.types {
+            if !types.iter().any(|name| matches_type(value, name)) {
+                self.fail(
+                    pointer,
+                    "type",
+                    format!(
+                        "Expected {}, found {}",
+                        types.join(" or "),
+                        value.kind_name()
+                    ),
+                );
+            }
+        }
+
+        if let Some(nodes) = &keywords.any_of {
+            let matched = nodes.iter().any(|node| {
+                let mut branch = Validator { errors: Vec::new() };
+                branch.validate(node, value, &mut pointer.clone());
+                return branch.errors.is_empty();
+            });
+
+            if !matched {
+                self.fail(
+                    pointer,
+                    "anyOf",
+                    "Value matches none of the schemas".to_string(),
+                );
+            }
+        }
+
+        if let Some(values) = &keywords.enum_values {
+            if !values
+                .iter()
+                .any(|allowed| compare_values(allowed, value) == Ordering::Equal)
+            {
+                self.fail(
+                    pointer,
+                    "enum",
+                    "Value is not one of the allowed values".to_string(),
+                );
+            }
+        }
+
+        match value {
+            JsonValue::Integer(_) | JsonValue::Float(_) => {
+                self.validate_number(keywords, value, pointer);
+            }
+            JsonValue::String(string) => {
+                let length = string.chars().count();
+
+                if let Some(min) = keywords.min_length {
+                    if length < min {
+                        self.fail(
+                            pointer,
+                            "minLength",
+                            format!("Shorter than {} characters", min),
+                        );
+                    }
+                }
+                if let Some(max) = keywords.max_length {
+                    if length > max {
+                        self.fail(
+                            pointer,
+                            "maxLength",
+                            format!("Longer than {} characters", max),
+                        );
+                    }
+                }
+            }
+            JsonValue::Array(array) => {
+                self.validate_array(keywords, array, pointer);
+            }
+            JsonValue::Object(object) => {
+                for name in &keywords.required {
+                    if !object.contains_key(name) {
+                        self.fail(
+                            pointer,
+                            "required",
+                            format!("Missing required property '{}'", name),
+                        );
+                    }
+                }
+
+                for (name, property) in &keywords.properties {
+                    if let Some(member) = object.get(name) {
+                        let len = pointer.len();
+                        pointer.push('/');
+                        pointer.push_str(&escape_pointer_token(name));
+                        self.validate(property, member, pointer);
+                        pointer.truncate(len);
+                    }
+                }
+
+                if keywords.additional_properties == Some(false) {
+                    let mut extra: Vec<&String> = object
+                        .keys()
+                        .filter(|key| !keywords.properties.iter().any(|(name, _)| name == *key))
+                        .collect();
+                    extra.sort();
+
+                    for key in extra {
+                        self.fail(
+                            &child_pointer(pointer, key),
+                            "additionalProperties",
+                            format!("Property '{}' is not allowed", key),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_number(&mut self, keywords: &Keywords, value: &JsonValue, pointer: &str) {
+        let number = match value.as_number() {
+            Some(number) => number.as_f64(),
+            None => return,
+        };
+
+        if let Some(minimum) = keywords.minimum {
+            if number < minimum {
+                self.fail(pointer, "minimum", format!("Less than {}", minimum));
+            }
+        }
+        if let Some(maximum) = keywords.maximum {
+            if number > maximum {
+                self.fail(pointer, "maximum", format!("Greater than {}", maximum));
+            }
+        }
+    }
+
+    fn validate_array(&mut self, keywords: &Keywords, array: &[JsonValue], pointer: &mut String) {
+        if let Some(min) = keywords.min_items {
+            if array.len() < min {
+                self.fail(pointer, "minItems", format!("Fewer than {} items", min));
+            }
+        }
+        if let Some(max) = keywords.max_items {
+            if array.len() > max {
+                self.fail(pointer, "maxItems", format!("More than {} items", max));
+            }
+        }
+
+        for (index, element) in array.iter().enumerate() {
+            let node = match &keywords.items {
+                Some(Items::All(node)) => node,
+                Some(Items::Tuple(nodes)) => match nodes.get(index) {
+                    Some(node) => node,
+                    None => break,
+                },
+                None => break,
+            };
+
+            let len = pointer.len();
+            pointer.push('/');
+            pointer.push_str(&index.to_string());
+            self.validate(node, element, pointer);
+            pointer.truncate(len);
+        }
+    }
+}
+
+impl Schema {
+    pub fn parse(schema: &JsonValue) -> Result<Schema, SchemaError> {
+        return Ok(Schema {
+            root: parse_node(schema, "")?,
+        });
+    }
+
+    /// Checks `doc` against the schema, collecting every failure rather
+    /// than stopping at the first.
+    pub fn validate(&self, doc: &JsonValue) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator { errors: Vec::new() };
+        validator.validate(&self.root, doc, &mut String::new());
+
+        if validator.errors.is_empty() {
+            return Ok(());
+        }
+
+        return Err(validator.errors);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InferOptions {
+    /// Marks every object property as required. When the sample has an
+    /// array of objects, only the properties present in all of them are.
+    pub require_all: bool,
+}
+
+impl Default for InferOptions {
+    fn default() -> InferOptions {
+        InferOptions { require_all: true }
+    }
+}
+
+fn type_schema(name: &str) -> JsonValue {
+    return ObjectBuilder::new().insert("type", name).build();
+}
+
+fn infer_objects(objects: &[&HashMap<String, JsonValue>], opts: &InferOptions) -> JsonValue {
+    let mut keys: Vec<&String> = objects.iter().flat_map(|object| object.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut properties = ObjectBuilder::new();
+    let mut required = Vec::new();
+
+    for key in keys {
+        let values: Vec<&JsonValue> = objects
+            .iter()
+            .filter_map(|object| object.get(key))
+            .collect();
+
+        if opts.require_all && values.len() == objects.len() {
+            required.push(JsonValue::String(key.clone()));
+        }
+        if let Some(schema) = infer_values(&values, opts) {
+            properties = properties.insert(key.as_str(), schema);
+        }
+    }
+
+    return ObjectBuilder::new()
+        .insert("type", "object")
+        .insert("properties", properties)
+        .insert_when(!required.is_empty(), "required", required)
+        .build();
+}
+
+// Infers one schema covering every value in `values`, or `None` when there
+// are no values to learn from. Values of different kinds become a `type`
+// array when every kind is a plain scalar and an `anyOf` otherwise.
+fn infer_values(values: &[&JsonValue], opts: &InferOptions) -> Option<JsonValue> {
+    let mut has_null = false;
+    let mut has_boolean = false;
+    let mut has_integer = false;
+    let mut has_float = false;
+    let mut has_string = false;
+    let mut arrays = Vec::new();
+    let mut objects = Vec::new();
+
+    for value in values {
+        match value {
+            JsonValue::Null => has_null = true,
+            JsonValue::Boolean(_) => has_boolean = true,
+            JsonValue::Integer(_) => has_integer = true,
+            JsonValue::Float(_) => has_float = true,
+            JsonValue::String(_) => has_string = true,
+            JsonValue::Array(array) => arrays.push(array),
+            JsonValue::Object(object) => objects.push(object),
+        }
+    }
+
+    let mut scalars = Vec::new();
+    if has_null {
+        scalars.push("null");
+    }
+    if has_boolean {
+        scalars.push("boolean");
+    }
+    if has_float {
+        scalars.push("number");
+    } else if has_integer {
+        scalars.push("integer");
+    }
+    if has_string {
+        scalars.push("string");
+    }
+
+    let mut schemas: Vec<JsonValue> = scalars.iter().map(|name| type_schema(name)).collect();
+
+    if !arrays.is_empty() {
+        let elements: Vec<&JsonValue> = arrays.iter().flat_map(|array| array.iter()).collect();
+        let array_schema = ObjectBuilder::new()
+            .insert("type", "array")
+            .insert_if("items", infer_values(&elements, opts))
+            .build();
+        schemas.push(array_schema);
+    }
+    if !objects.is_empty() {
+        schemas.push(infer_objects(&objects, opts));
+    }
+
+    match schemas.len() {
+        0 => {
+            return None;
+        }
+        1 => {
+            return schemas.pop();
+        }
+        _ => {}
+    }
+
+    if schemas.len() == scalars.len() {
+        let types: Vec<JsonValue> = scalars.into_iter().map(JsonValue::from).collect();
+        return Some(ObjectBuilder::new().insert("type", types).build());
+    }
+
+    return Some(ObjectBuilder::new().insert("anyOf", schemas).build());
+}
+
+/// Produces a draft-07 schema describing `doc`. Integers infer as
+/// `integer` and floats as `number`, array `items` cover every element,
+/// and the result always accepts `doc` itself.
+pub fn infer_schema(doc: &JsonValue) -> JsonValue {
+    return infer_schema_with(doc, &InferOptions::default());
+}
+
+pub fn infer_schema_with(doc: &JsonValue, opts: &InferOptions) -> JsonValue {
+    return infer_values(&[doc], opts).unwrap_or(JsonValue::Boolean(true));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::schema::{
+        infer_schema, infer_schema_with, InferOptions, Schema, SchemaError, ValidationError,
+    };
+
+    fn errors(schema: JsonValue, doc: JsonValue) -> Vec<(String, String)> {
+        let schema = Schema::parse(&schema).unwrap();
+        return match schema.validate(&doc) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .map(|error| (error.pointer, error.keyword))
+                .collect(),
+        };
+    }
+
+    fn pair(pointer: &str, keyword: &str) -> (String, String) {
+        return (pointer.to_string(), keyword.to_string());
+    }
+
+    #[test]
+    fn collects_every_failure_with_its_pointer_and_keyword() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "additionalProperties": false,
+            "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "tags": {"type": "array", "maxItems": 2, "items": {"type": "string", "minLength": 1}},
+                "a/b": {"enum": ["x", "y"]}
+            }
+        });
+        let doc = json!({"id": 0, "tags": ["", 3, "ok"], "a/b": "z", "extra~": true});
+
+        assert_eq!(
+            errors(schema, doc),
+            [
+                pair("", "required"),
+                pair("/a~1b", "enum"),
+                pair("/id", "minimum"),
+                pair("/tags", "maxItems"),
+                pair("/tags/0", "minLength"),
+                pair("/tags/1", "type"),
+                pair("/extra~0", "additionalProperties"),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_display_their_location() {
+        let schema = Schema::parse(&json!({"items": {"type": ["string", "null"]}})).unwrap();
+        let errors = schema.validate(&json!(["a", null, 1.5])).unwrap_err();
+
+        assert_eq!(
+            errors,
+            [ValidationError {
+                pointer: "/2".to_string(),
+                keyword: "type".to_string(),
+                message: "Expected string or null, found float".to_string(),
+            }]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "[Validation error at '/2' (type)]: Expected string or null, found float"
+        );
+    }
+
+    #[test]
+    fn malformed_schemas_are_errors() {
+        let parse_error = |schema| Schema::parse(&schema).unwrap_err();
+
+        assert_eq!(
+            parse_error(json!({"properties": {"a": {"type": "text"}}})),
+            SchemaError {
+                pointer: "/properties/a/type".to_string(),
+                message: "Expected a JSON Schema type name".to_string(),
+            }
+        );
+        assert_eq!(parse_error(json!({"items": [true, 3]})).pointer, "/items/1");
+        assert_eq!(parse_error(json!({"minLength": -1})).pointer, "/minLength");
+        assert_eq!(parse_error(json!({"minItems": 1.5})).pointer, "/minItems");
+        assert_eq!(parse_error(json!({"anyOf": []})).pointer, "/anyOf");
+        assert_eq!(
+            parse_error(json!({"additionalProperties": {}})).to_string(),
+            "[Schema error at '/additionalProperties']: Only the boolean form of additionalProperties is supported"
+        );
+        assert!(Schema::parse(&json!("object")).is_err());
+        assert!(Schema::parse(&json!({"$ref": "#/definitions/a", "format": "email"})).is_ok());
+    }
+
+    #[test]
+    fn infers_types_properties_and_required() {
+        let doc =
+            json!({"id": 1, "score": 2.5, "name": "a", "ok": true, "none": null, "tags": ["x"]});
+
+        assert_eq!(
+            infer_schema(&doc),
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "score": {"type": "number"},
+                    "name": {"type": "string"},
+                    "ok": {"type": "boolean"},
+                    "none": {"type": "null"},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["id", "name", "none", "ok", "score", "tags"]
+            })
+        );
+    }
+
+    #[test]
+    fn array_elements_are_merged() {
+        assert_eq!(
+            infer_schema(&json!([1, 2.5, "a", null])),
+            json!({"type": "array", "items": {"type": ["null", "number", "string"]}})
+        );
+        assert_eq!(
+            infer_schema(&json!([1, [true]])),
+            json!({"type": "array", "items": {"anyOf": [
+                {"type": "integer"},
+                {"type": "array", "items": {"type": "boolean"}}
+            ]}})
+        );
+        assert_eq!(infer_schema(&json!([])), json!({"type": "array"}));
+
+        // Only properties every object has are required.
+        assert_eq!(
+            infer_schema(&json!([{"a": 1, "b": 2}, {"a": 3}])),
+            json!({"type": "array", "items": {
+                "type": "object",
+                "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+                "required": ["a"]
+            }})
+        );
+    }
+
+    #[test]
+    fn required_can_be_relaxed() {
+        let opts = InferOptions { require_all: false };
+
+        assert_eq!(
+            infer_schema_with(&json!({"a": {"b": 1}}), &opts),
+            json!({
+                "type": "object",
+                "properties": {"a": {"type": "object", "properties": {"b": {"type": "integer"}}}}
+            })
+        );
+    }
+}
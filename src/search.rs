@@ -0,0 +1,117 @@
+use crate::json::JsonValue;
+use crate::path::escape_pointer_token;
+
+// Object members are visited in sorted key order so results are stable
+// regardless of the map's iteration order.
+fn find_all<'a>(
+    value: &'a JsonValue,
+    key: &str,
+    pointer: &mut String,
+    results: &mut Vec<(String, &'a JsonValue)>,
+    first_only: bool,
+) {
+    match value {
+        JsonValue::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+
+            for member_key in keys {
+                if first_only && !results.is_empty() {
+                    return;
+                }
+
+                let member = &object[member_key];
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer_token(member_key));
+
+                if member_key == key {
+                    results.push((pointer.clone(), member));
+                }
+                find_all(member, key, pointer, results, first_only);
+
+                pointer.truncate(len);
+            }
+        }
+        JsonValue::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                if first_only && !results.is_empty() {
+                    return;
+                }
+
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+
+                find_all(element, key, pointer, results, first_only);
+
+                pointer.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl JsonValue {
+    /// Returns the first value stored under `key` anywhere in the tree,
+    /// searching depth-first with a parent visited before its children.
+    pub fn find_key(&self, key: &str) -> Option<&JsonValue> {
+        let mut results = Vec::new();
+        find_all(self, key, &mut String::new(), &mut results, true);
+
+        return results.into_iter().next().map(|(_, value)| value);
+    }
+
+    /// Returns every value stored under `key` anywhere in the tree, paired
+    /// with its JSON Pointer, in the same order as `find_key`.
+    pub fn find_all_by_key(&self, key: &str) -> Vec<(String, &JsonValue)> {
+        let mut results = Vec::new();
+        find_all(self, key, &mut String::new(), &mut results, false);
+
+        return results;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+
+    #[test]
+    fn finds_keys_depth_first_in_sorted_order() {
+        let doc = json!({
+            "b": {"id": 2},
+            "a": [{"x": {"id": 1}}, {"id": {"id": 3}}],
+            "id": 0
+        });
+
+        assert_eq!(doc.find_key("id"), Some(&json!(1)));
+        assert_eq!(doc.find_key("x"), Some(&json!({"id": 1})));
+        assert_eq!(doc.find_key("missing"), None);
+        assert_eq!(
+            doc.find_all_by_key("id"),
+            vec![
+                ("/a/0/x/id".to_string(), &json!(1)),
+                ("/a/1/id".to_string(), &json!({"id": 3})),
+                ("/a/1/id/id".to_string(), &json!(3)),
+                ("/b/id".to_string(), &json!(2)),
+                ("/id".to_string(), &json!(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn pointers_escape_keys() {
+        let doc = json!({"a/b": {"~": 1}});
+
+        assert_eq!(
+            doc.find_all_by_key("~"),
+            vec![("/a~1b/~0".to_string(), &json!(1))]
+        );
+    }
+
+    #[test]
+    fn scalars_have_no_keys() {
+        assert_eq!(json!(1).find_key(""), None);
+        assert!(json!([null, "id"]).find_all_by_key("id").is_empty());
+    }
+}
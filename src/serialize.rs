@@ -0,0 +1,1084 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+use crate::color::ColorTheme;
+use crate::json::{Json, JsonValue};
+use crate::path::escape_pointer_token;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputStyle {
+    /// One member or element per line, indented by `indent`.
+    #[default]
+    Pretty,
+    /// No whitespace at all between tokens.
+    Minified,
+    /// Like `Pretty`, but arrays and objects that fit on the rest of their
+    /// line within `line_width` are written on one line, as in `[1, 2]`.
+    Fitted,
+    /// Laid out like `Pretty` as a JavaScript object literal: keys that are
+    /// identifiers go unquoted, as in `name: "x"`. See `single_quotes`
+    /// and `trailing_commas`. This isn't JSON and can't be parsed back.
+    JavaScript,
+}
+
+/// One level of indentation in multi-line output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(u8),
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Indent {
+        Indent::Spaces(2)
+    }
+}
+
+impl Indent {
+    // Tabs count as eight columns against `line_width`.
+    fn width(self) -> usize {
+        match self {
+            Indent::Spaces(count) => count as usize,
+            Indent::Tab => 8,
+        }
+    }
+}
+
+/// What to do with a NaN or infinite float, which JSON can't represent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFiniteFloats {
+    /// Fails with a `NonFiniteFloatError`.
+    #[default]
+    Error,
+    /// Writes `null`, losing the value.
+    Null,
+    /// Writes `NaN`, `Infinity` or `-Infinity` as JavaScript and JSON5 do.
+    /// This isn't JSON and can't be parsed back.
+    Literal,
+}
+
+/// A NaN or infinite float met while serializing under
+/// `NonFiniteFloats::Error`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonFiniteFloatError {
+    /// The JSON Pointer to the float.
+    pub pointer: String,
+    pub value: f64,
+}
+
+impl fmt::Display for NonFiniteFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[Serialize error at '{}']: {} can't be written as JSON",
+            self.pointer, self.value
+        )
+    }
+}
+
+impl std::error::Error for NonFiniteFloatError {}
+
+impl From<NonFiniteFloatError> for io::Error {
+    fn from(error: NonFiniteFloatError) -> io::Error {
+        return io::Error::new(io::ErrorKind::InvalidData, error);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializeOptions {
+    pub style: OutputStyle,
+    /// The indentation per level for every style but `Minified`.
+    pub indent: Indent,
+    /// Escapes `<`, `>`, `&`, U+2028 and U+2029 in strings as `\uXXXX`, so
+    /// the output can be embedded in an HTML `<script>` element.
+    pub escape_html: bool,
+    /// The column limit for `OutputStyle::Fitted`, in characters.
+    pub line_width: usize,
+    /// Quotes strings and keys with `'` in `OutputStyle::JavaScript`.
+    pub single_quotes: bool,
+    /// Ends every element and member with a comma in
+    /// `OutputStyle::JavaScript`, so appending a line touches only that
+    /// line in a diff.
+    pub trailing_commas: bool,
+    /// Writes object members sorted by key, comparing keys by code point,
+    /// instead of in the map's arbitrary order. Equal values then always
+    /// serialize to identical text.
+    pub sort_keys: bool,
+    /// How NaN and infinite floats are written. `to_string_lossy` and
+    /// `Display` treat `Error` as `Null`.
+    pub non_finite_floats: NonFiniteFloats,
+}
+
+impl SerializeOptions {
+    pub fn new(style: OutputStyle) -> SerializeOptions {
+        return SerializeOptions {
+            style,
+            indent: Indent::Spaces(2),
+            escape_html: false,
+            line_width: 80,
+            single_quotes: false,
+            trailing_commas: false,
+            sort_keys: false,
+            non_finite_floats: NonFiniteFloats::Error,
+        };
+    }
+
+    // The options with `NonFiniteFloats::Error` relaxed to `Null`.
+    fn lossy(&self) -> SerializeOptions {
+        let mut options = *self;
+        if options.non_finite_floats == NonFiniteFloats::Error {
+            options.non_finite_floats = NonFiniteFloats::Null;
+        }
+
+        return options;
+    }
+}
+
+impl Default for SerializeOptions {
+    fn default() -> SerializeOptions {
+        return SerializeOptions::new(OutputStyle::default());
+    }
+}
+
+pub(crate) fn write_string(
+    out: &mut impl fmt::Write,
+    string: &str,
+    quote: char,
+    escape_html: bool,
+) -> fmt::Result {
+    out.write_char(quote)?;
+
+    for c in string.chars() {
+        match c {
+            c if c == quote => write!(out, "\\{}", c)?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            '\u{8}' => out.write_str("\\b")?,
+            '\u{c}' => out.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if escape_html => {
+                write!(out, "\\u{:04x}", c as u32)?
+            }
+            c => out.write_char(c)?,
+        }
+    }
+
+    return out.write_char(quote);
+}
+
+// Keys made of ASCII letters, digits, `_` and `$`, not starting with a
+// digit. Reserved words are fine as property names.
+fn is_identifier(key: &str) -> bool {
+    return key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+}
+
+// Picks the shorter of the positional and exponent forms, keeping a `.0` or
+// exponent so the number reads back as a float. JSON has no NaN or
+// infinity, so those are written as `null`.
+pub(crate) fn format_float(value: f64) -> String {
+    if !value.is_finite() {
+        return "null".to_string();
+    }
+
+    let mut positional = value.to_string();
+    if !positional.contains('.') {
+        positional.push_str(".0");
+    }
+
+    let exponent = format!("{:e}", value);
+    if exponent.len() < positional.len() {
+        return exponent;
+    }
+
+    return positional;
+}
+
+// The text of a float under `policy`, or `None` if it's rejected.
+pub(crate) fn float_text(value: f64, policy: NonFiniteFloats) -> Option<String> {
+    if value.is_finite() || policy == NonFiniteFloats::Null {
+        return Some(format_float(value));
+    }
+    if policy == NonFiniteFloats::Error {
+        return None;
+    }
+
+    let literal = match value {
+        value if value.is_nan() => "NaN",
+        value if value > 0.0 => "Infinity",
+        _ => "-Infinity",
+    };
+    return Some(literal.to_string());
+}
+
+// The width of `value` written on one line with `, ` and `: ` separators,
+// or `None` once it passes `limit`.
+fn flat_width(value: &JsonValue, options: &SerializeOptions, limit: usize) -> Option<usize> {
+    let width = match value {
+        JsonValue::Null => 4,
+        JsonValue::Boolean(value) => value.to_string().len(),
+        JsonValue::Integer(value) => value.to_string().len(),
+        JsonValue::Float(value) => float_text(*value, options.non_finite_floats)?.len(),
+        JsonValue::String(value) => {
+            let mut escaped = String::new();
+            let _ = write_string(&mut escaped, value, '"', options.escape_html);
+            escaped.chars().count()
+        }
+        JsonValue::Array(array) => return flat_array_width(array, options, limit),
+        JsonValue::Object(object) => return flat_object_width(object, options, limit),
+    };
+
+    if width > limit {
+        return None;
+    }
+
+    return Some(width);
+}
+
+fn flat_array_width(
+    array: &[JsonValue],
+    options: &SerializeOptions,
+    limit: usize,
+) -> Option<usize> {
+    let mut width = 2 + array.len().saturating_sub(1) * 2;
+    for element in array {
+        width += flat_width(element, options, limit.checked_sub(width)?)?;
+    }
+
+    return Some(width).filter(|width| *width <= limit);
+}
+
+fn flat_object_width(
+    object: &HashMap<String, JsonValue>,
+    options: &SerializeOptions,
+    limit: usize,
+) -> Option<usize> {
+    let mut width = 2 + object.len().saturating_sub(1) * 2;
+    for (key, member) in object {
+        width += flat_width(&JsonValue::String(key.clone()), options, usize::MAX)? + 2;
+        width += flat_width(member, options, limit.checked_sub(width)?)?;
+    }
+
+    return Some(width).filter(|width| *width <= limit);
+}
+
+pub(crate) struct Serializer<'a, W: fmt::Write> {
+    out: &'a mut W,
+    options: &'a SerializeOptions,
+    theme: Option<&'a ColorTheme>,
+    depth: usize,
+    // Set while writing a collection that fits on one line.
+    inline: bool,
+    // The width already taken on the current line by indentation, a key or
+    // a trailing comma, for `OutputStyle::Fitted`.
+    taken: usize,
+    // The float that stopped serializing under `NonFiniteFloats::Error`.
+    // Its pointer is built up as the error returns through each level.
+    error: Option<NonFiniteFloatError>,
+}
+
+impl<'a, W: fmt::Write> Serializer<'a, W> {
+    pub(crate) fn new(out: &'a mut W, options: &'a SerializeOptions) -> Serializer<'a, W> {
+        return Serializer {
+            out,
+            options,
+            theme: None,
+            depth: 0,
+            inline: false,
+            taken: 0,
+            error: None,
+        };
+    }
+
+    pub(crate) fn with_theme(mut self, theme: &'a ColorTheme) -> Serializer<'a, W> {
+        self.theme = Some(theme);
+        return self;
+    }
+
+    // Colors wrap whole tokens, so escapes inside strings are never split.
+    fn token(
+        &mut self,
+        color: fn(&ColorTheme) -> &str,
+        write: impl FnOnce(&mut W) -> fmt::Result,
+    ) -> fmt::Result {
+        let code = match self.theme {
+            Some(theme) => color(theme),
+            None => "",
+        };
+
+        if code.is_empty() {
+            return write(self.out);
+        }
+
+        write!(self.out, "\x1b[{}m", code)?;
+        write(self.out)?;
+        return self.out.write_str("\x1b[0m");
+    }
+
+    fn punctuation(&mut self, c: char) -> fmt::Result {
+        return self.token(|theme| &theme.punctuation, |out| out.write_char(c));
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        if self.options.style == OutputStyle::Minified || self.inline {
+            return Ok(());
+        }
+
+        self.out.write_char('\n')?;
+        for _ in 0..self.depth {
+            match self.options.indent {
+                Indent::Spaces(count) => {
+                    for _ in 0..count {
+                        self.out.write_char(' ')?;
+                    }
+                }
+                Indent::Tab => self.out.write_char('\t')?,
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub(crate) fn value(&mut self, value: &JsonValue) -> fmt::Result {
+        match value {
+            JsonValue::Null => self.token(|theme| &theme.null, |out| out.write_str("null")),
+            JsonValue::Boolean(value) => {
+                self.token(|theme| &theme.boolean, |out| write!(out, "{}", value))
+            }
+            JsonValue::Integer(value) => {
+                self.token(|theme| &theme.number, |out| write!(out, "{}", value))
+            }
+            JsonValue::Float(value) => match float_text(*value, self.options.non_finite_floats) {
+                Some(text) => self.token(|theme| &theme.number, |out| out.write_str(&text)),
+                None => {
+                    self.error = Some(NonFiniteFloatError {
+                        pointer: String::new(),
+                        value: *value,
+                    });
+                    Err(fmt::Error)
+                }
+            },
+            JsonValue::String(value) => {
+                let (quote, escape_html) = (self.quote(), self.options.escape_html);
+                self.token(
+                    |theme| &theme.string,
+                    |out| write_string(out, value, quote, escape_html),
+                )
+            }
+            JsonValue::Array(array) => self.array(array),
+            JsonValue::Object(object) => self.object(object),
+        }
+    }
+
+    pub(crate) fn root(&mut self, json: &Json) -> fmt::Result {
+        match json {
+            Json::Object(object) => self.object(object),
+            Json::Array(array) => self.array(array),
+        }
+    }
+
+    // Writes a member or element, adding its key or index to the pointer
+    // of a non-finite float error from it.
+    fn child(&mut self, token: impl FnOnce() -> String, value: &JsonValue) -> fmt::Result {
+        let result = self.value(value);
+        if let Some(error) = &mut self.error {
+            error.pointer.insert_str(0, &format!("/{}", token()));
+        }
+
+        return result;
+    }
+
+    fn quote(&self) -> char {
+        if self.options.style == OutputStyle::JavaScript && self.options.single_quotes {
+            return '\'';
+        }
+
+        return '"';
+    }
+
+    fn trailing_comma(&mut self) -> fmt::Result {
+        if self.options.style == OutputStyle::JavaScript && self.options.trailing_commas {
+            return self.punctuation(',');
+        }
+
+        return Ok(());
+    }
+
+    fn separator(&mut self) -> fmt::Result {
+        self.punctuation(',')?;
+        if self.inline {
+            self.out.write_char(' ')?;
+        }
+
+        return Ok(());
+    }
+
+    // Decides whether a collection about to be written goes on one line,
+    // given a function measuring its one-line width within a limit.
+    // Returns the previous setting to restore once it's done.
+    fn enter_collection(
+        &mut self,
+        measure: impl FnOnce(&SerializeOptions, usize) -> Option<usize>,
+    ) -> bool {
+        let outer = self.inline;
+        if self.options.style == OutputStyle::Fitted && !self.inline {
+            let limit = self.options.line_width.saturating_sub(self.taken);
+            self.inline = measure(self.options, limit).is_some();
+        }
+
+        return outer;
+    }
+
+    // Records how much of the next element's line is taken before and after
+    // it, so nested collections know the width they have left.
+    fn take_width(&mut self, prefix: usize, last: bool) {
+        self.taken = self.depth * self.options.indent.width() + prefix + if last { 0 } else { 1 };
+    }
+
+    pub(crate) fn array(&mut self, array: &[JsonValue]) -> fmt::Result {
+        let outer = self.enter_collection(|options, limit| flat_array_width(array, options, limit));
+        let result = self.array_lines(array);
+        self.inline = outer;
+
+        return result;
+    }
+
+    fn array_lines(&mut self, array: &[JsonValue]) -> fmt::Result {
+        self.punctuation('[')?;
+        if array.is_empty() {
+            return self.punctuation(']');
+        }
+
+        self.depth += 1;
+        for (index, element) in array.iter().enumerate() {
+            if index > 0 {
+                self.separator()?;
+            }
+            self.newline()?;
+            self.take_width(0, index + 1 == array.len());
+            self.child(|| index.to_string(), element)?;
+        }
+        self.trailing_comma()?;
+        self.depth -= 1;
+
+        self.newline()?;
+        return self.punctuation(']');
+    }
+
+    pub(crate) fn object(&mut self, object: &HashMap<String, JsonValue>) -> fmt::Result {
+        let outer =
+            self.enter_collection(|options, limit| flat_object_width(object, options, limit));
+        let result = self.object_lines(object);
+        self.inline = outer;
+
+        return result;
+    }
+
+    fn object_lines(&mut self, object: &HashMap<String, JsonValue>) -> fmt::Result {
+        self.punctuation('{')?;
+        if object.is_empty() {
+            return self.punctuation('}');
+        }
+
+        let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+        if self.options.sort_keys {
+            members.sort_by_key(|(key, _)| *key);
+        }
+
+        self.depth += 1;
+        for (index, (key, member)) in members.into_iter().enumerate() {
+            if index > 0 {
+                self.separator()?;
+            }
+            self.newline()?;
+            let mut escaped = String::new();
+            if self.options.style == OutputStyle::JavaScript && is_identifier(key) {
+                escaped.push_str(key);
+            } else {
+                write_string(&mut escaped, key, self.quote(), self.options.escape_html)?;
+            }
+            self.token(|theme| &theme.key, |out| out.write_str(&escaped))?;
+            self.punctuation(':')?;
+            if self.options.style != OutputStyle::Minified {
+                self.out.write_char(' ')?;
+            }
+            self.take_width(escaped.chars().count() + 2, index + 1 == object.len());
+            self.child(|| escape_pointer_token(key), member)?;
+        }
+        self.trailing_comma()?;
+        self.depth -= 1;
+
+        self.newline()?;
+        return self.punctuation('}');
+    }
+}
+
+// Lets the `fmt::Write` serializer stream into an `io::Write`, keeping the
+// underlying IO error that `fmt::Error` can't carry.
+pub(crate) struct IoAdapter<'a, W: io::Write> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> IoAdapter<'a, W> {
+    pub(crate) fn new(inner: &'a mut W) -> IoAdapter<'a, W> {
+        return IoAdapter { inner, error: None };
+    }
+
+    pub(crate) fn finish(self, result: fmt::Result) -> io::Result<()> {
+        match (result, self.error) {
+            (Ok(()), _) => {
+                return Ok(());
+            }
+            (Err(_), Some(error)) => {
+                return Err(error);
+            }
+            (Err(_), None) => {
+                return Err(io::Error::other("formatter error"));
+            }
+        }
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.inner.write_all(s.as_bytes()) {
+            Ok(()) => {
+                return Ok(());
+            }
+            Err(error) => {
+                self.error = Some(error);
+                return Err(fmt::Error);
+            }
+        }
+    }
+}
+
+/// Writes to a temporary file beside `path` and renames it into place, so
+/// readers see either the old contents or the new, never a partial write.
+/// An existing file's permissions are kept.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let file_name = match path.file_name() {
+        Some(file_name) => file_name,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path has no file name",
+            ));
+        }
+    };
+
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        return fs::rename(&temp_path, path);
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    return result;
+}
+
+// Runs `write` with a serializer into a new string. Writing to a string
+// can't fail, so an error can only be a rejected float.
+pub(crate) fn serialize_to_string(
+    options: &SerializeOptions,
+    theme: Option<&ColorTheme>,
+    write: impl FnOnce(&mut Serializer<String>) -> fmt::Result,
+) -> Result<String, NonFiniteFloatError> {
+    let mut output = String::new();
+    let mut serializer = Serializer::new(&mut output, options);
+    serializer.theme = theme;
+    let _ = write(&mut serializer);
+
+    if let Some(error) = serializer.error {
+        return Err(error);
+    }
+    return Ok(output);
+}
+
+// Runs `write` with a serializer streaming into `writer`. A rejected float
+// is reported as an `InvalidData` error wrapping its `NonFiniteFloatError`.
+pub(crate) fn serialize_to_writer<W: io::Write>(
+    writer: &mut W,
+    options: &SerializeOptions,
+    theme: Option<&ColorTheme>,
+    write: impl FnOnce(&mut Serializer<IoAdapter<W>>) -> fmt::Result,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter::new(writer);
+    let mut serializer = Serializer::new(&mut adapter, options);
+    serializer.theme = theme;
+    let result = write(&mut serializer);
+
+    if let Some(error) = serializer.error {
+        return Err(error.into());
+    }
+    return adapter.finish(result);
+}
+
+impl JsonValue {
+    /// Fails with `fmt::Error` on a NaN or infinite float under
+    /// `NonFiniteFloats::Error`, leaving partial output in `out`.
+    pub fn write_fmt_with(
+        &self,
+        out: &mut impl fmt::Write,
+        options: &SerializeOptions,
+    ) -> fmt::Result {
+        return Serializer::new(out, options).value(self);
+    }
+
+    /// Streams the value into `writer` without building the whole string.
+    /// A NaN or infinite float rejected by `options` is an `InvalidData`
+    /// error wrapping a `NonFiniteFloatError`.
+    pub fn write_to(
+        &self,
+        writer: &mut impl io::Write,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        return serialize_to_writer(writer, options, None, |serializer| serializer.value(self));
+    }
+
+    /// Replaces the file at `path` with the serialized value and a final
+    /// newline. The file is written atomically: it's never left partially
+    /// written, even if this fails part way.
+    pub fn write_to_file(
+        &self,
+        path: impl AsRef<Path>,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        let mut contents = self.to_string_with(options)?;
+        contents.push('\n');
+
+        return write_atomically(path.as_ref(), contents.as_bytes());
+    }
+
+    pub fn to_string_with(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<String, NonFiniteFloatError> {
+        return serialize_to_string(options, None, |serializer| serializer.value(self));
+    }
+
+    /// Like `to_string_with`, but NaN and infinite floats are written as
+    /// `null` unless `options` asks for `NonFiniteFloats::Literal`, so it
+    /// can't fail.
+    pub fn to_string_lossy(&self, options: &SerializeOptions) -> String {
+        let mut output = String::new();
+        let _ = self.write_fmt_with(&mut output, &options.lossy());
+
+        return output;
+    }
+
+    pub fn to_string_pretty(&self) -> Result<String, NonFiniteFloatError> {
+        return self.to_string_with(&SerializeOptions::new(OutputStyle::Pretty));
+    }
+
+    /// Serializes with no insignificant whitespace, floats in their
+    /// shortest form that still reads back as a float.
+    pub fn to_string_minified(&self) -> Result<String, NonFiniteFloatError> {
+        return self.to_string_with(&SerializeOptions::new(OutputStyle::Minified));
+    }
+}
+
+impl Json {
+    pub fn write_fmt_with(
+        &self,
+        out: &mut impl fmt::Write,
+        options: &SerializeOptions,
+    ) -> fmt::Result {
+        return Serializer::new(out, options).root(self);
+    }
+
+    pub fn write_to(
+        &self,
+        writer: &mut impl io::Write,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        return serialize_to_writer(writer, options, None, |serializer| serializer.root(self));
+    }
+
+    pub fn write_to_file(
+        &self,
+        path: impl AsRef<Path>,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        let mut contents = self.to_string_with(options)?;
+        contents.push('\n');
+
+        return write_atomically(path.as_ref(), contents.as_bytes());
+    }
+
+    pub fn to_string_with(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<String, NonFiniteFloatError> {
+        return serialize_to_string(options, None, |serializer| serializer.root(self));
+    }
+
+    pub fn to_string_lossy(&self, options: &SerializeOptions) -> String {
+        let mut output = String::new();
+        let _ = self.write_fmt_with(&mut output, &options.lossy());
+
+        return output;
+    }
+
+    pub fn to_string_pretty(&self) -> Result<String, NonFiniteFloatError> {
+        return self.to_string_with(&SerializeOptions::new(OutputStyle::Pretty));
+    }
+
+    pub fn to_string_minified(&self) -> Result<String, NonFiniteFloatError> {
+        return self.to_string_with(&SerializeOptions::new(OutputStyle::Minified));
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_fmt_with(f, &SerializeOptions::default().lossy());
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_fmt_with(f, &SerializeOptions::default().lossy());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{parse_from_str, JsonValue};
+    use crate::serialize::{
+        Indent, NonFiniteFloatError, NonFiniteFloats, OutputStyle, SerializeOptions,
+    };
+
+    #[test]
+    fn minified_output_matches_byte_for_byte() {
+        let value = json!([
+            {"a": 1},
+            {"b": [true, null, 1.5, -0.0, 1e20, 1e-7, 0.1]},
+            "x\"y\n",
+            [],
+            {}
+        ]);
+
+        assert_eq!(
+            value.to_string_minified().unwrap(),
+            r#"[{"a":1},{"b":[true,null,1.5,-0.0,1e20,1e-7,0.1]},"x\"y\n",[],{}]"#
+        );
+    }
+
+    #[test]
+    fn minified_output_with_sorted_keys_is_deterministic() {
+        let value = json!({"b": {"y": [1, 2], "x": "s"}, "a": 2.5, "c": null});
+        let mut options = SerializeOptions::new(OutputStyle::Minified);
+        options.sort_keys = true;
+
+        assert_eq!(
+            value.to_string_with(&options).unwrap(),
+            r#"{"a":2.5,"b":{"x":"s","y":[1,2]},"c":null}"#
+        );
+    }
+
+    #[test]
+    fn minified_output_reparses_and_is_never_longer_than_pretty() {
+        let value = json!({
+            "name": "mini",
+            "nested": {"list": [1, 2.0, "three", [[]], {"deep": true}]},
+            "empty": {}
+        });
+
+        let minified = value.to_string_minified().unwrap();
+        let reparsed = JsonValue::from(parse_from_str(&minified).unwrap());
+        assert_eq!(reparsed, value);
+        assert!(!minified.contains([' ', '\n']));
+        assert!(minified.len() <= value.to_string_pretty().unwrap().len());
+    }
+
+    #[test]
+    fn escape_html_keeps_strings_inside_script_elements() {
+        let value = json!({"html": "</script><b>&amp;</b>\u{2028}\u{2029}é"});
+        let mut options = SerializeOptions::new(OutputStyle::Minified);
+        options.escape_html = true;
+
+        let escaped = value.to_string_with(&options).unwrap();
+        assert_eq!(
+            escaped,
+            r#"{"html":"\u003c/script\u003e\u003cb\u003e\u0026amp;\u003c/b\u003e\u2028\u2029é"}"#
+        );
+        assert!(!escaped.contains(['<', '>', '&', '\u{2028}', '\u{2029}']));
+        assert_eq!(JsonValue::from(parse_from_str(&escaped).unwrap()), value);
+
+        options.escape_html = false;
+        assert_eq!(
+            value.to_string_with(&options).unwrap(),
+            "{\"html\":\"</script><b>&amp;</b>\u{2028}\u{2029}é\"}"
+        );
+    }
+
+    #[test]
+    fn escape_html_applies_to_keys_and_every_style() {
+        let value = json!({"<k>": ["a&b"]});
+
+        for style in [OutputStyle::Pretty, OutputStyle::Fitted] {
+            let mut options = SerializeOptions::new(style);
+            options.escape_html = true;
+
+            let escaped = value.to_string_with(&options).unwrap();
+            assert!(escaped.contains(r#""\u003ck\u003e""#));
+            assert!(escaped.contains(r#""a\u0026b""#));
+            assert_eq!(JsonValue::from(parse_from_str(&escaped).unwrap()), value);
+        }
+    }
+
+    fn sorted(style: OutputStyle) -> SerializeOptions {
+        let mut options = SerializeOptions::new(style);
+        options.sort_keys = true;
+        return options;
+    }
+
+    #[test]
+    fn fitted_output_keeps_short_collections_on_one_line() {
+        let value = json!({
+            "a": {"point": [1, 2], "words": ["alpha", "beta", "gamma", "delta", "epsilon"]},
+            "b": []
+        });
+        let mut fitted = sorted(OutputStyle::Fitted);
+        fitted.line_width = 40;
+
+        assert_eq!(
+            value
+                .to_string_with(&sorted(OutputStyle::Minified))
+                .unwrap(),
+            r#"{"a":{"point":[1,2],"words":["alpha","beta","gamma","delta","epsilon"]},"b":[]}"#
+        );
+        assert_eq!(
+            value.to_string_with(&sorted(OutputStyle::Pretty)).unwrap(),
+            r#"{
+  "a": {
+    "point": [
+      1,
+      2
+    ],
+    "words": [
+      "alpha",
+      "beta",
+      "gamma",
+      "delta",
+      "epsilon"
+    ]
+  },
+  "b": []
+}"#
+        );
+        assert_eq!(
+            value.to_string_with(&fitted).unwrap(),
+            r#"{
+  "a": {
+    "point": [1, 2],
+    "words": [
+      "alpha",
+      "beta",
+      "gamma",
+      "delta",
+      "epsilon"
+    ]
+  },
+  "b": []
+}"#
+        );
+
+        // The line for "a" is 82 columns wide with its comma.
+        fitted.line_width = 82;
+        assert_eq!(
+            value.to_string_with(&fitted).unwrap(),
+            r#"{
+  "a": {"point": [1, 2], "words": ["alpha", "beta", "gamma", "delta", "epsilon"]},
+  "b": []
+}"#
+        );
+    }
+
+    #[test]
+    fn fitted_output_counts_indentation_keys_and_commas() {
+        let mut options = SerializeOptions::new(OutputStyle::Fitted);
+
+        options.line_width = 6;
+        assert_eq!(json!([1, 2]).to_string_with(&options).unwrap(), "[1, 2]");
+        options.line_width = 5;
+        assert_eq!(
+            json!([1, 2]).to_string_with(&options).unwrap(),
+            "[\n  1,\n  2\n]"
+        );
+
+        // `    "k": [1, 2],` is 16 columns wide with its comma, or 28 when
+        // indented with two tabs.
+        let value = json!([{"k": [1, 2], "l": "0123456789abcdef"}]);
+        let inline = "[\n  {\n    \"k\": [1, 2],\n    \"l\": \"0123456789abcdef\"\n  }\n]";
+        let mut nested = sorted(OutputStyle::Fitted);
+        nested.line_width = 16;
+        assert_eq!(value.to_string_with(&nested).unwrap(), inline);
+        nested.line_width = 15;
+        assert_ne!(value.to_string_with(&nested).unwrap(), inline);
+
+        nested.indent = Indent::Tab;
+        nested.line_width = 27;
+        assert_eq!(
+            value.to_string_with(&nested).unwrap(),
+            "[\n\t{\n\t\t\"k\": [\n\t\t\t1,\n\t\t\t2\n\t\t],\n\t\t\"l\": \"0123456789abcdef\"\n\t}\n]"
+        );
+        nested.line_width = 28;
+        assert_eq!(
+            value.to_string_with(&nested).unwrap(),
+            "[\n\t{\n\t\t\"k\": [1, 2],\n\t\t\"l\": \"0123456789abcdef\"\n\t}\n]"
+        );
+    }
+
+    #[test]
+    fn javascript_output_unquotes_identifier_keys() {
+        let value = json!({
+            "name": "x",
+            "two words": 1,
+            "$ok": [true, "it's \"quoted\""],
+            "_1": null,
+            "1a": {}
+        });
+
+        let mut options = sorted(OutputStyle::JavaScript);
+        assert_eq!(
+            value.to_string_with(&options).unwrap(),
+            r#"{
+  $ok: [
+    true,
+    "it's \"quoted\""
+  ],
+  "1a": {},
+  _1: null,
+  name: "x",
+  "two words": 1
+}"#
+        );
+
+        options.single_quotes = true;
+        options.trailing_commas = true;
+        assert_eq!(
+            value.to_string_with(&options).unwrap(),
+            r#"{
+  $ok: [
+    true,
+    'it\'s "quoted"',
+  ],
+  '1a': {},
+  _1: null,
+  name: 'x',
+  'two words': 1,
+}"#
+        );
+    }
+
+    #[test]
+    fn javascript_output_with_only_quoted_keys_is_json() {
+        let value = json!({"two words": [1, "x"], "1a": {"-": null, "": [2.5]}});
+
+        let options = sorted(OutputStyle::JavaScript);
+        let written = value.to_string_with(&options).unwrap();
+        assert_eq!(
+            written,
+            value.to_string_with(&sorted(OutputStyle::Pretty)).unwrap()
+        );
+        assert_eq!(JsonValue::from(parse_from_str(&written).unwrap()), value);
+    }
+
+    #[test]
+    fn non_finite_floats_follow_each_policy() {
+        let value = json!([1.5, [f64::NAN, {"k": [f64::INFINITY]}], f64::NEG_INFINITY]);
+        let mut options = SerializeOptions::new(OutputStyle::Minified);
+
+        options.non_finite_floats = NonFiniteFloats::Null;
+        assert_eq!(
+            value.to_string_with(&options).unwrap(),
+            "[1.5,[null,{\"k\":[null]}],null]"
+        );
+
+        options.non_finite_floats = NonFiniteFloats::Literal;
+        assert_eq!(
+            value.to_string_with(&options).unwrap(),
+            "[1.5,[NaN,{\"k\":[Infinity]}],-Infinity]"
+        );
+
+        // The error points at the first float that can't be written.
+        options.non_finite_floats = NonFiniteFloats::Error;
+        let error = value.to_string_with(&options).unwrap_err();
+        assert_eq!(error.pointer, "/1/0");
+        assert!(error.value.is_nan());
+        for (value, pointer) in [
+            (json!([[1, {"k": [f64::INFINITY]}]]), "/0/1/k/0"),
+            (json!([0.0, f64::NEG_INFINITY]), "/1"),
+        ] {
+            let error = value.to_string_with(&options).unwrap_err();
+            assert_eq!(error.pointer, pointer);
+            assert!(error.value.is_infinite());
+        }
+        assert_eq!(
+            json!([f64::NEG_INFINITY]).to_string_pretty(),
+            Err(NonFiniteFloatError {
+                pointer: "/0".to_string(),
+                value: f64::NEG_INFINITY
+            })
+        );
+    }
+
+    #[test]
+    fn non_finite_floats_in_lossy_and_streaming_output() {
+        let value = json!([f64::NAN, [f64::INFINITY, -1]]);
+        let options = SerializeOptions::new(OutputStyle::Minified);
+
+        assert_eq!(value.to_string_lossy(&options), "[null,[null,-1]]");
+        assert_eq!(
+            value.to_string(),
+            "[\n  null,\n  [\n    null,\n    -1\n  ]\n]"
+        );
+
+        let mut literal = options;
+        literal.non_finite_floats = NonFiniteFloats::Literal;
+        assert_eq!(value.to_string_lossy(&literal), "[NaN,[Infinity,-1]]");
+
+        let mut out = Vec::new();
+        let error = value.write_to(&mut out, &options).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        let inner = error.into_inner().unwrap();
+        assert_eq!(
+            inner
+                .downcast_ref::<NonFiniteFloatError>()
+                .map(|error| &error.pointer[..]),
+            Some("/0")
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::json::{JsonValue, WrongKindError};
+use crate::path::{parse_path, parse_pointer, resolve, PathError, PathSegment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortError {
+    WrongKind(WrongKindError),
+    InvalidPath(PathError),
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortError::WrongKind(error) => write!(f, "{}", error),
+            SortError::InvalidPath(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for SortError {}
+
+impl From<WrongKindError> for SortError {
+    fn from(error: WrongKindError) -> SortError {
+        SortError::WrongKind(error)
+    }
+}
+
+impl From<PathError> for SortError {
+    fn from(error: PathError) -> SortError {
+        SortError::InvalidPath(error)
+    }
+}
+
+fn kind_rank(value: &JsonValue) -> u8 {
+    match value {
+        JsonValue::Null => 0,
+        JsonValue::Boolean(_) => 1,
+        JsonValue::Integer(_) | JsonValue::Float(_) => 2,
+        JsonValue::String(_) => 3,
+        JsonValue::Array(_) => 4,
+        JsonValue::Object(_) => 5,
+    }
+}
+
+fn compare_floats(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+    }
+}
+
+// Compares exactly, rather than through `integer as f64`, which rounds
+// integers past 2^53 and would make the order intransitive. NaN sorts
+// last, as in `compare_floats`.
+fn compare_integer_float(integer: i64, float: f64) -> Ordering {
+    // 2^63, the first float past `i64::MAX`.
+    const I64_BOUND: f64 = 9_223_372_036_854_775_808.0;
+
+    if float.is_nan() || float >= I64_BOUND {
+        return Ordering::Less;
+    }
+    if float < -I64_BOUND {
+        return Ordering::Greater;
+    }
+
+    // The whole part now fits in an `i64` exactly.
+    let whole = float.trunc();
+    return integer
+        .cmp(&(whole as i64))
+        .then_with(|| compare_floats(0.0, float - whole));
+}
+
+// The order documented on `JsonValue::compare`.
+pub(crate) fn compare_values(a: &JsonValue, b: &JsonValue) -> Ordering {
+    match (a, b) {
+        (JsonValue::Null, JsonValue::Null) => Ordering::Equal,
+        (JsonValue::Boolean(a), JsonValue::Boolean(b)) => a.cmp(b),
+        (JsonValue::Integer(a), JsonValue::Integer(b)) => a.cmp(b),
+        (JsonValue::Integer(a), JsonValue::Float(b)) => compare_integer_float(*a as i64, *b),
+        (JsonValue::Float(a), JsonValue::Integer(b)) => {
+            compare_integer_float(*b as i64, *a).reverse()
+        }
+        (JsonValue::Float(a), JsonValue::Float(b)) => compare_floats(*a, *b),
+        (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            for (a, b) in a.iter().zip(b) {
+                let ordering = compare_values(a, b);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            return a.len().cmp(&b.len());
+        }
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            let mut a_members: Vec<(&String, &JsonValue)> = a.iter().collect();
+            let mut b_members: Vec<(&String, &JsonValue)> = b.iter().collect();
+            a_members.sort_by(|x, y| x.0.cmp(y.0));
+            b_members.sort_by(|x, y| x.0.cmp(y.0));
+
+            for ((a_key, a_value), (b_key, b_value)) in a_members.iter().zip(&b_members) {
+                let ordering = a_key
+                    .cmp(b_key)
+                    .then_with(|| compare_values(a_value, b_value));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            return a_members.len().cmp(&b_members.len());
+        }
+        _ => kind_rank(a).cmp(&kind_rank(b)),
+    }
+}
+
+fn parse_sort_path(path: &str) -> Result<Vec<PathSegment>, PathError> {
+    if path.is_empty() || path.starts_with('/') {
+        match parse_pointer(path) {
+            Some(segments) => {
+                return Ok(segments);
+            }
+            None => {
+                return Err(PathError {
+                    message: "Invalid JSON Pointer".to_string(),
+                    position: 0,
+                });
+            }
+        }
+    }
+
+    return parse_path(path);
+}
+
+impl JsonValue {
+    /// Compares two values under a total order: null < booleans < numbers <
+    /// strings < arrays < objects. Numbers compare numerically whether
+    /// stored as integers or floats, with NaN after every other number and
+    /// equal to itself. Strings compare by code point, arrays element by
+    /// element, and objects by their members in sorted key order, with a
+    /// shorter prefix first.
+    ///
+    /// This is consistent with `==` except that `Integer(1)` and
+    /// `Float(1.0)` compare as equal, and so do two NaNs.
+    pub fn compare(&self, other: &JsonValue) -> Ordering {
+        return compare_values(self, other);
+    }
+
+    /// Sorts an array's elements by `compare`. The sort is stable.
+    pub fn sort_array(&mut self, order: SortOrder) -> Result<(), WrongKindError> {
+        return self.sort_array_by(|a, b| match order {
+            SortOrder::Ascending => a.compare(b),
+            SortOrder::Descending => a.compare(b).reverse(),
+        });
+    }
+
+    /// Sorts an array of objects by the value found at `path` inside each
+    /// element, given either as a JSON Pointer (`/user/age`) or a dotted
+    /// path (`user.age`). Elements where the path doesn't resolve go last
+    /// in either order. The sort is stable.
+    pub fn sort_array_by_key(&mut self, path: &str, order: SortOrder) -> Result<(), SortError> {
+        let segments = parse_sort_path(path)?;
+
+        let array = match self {
+            JsonValue::Array(array) => array,
+            _ => return Err(self.wrong_kind("array").into()),
+        };
+
+        array.sort_by(
+            |a, b| match (resolve(a, &segments), resolve(b, &segments)) {
+                (Some(a), Some(b)) => {
+                    let ordering = a.compare(b);
+                    match order {
+                        SortOrder::Ascending => ordering,
+                        SortOrder::Descending => ordering.reverse(),
+                    }
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        );
+
+        return Ok(());
+    }
+
+    pub fn sort_array_by(
+        &mut self,
+        cmp: impl FnMut(&JsonValue, &JsonValue) -> Ordering,
+    ) -> Result<(), WrongKindError> {
+        match self {
+            JsonValue::Array(array) => {
+                array.sort_by(cmp);
+                return Ok(());
+            }
+            _ => {
+                return Err(self.wrong_kind("array"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::json::JsonValue;
+    use crate::sort::{SortError, SortOrder};
+
+    const TWO_53: isize = 1 << 53;
+
+    #[test]
+    fn integers_and_floats_compare_exactly() {
+        let int = |value: isize| JsonValue::Integer(value);
+        let float = |value: f64| JsonValue::Float(value);
+
+        assert_eq!(int(TWO_53).compare(&float(TWO_53 as f64)), Ordering::Equal);
+        // 2^53 + 1 rounds to 2^53 as a float, but isn't equal to it.
+        assert_eq!(
+            float(TWO_53 as f64).compare(&int(TWO_53 + 1)),
+            Ordering::Less
+        );
+        assert_eq!(int(TWO_53).compare(&int(TWO_53 + 1)), Ordering::Less);
+
+        assert_eq!(int(1).compare(&float(1.5)), Ordering::Less);
+        assert_eq!(int(2).compare(&float(1.5)), Ordering::Greater);
+        assert_eq!(int(-1).compare(&float(-1.5)), Ordering::Greater);
+        assert_eq!(int(-2).compare(&float(-1.5)), Ordering::Less);
+        assert_eq!(int(0).compare(&float(-0.0)), Ordering::Equal);
+        assert_eq!(int(isize::MAX).compare(&float(9.3e18)), Ordering::Less);
+        assert_eq!(int(isize::MIN).compare(&float(-9.3e18)), Ordering::Greater);
+        assert_eq!(
+            int(isize::MIN).compare(&float(isize::MIN as f64)),
+            Ordering::Equal
+        );
+        assert_eq!(int(0).compare(&float(f64::INFINITY)), Ordering::Less);
+        assert_eq!(int(0).compare(&float(f64::NEG_INFINITY)), Ordering::Greater);
+        assert_eq!(int(0).compare(&float(f64::NAN)), Ordering::Less);
+        assert_eq!(float(f64::NAN).compare(&int(0)), Ordering::Greater);
+    }
+
+    #[test]
+    fn mixed_numbers_sort_consistently() {
+        let mut values = Vec::new();
+        for offset in -3..=3 {
+            values.push(JsonValue::Integer(TWO_53 + offset));
+            values.push(JsonValue::Float((TWO_53 + offset) as f64));
+            values.push(JsonValue::Float((TWO_53 + offset) as f64 + 0.5));
+        }
+        values.push(JsonValue::Float(f64::NAN));
+        values.push(JsonValue::Integer(-1));
+
+        // Every triple must be transitive for `sort_by` to be well defined.
+        for a in &values {
+            for b in &values {
+                for c in &values {
+                    if a.compare(b) != Ordering::Greater && b.compare(c) != Ordering::Greater {
+                        assert_ne!(a.compare(c), Ordering::Greater, "{} {} {}", a, b, c);
+                    }
+                }
+            }
+        }
+
+        let mut array = JsonValue::Array(values.iter().rev().cloned().collect());
+        array.sort_array(SortOrder::Ascending).unwrap();
+        let sorted = match &array {
+            JsonValue::Array(sorted) => sorted,
+            _ => unreachable!(),
+        };
+        for pair in sorted.windows(2) {
+            assert_ne!(pair[0].compare(&pair[1]), Ordering::Greater);
+        }
+        assert_eq!(sorted[0], JsonValue::Integer(-1));
+        assert!(matches!(sorted.last(), Some(JsonValue::Float(nan)) if nan.is_nan()));
+    }
+
+    #[test]
+    fn kinds_sort_in_rank_order() {
+        let mut array = crate::json!(["a", 1, null, {}, [], true]);
+        array.sort_array(SortOrder::Ascending).unwrap();
+        assert_eq!(array, crate::json!([null, true, 1, "a", [], {}]));
+
+        array.sort_array(SortOrder::Descending).unwrap();
+        assert_eq!(array, crate::json!([{}, [], "a", 1, true, null]));
+    }
+
+    #[test]
+    fn sort_by_key_puts_missing_last() {
+        let mut array = crate::json!([{"n": 2}, {"x": 0}, {"n": 1.5}, {"n": 1}]);
+        array.sort_array_by_key("/n", SortOrder::Ascending).unwrap();
+        assert_eq!(
+            array,
+            crate::json!([{"n": 1}, {"n": 1.5}, {"n": 2}, {"x": 0}])
+        );
+
+        array.sort_array_by_key("n", SortOrder::Descending).unwrap();
+        assert_eq!(
+            array,
+            crate::json!([{"n": 2}, {"n": 1.5}, {"n": 1}, {"x": 0}])
+        );
+    }
+
+    fn records() -> JsonValue {
+        return crate::json!([
+            {"name": "carol", "age": 41},
+            {"name": "Bob", "age": 29.5},
+            {"name": "alice"},
+            {"name": "dave", "age": 29},
+            {"age": 35}
+        ]);
+    }
+
+    fn names(array: &JsonValue) -> Vec<&JsonValue> {
+        return array
+            .elements()
+            .map(|record| record.pointer("/name").unwrap_or(&JsonValue::Null))
+            .collect();
+    }
+
+    #[test]
+    fn sorts_records_by_age_and_name() {
+        let mut array = records();
+        array
+            .sort_array_by_key("age", SortOrder::Ascending)
+            .unwrap();
+        assert_eq!(
+            names(&array),
+            [
+                &crate::json!("dave"),
+                &crate::json!("Bob"),
+                &JsonValue::Null,
+                &crate::json!("carol"),
+                &crate::json!("alice")
+            ]
+        );
+
+        // Strings compare by code point, so capitals come first.
+        array
+            .sort_array_by_key("/name", SortOrder::Descending)
+            .unwrap();
+        assert_eq!(
+            names(&array),
+            [
+                &crate::json!("dave"),
+                &crate::json!("carol"),
+                &crate::json!("alice"),
+                &crate::json!("Bob"),
+                &JsonValue::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_by_key_keeps_equal_keys_in_order() {
+        let mut array = crate::json!([{"k": 1, "i": 0}, {"i": 1}, {"k": 1.0, "i": 2}, {"i": 3}, {"k": 0, "i": 4}]);
+        array.sort_array_by_key("k", SortOrder::Descending).unwrap();
+
+        let order: Vec<&JsonValue> = array
+            .elements()
+            .map(|record| record.pointer("/i").unwrap())
+            .collect();
+        assert_eq!(
+            order,
+            [
+                &crate::json!(0),
+                &crate::json!(2),
+                &crate::json!(4),
+                &crate::json!(1),
+                &crate::json!(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_by_takes_a_custom_comparator() {
+        let mut array = crate::json!(["ccc", "a", "bb", "dd"]);
+        array
+            .sort_array_by(|a, b| a.len().cmp(&b.len()).reverse())
+            .unwrap();
+        assert_eq!(array, crate::json!(["ccc", "bb", "dd", "a"]));
+    }
+
+    #[test]
+    fn sorting_non_arrays_and_bad_paths_are_errors() {
+        let mut object = crate::json!({"a": 1});
+
+        assert!(matches!(
+            object.sort_array_by_key("a", SortOrder::Ascending),
+            Err(SortError::WrongKind(_))
+        ));
+        assert!(object.sort_array(SortOrder::Ascending).is_err());
+        assert!(object.sort_array_by(|a, b| a.compare(b)).is_err());
+        assert!(matches!(
+            records().sort_array_by_key("a..b", SortOrder::Ascending),
+            Err(SortError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            records().sort_array_by_key("/a~2", SortOrder::Ascending),
+            Err(SortError::InvalidPath(_))
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+use std::{collections::HashMap, mem};
+
+use crate::json::{Json, JsonValue};
+
+/// Counts and sizes gathered from one pass over a document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonStats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub integers: usize,
+    pub floats: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// Bytes of UTF-8 in string values and object keys.
+    pub string_bytes: usize,
+    /// The number of containers on the deepest path, so a scalar root has
+    /// depth 0 and `{"a": []}` has depth 2.
+    pub max_depth: usize,
+    pub longest_array: usize,
+    /// A rough estimate of the heap memory owned by the tree, from the
+    /// capacities of its vectors, maps and strings. Allocator overhead and
+    /// hash table control bytes beyond one per slot aren't counted.
+    pub estimated_heap_bytes: usize,
+}
+
+impl JsonStats {
+    pub fn total_nodes(&self) -> usize {
+        return self.objects
+            + self.arrays
+            + self.strings
+            + self.integers
+            + self.floats
+            + self.booleans
+            + self.nulls;
+    }
+}
+
+struct Collector<'a> {
+    stats: JsonStats,
+    stack: Vec<(&'a JsonValue, usize)>,
+}
+
+impl<'a> Collector<'a> {
+    fn object(&mut self, object: &'a HashMap<String, JsonValue>, depth: usize) {
+        let slot = mem::size_of::<String>() + mem::size_of::<JsonValue>() + 1;
+
+        self.stats.objects += 1;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        self.stats.estimated_heap_bytes += object.capacity() * slot;
+
+        for (key, member) in object {
+            self.stats.string_bytes += key.len();
+            self.stats.estimated_heap_bytes += key.capacity();
+            self.stack.push((member, depth));
+        }
+    }
+
+    fn array(&mut self, array: &'a Vec<JsonValue>, depth: usize) {
+        self.stats.arrays += 1;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        self.stats.longest_array = self.stats.longest_array.max(array.len());
+        self.stats.estimated_heap_bytes += array.capacity() * mem::size_of::<JsonValue>();
+
+        for element in array {
+            self.stack.push((element, depth));
+        }
+    }
+
+    // Walks with an explicit stack so deeply nested input can't overflow
+    // the call stack. `depth` on the stack is the parent's depth.
+    fn run(mut self) -> JsonStats {
+        while let Some((value, depth)) = self.stack.pop() {
+            match value {
+                JsonValue::Null => self.stats.nulls += 1,
+                JsonValue::Boolean(_) => self.stats.booleans += 1,
+                JsonValue::Integer(_) => self.stats.integers += 1,
+                JsonValue::Float(_) => self.stats.floats += 1,
+                JsonValue::String(string) => {
+                    self.stats.strings += 1;
+                    self.stats.string_bytes += string.len();
+                    self.stats.estimated_heap_bytes += string.capacity();
+                }
+                JsonValue::Object(object) => self.object(object, depth + 1),
+                JsonValue::Array(array) => self.array(array, depth + 1),
+            }
+        }
+
+        return self.stats;
+    }
+}
+
+// Counts each object key under the values on `stack`, walking with an
+// explicit stack like `Collector`.
+fn count_keys<'a>(mut stack: Vec<&'a JsonValue>, counts: &mut HashMap<&'a str, usize>) {
+    while let Some(value) = stack.pop() {
+        match value {
+            JsonValue::Object(object) => {
+                for (key, member) in object {
+                    *counts.entry(key.as_str()).or_default() += 1;
+                    stack.push(member);
+                }
+            }
+            JsonValue::Array(array) => stack.extend(array),
+            _ => {}
+        }
+    }
+}
+
+impl JsonValue {
+    /// How many objects in the tree have each key.
+    pub fn key_counts(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+        count_keys(vec![self], &mut counts);
+
+        return counts;
+    }
+
+    pub fn stats(&self) -> JsonStats {
+        let collector = Collector {
+            stats: JsonStats::default(),
+            stack: vec![(self, 0)],
+        };
+
+        return collector.run();
+    }
+}
+
+impl Json {
+    /// How many objects in the tree have each key.
+    pub fn key_counts(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+
+        match self {
+            Json::Object(object) => {
+                for (key, member) in object {
+                    *counts.entry(key.as_str()).or_default() += 1;
+                    count_keys(vec![member], &mut counts);
+                }
+            }
+            Json::Array(array) => count_keys(array.iter().collect(), &mut counts),
+        }
+
+        return counts;
+    }
+
+    pub fn stats(&self) -> JsonStats {
+        let mut collector = Collector {
+            stats: JsonStats::default(),
+            stack: Vec::new(),
+        };
+
+        match self {
+            Json::Object(object) => collector.object(object, 1),
+            Json::Array(array) => collector.array(array, 1),
+        }
+
+        return collector.run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{parse_from_str, JsonValue};
+    use crate::stats::JsonStats;
+
+    const FIXTURE: &str = r#"{"id": 7, "name": "ada", "ratio": 0.5, "tags": ["x", "yz", null],
+        "nested": {"ok": true, "list": [[], [1, 2, 3, 4], {"deep": [false]}]}}"#;
+
+    #[test]
+    fn counts_a_small_fixture_exactly() {
+        let json = parse_from_str(FIXTURE).unwrap();
+        let stats = json.stats();
+
+        assert_eq!(
+            JsonStats {
+                estimated_heap_bytes: 0,
+                ..stats
+            },
+            JsonStats {
+                objects: 3,
+                arrays: 5,
+                strings: 3,
+                integers: 5,
+                floats: 1,
+                booleans: 2,
+                nulls: 1,
+                // "ada", "x", "yz" and the keys id, name, ratio, tags,
+                // nested, ok, list and deep.
+                string_bytes: 6 + 31,
+                max_depth: 5,
+                longest_array: 4,
+                estimated_heap_bytes: 0,
+            }
+        );
+        assert_eq!(stats.total_nodes(), 20);
+        assert_eq!(JsonValue::from(json).stats(), stats);
+    }
+
+    #[test]
+    fn depth_counts_containers_on_the_deepest_path() {
+        assert_eq!(json!(1).stats().max_depth, 0);
+        assert_eq!(json!([]).stats().max_depth, 1);
+        assert_eq!(json!({"a": []}).stats().max_depth, 2);
+        assert_eq!(json!([1, [2, [3]], []]).stats().max_depth, 3);
+        assert_eq!(json!("s").stats().total_nodes(), 1);
+    }
+
+    #[test]
+    fn heap_estimate_grows_with_the_document() {
+        let small = json!({"a": "b"}).stats().estimated_heap_bytes;
+        let large = json!({"a": "b".repeat(1000), "c": vec![JsonValue::Null; 100]})
+            .stats()
+            .estimated_heap_bytes;
+
+        assert!(small > 0);
+        assert!(large >= small + 1000 + 100 * std::mem::size_of::<JsonValue>());
+        assert_eq!(json!(null).stats().estimated_heap_bytes, 0);
+    }
+}
@@ -0,0 +1,1299 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::json::JsonValue;
+use crate::parser::MAX_DEPTH;
+use crate::path::{escape_pointer_token, PathSegment};
+use crate::serialize::format_float;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlConvertError {
+    /// JSON Pointer to the value that couldn't be converted, or to the table
+    /// or key being defined when a document failed to parse.
+    pub pointer: String,
+    /// Set for errors in a TOML document.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for TomlConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(
+                f,
+                "[TOML error at line {} ('{}')]: {}",
+                line, self.pointer, self.message
+            ),
+            None => write!(f, "[TOML error at '{}']: {}", self.pointer, self.message),
+        }
+    }
+}
+
+impl std::error::Error for TomlConvertError {}
+
+fn unrepresentable(pointer: &str, message: &str) -> TomlConvertError {
+    TomlConvertError {
+        pointer: pointer.to_string(),
+        line: None,
+        message: message.to_string(),
+    }
+}
+
+fn is_bare_key(key: &str) -> bool {
+    return !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_key(out: &mut String, key: &str) {
+    if is_bare_key(key) {
+        out.push_str(key);
+    } else {
+        write_string(out, key);
+    }
+}
+
+// Arrays of objects become `[[name]]` sections; anything else is written
+// inline.
+fn is_table_array(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Array(array) => {
+            return !array.is_empty()
+                && array
+                    .iter()
+                    .all(|element| matches!(element, JsonValue::Object(_)));
+        }
+        _ => {
+            return false;
+        }
+    }
+}
+
+fn is_section(value: &JsonValue) -> bool {
+    return matches!(value, JsonValue::Object(_)) || is_table_array(value);
+}
+
+fn sorted_keys(object: &HashMap<String, JsonValue>) -> Vec<&String> {
+    let mut keys: Vec<&String> = object.keys().collect();
+    keys.sort();
+
+    return keys;
+}
+
+fn write_inline(
+    out: &mut String,
+    value: &JsonValue,
+    pointer: &mut String,
+) -> Result<(), TomlConvertError> {
+    match value {
+        JsonValue::Null => {
+            return Err(unrepresentable(pointer, "TOML has no null"));
+        }
+        JsonValue::Boolean(value) => out.push_str(&value.to_string()),
+        JsonValue::Integer(value) => out.push_str(&value.to_string()),
+        JsonValue::Float(value) if value.is_nan() => out.push_str("nan"),
+        JsonValue::Float(value) if value.is_infinite() => {
+            out.push_str(if *value > 0.0 { "inf" } else { "-inf" })
+        }
+        JsonValue::Float(value) => out.push_str(&format_float(*value)),
+        JsonValue::String(value) => write_string(out, value),
+        JsonValue::Array(array) => {
+            out.push('[');
+            for (index, element) in array.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+
+                let len = pointer.len();
+                pointer.push_str(&format!("/{}", index));
+                write_inline(out, element, pointer)?;
+                pointer.truncate(len);
+            }
+            out.push(']');
+        }
+        JsonValue::Object(object) => {
+            if object.is_empty() {
+                out.push_str("{}");
+                return Ok(());
+            }
+
+            out.push_str("{ ");
+            for (index, key) in sorted_keys(object).into_iter().enumerate() {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+                write_key(out, key);
+                out.push_str(" = ");
+
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer_token(key));
+                write_inline(out, &object[key], pointer)?;
+                pointer.truncate(len);
+            }
+            out.push_str(" }");
+        }
+    }
+
+    return Ok(());
+}
+
+fn write_header(out: &mut String, header: &[String], array: bool) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str(if array { "[[" } else { "[" });
+    out.push_str(&header.join("."));
+    out.push_str(if array { "]]\n" } else { "]\n" });
+}
+
+// Writes a table's own key/value pairs, then its sub-tables and arrays of
+// tables as sections. A sub-table with nothing but sections of its own gets
+// no header, since its children's headers define it implicitly.
+fn write_table(
+    out: &mut String,
+    object: &HashMap<String, JsonValue>,
+    pointer: &mut String,
+    header: &mut Vec<String>,
+) -> Result<(), TomlConvertError> {
+    let keys = sorted_keys(object);
+
+    for key in &keys {
+        let value = &object[*key];
+        if is_section(value) {
+            continue;
+        }
+
+        let len = pointer.len();
+        pointer.push('/');
+        pointer.push_str(&escape_pointer_token(key));
+
+        write_key(out, key);
+        out.push_str(" = ");
+        write_inline(out, value, pointer)?;
+        out.push('\n');
+
+        pointer.truncate(len);
+    }
+
+    for key in &keys {
+        let len = pointer.len();
+        pointer.push('/');
+        pointer.push_str(&escape_pointer_token(key));
+
+        let mut encoded = String::new();
+        write_key(&mut encoded, key);
+        header.push(encoded);
+
+        match &object[*key] {
+            JsonValue::Object(child) => {
+                if child.is_empty() || child.values().any(|value| !is_section(value)) {
+                    write_header(out, header, false);
+                }
+                write_table(out, child, pointer, header)?;
+            }
+            JsonValue::Array(array) if is_table_array(&object[*key]) => {
+                for (index, element) in array.iter().enumerate() {
+                    let element_len = pointer.len();
+                    pointer.push_str(&format!("/{}", index));
+
+                    write_header(out, header, true);
+                    if let JsonValue::Object(element) = element {
+                        write_table(out, element, pointer, header)?;
+                    }
+
+                    pointer.truncate(element_len);
+                }
+            }
+            _ => {}
+        }
+
+        header.pop();
+        pointer.truncate(len);
+    }
+
+    return Ok(());
+}
+
+/// Writes an object as a TOML document. Nested objects become `[table]`
+/// sections and arrays made up only of objects become `[[array]]` tables;
+/// all other arrays, including mixed ones, are written inline with any
+/// objects in them as inline tables. Keys are sorted. Fails, naming the
+/// value's pointer, for a root that isn't an object and for nulls, which
+/// TOML can't represent.
+pub fn to_toml_string(value: &JsonValue) -> Result<String, TomlConvertError> {
+    let object = match value {
+        JsonValue::Object(object) => object,
+        _ => {
+            return Err(unrepresentable(
+                "",
+                "The root of a TOML document must be a table",
+            ));
+        }
+    };
+
+    let mut output = String::new();
+    write_table(&mut output, object, &mut String::new(), &mut Vec::new())?;
+
+    return Ok(output);
+}
+
+fn segments_pointer(segments: &[PathSegment]) -> String {
+    let mut pointer = String::new();
+
+    for segment in segments {
+        pointer.push('/');
+        match segment {
+            PathSegment::Key(key) => pointer.push_str(&escape_pointer_token(key)),
+            PathSegment::Index(index) => pointer.push_str(&index.to_string()),
+        }
+    }
+
+    return pointer;
+}
+
+// Follows a path that `open_table` or `insert` has already checked leads
+// through tables.
+fn table_mut<'a>(
+    root: &'a mut HashMap<String, JsonValue>,
+    path: &[PathSegment],
+) -> &'a mut HashMap<String, JsonValue> {
+    let mut table = root;
+
+    for (index, segment) in path.iter().enumerate() {
+        let next = match segment {
+            PathSegment::Key(key) => table.get_mut(key),
+            PathSegment::Index(_) => continue,
+        };
+
+        table = match (next, path.get(index + 1)) {
+            (Some(JsonValue::Array(array)), Some(PathSegment::Index(i))) => match &mut array[*i] {
+                JsonValue::Object(object) => object,
+                _ => unreachable!("array of tables holds a non-table"),
+            },
+            (Some(JsonValue::Object(object)), _) => object,
+            _ => unreachable!("path leads through a non-table"),
+        };
+    }
+
+    return table;
+}
+
+fn digits_with_underscores(text: &str, is_digit: fn(char) -> bool) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut digits = String::new();
+
+    for (index, &c) in chars.iter().enumerate() {
+        if c == '_' {
+            // Underscores must sit between two digits.
+            let before = index > 0 && is_digit(chars[index - 1]);
+            let after = chars.get(index + 1).copied().is_some_and(is_digit);
+            if !before || !after {
+                return None;
+            }
+            continue;
+        }
+        if !is_digit(c) {
+            return None;
+        }
+        digits.push(c);
+    }
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    return Some(digits);
+}
+
+fn parse_integer(token: &str) -> Option<Result<i64, ()>> {
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(rest) = token.strip_prefix(prefix) {
+            let digits = digits_with_underscores(
+                rest,
+                match radix {
+                    16 => |c: char| c.is_ascii_hexdigit(),
+                    8 => |c: char| ('0'..='7').contains(&c),
+                    _ => |c: char| c == '0' || c == '1',
+                },
+            )?;
+            return Some(i64::from_str_radix(&digits, radix).map_err(|_| ()));
+        }
+    }
+
+    let (sign, unsigned) = match token.strip_prefix(['+', '-']) {
+        Some(unsigned) => (&token[..1], unsigned),
+        None => ("", token),
+    };
+    let digits = digits_with_underscores(unsigned, |c| c.is_ascii_digit())?;
+    if digits.len() > 1 && digits.starts_with('0') {
+        return None;
+    }
+
+    return Some(format!("{}{}", sign, digits).parse::<i64>().map_err(|_| ()));
+}
+
+fn parse_float(token: &str) -> Option<f64> {
+    let unsigned = token.strip_prefix(['+', '-']).unwrap_or(token);
+    let negative = token.starts_with('-');
+
+    match unsigned {
+        "inf" => {
+            return Some(if negative {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            });
+        }
+        "nan" => {
+            return Some(f64::NAN);
+        }
+        _ => {}
+    }
+
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (&unsigned[..index], Some(&unsigned[index + 1..])),
+        None => (unsigned, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+    if fraction.is_none() && exponent.is_none() {
+        return None;
+    }
+
+    let is_digit = |c: char| c.is_ascii_digit();
+    let whole = digits_with_underscores(whole, is_digit)?;
+    if whole.len() > 1 && whole.starts_with('0') {
+        return None;
+    }
+
+    let mut number = whole;
+    if let Some(fraction) = fraction {
+        number.push('.');
+        number.push_str(&digits_with_underscores(fraction, is_digit)?);
+    }
+    if let Some(exponent) = exponent {
+        let (sign, digits) = match exponent.strip_prefix(['+', '-']) {
+            Some(digits) => (&exponent[..1], digits),
+            None => ("", exponent),
+        };
+        number.push('e');
+        number.push_str(sign);
+        number.push_str(&digits_with_underscores(digits, is_digit)?);
+    }
+
+    let value = number.parse::<f64>().ok()?;
+    return Some(if negative { -value } else { value });
+}
+
+fn is_digits(text: &str, count: usize) -> bool {
+    return text.len() == count && text.chars().all(|c| c.is_ascii_digit());
+}
+
+fn in_range(text: &str, max: u32) -> bool {
+    return text.parse::<u32>().is_ok_and(|value| value <= max);
+}
+
+fn is_date(text: &str) -> bool {
+    let parts: Vec<&str> = text.split('-').collect();
+
+    return parts.len() == 3
+        && is_digits(parts[0], 4)
+        && is_digits(parts[1], 2)
+        && is_digits(parts[2], 2)
+        && in_range(parts[1], 12)
+        && parts[1] != "00"
+        && in_range(parts[2], 31)
+        && parts[2] != "00";
+}
+
+fn is_time(text: &str) -> bool {
+    let (time, fraction) = match text.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (text, None),
+    };
+    let parts: Vec<&str> = time.split(':').collect();
+
+    return parts.len() == 3
+        && parts.iter().all(|part| is_digits(part, 2))
+        && in_range(parts[0], 23)
+        && in_range(parts[1], 59)
+        && in_range(parts[2], 60)
+        && fraction.is_none_or(|fraction| {
+            !fraction.is_empty() && fraction.chars().all(|c| c.is_ascii_digit())
+        });
+}
+
+fn is_offset(text: &str) -> bool {
+    if text == "Z" {
+        return true;
+    }
+
+    return match text
+        .strip_prefix(['+', '-'])
+        .and_then(|rest| rest.split_once(':'))
+    {
+        Some((hours, minutes)) => {
+            is_digits(hours, 2)
+                && is_digits(minutes, 2)
+                && in_range(hours, 23)
+                && in_range(minutes, 59)
+        }
+        None => false,
+    };
+}
+
+// Normalizes a TOML date, time or datetime to its RFC 3339 spelling: an
+// uppercase `T` between date and time and an uppercase `Z`. Local dates and
+// times stay in RFC 3339's `full-date` and `partial-time` forms.
+fn parse_datetime(token: &str) -> Option<String> {
+    let token = token.replacen(['t', ' '], "T", 1).replace('z', "Z");
+
+    if is_date(&token) || is_time(&token) {
+        return Some(token);
+    }
+
+    let (date, rest) = token.split_once('T')?;
+    if !is_date(date) {
+        return None;
+    }
+
+    let offset_start = rest.find(['Z', '+', '-']).unwrap_or(rest.len());
+    let (time, offset) = rest.split_at(offset_start);
+    if !is_time(time) || !(offset.is_empty() || is_offset(offset)) {
+        return None;
+    }
+
+    return Some(token);
+}
+
+struct TomlParser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    depth: usize,
+    root: HashMap<String, JsonValue>,
+    current: Vec<PathSegment>,
+    // Pointers of tables defined by a `[header]`, tables created by dotted
+    // keys, arrays created by `[[headers]]`, and inline tables and arrays,
+    // which can't be extended.
+    defined: HashSet<String>,
+    dotted: HashSet<String>,
+    table_arrays: HashSet<String>,
+    frozen: HashSet<String>,
+    // Errors name `prefix` (the pointer of an enclosing inline table) joined
+    // with `context` (the table or key being defined).
+    prefix: String,
+    context: String,
+}
+
+impl TomlParser {
+    fn new(source: &str) -> TomlParser {
+        return TomlParser {
+            chars: source.chars().collect(),
+            pos: 0,
+            line: 1,
+            depth: 0,
+            root: HashMap::new(),
+            current: Vec::new(),
+            defined: HashSet::new(),
+            dotted: HashSet::new(),
+            table_arrays: HashSet::new(),
+            frozen: HashSet::new(),
+            prefix: String::new(),
+            context: String::new(),
+        };
+    }
+
+    fn error_at(&self, pointer: &str, message: &str) -> TomlConvertError {
+        return TomlConvertError {
+            pointer: format!("{}{}", self.prefix, pointer),
+            line: Some(self.line),
+            message: message.to_string(),
+        };
+    }
+
+    fn error(&self, message: &str) -> TomlConvertError {
+        return self.error_at(&self.context, message);
+    }
+
+    fn key_pointer(&self, keys: &[String]) -> String {
+        let mut pointer = segments_pointer(&self.current);
+        for key in keys {
+            pointer.push('/');
+            pointer.push_str(&escape_pointer_token(key));
+        }
+
+        return pointer;
+    }
+
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.pos).copied();
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        return self.chars.get(self.pos + offset).copied();
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+
+        return Some(c);
+    }
+
+    fn at_newline(&self) -> bool {
+        return self.peek() == Some('\n')
+            || (self.peek() == Some('\r') && self.peek_at(1) == Some('\n'));
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), TomlConvertError> {
+        if self.peek() != Some('#') {
+            return Ok(());
+        }
+
+        while let Some(c) = self.peek() {
+            if self.at_newline() {
+                break;
+            }
+            if (c as u32) < 0x20 && c != '\t' || c == '\u{7f}' {
+                return Err(self.error("Control character in comment"));
+            }
+            self.pos += 1;
+        }
+
+        return Ok(());
+    }
+
+    // Skips whitespace, comments and newlines, as allowed between lines and
+    // between array elements.
+    fn skip_blank(&mut self) -> Result<(), TomlConvertError> {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment()?;
+
+            if self.peek() == Some('\r') && self.peek_at(1) == Some('\n') {
+                self.pos += 1;
+            }
+            if self.peek() != Some('\n') {
+                return Ok(());
+            }
+            self.advance();
+        }
+    }
+
+    fn expect_line_end(&mut self) -> Result<(), TomlConvertError> {
+        self.skip_whitespace();
+        self.skip_comment()?;
+
+        if self.peek().is_none() || self.at_newline() {
+            return Ok(());
+        }
+
+        return Err(self.error("Expected a newline"));
+    }
+
+    fn parse_document(mut self) -> Result<JsonValue, TomlConvertError> {
+        loop {
+            self.skip_blank()?;
+
+            match self.peek() {
+                None => {
+                    return Ok(JsonValue::Object(self.root));
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    let array = self.peek() == Some('[');
+                    if array {
+                        self.pos += 1;
+                    }
+
+                    self.skip_whitespace();
+                    let keys = self.parse_key()?;
+                    self.skip_whitespace();
+
+                    let close = if array { "]]" } else { "]" };
+                    for c in close.chars() {
+                        if self.advance() != Some(c) {
+                            return Err(self.error(&format!("Expected '{}'", close)));
+                        }
+                    }
+
+                    self.open_table(&keys, array)?;
+                    self.context = segments_pointer(&self.current);
+                }
+                Some(_) => {
+                    let keys = self.parse_key()?;
+                    self.skip_whitespace();
+                    if self.advance() != Some('=') {
+                        return Err(self.error("Expected '=' after key"));
+                    }
+                    self.skip_whitespace();
+
+                    self.context = self.key_pointer(&keys);
+                    let value = self.parse_value()?;
+                    let path = self.current.clone();
+                    self.insert(&path, &keys, value)?;
+                }
+            }
+
+            self.expect_line_end()?;
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<Vec<String>, TomlConvertError> {
+        let mut keys = Vec::new();
+
+        loop {
+            let key = match self.peek() {
+                Some('"') => self.parse_basic_string()?,
+                Some('\'') => self.parse_literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        self.pos += 1;
+                    }
+                    if self.pos == start {
+                        return Err(self.error("Expected a key"));
+                    }
+
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            keys.push(key);
+
+            self.skip_whitespace();
+            if self.peek() != Some('.') {
+                return Ok(keys);
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    fn open_table(&mut self, keys: &[String], array: bool) -> Result<(), TomlConvertError> {
+        let mut path = Vec::new();
+
+        for (index, key) in keys.iter().enumerate() {
+            let last = index == keys.len() - 1;
+            path.push(PathSegment::Key(key.clone()));
+            let pointer = segments_pointer(&path);
+
+            let table = table_mut(&mut self.root, &path[..path.len() - 1]);
+            match table.get_mut(key) {
+                None => {
+                    if last && array {
+                        table.insert(
+                            key.clone(),
+                            JsonValue::Array(vec![JsonValue::Object(HashMap::new())]),
+                        );
+                        self.table_arrays.insert(pointer);
+                        path.push(PathSegment::Index(0));
+                    } else {
+                        table.insert(key.clone(), JsonValue::Object(HashMap::new()));
+                        if last {
+                            self.defined.insert(pointer);
+                        }
+                    }
+                }
+                Some(JsonValue::Object(_)) => {
+                    if self.frozen.contains(&pointer) {
+                        return Err(self.error_at(&pointer, "Inline tables can't be extended"));
+                    }
+                    if last && array {
+                        return Err(self.error_at(&pointer, "Key is already a table"));
+                    }
+                    if last && !self.defined.insert(pointer.clone()) {
+                        return Err(self.error_at(&pointer, "Table is defined twice"));
+                    }
+                    if last && self.dotted.contains(&pointer) {
+                        return Err(
+                            self.error_at(&pointer, "Table was already defined by dotted keys")
+                        );
+                    }
+                }
+                Some(JsonValue::Array(elements)) if self.table_arrays.contains(&pointer) => {
+                    if last && !array {
+                        return Err(self.error_at(&pointer, "Key is already an array of tables"));
+                    }
+                    if last {
+                        elements.push(JsonValue::Object(HashMap::new()));
+                    }
+                    path.push(PathSegment::Index(elements.len() - 1));
+                }
+                Some(_) => {
+                    return Err(self.error_at(&pointer, "Key is already defined as a value"));
+                }
+            }
+        }
+
+        self.current = path;
+        return Ok(());
+    }
+
+    // Inserts `value` under a dotted key relative to the table at `path`,
+    // creating the intermediate tables.
+    fn insert(
+        &mut self,
+        path: &[PathSegment],
+        keys: &[String],
+        value: JsonValue,
+    ) -> Result<(), TomlConvertError> {
+        let mut path = path.to_vec();
+
+        for key in &keys[..keys.len() - 1] {
+            path.push(PathSegment::Key(key.clone()));
+            let pointer = segments_pointer(&path);
+
+            let table = table_mut(&mut self.root, &path[..path.len() - 1]);
+            match table.get(key) {
+                None => {
+                    table.insert(key.clone(), JsonValue::Object(HashMap::new()));
+                    self.dotted.insert(pointer);
+                }
+                Some(JsonValue::Object(_)) if self.dotted.contains(&pointer) => {}
+                Some(_) => {
+                    return Err(self.error_at(&pointer, "Key is already defined"));
+                }
+            }
+        }
+
+        let key = &keys[keys.len() - 1];
+        path.push(PathSegment::Key(key.clone()));
+        let pointer = segments_pointer(&path);
+
+        let table = table_mut(&mut self.root, &path[..path.len() - 1]);
+        if table.contains_key(key) {
+            return Err(self.error_at(&pointer, "Duplicate key"));
+        }
+        if matches!(value, JsonValue::Object(_) | JsonValue::Array(_)) {
+            self.frozen.insert(pointer);
+        }
+        table.insert(key.clone(), value);
+
+        return Ok(());
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, TomlConvertError> {
+        match self.peek() {
+            Some('"') => {
+                return Ok(JsonValue::String(self.parse_basic_string()?));
+            }
+            Some('\'') => {
+                return Ok(JsonValue::String(self.parse_literal_string()?));
+            }
+            Some('[' | '{') => {
+                self.depth += 1;
+                if self.depth > MAX_DEPTH {
+                    return Err(self.error(&format!("Nested more than {} levels deep", MAX_DEPTH)));
+                }
+
+                let value = match self.peek() {
+                    Some('[') => self.parse_array(),
+                    _ => self.parse_inline_table(),
+                };
+                self.depth -= 1;
+                return value;
+            }
+            Some(_) => {
+                return self.parse_scalar();
+            }
+            None => {
+                return Err(self.error("Expected a value"));
+            }
+        }
+    }
+
+    fn parse_scalar(&mut self) -> Result<JsonValue, TomlConvertError> {
+        let start = self.pos;
+        let is_end = |c: char| c.is_whitespace() || matches!(c, ',' | ']' | '}' | '#');
+
+        while self.peek().is_some_and(|c| !is_end(c)) {
+            self.pos += 1;
+        }
+
+        // A datetime may separate its date and time with a space.
+        let mut token: String = self.chars[start..self.pos].iter().collect();
+        if is_date(&token)
+            && self.peek() == Some(' ')
+            && self.peek_at(1).is_some_and(|c| c.is_ascii_digit())
+            && self.peek_at(2).is_some_and(|c| c.is_ascii_digit())
+            && self.peek_at(3) == Some(':')
+        {
+            self.pos += 1;
+            while self.peek().is_some_and(|c| !is_end(c)) {
+                self.pos += 1;
+            }
+            token = self.chars[start..self.pos].iter().collect();
+        }
+
+        match token.as_str() {
+            "true" => {
+                return Ok(JsonValue::Boolean(true));
+            }
+            "false" => {
+                return Ok(JsonValue::Boolean(false));
+            }
+            _ => {}
+        }
+
+        if let Some(integer) = parse_integer(&token) {
+            return match integer.ok().and_then(|value| isize::try_from(value).ok()) {
+                Some(value) => Ok(JsonValue::Integer(value)),
+                None => Err(self.error("Integer out of range")),
+            };
+        }
+        if let Some(float) = parse_float(&token) {
+            return Ok(JsonValue::Float(float));
+        }
+        if let Some(datetime) = parse_datetime(&token) {
+            return Ok(JsonValue::String(datetime));
+        }
+
+        return Err(self.error(&format!("Invalid value '{}'", token)));
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, TomlConvertError> {
+        self.pos += 1;
+        let mut array = Vec::new();
+
+        loop {
+            self.skip_blank()?;
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(JsonValue::Array(array));
+            }
+
+            array.push(self.parse_value()?);
+            self.skip_blank()?;
+
+            match self.advance() {
+                Some(',') => {}
+                Some(']') => {
+                    return Ok(JsonValue::Array(array));
+                }
+                _ => {
+                    return Err(self.error("Expected ',' or ']' in array"));
+                }
+            }
+        }
+    }
+
+    // Inline tables are parsed into a detached parser state so the dotted
+    // key rules apply to them on their own.
+    fn parse_inline_table(&mut self) -> Result<JsonValue, TomlConvertError> {
+        self.pos += 1;
+
+        let mut inner = TomlParser::new("");
+        std::mem::swap(&mut inner.chars, &mut self.chars);
+        inner.pos = self.pos;
+        inner.line = self.line;
+        inner.depth = self.depth;
+        inner.prefix = format!("{}{}", self.prefix, self.context);
+
+        let result = inner.parse_inline_members();
+
+        std::mem::swap(&mut inner.chars, &mut self.chars);
+        self.pos = inner.pos;
+        self.line = inner.line;
+        result?;
+
+        return Ok(JsonValue::Object(inner.root));
+    }
+
+    fn parse_inline_members(&mut self) -> Result<(), TomlConvertError> {
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let keys = self.parse_key()?;
+            self.skip_whitespace();
+            if self.advance() != Some('=') {
+                return Err(self.error("Expected '=' after key"));
+            }
+            self.skip_whitespace();
+
+            self.context = self.key_pointer(&keys);
+            let value = self.parse_value()?;
+            self.insert(&[], &keys, value)?;
+
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => {}
+                Some('}') => {
+                    return Ok(());
+                }
+                _ => {
+                    return Err(self.error("Expected ',' or '}' in inline table"));
+                }
+            }
+        }
+    }
+
+    fn parse_basic_string(&mut self) -> Result<String, TomlConvertError> {
+        let multiline = self.peek_at(1) == Some('"') && self.peek_at(2) == Some('"');
+        self.pos += if multiline { 3 } else { 1 };
+
+        if multiline {
+            self.skip_first_newline();
+        }
+
+        let mut string = String::new();
+        loop {
+            let c = match self.advance() {
+                Some(c) => c,
+                None => return Err(self.error("Unterminated string")),
+            };
+
+            match c {
+                '"' if !multiline => {
+                    return Ok(string);
+                }
+                '"' => {
+                    if self.close_multiline('"', &mut string) {
+                        return Ok(string);
+                    }
+                }
+                '\\' => {
+                    if multiline && self.skip_line_ending_backslash() {
+                        continue;
+                    }
+                    string.push(self.parse_escape()?);
+                }
+                '\n' if multiline => string.push('\n'),
+                '\r' if multiline && self.peek() == Some('\n') => {}
+                c if (c as u32) < 0x20 && c != '\t' || c == '\u{7f}' => {
+                    return Err(self.error("Control character in string"));
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn parse_literal_string(&mut self) -> Result<String, TomlConvertError> {
+        let multiline = self.peek_at(1) == Some('\'') && self.peek_at(2) == Some('\'');
+        self.pos += if multiline { 3 } else { 1 };
+
+        if multiline {
+            self.skip_first_newline();
+        }
+
+        let mut string = String::new();
+        loop {
+            let c = match self.advance() {
+                Some(c) => c,
+                None => return Err(self.error("Unterminated string")),
+            };
+
+            match c {
+                '\'' if !multiline => {
+                    return Ok(string);
+                }
+                '\'' => {
+                    if self.close_multiline('\'', &mut string) {
+                        return Ok(string);
+                    }
+                }
+                '\n' if multiline => string.push('\n'),
+                '\r' if multiline && self.peek() == Some('\n') => {}
+                c if (c as u32) < 0x20 && c != '\t' || c == '\u{7f}' => {
+                    return Err(self.error("Control character in string"));
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn skip_first_newline(&mut self) {
+        if self.peek() == Some('\r') && self.peek_at(1) == Some('\n') {
+            self.pos += 1;
+        }
+        if self.peek() == Some('\n') {
+            self.advance();
+        }
+    }
+
+    // Called after one quote inside a multi-line string. Three or more in a
+    // row close it, and up to two quotes before the closing three belong to
+    // the content.
+    fn close_multiline(&mut self, quote: char, string: &mut String) -> bool {
+        let mut count = 1;
+        while count < 5 && self.peek() == Some(quote) {
+            self.pos += 1;
+            count += 1;
+        }
+
+        if count < 3 {
+            for _ in 0..count {
+                string.push(quote);
+            }
+            return false;
+        }
+
+        for _ in 3..count {
+            string.push(quote);
+        }
+        return true;
+    }
+
+    // A backslash at the end of a line trims the newline and all whitespace
+    // up to the next visible character.
+    fn skip_line_ending_backslash(&mut self) -> bool {
+        let mut offset = 0;
+        while matches!(self.peek_at(offset), Some(' ') | Some('\t')) {
+            offset += 1;
+        }
+        if !matches!(self.peek_at(offset), Some('\n') | Some('\r')) {
+            return false;
+        }
+
+        while matches!(
+            self.peek(),
+            Some(' ') | Some('\t') | Some('\n') | Some('\r')
+        ) {
+            self.advance();
+        }
+        return true;
+    }
+
+    fn parse_escape(&mut self) -> Result<char, TomlConvertError> {
+        let escape = match self.advance() {
+            Some(escape) => escape,
+            None => return Err(self.error("Unterminated string")),
+        };
+
+        let digits = match escape {
+            'b' => return Ok('\u{8}'),
+            't' => return Ok('\t'),
+            'n' => return Ok('\n'),
+            'f' => return Ok('\u{c}'),
+            'r' => return Ok('\r'),
+            '"' => return Ok('"'),
+            '\\' => return Ok('\\'),
+            'u' => 4,
+            'U' => 8,
+            _ => return Err(self.error("Invalid escape")),
+        };
+
+        let hex: String = self.chars.iter().skip(self.pos).take(digits).collect();
+        self.pos += hex.chars().count();
+
+        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+            Some(decoded) if hex.len() == digits => {
+                return Ok(decoded);
+            }
+            _ => {
+                return Err(self.error("Invalid unicode escape"));
+            }
+        }
+    }
+}
+
+/// Parses a TOML 1.0 document into an object. Offset and local datetimes,
+/// dates and times become RFC 3339 strings. Errors carry the line and the
+/// pointer of the table or key being defined.
+pub fn from_toml_str(source: &str) -> Result<JsonValue, TomlConvertError> {
+    return TomlParser::new(source).parse_document();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::toml::{from_toml_str, to_toml_string};
+
+    const MANIFEST: &str = r#"# A Cargo manifest.
+[package]
+name = "mini-json"
+version = "0.1.1"
+edition = "2021"
+authors = ["Dev <dev@example.com>"]
+published = 2024-03-01T12:30:00Z
+released = 2024-03-01
+
+[features]
+default = []
+yaml = []
+
+[dependencies]
+serde = { version = "1.0", optional = true }
+"mini-json-derive" = { path = "mini-json-derive", version = "0.1" }
+
+[[bin]]
+name = "mini-json"
+path = "src/main.rs"
+
+[[bin]]
+name = "other"
+test = false
+
+[profile.release]
+lto = true
+opt-level = 3
+"#;
+
+    fn manifest() -> crate::json::JsonValue {
+        return json!({
+            "package": {
+                "name": "mini-json",
+                "version": "0.1.1",
+                "edition": "2021",
+                "authors": ["Dev <dev@example.com>"],
+                "published": "2024-03-01T12:30:00Z",
+                "released": "2024-03-01"
+            },
+            "features": {"default": [], "yaml": []},
+            "dependencies": {
+                "serde": {"version": "1.0", "optional": true},
+                "mini-json-derive": {"path": "mini-json-derive", "version": "0.1"}
+            },
+            "bin": [
+                {"name": "mini-json", "path": "src/main.rs"},
+                {"name": "other", "test": false}
+            ],
+            "profile": {"release": {"lto": true, "opt-level": 3}}
+        });
+    }
+
+    #[test]
+    fn cargo_manifests_convert_both_ways() {
+        assert_eq!(from_toml_str(MANIFEST), Ok(manifest()));
+
+        let written = to_toml_string(&manifest()).unwrap();
+        assert_eq!(
+            written,
+            r#"[[bin]]
+name = "mini-json"
+path = "src/main.rs"
+
+[[bin]]
+name = "other"
+test = false
+
+[dependencies.mini-json-derive]
+path = "mini-json-derive"
+version = "0.1"
+
+[dependencies.serde]
+optional = true
+version = "1.0"
+
+[features]
+default = []
+yaml = []
+
+[package]
+authors = ["Dev <dev@example.com>"]
+edition = "2021"
+name = "mini-json"
+published = "2024-03-01T12:30:00Z"
+released = "2024-03-01"
+version = "0.1.1"
+
+[profile.release]
+lto = true
+opt-level = 3
+"#
+        );
+        assert_eq!(from_toml_str(&written), Ok(manifest()));
+    }
+
+    #[test]
+    fn mixed_arrays_and_odd_keys_are_written_inline() {
+        let value = json!({
+            "mixed": [1, "two", {"three": 3.0}],
+            "a b": {"c.d": "\u{1}\n"},
+            "empty": {}
+        });
+
+        let written = to_toml_string(&value).unwrap();
+        assert_eq!(
+            written,
+            "mixed = [1, \"two\", { three = 3.0 }]\n\n\
+             [\"a b\"]\n\"c.d\" = \"\\u0001\\n\"\n\n\
+             [empty]\n"
+        );
+        assert_eq!(from_toml_str(&written), Ok(value));
+    }
+
+    #[test]
+    fn unrepresentable_values_name_their_pointer() {
+        let error = |value| to_toml_string(&value).unwrap_err().to_string();
+
+        assert_eq!(
+            error(json!([1])),
+            "[TOML error at '']: The root of a TOML document must be a table"
+        );
+        assert_eq!(
+            error(json!({"a": {"b/c": [1, null]}})),
+            "[TOML error at '/a/b~1c/1']: TOML has no null"
+        );
+        assert_eq!(
+            error(json!({"list": [{"x": 1}, {"y": null}]})),
+            "[TOML error at '/list/1/y']: TOML has no null"
+        );
+    }
+
+    #[test]
+    fn invalid_documents_name_their_line_and_pointer() {
+        let error = |source| from_toml_str(source).unwrap_err().to_string();
+
+        assert_eq!(
+            error("a = 1\na = 2\n"),
+            "[TOML error at line 2 ('/a')]: Duplicate key"
+        );
+        assert_eq!(
+            error("[t]\nx = 1\n[t]\n"),
+            "[TOML error at line 3 ('/t')]: Table is defined twice"
+        );
+        assert_eq!(
+            error("t = { x = 1 }\n[t.y]\n"),
+            "[TOML error at line 2 ('/t')]: Inline tables can't be extended"
+        );
+        assert_eq!(
+            error("[s]\nv = \"open"),
+            "[TOML error at line 2 ('/s/v')]: Unterminated string"
+        );
+    }
+}
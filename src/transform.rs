@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use crate::json::{JsonValue, WrongKindError};
+use crate::walk::{JsonPath, PathStep};
+
+fn map_value<'a>(
+    value: &'a JsonValue,
+    path: &mut JsonPath<'a>,
+    f: &mut impl FnMut(&JsonPath, &JsonValue) -> JsonValue,
+) -> JsonValue {
+    match value {
+        JsonValue::Object(object) => {
+            let mut mapped = HashMap::with_capacity(object.len());
+
+            for (key, member) in object {
+                path.push(PathStep::Key(key));
+                mapped.insert(key.clone(), map_value(member, path, f));
+                path.pop();
+            }
+
+            return JsonValue::Object(mapped);
+        }
+        JsonValue::Array(array) => {
+            let mut mapped = Vec::with_capacity(array.len());
+
+            for (index, element) in array.iter().enumerate() {
+                path.push(PathStep::Index(index));
+                mapped.push(map_value(element, path, f));
+                path.pop();
+            }
+
+            return JsonValue::Array(mapped);
+        }
+        _ => {
+            return f(path, value);
+        }
+    }
+}
+
+fn map_key(value: &JsonValue, f: &mut impl FnMut(&str) -> String) -> JsonValue {
+    match value {
+        JsonValue::Object(object) => {
+            // Sorted so that keys mapping to the same name collide in the
+            // same order every time.
+            let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+
+            return JsonValue::Object(
+                members
+                    .into_iter()
+                    .map(|(key, member)| (f(key), map_key(member, f)))
+                    .collect(),
+            );
+        }
+        JsonValue::Array(array) => {
+            return JsonValue::Array(array.iter().map(|element| map_key(element, f)).collect());
+        }
+        _ => {
+            return value.clone();
+        }
+    }
+}
+
+impl JsonValue {
+    /// Builds a new tree with the same structure where every scalar is
+    /// replaced by `f(path, scalar)`. The closure may return any kind of
+    /// value, including containers.
+    pub fn map_values(&self, mut f: impl FnMut(&JsonPath, &JsonValue) -> JsonValue) -> JsonValue {
+        return map_value(self, &mut JsonPath::default(), &mut f);
+    }
+
+    /// Builds a new tree where every object key at every depth is replaced
+    /// by `f(key)`. If two keys map to the same name, the value of the one
+    /// that sorts last by code point wins, so `{"a": 1, "A": 2}` with keys
+    /// uppercased becomes `{"A": 1}`.
+    pub fn map_keys(&self, mut f: impl FnMut(&str) -> String) -> JsonValue {
+        return map_key(self, &mut f);
+    }
+}
+
+fn retain_value<'a>(
+    value: &'a mut JsonValue,
+    path: &mut JsonPath<'a>,
+    f: &mut impl FnMut(&JsonPath, &JsonValue) -> bool,
+) {
+    match value {
+        JsonValue::Object(object) => {
+            let mut removed = Vec::new();
+            let mut scratch = path.clone();
+
+            for (key, member) in object.iter() {
+                scratch.push(PathStep::Key(key));
+                if !f(&scratch, member) {
+                    removed.push(key.clone());
+                }
+                scratch.pop();
+            }
+
+            for key in removed {
+                object.remove(&key);
+            }
+
+            for (key, member) in object {
+                path.push(PathStep::Key(key));
+                retain_value(member, path, f);
+                path.pop();
+            }
+        }
+        JsonValue::Array(array) => {
+            let mut keep = Vec::with_capacity(array.len());
+            let mut scratch = path.clone();
+
+            for (index, element) in array.iter().enumerate() {
+                scratch.push(PathStep::Index(index));
+                keep.push(f(&scratch, element));
+                scratch.pop();
+            }
+
+            let survivors: Vec<usize> = (0..keep.len()).filter(|index| keep[*index]).collect();
+            let mut flags = keep.into_iter();
+            array.retain(|_| flags.next().unwrap_or(true));
+
+            for (index, element) in survivors.into_iter().zip(array.iter_mut()) {
+                path.push(PathStep::Index(index));
+                retain_value(element, path, f);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+impl JsonValue {
+    pub fn retain_object(
+        &mut self,
+        mut f: impl FnMut(&str, &JsonValue) -> bool,
+    ) -> Result<(), WrongKindError> {
+        match self {
+            JsonValue::Object(object) => {
+                object.retain(|key, value| f(key, value));
+                return Ok(());
+            }
+            _ => {
+                return Err(self.wrong_kind("object"));
+            }
+        }
+    }
+
+    pub fn retain_array(
+        &mut self,
+        mut f: impl FnMut(usize, &JsonValue) -> bool,
+    ) -> Result<(), WrongKindError> {
+        match self {
+            JsonValue::Array(array) => {
+                let mut index = 0;
+                array.retain(|value| {
+                    index += 1;
+                    return f(index - 1, value);
+                });
+                return Ok(());
+            }
+            _ => {
+                return Err(self.wrong_kind("array"));
+            }
+        }
+    }
+
+    /// Applies `f` to every object member and array element at every depth,
+    /// removing those for which it returns `false`. The path passed to `f`
+    /// ends with the member's key or the element's index before filtering.
+    /// Removed subtrees are not visited.
+    pub fn retain_recursive(&mut self, mut f: impl FnMut(&JsonPath, &JsonValue) -> bool) {
+        retain_value(self, &mut JsonPath::default(), &mut f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::walk::PathStep;
+
+    #[test]
+    fn map_values_rounds_floats_to_two_decimals() {
+        let value = json!({"a": 1.2345, "b": [2.006, {"c": -5.4321}], "d": "x", "e": 7});
+
+        let rounded = value.map_values(|_, scalar| match scalar {
+            JsonValue::Float(float) => JsonValue::Float((float * 100.0).round() / 100.0),
+            _ => scalar.clone(),
+        });
+        assert_eq!(
+            rounded,
+            json!({"a": 1.23, "b": [2.01, {"c": -5.43}], "d": "x", "e": 7})
+        );
+        // The original is left as it was.
+        assert_eq!(value.pointer("/a"), Some(&json!(1.2345)));
+    }
+
+    #[test]
+    fn map_values_can_change_kinds_and_sees_paths() {
+        let value = json!({"a": [1, null], "b": {"c": true}, "empty": {}});
+
+        let mapped = value.map_values(|path, scalar| match scalar {
+            JsonValue::Integer(_) | JsonValue::Boolean(_) => JsonValue::from(path.to_pointer()),
+            JsonValue::Null => json!([]),
+            _ => scalar.clone(),
+        });
+        assert_eq!(
+            mapped,
+            json!({"a": ["/a/0", []], "b": {"c": "/b/c"}, "empty": {}})
+        );
+    }
+
+    #[test]
+    fn map_keys_uppercases_every_key() {
+        let value = json!({"a": {"bc": [{"d": 1}, 2]}, "e": "f"});
+
+        assert_eq!(
+            value.map_keys(|key| key.to_uppercase()),
+            json!({"A": {"BC": [{"D": 1}, 2]}, "E": "f"})
+        );
+    }
+
+    #[test]
+    fn map_keys_collisions_keep_the_last_key_in_code_point_order() {
+        let value = json!({"a": 1, "A": 2, "b": {"x": 3, "X": 4, "y": 5}});
+
+        for _ in 0..10 {
+            assert_eq!(
+                value.map_keys(|key| key.to_uppercase()),
+                json!({"A": 1, "B": {"X": 3, "Y": 5}})
+            );
+        }
+    }
+
+    #[test]
+    fn retain_recursive_skips_removed_subtrees() {
+        let mut value = json!({"keep": [1, -2, {"n": -3}], "drop": {"n": 4}});
+        let mut visited = Vec::new();
+
+        value.retain_recursive(|path, member| {
+            visited.push(path.to_pointer());
+            return path.to_pointer() != "/drop"
+                && !matches!(member, JsonValue::Integer(n) if *n < 0);
+        });
+        assert_eq!(value, json!({"keep": [1, {}]}));
+        assert!(!visited.contains(&"/drop/n".to_string()));
+    }
+
+    #[test]
+    fn retain_object_and_array_filter_one_level() {
+        let mut object = json!({"a": 1, "_b": {"_c": 2}, "d": [3, 4, 5]});
+        object
+            .retain_object(|key, _| !key.starts_with('_'))
+            .unwrap();
+        assert_eq!(object, json!({"a": 1, "d": [3, 4, 5]}));
+
+        let mut array = json!([3, 4, 5, [6]]);
+        array.retain_array(|index, _| index % 2 == 0).unwrap();
+        assert_eq!(array, json!([3, 5]));
+
+        assert!(array.retain_object(|_, _| true).is_err());
+        assert!(object.retain_array(|_, _| true).is_err());
+        assert!(json!(null).retain_array(|_, _| true).is_err());
+    }
+
+    #[test]
+    fn retain_recursive_drops_underscored_keys_anywhere() {
+        let mut value = json!({
+            "_id": 1,
+            "name": "x",
+            "items": [{"_hidden": true, "id": 2, "tags": ["_a"]}, {"nested": {"_b": 3, "c": 4}}]
+        });
+
+        value.retain_recursive(|path, _| match path.last() {
+            Some(PathStep::Key(key)) => !key.starts_with('_'),
+            _ => true,
+        });
+        assert_eq!(
+            value,
+            json!({
+                "name": "x",
+                "items": [{"id": 2, "tags": ["_a"]}, {"nested": {"c": 4}}]
+            })
+        );
+    }
+
+    #[test]
+    fn retain_recursive_passes_indexes_from_before_filtering() {
+        let mut value = json!([[0, 1], "drop", [2, 3]]);
+        let mut visited = Vec::new();
+
+        value.retain_recursive(|path, element| {
+            visited.push(path.to_pointer());
+            return *element != json!("drop") && *element != json!(3);
+        });
+        assert_eq!(value, json!([[0, 1], [2]]));
+        assert_eq!(visited, ["/0", "/1", "/2", "/0/0", "/0/1", "/2/0", "/2/1"]);
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt;
+
+use crate::json::JsonValue;
+use crate::path::{escape_pointer_token, PathSegment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStep<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// The location of a node during a walk, borrowed from the tree being
+/// walked so that building it never copies keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonPath<'a> {
+    steps: Vec<PathStep<'a>>,
+}
+
+impl<'a> JsonPath<'a> {
+    pub(crate) fn push(&mut self, step: PathStep<'a>) {
+        self.steps.push(step);
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.steps.pop();
+    }
+
+    pub fn steps(&self) -> &[PathStep<'a>] {
+        return &self.steps;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.steps.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.steps.is_empty();
+    }
+
+    pub fn last(&self) -> Option<PathStep<'a>> {
+        return self.steps.last().copied();
+    }
+
+    pub fn to_pointer(&self) -> String {
+        return self.to_string();
+    }
+
+    pub fn to_segments(&self) -> Vec<PathSegment> {
+        return self
+            .steps
+            .iter()
+            .map(|step| match step {
+                PathStep::Key(key) => PathSegment::Key(key.to_string()),
+                PathStep::Index(index) => PathSegment::Index(*index),
+            })
+            .collect();
+    }
+}
+
+impl fmt::Display for JsonPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            match step {
+                PathStep::Key(key) => write!(f, "/{}", escape_pointer_token(key))?,
+                PathStep::Index(index) => write!(f, "/{}", index)?,
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkControl {
+    Continue,
+    SkipChildren,
+    Stop,
+}
+
+fn walk_value<'a>(
+    value: &'a JsonValue,
+    path: &mut JsonPath<'a>,
+    f: &mut impl FnMut(&JsonPath, &JsonValue) -> WalkControl,
+) -> bool {
+    match f(path, value) {
+        WalkControl::Continue => {}
+        WalkControl::SkipChildren => return true,
+        WalkControl::Stop => return false,
+    }
+
+    match value {
+        JsonValue::Object(object) => {
+            let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (key, member) in members {
+                path.push(PathStep::Key(key));
+                let keep_going = walk_value(member, path, f);
+                path.pop();
+
+                if !keep_going {
+                    return false;
+                }
+            }
+        }
+        JsonValue::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                path.push(PathStep::Index(index));
+                let keep_going = walk_value(element, path, f);
+                path.pop();
+
+                if !keep_going {
+                    return false;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    return true;
+}
+
+fn walk_value_mut<'a>(
+    value: &'a mut JsonValue,
+    path: &mut JsonPath<'a>,
+    f: &mut impl FnMut(&JsonPath, &mut JsonValue) -> WalkControl,
+) -> bool {
+    match f(path, value) {
+        WalkControl::Continue => {}
+        WalkControl::SkipChildren => return true,
+        WalkControl::Stop => return false,
+    }
+
+    match value {
+        JsonValue::Object(object) => {
+            let mut members: Vec<(&String, &mut JsonValue)> = object.iter_mut().collect();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (key, member) in members {
+                path.push(PathStep::Key(key));
+                let keep_going = walk_value_mut(member, path, f);
+                path.pop();
+
+                if !keep_going {
+                    return false;
+                }
+            }
+        }
+        JsonValue::Array(array) => {
+            for (index, element) in array.iter_mut().enumerate() {
+                path.push(PathStep::Index(index));
+                let keep_going = walk_value_mut(element, path, f);
+                path.pop();
+
+                if !keep_going {
+                    return false;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    return true;
+}
+
+// Walks are depth-first and pre-order: a node is visited before its
+// children, array elements in index order and object members in sorted key
+// order.
+impl JsonValue {
+    pub fn walk(&self, mut f: impl FnMut(&JsonPath, &JsonValue)) {
+        self.walk_with(|path, value| {
+            f(path, value);
+            return WalkControl::Continue;
+        });
+    }
+
+    /// Like `walk`, but the visitor decides whether to descend into the
+    /// current node, skip its children, or stop the walk entirely. Returns
+    /// `false` if the walk was stopped early.
+    pub fn walk_with(&self, mut f: impl FnMut(&JsonPath, &JsonValue) -> WalkControl) -> bool {
+        return walk_value(self, &mut JsonPath::default(), &mut f);
+    }
+
+    /// Like `walk`, with mutable access to every node. Children are visited
+    /// after the visitor returns, so replacing a node walks the replacement.
+    pub fn walk_mut(&mut self, mut f: impl FnMut(&JsonPath, &mut JsonValue)) {
+        self.walk_mut_with(|path, value| {
+            f(path, value);
+            return WalkControl::Continue;
+        });
+    }
+
+    pub fn walk_mut_with(
+        &mut self,
+        mut f: impl FnMut(&JsonPath, &mut JsonValue) -> WalkControl,
+    ) -> bool {
+        return walk_value_mut(self, &mut JsonPath::default(), &mut f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::JsonValue;
+    use crate::path::PathSegment;
+    use crate::walk::{PathStep, WalkControl};
+
+    fn fixture() -> JsonValue {
+        return json!({"b": [1, {"d": 2, "c": 3}], "a": {"x~/y": null}, "e": []});
+    }
+
+    #[test]
+    fn walks_depth_first_in_sorted_key_and_index_order() {
+        let mut visited = Vec::new();
+        fixture().walk(|path, _| visited.push(path.to_pointer()));
+
+        assert_eq!(
+            visited,
+            [
+                "",
+                "/a",
+                "/a/x~0~1y",
+                "/b",
+                "/b/0",
+                "/b/1",
+                "/b/1/c",
+                "/b/1/d",
+                "/e"
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_expose_their_steps() {
+        let mut found = 0;
+        fixture().walk(|path, value| {
+            if *value != json!(3) {
+                return;
+            }
+            found += 1;
+
+            assert_eq!(
+                path.steps(),
+                [PathStep::Key("b"), PathStep::Index(1), PathStep::Key("c")]
+            );
+            assert_eq!(path.last(), Some(PathStep::Key("c")));
+            assert_eq!(
+                path.to_segments(),
+                [
+                    PathSegment::Key("b".to_string()),
+                    PathSegment::Index(1),
+                    PathSegment::Key("c".to_string())
+                ]
+            );
+        });
+        assert_eq!(found, 1);
+    }
+
+    #[test]
+    fn visitors_can_skip_children_or_stop() {
+        let mut visited = Vec::new();
+        let finished = fixture().walk_with(|path, _| {
+            visited.push(path.to_pointer());
+            return match path.to_pointer().as_str() {
+                "/a" => WalkControl::SkipChildren,
+                "/b/1" => WalkControl::Stop,
+                _ => WalkControl::Continue,
+            };
+        });
+
+        assert!(!finished);
+        assert_eq!(visited, ["", "/a", "/b", "/b/0", "/b/1"]);
+        assert!(fixture().walk_with(|_, _| WalkControl::Continue));
+    }
+
+    #[test]
+    fn walk_mut_edits_scalars_in_place() {
+        let mut value = fixture();
+        value.walk_mut(|_, node| {
+            if let JsonValue::Integer(integer) = node {
+                *integer *= 10;
+            }
+        });
+        assert_eq!(
+            value,
+            json!({"b": [10, {"d": 20, "c": 30}], "a": {"x~/y": null}, "e": []})
+        );
+
+        // A replacement is walked in place of the original node.
+        let mut visited = Vec::new();
+        let finished = value.walk_mut_with(|path, node| {
+            visited.push(path.to_pointer());
+            if path.to_pointer() == "/e" {
+                *node = json!([true]);
+                return WalkControl::Continue;
+            }
+            return match path.len() {
+                1 => WalkControl::SkipChildren,
+                _ => WalkControl::Continue,
+            };
+        });
+        assert!(finished);
+        assert_eq!(visited, ["", "/a", "/b", "/e", "/e/0"]);
+        assert_eq!(value.pointer("/e"), Some(&json!([true])));
+    }
+}
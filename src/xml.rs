@@ -0,0 +1,734 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::JsonValue;
+use crate::parser::MAX_DEPTH;
+use crate::serialize::format_float;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XmlOptions {
+    /// Writes scalar members whose key starts with `@` as attributes, and a
+    /// `#text` member as the element's text, so `{"@id": 1, "#text": "x"}`
+    /// becomes `<a id="1">x</a>`. Otherwise they're child elements like any
+    /// other key.
+    pub attributes: bool,
+    /// Starts the output with `<?xml version="1.0" encoding="UTF-8"?>`.
+    pub declaration: bool,
+}
+
+impl Default for XmlOptions {
+    fn default() -> XmlOptions {
+        XmlOptions {
+            attributes: true,
+            declaration: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlError {
+    pub message: String,
+    pub line: usize,
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[XML error at line {}]: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+fn is_name_start(c: char) -> bool {
+    return c.is_alphabetic() || c == '_';
+}
+
+fn is_name_char(c: char) -> bool {
+    return c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+}
+
+// Whether `_xHHHH_` starts at the beginning of `text`.
+fn escape_at(text: &str) -> Option<(char, usize)> {
+    let hex = text.strip_prefix("_x")?;
+    let end = hex.find('_')?;
+    if !(4..=6).contains(&end) || !hex[..end].chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let c = char::from_u32(u32::from_str_radix(&hex[..end], 16).ok()?)?;
+    return Some((c, end + 3));
+}
+
+/// Mangles a key into an XML name the way .NET's `XmlConvert.EncodeName`
+/// does: characters that can't appear in a name, or can't start one, are
+/// written as `_xHHHH_`, as is a `_` that would otherwise read as the start
+/// of such an escape. Colons are escaped too, so no name has a namespace
+/// prefix. An empty key becomes `_`.
+fn encode_name(key: &str) -> String {
+    if key.is_empty() {
+        return "_".to_string();
+    }
+
+    let mut name = String::with_capacity(key.len());
+    for (index, c) in key.char_indices() {
+        let valid = match index {
+            0 => is_name_start(c),
+            _ => is_name_char(c),
+        };
+
+        if !valid || (c == '_' && escape_at(&key[index..]).is_some()) {
+            name.push_str(&format!("_x{:04X}_", c as u32));
+        } else {
+            name.push(c);
+        }
+    }
+
+    return name;
+}
+
+fn decode_name(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    let mut rest = name;
+
+    while let Some(c) = rest.chars().next() {
+        match escape_at(rest) {
+            Some((decoded, len)) => {
+                key.push(decoded);
+                rest = &rest[len..];
+            }
+            None => {
+                key.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    return key;
+}
+
+// Escapes markup characters, including `>` so `]]>` can't appear. Control
+// characters XML 1.0 can't represent at all become U+FFFD.
+fn write_escaped(out: &mut String, text: &str, attribute: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '\n' if attribute => out.push_str("&#xA;"),
+            '\t' if attribute => out.push_str("&#x9;"),
+            '\r' => out.push_str("&#xD;"),
+            '\t' | '\n' => out.push(c),
+            c if (c as u32) < 0x20 || c == '\u{fffe}' || c == '\u{ffff}' => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+}
+
+fn scalar_text(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::Null => Some(String::new()),
+        JsonValue::Boolean(value) => Some(value.to_string()),
+        JsonValue::Integer(value) => Some(value.to_string()),
+        JsonValue::Float(value) if value.is_finite() => Some(format_float(*value)),
+        JsonValue::Float(_) => Some(String::new()),
+        JsonValue::String(value) => Some(value.clone()),
+        JsonValue::Array(_) | JsonValue::Object(_) => None,
+    }
+}
+
+struct Writer<'a> {
+    out: String,
+    options: &'a XmlOptions,
+}
+
+impl Writer<'_> {
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+    }
+
+    // Writes `value` as elements named `name`: one for a scalar or object,
+    // one per element for an array.
+    fn elements(&mut self, name: &str, value: &JsonValue, depth: usize) {
+        match value {
+            JsonValue::Array(array) => {
+                for element in array {
+                    match element {
+                        JsonValue::Array(_) => self.element(name, element, depth),
+                        _ => self.elements(name, element, depth),
+                    }
+                }
+            }
+            _ => self.element(name, value, depth),
+        }
+    }
+
+    // Writes exactly one element. An array needs a wrapper here, so its
+    // elements become `item` children.
+    fn element(&mut self, name: &str, value: &JsonValue, depth: usize) {
+        self.indent(depth);
+        self.out.push('<');
+        self.out.push_str(name);
+
+        let object = match value {
+            JsonValue::Object(object) => object,
+            JsonValue::Array(array) if array.is_empty() => {
+                self.out.push_str("/>\n");
+                return;
+            }
+            JsonValue::Array(_) => {
+                self.out.push_str(">\n");
+                self.elements("item", value, depth + 1);
+                self.close(name, depth);
+                return;
+            }
+            scalar => {
+                let text = scalar_text(scalar).unwrap_or_default();
+                if matches!(scalar, JsonValue::Null) {
+                    self.out.push_str("/>\n");
+                } else {
+                    self.out.push('>');
+                    write_escaped(&mut self.out, &text, false);
+                    self.out.push_str(&format!("</{}>\n", name));
+                }
+                return;
+            }
+        };
+
+        let mut keys: Vec<&String> = object.keys().collect();
+        keys.sort();
+
+        let mut text = None;
+        let mut children = Vec::new();
+        for key in keys {
+            let member = &object[key];
+            let scalar = scalar_text(member);
+
+            match (key.strip_prefix('@'), scalar) {
+                (Some(attribute), Some(scalar)) if self.options.attributes => {
+                    self.out.push(' ');
+                    self.out.push_str(&encode_name(attribute));
+                    self.out.push_str("=\"");
+                    write_escaped(&mut self.out, &scalar, true);
+                    self.out.push('"');
+                }
+                (_, Some(scalar)) if self.options.attributes && key == "#text" => {
+                    text = Some(scalar);
+                }
+                _ => children.push((key, member)),
+            }
+        }
+
+        if children.is_empty() {
+            match text {
+                Some(text) if !text.is_empty() => {
+                    self.out.push('>');
+                    write_escaped(&mut self.out, &text, false);
+                    self.out.push_str(&format!("</{}>\n", name));
+                }
+                _ => self.out.push_str("/>\n"),
+            }
+            return;
+        }
+
+        self.out.push('>');
+        if let Some(text) = text {
+            write_escaped(&mut self.out, &text, false);
+        }
+        self.out.push('\n');
+        for (key, member) in children {
+            self.elements(&encode_name(key), member, depth + 1);
+        }
+        self.close(name, depth);
+    }
+
+    fn close(&mut self, name: &str, depth: usize) {
+        self.indent(depth);
+        self.out.push_str(&format!("</{}>\n", name));
+    }
+}
+
+/// Writes `value` as an XML document whose root element is `root`. Object
+/// keys become child elements in sorted order, arrays become repeated
+/// elements, and scalars become text, with `null` as an empty element.
+/// Keys that aren't XML names are mangled as `_xHHHH_` escapes, which
+/// `from_xml` reverses. Arrays directly inside arrays, or at the root, are
+/// wrapped in an element holding one `item` per element. Empty arrays and
+/// strings, `null` and the type of every scalar don't survive a round trip.
+pub fn to_xml(value: &JsonValue, root: &str, options: &XmlOptions) -> String {
+    let mut writer = Writer {
+        out: String::new(),
+        options,
+    };
+
+    if options.declaration {
+        writer
+            .out
+            .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    }
+    writer.element(&encode_name(root), value, 0);
+
+    return writer.out;
+}
+
+struct Element {
+    attributes: Vec<(String, String)>,
+    children: Vec<(String, JsonValue)>,
+    text: String,
+}
+
+struct XmlParser<'a> {
+    source: &'a str,
+    current: usize,
+    line: usize,
+    depth: usize,
+}
+
+impl XmlParser<'_> {
+    fn error(&self, message: &str) -> XmlError {
+        return XmlError {
+            message: message.to_string(),
+            line: self.line,
+        };
+    }
+
+    fn rest(&self) -> &str {
+        return &self.source[self.current..];
+    }
+
+    fn advance(&mut self, len: usize) {
+        let skipped = &self.source[self.current..self.current + len];
+        self.line += skipped.matches('\n').count();
+        self.current += len;
+    }
+
+    // Skips up to and including `end`, failing with `what` if it's missing.
+    fn skip_past(&mut self, end: &str, what: &str) -> Result<usize, XmlError> {
+        match self.rest().find(end) {
+            Some(index) => {
+                let start = self.current;
+                self.advance(index + end.len());
+                return Ok(start);
+            }
+            None => {
+                return Err(self.error(&format!("Unterminated {}", what)));
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let len = self.rest().len() - self.rest().trim_start().len();
+        self.advance(len);
+    }
+
+    // Skips whitespace, comments and processing instructions between
+    // elements, and a DOCTYPE without an internal subset.
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                let end = self.rest().find('>');
+                if end.is_some_and(|end| self.rest()[..end].contains('[')) {
+                    return Err(self.error("DOCTYPE internal subsets aren't supported"));
+                }
+                self.skip_past(">", "DOCTYPE")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, XmlError> {
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|(index, c)| match index {
+                0 => !(is_name_start(*c) || *c == ':'),
+                _ => !(is_name_char(*c) || *c == ':'),
+            })
+            .map(|(index, _)| index)
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(self.error("Expected a name"));
+        }
+
+        let name = rest[..len].to_string();
+        self.advance(len);
+        return Ok(name);
+    }
+
+    fn unescape(&self, text: &str) -> Result<String, XmlError> {
+        let mut unescaped = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(index) = rest.find('&') {
+            unescaped.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+
+            let end = match rest.find(';') {
+                Some(end) => end,
+                None => return Err(self.error("Unterminated entity reference")),
+            };
+            let entity = &rest[..end];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity
+                        .strip_prefix('#')
+                        .and_then(|decimal| decimal.parse::<u32>().ok())
+                        .and_then(char::from_u32),
+                },
+            };
+
+            match c {
+                Some(c) => unescaped.push(c),
+                None => return Err(self.error(&format!("Unknown entity '&{};'", entity))),
+            }
+            rest = &rest[end + 1..];
+        }
+
+        unescaped.push_str(rest);
+        return Ok(unescaped);
+    }
+
+    fn attribute_value(&mut self) -> Result<String, XmlError> {
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error("Expected a quoted attribute value")),
+        };
+        self.advance(1);
+
+        let start = self.skip_past(&quote.to_string(), "attribute value")?;
+        let raw = &self.source[start..self.current - 1];
+        if raw.contains('<') {
+            return Err(self.error("Unescaped '<' in attribute value"));
+        }
+
+        // Attribute value normalization turns literal whitespace into
+        // spaces; escaped whitespace survives.
+        let raw = raw.replace(['\n', '\t', '\r'], " ");
+        return self.unescape(&raw);
+    }
+
+    fn element(&mut self) -> Result<(String, JsonValue), XmlError> {
+        if !self.rest().starts_with('<') {
+            return Err(self.error("Expected an element"));
+        }
+        self.advance(1);
+        let name = self.name()?;
+
+        let mut element = Element {
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+        };
+
+        loop {
+            self.skip_whitespace();
+
+            if self.rest().starts_with("/>") {
+                self.advance(2);
+                return Ok((decode_name(&name), self.value(element)));
+            }
+            if self.rest().starts_with('>') {
+                self.advance(1);
+                break;
+            }
+
+            let attribute = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error(&format!("Expected '=' after attribute '{}'", attribute)));
+            }
+            self.advance(1);
+            self.skip_whitespace();
+
+            let value = self.attribute_value()?;
+            if element
+                .attributes
+                .iter()
+                .any(|(name, _)| *name == attribute)
+            {
+                return Err(self.error(&format!("Duplicate attribute '{}'", attribute)));
+            }
+            element.attributes.push((attribute, value));
+        }
+
+        loop {
+            let rest = self.rest();
+
+            if rest.is_empty() {
+                return Err(self.error(&format!("Unclosed element '{}'", name)));
+            } else if rest.starts_with("</") {
+                self.advance(2);
+                let closing = self.name()?;
+                if closing != name {
+                    return Err(
+                        self.error(&format!("Expected '</{}>', found '</{}>'", name, closing))
+                    );
+                }
+                self.skip_whitespace();
+                if !self.rest().starts_with('>') {
+                    return Err(self.error("Expected '>'"));
+                }
+                self.advance(1);
+                return Ok((decode_name(&name), self.value(element)));
+            } else if rest.starts_with("<![CDATA[") {
+                self.advance(9);
+                let start = self.skip_past("]]>", "CDATA section")?;
+                element.text.push_str(&self.source[start..self.current - 3]);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else if rest.starts_with('<') {
+                // Nesting is limited like the JSON parser's, so deep input
+                // can't overflow the stack.
+                self.depth += 1;
+                if self.depth >= MAX_DEPTH {
+                    return Err(self.error(&format!("Nested more than {} levels deep", MAX_DEPTH)));
+                }
+                let child = self.element()?;
+                self.depth -= 1;
+                element.children.push(child);
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                let text = self.unescape(&rest[..len].replace("\r\n", "\n"))?;
+                element.text.push_str(&text);
+                self.advance(len);
+            }
+        }
+    }
+
+    // An element with only text is a string, or null if it's empty. Anything
+    // else is an object, with attributes under `@` keys, repeated children
+    // collected into arrays, and text that isn't just whitespace under
+    // `#text`.
+    fn value(&self, element: Element) -> JsonValue {
+        if element.attributes.is_empty() && element.children.is_empty() {
+            if element.text.is_empty() {
+                return JsonValue::Null;
+            }
+            return JsonValue::String(element.text);
+        }
+
+        let mut object = HashMap::new();
+        for (name, value) in element.attributes {
+            object.insert(format!("@{}", decode_name(&name)), JsonValue::String(value));
+        }
+
+        for (name, value) in element.children {
+            match object.get_mut(&name) {
+                Some(JsonValue::Array(array)) => array.push(value),
+                Some(existing) => {
+                    let first = existing.take();
+                    *existing = JsonValue::Array(vec![first, value]);
+                }
+                None => {
+                    object.insert(name, value);
+                }
+            }
+        }
+
+        let text = element.text.trim();
+        if !text.is_empty() {
+            object.insert("#text".to_string(), JsonValue::String(text.to_string()));
+        }
+
+        return JsonValue::Object(object);
+    }
+}
+
+/// Reads the element and text subset of XML into the value inside the root
+/// element, the reverse of `to_xml`. Every scalar comes back as a string,
+/// and a child element that appears once is a single value even if it came
+/// from a one-element array. Comments and processing instructions are
+/// skipped, CDATA sections are read as text and the five predefined and
+/// numeric entities are expanded. Namespaces aren't interpreted, so a
+/// prefixed name keeps its prefix as part of the key.
+pub fn from_xml(source: &str) -> Result<JsonValue, XmlError> {
+    let mut parser = XmlParser {
+        source: source.strip_prefix('\u{feff}').unwrap_or(source),
+        current: 0,
+        line: 1,
+        depth: 0,
+    };
+
+    parser.skip_misc()?;
+    let (_, value) = parser.element()?;
+    parser.skip_misc()?;
+
+    if !parser.rest().is_empty() {
+        return Err(parser.error("Unexpected content after the root element"));
+    }
+
+    return Ok(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::xml::{from_xml, to_xml, XmlOptions};
+
+    const BARE: XmlOptions = XmlOptions {
+        attributes: true,
+        declaration: false,
+    };
+
+    fn error(source: &str) -> String {
+        return from_xml(source).unwrap_err().to_string();
+    }
+
+    #[test]
+    fn objects_and_arrays_become_elements() {
+        let value = json!({
+            "title": "Moby Dick",
+            "author": {"first": "Herman", "last": "Melville"},
+            "tag": ["novel", "sea"],
+            "price": 8.99,
+            "stock": null
+        });
+
+        let xml = to_xml(&value, "book", &XmlOptions::default());
+        assert_eq!(
+            xml,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<book>
+  <author>
+    <first>Herman</first>
+    <last>Melville</last>
+  </author>
+  <price>8.99</price>
+  <stock/>
+  <tag>novel</tag>
+  <tag>sea</tag>
+  <title>Moby Dick</title>
+</book>
+"
+        );
+        assert_eq!(
+            from_xml(&xml),
+            Ok(json!({
+                "title": "Moby Dick",
+                "author": {"first": "Herman", "last": "Melville"},
+                "tag": ["novel", "sea"],
+                "price": "8.99",
+                "stock": null
+            }))
+        );
+    }
+
+    #[test]
+    fn string_documents_round_trip() {
+        let value = json!({
+            "a": {"b": ["x", "y", "z"], "c": {"d": "e"}},
+            "@id": "7",
+            "#text": "body",
+            "with space": "1",
+            "_x0041_": "2",
+            "1st": {"@lang": "en", "#text": "t"}
+        });
+
+        let xml = to_xml(&value, "root", &BARE);
+        assert_eq!(from_xml(&xml), Ok(value));
+    }
+
+    #[test]
+    fn markup_in_values_is_escaped() {
+        let value = json!({"@attr": "a \"b\" <c>\n", "text": "</text> & ]]> <![CDATA["});
+
+        let xml = to_xml(&value, "r", &BARE);
+        assert_eq!(
+            xml,
+            "<r attr=\"a &quot;b&quot; &lt;c&gt;&#xA;\">\n  \
+             <text>&lt;/text&gt; &amp; ]]&gt; &lt;![CDATA[</text>\n</r>\n"
+        );
+        assert!(!xml.contains("]]>"));
+        assert_eq!(from_xml(&xml), Ok(value));
+    }
+
+    #[test]
+    fn keys_that_are_not_names_are_mangled() {
+        let xml = to_xml(
+            &json!({"a b": 1, "2x": 2, "ns:k": 3, "_x1234_": 4, "": 5}),
+            "r",
+            &BARE,
+        );
+
+        assert_eq!(
+            xml,
+            "<r>\n  <_>5</_>\n  <_x0032_x>2</_x0032_x>\n  <_x005F_x1234_>4</_x005F_x1234_>\n  \
+             <a_x0020_b>1</a_x0020_b>\n  <ns_x003A_k>3</ns_x003A_k>\n</r>\n"
+        );
+    }
+
+    #[test]
+    fn nested_and_root_arrays_are_wrapped_in_items() {
+        let value = json!([[1, 2], {"k": [[3]]}]);
+
+        let xml = to_xml(&value, "list", &BARE);
+        assert_eq!(
+            xml,
+            "<list>\n  <item>\n    <item>1</item>\n    <item>2</item>\n  </item>\n  \
+             <item>\n    <k>\n      <item>3</item>\n    </k>\n  </item>\n</list>\n"
+        );
+        assert_eq!(
+            from_xml(&xml),
+            Ok(json!({"item": [{"item": ["1", "2"]}, {"k": {"item": "3"}}]}))
+        );
+    }
+
+    #[test]
+    fn attributes_can_be_written_as_elements() {
+        let options = XmlOptions {
+            attributes: false,
+            declaration: false,
+        };
+
+        assert_eq!(
+            to_xml(&json!({"@id": 1}), "r", &options),
+            "<r>\n  <_x0040_id>1</_x0040_id>\n</r>\n"
+        );
+    }
+
+    #[test]
+    fn comments_cdata_and_entities_are_read() {
+        let xml = "<?xml version=\"1.0\"?>\n<!-- c --><r><a><![CDATA[<b>&</b>]]></a>\
+                   <?pi x?><e>&lt;&#65;&#x42;&apos;</e></r>";
+
+        assert_eq!(from_xml(xml), Ok(json!({"a": "<b>&</b>", "e": "<AB'"})));
+    }
+
+    #[test]
+    fn malformed_documents_are_rejected() {
+        assert_eq!(
+            error("<a>\n<b></c></a>"),
+            "[XML error at line 2]: Expected '</b>', found '</c>'"
+        );
+        assert_eq!(error("<a>"), "[XML error at line 1]: Unclosed element 'a'");
+        assert_eq!(
+            error("<a x=\"1\" x=\"2\"/>"),
+            "[XML error at line 1]: Duplicate attribute 'x'"
+        );
+        assert_eq!(
+            error("<a>&nope;</a>"),
+            "[XML error at line 1]: Unknown entity '&nope;'"
+        );
+        assert_eq!(
+            error("<a/><b/>"),
+            "[XML error at line 1]: Unexpected content after the root element"
+        );
+    }
+}
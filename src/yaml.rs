@@ -0,0 +1,1139 @@
+use std::{collections::HashMap, fmt};
+
+use crate::json::JsonValue;
+use crate::parser::MAX_DEPTH;
+use crate::serialize::format_float;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlError {
+    pub message: String,
+    pub line: usize,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[YAML error at line {}]: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+fn yaml_error(message: &str, line: usize) -> YamlError {
+    YamlError {
+        message: message.to_string(),
+        line,
+    }
+}
+
+// Plain scalars resolve with the YAML 1.2 core schema, so `yes` and `on`
+// stay strings.
+fn resolve_plain(text: &str) -> JsonValue {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => {
+            return JsonValue::Null;
+        }
+        "true" | "True" | "TRUE" => {
+            return JsonValue::Boolean(true);
+        }
+        "false" | "False" | "FALSE" => {
+            return JsonValue::Boolean(false);
+        }
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => {
+            return JsonValue::Float(f64::INFINITY);
+        }
+        "-.inf" | "-.Inf" | "-.INF" => {
+            return JsonValue::Float(f64::NEG_INFINITY);
+        }
+        ".nan" | ".NaN" | ".NAN" => {
+            return JsonValue::Float(f64::NAN);
+        }
+        _ => {}
+    }
+
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+
+    if !unsigned.is_empty() && unsigned.chars().all(|c| c.is_ascii_digit()) {
+        match text.parse::<isize>() {
+            Ok(value) => return JsonValue::Integer(value),
+            Err(_) => return JsonValue::Float(text.parse::<f64>().unwrap_or(f64::NAN)),
+        }
+    }
+    if let Some(hex) = text.strip_prefix("0x") {
+        if let Ok(value) = isize::from_str_radix(hex, 16) {
+            return JsonValue::Integer(value);
+        }
+    }
+    if let Some(octal) = text.strip_prefix("0o") {
+        if let Ok(value) = isize::from_str_radix(octal, 8) {
+            return JsonValue::Integer(value);
+        }
+    }
+    if is_yaml_float(unsigned) {
+        if let Ok(value) = text.parse::<f64>() {
+            return JsonValue::Float(value);
+        }
+    }
+
+    return JsonValue::String(text.to_string());
+}
+
+// Matches `(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?` on an unsigned
+// number.
+fn is_yaml_float(text: &str) -> bool {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let mantissa_ok = match fraction {
+        Some(fraction) => {
+            digits(whole) && digits(fraction) && !(whole.is_empty() && fraction.is_empty())
+        }
+        None => !whole.is_empty() && digits(whole),
+    };
+    let exponent_ok = match exponent {
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+            !exponent.is_empty() && digits(exponent)
+        }
+        None => true,
+    };
+
+    return mantissa_ok && exponent_ok;
+}
+
+// Cuts a trailing comment: a `#` outside quotes at the start of the text or
+// after whitespace.
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+
+    for (index, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return text[..index].trim_end(),
+            None => {}
+        }
+        previous = c;
+    }
+
+    return text.trim_end();
+}
+
+fn check_plain(text: &str, line: usize) -> Result<(), YamlError> {
+    if text.starts_with(['&', '*']) {
+        return Err(yaml_error("Anchors and aliases are not supported", line));
+    }
+    if text.starts_with('!') {
+        return Err(yaml_error("Tags are not supported", line));
+    }
+    if text.starts_with(['|', '>']) {
+        return Err(yaml_error("Block scalars must start a value", line));
+    }
+
+    return Ok(());
+}
+
+// Parses a quoted scalar starting at `chars[*pos]` and leaves `pos` just
+// past the closing quote.
+fn parse_quoted(chars: &[char], pos: &mut usize, line: usize) -> Result<String, YamlError> {
+    let quote = chars[*pos];
+    let mut string = String::new();
+    *pos += 1;
+
+    loop {
+        let c = match chars.get(*pos) {
+            Some(c) => *c,
+            None => return Err(yaml_error("Unterminated quoted string", line)),
+        };
+        *pos += 1;
+
+        if c == quote {
+            if quote == '\'' && chars.get(*pos) == Some(&'\'') {
+                string.push('\'');
+                *pos += 1;
+                continue;
+            }
+            return Ok(string);
+        }
+
+        if c != '\\' || quote == '\'' {
+            string.push(c);
+            continue;
+        }
+
+        let escape = match chars.get(*pos) {
+            Some(escape) => *escape,
+            None => return Err(yaml_error("Unterminated quoted string", line)),
+        };
+        *pos += 1;
+
+        let digits = match escape {
+            'x' => 2,
+            'u' => 4,
+            'U' => 8,
+            _ => 0,
+        };
+
+        if digits > 0 {
+            let hex: String = chars.iter().skip(*pos).take(digits).collect();
+            *pos += digits;
+
+            match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                Some(decoded) if hex.len() == digits => string.push(decoded),
+                _ => return Err(yaml_error("Invalid escape in quoted string", line)),
+            }
+            continue;
+        }
+
+        let decoded = match escape {
+            '0' => '\0',
+            'a' => '\u{7}',
+            'b' => '\u{8}',
+            't' | '\t' => '\t',
+            'n' => '\n',
+            'v' => '\u{b}',
+            'f' => '\u{c}',
+            'r' => '\r',
+            'e' => '\u{1b}',
+            ' ' => ' ',
+            '"' => '"',
+            '/' => '/',
+            '\\' => '\\',
+            'N' => '\u{85}',
+            '_' => '\u{a0}',
+            'L' => '\u{2028}',
+            'P' => '\u{2029}',
+            _ => return Err(yaml_error("Invalid escape in quoted string", line)),
+        };
+        string.push(decoded);
+    }
+}
+
+fn depth_error(line: usize) -> YamlError {
+    return yaml_error(&format!("Nested more than {} levels deep", MAX_DEPTH), line);
+}
+
+struct Flow {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    // Carried on from the enclosing block collections.
+    depth: usize,
+}
+
+impl Flow {
+    fn error(&self, message: &str) -> YamlError {
+        return yaml_error(message, self.line);
+    }
+
+    // Called on entering a collection, which must decrement `depth` on
+    // leaving it.
+    fn enter(&mut self) -> Result<(), YamlError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(depth_error(self.line));
+        }
+
+        return Ok(());
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.pos).copied();
+    }
+
+    // Plain scalars in flow context end at `,`, `]`, `}`, or a `:` followed
+    // by whitespace or a delimiter.
+    fn plain(&mut self) -> String {
+        let start = self.pos;
+
+        while let Some(c) = self.peek() {
+            if c == ',' || c == ']' || c == '}' {
+                break;
+            }
+            if c == ':' {
+                let next = self.chars.get(self.pos + 1).copied().unwrap_or(' ');
+                if next.is_whitespace() || next == ',' || next == ']' || next == '}' {
+                    break;
+                }
+            }
+            self.pos += 1;
+        }
+
+        return self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string();
+    }
+
+    fn scalar_or_key(&mut self) -> Result<(String, bool), YamlError> {
+        match self.peek() {
+            Some('"') | Some('\'') => {
+                let string = parse_quoted(&self.chars, &mut self.pos, self.line)?;
+                return Ok((string, true));
+            }
+            _ => {
+                let plain = self.plain();
+                check_plain(&plain, self.line)?;
+                return Ok((plain, false));
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, YamlError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                self.enter()?;
+                let mut array = Vec::new();
+
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        self.depth -= 1;
+                        return Ok(JsonValue::Array(array));
+                    }
+
+                    array.push(self.value()?);
+                    self.skip_whitespace();
+
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {}
+                        _ => return Err(self.error("Expected ',' or ']' in flow sequence")),
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                self.enter()?;
+                let mut object = HashMap::new();
+
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some('}') {
+                        self.pos += 1;
+                        self.depth -= 1;
+                        return Ok(JsonValue::Object(object));
+                    }
+                    if matches!(self.peek(), Some('[') | Some('{')) {
+                        return Err(self.error("Mapping keys must be scalars"));
+                    }
+
+                    let (key, _) = self.scalar_or_key()?;
+                    self.skip_whitespace();
+
+                    let value = if self.peek() == Some(':') {
+                        self.pos += 1;
+                        self.skip_whitespace();
+                        match self.peek() {
+                            Some(',') | Some('}') => JsonValue::Null,
+                            _ => self.value()?,
+                        }
+                    } else {
+                        JsonValue::Null
+                    };
+
+                    if object.insert(key, value).is_some() {
+                        return Err(self.error("Duplicate key in flow mapping"));
+                    }
+
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {}
+                        _ => return Err(self.error("Expected ',' or '}' in flow mapping")),
+                    }
+                }
+            }
+            None => {
+                return Err(self.error("Unexpected end of flow collection"));
+            }
+            _ => {
+                let (text, quoted) = self.scalar_or_key()?;
+                if quoted {
+                    return Ok(JsonValue::String(text));
+                }
+                return Ok(resolve_plain(&text));
+            }
+        }
+    }
+}
+
+fn flow_depth(text: &str) -> isize {
+    let mut depth = 0;
+    let mut quote = None;
+
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth -= 1,
+                _ => {}
+            },
+        }
+    }
+
+    return depth;
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    raw: &'a str,
+    text: &'a str,
+}
+
+struct YamlParser<'a> {
+    lines: Vec<Line<'a>>,
+    current: usize,
+    depth: usize,
+}
+
+impl<'a> YamlParser<'a> {
+    fn new(source: &'a str) -> Result<YamlParser<'a>, YamlError> {
+        let mut lines = Vec::new();
+
+        for (index, raw) in source.lines().enumerate() {
+            let text = raw.trim_start_matches(' ');
+            let indent = raw.len() - text.len();
+
+            if text.starts_with('\t') && !strip_comment(text).is_empty() {
+                return Err(yaml_error("Tabs are not allowed in indentation", index + 1));
+            }
+
+            lines.push(Line {
+                number: index + 1,
+                indent,
+                raw,
+                text,
+            });
+        }
+
+        return Ok(YamlParser {
+            lines,
+            current: 0,
+            depth: 0,
+        });
+    }
+
+    fn content(&self) -> &'a str {
+        return strip_comment(self.lines[self.current].text);
+    }
+
+    fn line_number(&self) -> usize {
+        match self.lines.get(self.current) {
+            Some(line) => line.number,
+            None => self.lines.last().map(|line| line.number).unwrap_or(1),
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while self.current < self.lines.len() && self.content().is_empty() {
+            self.current += 1;
+        }
+    }
+
+    // Document markers end any open block, whatever its indentation.
+    fn at_end(&self) -> bool {
+        if self.current >= self.lines.len() {
+            return true;
+        }
+
+        let line = &self.lines[self.current];
+        return line.indent == 0 && (self.content() == "---" || self.content() == "...");
+    }
+
+    fn at_end_of_source(&self) -> bool {
+        return self.current >= self.lines.len();
+    }
+
+    fn is_sequence_item(content: &str) -> bool {
+        return content == "-" || content.starts_with("- ");
+    }
+
+    fn parse_document(&mut self) -> Result<JsonValue, YamlError> {
+        self.skip_blank();
+        while !self.at_end_of_source() && self.content().starts_with('%') {
+            self.current += 1;
+            self.skip_blank();
+        }
+        if !self.at_end_of_source() && self.content() == "---" {
+            self.current += 1;
+        }
+
+        self.skip_blank();
+        let value = if self.at_end() {
+            JsonValue::Null
+        } else {
+            self.parse_node()?
+        };
+
+        self.skip_blank();
+        if !self.at_end_of_source() && self.content() == "..." {
+            self.current += 1;
+            self.skip_blank();
+        }
+        if !self.at_end_of_source() {
+            if self.content() == "---" {
+                return Err(yaml_error(
+                    "Multiple documents are not supported",
+                    self.line_number(),
+                ));
+            }
+            return Err(yaml_error("Unexpected content", self.line_number()));
+        }
+
+        return Ok(value);
+    }
+
+    // Parses the node starting on the current (non-blank) line.
+    fn parse_node(&mut self) -> Result<JsonValue, YamlError> {
+        let indent = self.lines[self.current].indent;
+        let content = self.content();
+
+        let is_sequence = YamlParser::is_sequence_item(content);
+        if is_sequence || self.split_entry(content)?.is_some() {
+            self.depth += 1;
+            if self.depth > MAX_DEPTH {
+                return Err(depth_error(self.line_number()));
+            }
+
+            let value = match is_sequence {
+                true => self.parse_sequence(indent),
+                false => self.parse_mapping(indent),
+            };
+            self.depth -= 1;
+            return value;
+        }
+
+        self.current += 1;
+        return self.parse_inline(content, indent);
+    }
+
+    // Parses the block belonging to a key or `-` at `parent_indent`, or null
+    // when there is none. A mapping value may be a sequence at the key's own
+    // indentation.
+    fn parse_child(
+        &mut self,
+        parent_indent: usize,
+        allow_sequence: bool,
+    ) -> Result<JsonValue, YamlError> {
+        self.skip_blank();
+        if self.at_end() {
+            return Ok(JsonValue::Null);
+        }
+
+        let indent = self.lines[self.current].indent;
+        if indent > parent_indent
+            || (allow_sequence
+                && indent == parent_indent
+                && YamlParser::is_sequence_item(self.content()))
+        {
+            return self.parse_node();
+        }
+
+        return Ok(JsonValue::Null);
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<JsonValue, YamlError> {
+        let mut array = Vec::new();
+
+        loop {
+            self.skip_blank();
+            if self.at_end() {
+                break;
+            }
+
+            let line = &self.lines[self.current];
+            let content = self.content();
+            if line.indent < indent || !YamlParser::is_sequence_item(content) {
+                break;
+            }
+            if line.indent > indent {
+                return Err(yaml_error("Unexpected indentation", line.number));
+            }
+
+            if content == "-" {
+                self.current += 1;
+                array.push(self.parse_child(indent, false)?);
+                continue;
+            }
+
+            let rest = &line.text[1..];
+            let item = strip_comment(rest).trim_start();
+            if item.starts_with(['|', '>']) {
+                let number = line.number;
+                self.current += 1;
+                array.push(self.parse_block_scalar(item, indent, number)?);
+                continue;
+            }
+
+            // Re-read the rest of the line as a node indented to where its
+            // content starts, so `- a: 1` continues with keys below `a`.
+            let offset = 1 + (rest.len() - rest.trim_start_matches(' ').len());
+            let line = &mut self.lines[self.current];
+            line.indent += offset;
+            line.text = &line.text[offset..];
+
+            array.push(self.parse_node()?);
+        }
+
+        return Ok(JsonValue::Array(array));
+    }
+
+    // Splits `key: rest` off a line, or returns `None` if the line isn't a
+    // mapping entry. Keys are always strings, so `1: a` has the key `"1"`.
+    fn split_entry(&self, content: &'a str) -> Result<Option<(String, &'a str)>, YamlError> {
+        let line = self.line_number();
+
+        if content.starts_with("? ") || content == "?" {
+            return Err(yaml_error("Complex mapping keys are not supported", line));
+        }
+        if content.starts_with(['[', '{']) {
+            return Ok(None);
+        }
+
+        if content.starts_with(['"', '\'']) {
+            let chars: Vec<char> = content.chars().collect();
+            let mut pos = 0;
+            let key = parse_quoted(&chars, &mut pos, line)?;
+            let rest: String = chars[pos..].iter().collect();
+            let rest = rest.trim_start();
+
+            if rest == ":" || rest.starts_with(": ") {
+                let consumed = content.len() - rest.len();
+                return Ok(Some((key, content[consumed + 1..].trim())));
+            }
+            return Ok(None);
+        }
+
+        let mut search = 0;
+        while let Some(index) = content[search..].find(':') {
+            let index = search + index;
+            let after = &content[index + 1..];
+
+            if after.is_empty() || after.starts_with([' ', '\t']) {
+                let key = content[..index].trim_end();
+                if key.is_empty() {
+                    return Err(yaml_error("Empty mapping key", line));
+                }
+                check_plain(key, line)?;
+                return Ok(Some((key.to_string(), after.trim())));
+            }
+
+            search = index + 1;
+        }
+
+        return Ok(None);
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<JsonValue, YamlError> {
+        let mut object = HashMap::new();
+
+        loop {
+            self.skip_blank();
+            if self.at_end() {
+                break;
+            }
+
+            let line_indent = self.lines[self.current].indent;
+            let number = self.lines[self.current].number;
+            if line_indent < indent {
+                break;
+            }
+            if line_indent > indent {
+                return Err(yaml_error("Unexpected indentation", number));
+            }
+
+            let content = self.content();
+            let (key, rest) = match self.split_entry(content)? {
+                Some(entry) => entry,
+                None => return Err(yaml_error("Expected 'key: value'", number)),
+            };
+            self.current += 1;
+
+            let value = if rest.is_empty() {
+                self.parse_child(indent, true)?
+            } else if rest.starts_with(['|', '>']) {
+                self.parse_block_scalar(rest, indent, number)?
+            } else {
+                self.parse_inline(rest, indent)?
+            };
+
+            if object.insert(key, value).is_some() {
+                return Err(yaml_error("Duplicate mapping key", number));
+            }
+        }
+
+        return Ok(JsonValue::Object(object));
+    }
+
+    // Parses a value written on one line after `key:` or `-`. Flow
+    // collections may continue on following lines until their brackets
+    // balance.
+    fn parse_inline(&mut self, text: &str, indent: usize) -> Result<JsonValue, YamlError> {
+        let line = self.lines[self.current - 1].number;
+
+        if text.starts_with(['[', '{']) {
+            let mut source = text.to_string();
+
+            while flow_depth(&source) > 0 {
+                if self.at_end() {
+                    return Err(yaml_error("Unterminated flow collection", line));
+                }
+                source.push(' ');
+                source.push_str(self.content());
+                self.current += 1;
+            }
+
+            let mut flow = Flow {
+                chars: source.chars().collect(),
+                pos: 0,
+                line,
+                depth: self.depth,
+            };
+            let value = flow.value()?;
+            flow.skip_whitespace();
+            if flow.pos < flow.chars.len() {
+                return Err(yaml_error("Unexpected content after flow collection", line));
+            }
+
+            return Ok(value);
+        }
+
+        if text.starts_with(['"', '\'']) {
+            let chars: Vec<char> = text.chars().collect();
+            let mut pos = 0;
+            let string = parse_quoted(&chars, &mut pos, line)?;
+            if pos < chars.len() {
+                return Err(yaml_error("Unexpected content after quoted string", line));
+            }
+
+            return Ok(JsonValue::String(string));
+        }
+
+        if text.starts_with(['|', '>']) {
+            return self.parse_block_scalar(text, indent, line);
+        }
+
+        check_plain(text, line)?;
+
+        // A plain scalar may not continue on more deeply indented lines
+        // here; catching that gives a clearer error than the parent would.
+        self.skip_blank();
+        if !self.at_end() && self.lines[self.current].indent > indent {
+            return Err(yaml_error(
+                "Multi-line plain scalars are not supported",
+                self.line_number(),
+            ));
+        }
+
+        return Ok(resolve_plain(text));
+    }
+
+    fn parse_block_scalar(
+        &mut self,
+        header: &str,
+        parent_indent: usize,
+        line: usize,
+    ) -> Result<JsonValue, YamlError> {
+        let folded = header.starts_with('>');
+        let chomping = match &header[1..] {
+            "" => ' ',
+            "-" => '-',
+            "+" => '+',
+            _ => return Err(yaml_error("Unsupported block scalar header", line)),
+        };
+
+        let mut content_indent = None;
+        let mut lines: Vec<&str> = Vec::new();
+
+        while !self.at_end() {
+            let line = &self.lines[self.current];
+            let blank = line.text.trim().is_empty();
+
+            if !blank && line.indent <= parent_indent {
+                break;
+            }
+            if blank {
+                lines.push("");
+            } else {
+                let indent = *content_indent.get_or_insert(line.indent);
+                if line.indent < indent {
+                    return Err(yaml_error("Unexpected indentation", line.number));
+                }
+                // Indentation beyond the first line's is part of the content.
+                lines.push(&line.raw[indent..]);
+            }
+
+            self.current += 1;
+        }
+
+        let mut trailing = 0;
+        while lines.last() == Some(&"") {
+            lines.pop();
+            trailing += 1;
+        }
+
+        let mut text = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            // Folding joins adjacent lines with a space and drops the break
+            // before a run of empty lines; more-indented lines keep theirs.
+            if index > 0 {
+                let previous = lines[index - 1];
+                let plain = |line: &str| !line.is_empty() && !line.starts_with(' ');
+
+                if folded && plain(line) && plain(previous) {
+                    text.push(' ');
+                } else if !(folded && line.is_empty() && plain(previous)) {
+                    text.push('\n');
+                }
+            }
+            text.push_str(line);
+        }
+
+        match chomping {
+            '-' => {}
+            '+' => {
+                if !lines.is_empty() {
+                    text.push('\n');
+                }
+                for _ in 0..trailing {
+                    text.push('\n');
+                }
+            }
+            _ => {
+                if !lines.is_empty() {
+                    text.push('\n');
+                }
+            }
+        }
+
+        return Ok(JsonValue::String(text));
+    }
+}
+
+fn is_plain_safe(string: &str) -> bool {
+    let first = match string.chars().next() {
+        Some(first) => first,
+        None => return false,
+    };
+
+    if string.trim() != string
+        || "-?:,[]{}#&*!|>'\"%@`".contains(first)
+        || string.starts_with("...")
+        || string.contains(": ")
+        || string.contains(" #")
+        || string.ends_with(':')
+        || string
+            .chars()
+            .any(|c| c.is_control() || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}'))
+    {
+        return false;
+    }
+
+    // Anything that would read back as a null, boolean or number must be
+    // quoted to stay a string.
+    return matches!(resolve_plain(string), JsonValue::String(_));
+}
+
+fn write_string(out: &mut String, string: &str) {
+    if is_plain_safe(string) {
+        out.push_str(string);
+        return;
+    }
+
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}') => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// Writes scalars and empty collections, which always fit on one line.
+// Returns false for anything that needs a block.
+fn write_inline(out: &mut String, value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Boolean(value) => out.push_str(&value.to_string()),
+        JsonValue::Integer(value) => out.push_str(&value.to_string()),
+        JsonValue::Float(value) if value.is_nan() => out.push_str(".nan"),
+        JsonValue::Float(value) if value.is_infinite() => {
+            out.push_str(if *value > 0.0 { ".inf" } else { "-.inf" })
+        }
+        JsonValue::Float(value) => out.push_str(&format_float(*value)),
+        JsonValue::String(value) => write_string(out, value),
+        JsonValue::Array(array) if array.is_empty() => out.push_str("[]"),
+        JsonValue::Object(object) if object.is_empty() => out.push_str("{}"),
+        _ => return false,
+    }
+
+    return true;
+}
+
+// Writes a non-empty collection as a block, every line starting at
+// `indent` spaces.
+fn write_block(out: &mut String, value: &JsonValue, indent: usize) {
+    let padding = " ".repeat(indent);
+
+    match value {
+        JsonValue::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+
+            for key in keys {
+                out.push_str(&padding);
+                write_string(out, key);
+                out.push(':');
+
+                let mut inline = String::from(" ");
+                if write_inline(&mut inline, &object[key]) {
+                    out.push_str(&inline);
+                    out.push('\n');
+                } else {
+                    out.push('\n');
+                    write_block(out, &object[key], indent + 2);
+                }
+            }
+        }
+        JsonValue::Array(array) => {
+            for element in array {
+                out.push_str(&padding);
+                out.push('-');
+
+                let mut inline = String::from(" ");
+                if write_inline(&mut inline, element) {
+                    out.push_str(&inline);
+                    out.push('\n');
+                } else {
+                    // A nested collection starts on the dash's line.
+                    let mut block = String::new();
+                    write_block(&mut block, element, indent + 2);
+                    out.push(' ');
+                    out.push_str(&block[indent + 2..]);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Writes `value` as a block-style YAML document. Object keys are sorted,
+/// strings are quoted only when a plain scalar would read back differently,
+/// and non-finite floats use YAML's `.inf` and `.nan`.
+pub fn to_yaml_string(value: &JsonValue) -> String {
+    let mut output = String::new();
+
+    if write_inline(&mut output, value) {
+        output.push('\n');
+    } else {
+        write_block(&mut output, value, 0);
+    }
+
+    return output;
+}
+
+/// Parses a single YAML document using the 1.2 core schema: block and flow
+/// collections, quoted, plain and block scalars. Anchors, aliases, tags,
+/// complex keys and multiple documents are rejected, and so are duplicate
+/// keys.
+pub fn from_yaml_str(source: &str) -> Result<JsonValue, YamlError> {
+    let mut parser = YamlParser::new(source)?;
+
+    return parser.parse_document();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+    use crate::json::{parse_from_str, JsonValue};
+    use crate::yaml::{from_yaml_str, to_yaml_string};
+
+    const FIXTURES: [&str; 2] = [
+        include_str!("../tests/fixtures/goessner.json"),
+        include_str!("../tests/fixtures/schema-test-suite.json"),
+    ];
+
+    fn error(source: &str) -> String {
+        return from_yaml_str(source).unwrap_err().to_string();
+    }
+
+    #[test]
+    fn fixtures_round_trip_through_yaml() {
+        for fixture in FIXTURES {
+            let value = JsonValue::from(parse_from_str(fixture).unwrap());
+            let yaml = to_yaml_string(&value);
+
+            assert_eq!(from_yaml_str(&yaml), Ok(value), "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn nested_documents_are_written_as_blocks() {
+        let value = json!({
+            "name": "app",
+            "ports": [80, 443],
+            "env": {"debug": false, "ratio": 0.5, "empty": {}},
+            "users": [{"id": 1, "tags": []}, [null]]
+        });
+
+        assert_eq!(
+            to_yaml_string(&value),
+            "env:\n  debug: false\n  empty: {}\n  ratio: 0.5\n\
+             name: app\n\
+             ports:\n  - 80\n  - 443\n\
+             users:\n  - id: 1\n    tags: []\n  - - null\n"
+        );
+        assert_eq!(to_yaml_string(&json!("plain")), "plain\n");
+    }
+
+    #[test]
+    fn special_strings_are_quoted() {
+        let value = json!({
+            "key: colon": "a: b",
+            "#hash": "x #y",
+            "yes": "true",
+            "n": "null",
+            "number": "1.5",
+            "hex": "0x1f",
+            "dash": "- item",
+            "empty": "",
+            "space": " padded",
+            "line": "a\nb\t\u{1}"
+        });
+        let yaml = to_yaml_string(&value);
+
+        for quoted in [
+            "\"key: colon\": \"a: b\"",
+            "\"#hash\": \"x #y\"",
+            "yes: \"true\"",
+            "n: \"null\"",
+            "number: \"1.5\"",
+            "hex: \"0x1f\"",
+            "dash: \"- item\"",
+            "empty: \"\"",
+            "space: \" padded\"",
+            "line: \"a\\nb\\t\\u0001\"",
+        ] {
+            assert!(yaml.contains(quoted), "{} in\n{}", quoted, yaml);
+        }
+        assert_eq!(from_yaml_str(&yaml), Ok(value));
+    }
+
+    #[test]
+    fn scalars_resolve_with_the_core_schema() {
+        let yaml = "\
+a: ~
+b: yes
+c: True
+d: 0x10
+e: 0o17
+f: -.inf
+g: 1e3
+h: 007
+i: 'it''s'
+j: \"tab\\t\"
+k: [1, {x: y}]   # a comment
+l: |
+  line one
+  line two
+m: >-
+  folded
+  text
+";
+
+        let value = from_yaml_str(yaml).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "a": null,
+                "b": "yes",
+                "c": true,
+                "d": 16,
+                "e": 15,
+                "f": f64::NEG_INFINITY,
+                "g": 1000.0,
+                "h": 7,
+                "i": "it's",
+                "j": "tab\t",
+                "k": [1, {"x": "y"}],
+                "l": "line one\nline two\n",
+                "m": "folded text"
+            })
+        );
+    }
+
+    #[test]
+    fn unsupported_features_are_rejected_with_their_line() {
+        assert_eq!(
+            error("a: 1\nb: &anchor 2\n"),
+            "[YAML error at line 2]: Anchors and aliases are not supported"
+        );
+        assert_eq!(
+            error("a: *anchor\n"),
+            "[YAML error at line 1]: Anchors and aliases are not supported"
+        );
+        assert_eq!(
+            error("a: !!str 1\n"),
+            "[YAML error at line 1]: Tags are not supported"
+        );
+        assert_eq!(
+            error("a: 1\na: 2\n"),
+            "[YAML error at line 2]: Duplicate mapping key"
+        );
+        assert_eq!(
+            error("{[1]: 2}"),
+            "[YAML error at line 1]: Mapping keys must be scalars"
+        );
+        assert_eq!(
+            error("a:\n\t- 1\n"),
+            "[YAML error at line 2]: Tabs are not allowed in indentation"
+        );
+        assert_eq!(
+            error("a: \"open\n"),
+            "[YAML error at line 1]: Unterminated quoted string"
+        );
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        let yaml = "[".repeat(1000) + &"]".repeat(1000);
+
+        assert!(from_yaml_str(&yaml)
+            .unwrap_err()
+            .message
+            .starts_with("Nested more than"));
+    }
+}
@@ -0,0 +1,155 @@
+#![allow(clippy::needless_return)]
+
+// Uses only what the crate exports, as a dependent project would.
+use std::{error::Error, io, path::Path};
+
+use mini_json::{
+    json, parse_from_bytes, parse_from_file, parse_from_reader, parse_from_str,
+    parse_from_str_with, parse_from_string, Indent, Json, JsonError, JsonValue, OutputStyle,
+    ParseError, ParseErrorKind, ParserOptions, SerializeOptions,
+};
+
+// A file in tests/data.
+fn data(name: &str) -> String {
+    return format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name);
+}
+
+#[test]
+fn files_parse_into_documents() {
+    let array = parse_from_file(data("array.json")).unwrap();
+    assert_eq!(
+        array,
+        Json::Array(vec![
+            JsonValue::Integer(1),
+            JsonValue::from("two"),
+            JsonValue::Null
+        ])
+    );
+
+    let nested = JsonValue::from(parse_from_file(Path::new(&data("nested.json"))).unwrap());
+    assert_eq!(nested, json!({"outer": {"inner": [true, 2.5]}}));
+    assert_eq!(nested.pointer("/outer/inner/1"), Some(&json!(2.5)));
+}
+
+#[test]
+fn file_errors_say_which_file_and_why() {
+    match parse_from_file(data("trailing-comma.json")) {
+        Err(JsonError::Parse {
+            path: Some(path),
+            error,
+        }) => {
+            assert_eq!(path, Path::new(&data("trailing-comma.json")));
+            assert_eq!(
+                error,
+                ParseError {
+                    kind: ParseErrorKind::UnexpectedCharacter {
+                        found: '}',
+                        expected: "'\"' to start a key"
+                    },
+                    line: 1,
+                    column: 9,
+                }
+            );
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    let missing = parse_from_file(data("missing.json")).unwrap_err();
+    match &missing {
+        JsonError::Io {
+            path: Some(path),
+            error,
+        } => {
+            assert_eq!(path, Path::new(&data("missing.json")));
+            assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        }
+        other => panic!("expected an IO error, got {:?}", other),
+    }
+    assert!(missing.to_string().starts_with(&data("missing.json")));
+    assert!(missing.source().is_some());
+}
+
+#[test]
+fn readers_bytes_and_strings_parse_alike() {
+    let source = r#"{"a": [1, "é"]}"#;
+    let expected = parse_from_str(source).unwrap();
+
+    assert_eq!(parse_from_reader(source.as_bytes()).unwrap(), expected);
+    assert_eq!(parse_from_bytes(source.as_bytes()).unwrap(), expected);
+    assert_eq!(parse_from_string(source.to_string()), expected);
+    assert_eq!(source.parse::<Json>().unwrap(), expected);
+
+    match parse_from_reader(&b"[\"\xff\"]"[..]) {
+        Err(JsonError::Io { path: None, error }) => {
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+        other => panic!("expected an IO error, got {:?}", other),
+    }
+    let error = parse_from_bytes(b"[\"\xff\"]").unwrap_err();
+    assert!(matches!(error.kind, ParseErrorKind::InvalidUtf8 { .. }));
+    assert!(error.source().is_some());
+}
+
+#[test]
+#[should_panic(expected = "Unexpected end of input")]
+fn parse_from_string_panics_on_bad_input() {
+    parse_from_string("[1, 2".to_string());
+}
+
+#[test]
+fn errors_work_with_the_question_mark() {
+    fn first_tag(source: &str) -> Result<String, Box<dyn Error>> {
+        let value = JsonValue::from(parse_from_str(source)?);
+        let tag = value.pointer("/tags/0").ok_or("no tags")?;
+        return Ok(tag.to_string());
+    }
+
+    assert_eq!(first_tag(r#"{"tags": ["x"]}"#).unwrap(), "\"x\"");
+    assert_eq!(first_tag("{}").unwrap_err().to_string(), "no tags");
+    assert_eq!(
+        first_tag("{").unwrap_err().to_string(),
+        "[Error at line 1, column 1]: Unexpected end of input, expected '\"' to start a key"
+    );
+}
+
+#[test]
+fn parser_options_are_public() {
+    let source = "[\"a\tb\", 5]";
+    assert!(matches!(
+        parse_from_str(source).unwrap_err().kind,
+        ParseErrorKind::UnescapedControlCharacter { found: '\t' }
+    ));
+
+    let options = ParserOptions {
+        strict: false,
+        numbers_as_f64: true,
+        ..ParserOptions::default()
+    };
+    assert_eq!(
+        JsonValue::from(parse_from_str_with(source, &options).unwrap()),
+        json!(["a\tb", 5.0])
+    );
+}
+
+#[test]
+fn serialize_options_are_public() {
+    let value = JsonValue::from(parse_from_file(data("nested.json")).unwrap());
+
+    let mut options = SerializeOptions::new(OutputStyle::Pretty);
+    options.indent = Indent::Tab;
+    options.sort_keys = true;
+    assert_eq!(
+        value.to_string_with(&options).unwrap(),
+        "{\n\t\"outer\": {\n\t\t\"inner\": [\n\t\t\ttrue,\n\t\t\t2.5\n\t\t]\n\t}\n}"
+    );
+
+    let options = SerializeOptions::new(OutputStyle::Minified);
+    assert_eq!(
+        value.to_string_with(&options).unwrap(),
+        r#"{"outer":{"inner":[true,2.5]}}"#
+    );
+    assert_eq!(
+        value.to_string_minified().unwrap(),
+        r#"{"outer":{"inner":[true,2.5]}}"#
+    );
+}
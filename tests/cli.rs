@@ -0,0 +1,1011 @@
+#![allow(clippy::needless_return)]
+
+// Runs the built binary against files in a scratch directory per test.
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+use mini_json::{json, parse_from_str, strip_ansi, JsonValue};
+
+struct Dir {
+    path: PathBuf,
+}
+
+impl Dir {
+    fn new(name: &str) -> Dir {
+        let path =
+            std::env::temp_dir().join(format!("mini-json-cli-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        return Dir { path };
+    }
+
+    fn file(&self, name: &str, contents: &str) -> String {
+        let path = self.path.join(name);
+        fs::write(&path, contents).unwrap();
+        return path.to_string_lossy().into_owned();
+    }
+
+    fn read(&self, name: &str) -> String {
+        return fs::read_to_string(self.path.join(name)).unwrap();
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn run(args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_mini-json"))
+        .args(args)
+        .output()
+        .unwrap();
+}
+
+fn run_with_stdin(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mini-json"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    return child.wait_with_output().unwrap();
+}
+
+// A file in tests/data.
+fn data(name: &str) -> String {
+    return format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name);
+}
+
+fn stdout(output: &Output) -> String {
+    return String::from_utf8_lossy(&output.stdout).into_owned();
+}
+
+fn stderr(output: &Output) -> String {
+    return String::from_utf8_lossy(&output.stderr).into_owned();
+}
+
+#[test]
+fn files_are_printed_in_argument_order() {
+    let (array, nested) = (data("array.json"), data("nested.json"));
+
+    let output = run(&[&nested, &array]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}: {{\n  \"outer\": {{\n    \"inner\": [\n      true,\n      2.5\n    ]\n  }}\n}}\n\
+             {}: [\n  1,\n  \"two\",\n  null\n]\n",
+            nested, array
+        )
+    );
+    assert_eq!(stderr(&output), "");
+
+    let output = run(&[&array]);
+    assert_eq!(stdout(&output), "[\n  1,\n  \"two\",\n  null\n]\n");
+}
+
+#[test]
+fn failing_files_are_named_and_the_rest_still_print() {
+    let (array, invalid) = (data("array.json"), data("trailing-comma.json"));
+    let missing = data("missing.json");
+
+    let output = run(&[&invalid, &missing, &array]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with(&format!("{}: [", array)));
+
+    let errors = stderr(&output);
+    let lines: Vec<&str> = errors.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", errors);
+    assert!(
+        lines[0].starts_with(&format!("{}:1:9: ", invalid)),
+        "{}",
+        errors
+    );
+    assert!(
+        lines[1].starts_with(&format!("{}: ", missing)),
+        "{}",
+        errors
+    );
+}
+
+#[test]
+fn standard_input_is_read_without_a_file_or_for_a_dash() {
+    for args in [&[][..], &["-"][..]] {
+        let output = run_with_stdin(args, "{\"a\": [1, {}]}");
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output), "{\n  \"a\": [\n    1,\n    {}\n  ]\n}\n");
+        assert_eq!(stderr(&output), "");
+    }
+
+    let array = data("array.json");
+    let output = run_with_stdin(&[&array, "-"], "[true]");
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}: [\n  1,\n  \"two\",\n  null\n]\n<stdin>: [\n  true\n]\n",
+            array
+        )
+    );
+}
+
+#[test]
+fn standard_input_errors_give_the_line_and_column() {
+    let output = run_with_stdin(&[], "{\"a\":\n [1,}");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(
+        stderr(&output),
+        "<stdin>:2:5: Unexpected '}', expected a value\n"
+    );
+
+    for empty in ["", "  \n"] {
+        let output = run_with_stdin(&["-"], empty);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr(&output).ends_with(": Empty document\n"));
+    }
+}
+
+#[test]
+fn compact_and_pretty_print_the_same_document() {
+    let nested = data("nested.json");
+
+    let compact = run(&["--compact", &nested]);
+    assert_eq!(compact.status.code(), Some(0));
+    assert_eq!(stdout(&compact), "{\"outer\":{\"inner\":[true,2.5]}}\n");
+
+    let pretty = run(&["--pretty", &nested]);
+    assert_eq!(stdout(&pretty), stdout(&run(&[&nested])));
+    assert_eq!(
+        stdout(&pretty),
+        "{\n  \"outer\": {\n    \"inner\": [\n      true,\n      2.5\n    ]\n  }\n}\n"
+    );
+
+    let expected = parse_from_str(&fs::read_to_string(&nested).unwrap()).unwrap();
+    for output in [compact, pretty] {
+        assert_eq!(parse_from_str(&stdout(&output)), Ok(expected.clone()));
+    }
+}
+
+#[test]
+fn conflicting_or_unknown_flags_are_usage_errors() {
+    let array = data("array.json");
+
+    let output = run(&["--pretty", "--compact", &array]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output)
+        .starts_with("mini-json: --pretty and --compact can't be used together\n\nUsage:"));
+
+    // Repeating the same flag is fine.
+    let output = run(&["--compact", "--compact", &array]);
+    assert_eq!(stdout(&output), "[1,\"two\",null]\n");
+
+    let output = run(&["--compcat", &array]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("mini-json: unknown option '--compcat'\n"));
+
+    // After `--`, anything is a file name.
+    let output = run(&["--", "--compact"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("--compact: "));
+}
+
+#[test]
+fn help_prints_the_usage() {
+    let output = run(&["--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("Usage: mini-json [OPTIONS] [FILE]..."));
+}
+
+#[test]
+fn query_prints_scalars_and_objects() {
+    let dir = Dir::new("query");
+    let users = dir.file(
+        "users.json",
+        r#"{"users": [{"name": "ada", "langs": {"first": "en"}}], "a/b": {"c~d": 7}}"#,
+    );
+
+    let output = run(&["--query", "/users/0/name", &users]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "\"ada\"\n");
+
+    let output = run(&["--query", "/users/0/langs", &users]);
+    assert_eq!(stdout(&output), "{\n  \"first\": \"en\"\n}\n");
+
+    let output = run(&["--compact", "--query", "/users/0/langs", &users]);
+    assert_eq!(stdout(&output), "{\"first\":\"en\"}\n");
+
+    let output = run(&["--query", "/a~1b/c~0d", &users]);
+    assert_eq!(stdout(&output), "7\n");
+}
+
+#[test]
+fn repeated_queries_print_one_result_per_line() {
+    let dir = Dir::new("query-repeated");
+    let users = dir.file("users.json", r#"{"a": [1, 2], "b": "x"}"#);
+
+    let output = run(&[
+        "--compact",
+        "--query",
+        "/b",
+        "--query",
+        "/a",
+        "--query",
+        "/a/1",
+        &users,
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "\"x\"\n[1,2]\n2\n");
+}
+
+#[test]
+fn missing_pointers_exit_with_their_own_code() {
+    let dir = Dir::new("query-missing");
+    let users = dir.file("users.json", r#"{"a": [1]}"#);
+    let invalid = dir.file("invalid.json", "{");
+
+    let output = run(&["--query", "/a/5", "--query", "/a/0", &users]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output), "1\n");
+    assert_eq!(stderr(&output), format!("{}: nothing at '/a/5'\n", users));
+
+    let output = run(&["--query", "/a/5", "--skip-missing", &users]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output), "");
+
+    // A parse failure is a different status.
+    let output = run(&["--query", "/a", &invalid]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = run(&["--query", "a", &users]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("'a' isn't a JSON Pointer"));
+}
+
+#[test]
+fn sort_keys_sorts_every_level() {
+    let dir = Dir::new("sort-keys");
+    let messy = dir.file(
+        "messy.json",
+        r#"{"b": {"z": 1, "é": 2, "Z": 3}, "a": [{"y": 1, "x": 2}], "B": null}"#,
+    );
+
+    let output = run(&["--sort-keys", "--compact", &messy]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "{\"B\":null,\"a\":[{\"x\":2,\"y\":1}],\"b\":{\"Z\":3,\"z\":1,\"é\":2}}\n"
+    );
+}
+
+#[test]
+fn sort_keys_output_is_a_fixed_point() {
+    let dir = Dir::new("sort-keys-fixed");
+    let source = r#"{"k": {"c": [3, {"f": 1, "e": 2}], "b": 2.5, "a": "x"}, "j": []}"#;
+
+    for style in ["--pretty", "--compact"] {
+        let first = stdout(&run(&["--sort-keys", style, &dir.file("in.json", source)]));
+        let second = stdout(&run(&["--sort-keys", style, &dir.file("out.json", &first)]));
+        assert_eq!(second, first);
+
+        // The order can't depend on the map's iteration order.
+        for _ in 0..5 {
+            let again = stdout(&run(&["--sort-keys", style, &dir.file("in.json", source)]));
+            assert_eq!(again, first);
+        }
+    }
+}
+
+const RECORDS: &str =
+    "{\"id\": 1, \"tags\": [\"a\"]}\n\n  \n[1, 2]\n{\"id\": 2,}\n\"str\"\n{\"id\": 3}\n";
+
+#[test]
+fn ndjson_prints_one_compact_record_per_line() {
+    let output = run_with_stdin(&["--ndjson", "--sort-keys"], RECORDS);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "{\"id\":1,\"tags\":[\"a\"]}\n[1,2]\n\"str\"\n{\"id\":3}\n"
+    );
+    assert_eq!(
+        stderr(&output),
+        "<stdin>:5:10: Unexpected '}', expected '\"' to start a key\n"
+    );
+
+    let output = run_with_stdin(&["--ndjson"], "{\"a\": [\n1]}\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).starts_with("<stdin>:1:"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn ndjson_validate_reports_the_bad_line() {
+    let dir = Dir::new("ndjson-validate");
+    let records = dir.file("records.ndjson", RECORDS);
+    let good = dir.file("good.ndjson", "{}\n\n[]\n");
+
+    let output = run(&["--ndjson", "--validate", &good]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+
+    let output = run(&["--ndjson", "--validate", &records]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(
+        stderr(&output),
+        format!(
+            "{}:5:10: Unexpected '}}', expected '\"' to start a key\n",
+            records
+        )
+    );
+}
+
+#[test]
+fn ndjson_queries_every_record() {
+    let output = run_with_stdin(&["--ndjson", "--query", "/id"], RECORDS);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "1\n3\n");
+    assert_eq!(
+        stderr(&output),
+        "<stdin>:4: nothing at '/id'\n<stdin>:5:10: Unexpected '}', expected '\"' to start a key\n<stdin>:6: nothing at '/id'\n"
+    );
+
+    let valid = "{\"id\": 1}\n[]\n{\"id\": 2}\n";
+    let output = run_with_stdin(&["--ndjson", "--query", "/id"], valid);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output), "1\n2\n");
+
+    let output = run_with_stdin(&["--ndjson", "--skip-missing", "--query", "/id"], valid);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "1\n2\n");
+    assert_eq!(stderr(&output), "");
+}
+
+// Wraps `text` in the escape codes for `code`.
+fn paint(code: &str, text: &str) -> String {
+    return format!("\x1b[{}m{}\x1b[0m", code, text);
+}
+
+#[test]
+fn color_always_uses_the_default_theme() {
+    let dir = Dir::new("color");
+    let file = dir.file("c.json", r#"{"a": [1, true, null, "s"]}"#);
+
+    let punctuation = |text| paint("1;39", text);
+    let expected = [
+        punctuation("{"),
+        paint("34;1", "\"a\""),
+        punctuation(":"),
+        punctuation("["),
+        paint("0;39", "1"),
+        punctuation(","),
+        paint("0;39", "true"),
+        punctuation(","),
+        paint("1;30", "null"),
+        punctuation(","),
+        paint("0;32", "\"s\""),
+        punctuation("]"),
+        punctuation("}"),
+    ]
+    .concat();
+
+    for flag in [&["--color", "always"][..], &["--color=always"]] {
+        let output = run(&[flag, &["--compact", &file]].concat());
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output), format!("{}\n", expected));
+    }
+}
+
+#[test]
+fn colored_output_strips_to_the_plain_output() {
+    let dir = Dir::new("color-strip");
+    let file = dir.file(
+        "c.json",
+        r#"{"k": {"n": -1.5e3, "s": "\u001b[0m"}, "l": []}"#,
+    );
+
+    for style in ["--pretty", "--compact"] {
+        let colored = stdout(&run(&["--color", "always", "--sort-keys", style, &file]));
+        let plain = stdout(&run(&["--color", "never", "--sort-keys", style, &file]));
+        assert!(colored.contains('\x1b'));
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(strip_ansi(&colored), plain);
+    }
+
+    let colored = stdout(&run(&["--color", "always", "--compact", &file]));
+    assert_eq!(colored.lines().count(), 1);
+}
+
+#[test]
+fn color_auto_is_plain_when_piped_and_always_ignores_no_color() {
+    let dir = Dir::new("color-auto");
+    let file = dir.file("c.json", "[1]");
+
+    assert_eq!(stdout(&run(&["--compact", &file])), "[1]\n");
+    assert_eq!(
+        stdout(&run(&["--color", "auto", "--compact", &file])),
+        "[1]\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mini-json"))
+        .args(["--color", "always", "--compact", &file])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(stdout(&output).contains('\x1b'));
+
+    let output = run(&["--color", "sometimes", &file]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--color takes always, never or auto"));
+}
+
+#[test]
+fn indent_sets_the_width_or_uses_tabs() {
+    let dir = Dir::new("indent");
+    let file = dir.file("f.json", r#"{"b": [1, {"c": null}], "a": {}}"#);
+    // The output with tabs, which every other indent replaces.
+    let expected = |unit: &str| {
+        return "{\n\t\"a\": {},\n\t\"b\": [\n\t\t1,\n\t\t{\n\t\t\t\"c\": null\n\t\t}\n\t]\n}\n"
+            .replace('\t', unit);
+    };
+
+    let mut outputs = Vec::new();
+    for (width, unit) in [("2", "  "), ("4", "    "), ("tab", "\t")] {
+        let output = run(&["--sort-keys", "--indent", width, &file]);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output), expected(unit));
+        outputs.push(parse_from_str(&stdout(&output)).unwrap());
+    }
+    assert_eq!(stdout(&run(&["--sort-keys", &file])), expected("  "));
+    assert!(outputs.iter().all(|output| *output == outputs[0]));
+}
+
+#[test]
+fn indent_is_capped_and_needs_a_width() {
+    let dir = Dir::new("indent-cap");
+    let file = dir.file("f.json", "[1]");
+
+    assert_eq!(
+        stdout(&run(&["--indent", "16", &file])),
+        format!("[\n{}1\n]\n", " ".repeat(16))
+    );
+
+    for width in ["17", "-1", "x"] {
+        let output = run(&["--indent", width, &file]);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(stderr(&output).contains("--indent takes a number of spaces up to 16 or 'tab'"));
+    }
+
+    let output = run(&["--indent"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--indent needs a width"));
+}
+
+#[test]
+fn write_uses_the_indent() {
+    let dir = Dir::new("indent-write");
+    let file = dir.file("f.json", r#"{"b": [1], "a": 2}"#);
+
+    let output = run(&["--write", "--indent", "tab", &file]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        dir.read("f.json"),
+        "{\n\t\"a\": 2,\n\t\"b\": [\n\t\t1\n\t]\n}\n"
+    );
+}
+
+// A tree of files with one invalid file, a hidden directory and files
+// that only count with `--ext`.
+fn tree(dir: &Dir) -> String {
+    for sub in ["sub/deeper", ".hidden"] {
+        fs::create_dir_all(dir.path.join(sub)).unwrap();
+    }
+    dir.file("b.json", "[1]");
+    dir.file("a.json", r#"{"a": 1}"#);
+    dir.file("notes.txt", "not json");
+    dir.file("sub/bad.json", r#"{"x":}"#);
+    dir.file("sub/deeper/map.geojson", r#"{"type": "Point"}"#);
+    dir.file("sub/deeper/z.json", "[2]");
+    dir.file(".hidden/h.json", "[3]");
+    return dir.path.to_string_lossy().into_owned();
+}
+
+#[test]
+fn directories_are_searched_in_sorted_order() {
+    let dir = Dir::new("tree");
+    let root = tree(&dir);
+
+    let output = run(&["--compact", &root]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{0}/a.json: {{\"a\":1}}\n{0}/b.json: [1]\n{0}/sub/deeper/z.json: [2]\n",
+            root
+        )
+    );
+    assert_eq!(
+        stderr(&output),
+        format!(
+            "{}/sub/bad.json:1:6: Unexpected '}}', expected a value\n",
+            root
+        )
+    );
+
+    let output = run(&["--compact", "--no-filename", &root]);
+    assert_eq!(stdout(&output), "{\"a\":1}\n[1]\n[2]\n");
+    assert!(stderr(&output).starts_with(&format!("{}/sub/bad.json:", root)));
+}
+
+#[test]
+fn ext_adds_extensions_to_the_search() {
+    let dir = Dir::new("tree-ext");
+    let root = tree(&dir);
+
+    let output = run(&["--compact", "--no-filename", "--ext", "geojson", &root]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "{\"a\":1}\n[1]\n{\"type\":\"Point\"}\n[2]\n"
+    );
+
+    // Named files are read whatever their extension.
+    let notes = dir.path.join("notes.txt");
+    let output = run(&[notes.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains("notes.txt:1:1: "),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn jobs_keep_the_output_in_order() {
+    let dir = Dir::new("tree-jobs");
+    let root = tree(&dir);
+    for index in 0..20 {
+        dir.file(&format!("sub/n{:02}.json", index), &format!("[{}]", index));
+    }
+
+    let serial = run(&["--compact", "--ext", "geojson", &root]);
+    for jobs in ["2", "8"] {
+        let parallel = run(&["--compact", "--ext", "geojson", "--jobs", jobs, &root]);
+        assert_eq!(parallel.status.code(), Some(1));
+        assert_eq!(stdout(&parallel), stdout(&serial));
+        assert_eq!(stderr(&parallel), stderr(&serial));
+    }
+    assert_eq!(stdout(&serial).lines().count(), 24);
+
+    assert_eq!(run(&["--jobs", "0", &root]).status.code(), Some(2));
+}
+
+#[test]
+fn output_writes_the_result_to_a_file() {
+    let dir = Dir::new("output");
+    let input = dir.file("in.json", r#"{"b": {"c": [1, 2]}, "a": 1}"#);
+    let out = dir.path.join("out.json");
+    let out = out.to_str().unwrap();
+
+    let output = run(&["--compact", "--sort-keys", "-o", out, &input]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(dir.read("out.json"), "{\"a\":1,\"b\":{\"c\":[1,2]}}\n");
+
+    let output = run(&[
+        "--query",
+        "/b/c",
+        "--compact",
+        &format!("--output={}", out),
+        &input,
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(dir.read("out.json"), "[1,2]\n");
+
+    let output = run(&["-o"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("-o needs a file"));
+}
+
+#[test]
+fn output_is_left_alone_when_an_input_fails() {
+    let dir = Dir::new("output-fails");
+    let input = dir.file("in.json", "[1]");
+    let bad = dir.file("bad.json", "{,}");
+    let out = dir.file("out.json", "old");
+
+    let output = run(&["-o", &out, &input, &bad]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert!(
+        stderr(&output).contains("bad.json:1:2: "),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(dir.read("out.json"), "old");
+}
+
+#[test]
+fn create_dirs_makes_the_output_parents() {
+    let dir = Dir::new("output-dirs");
+    let input = dir.file("in.json", "[1]");
+    let out = dir.path.join("x/y/out.json");
+    let out = out.to_str().unwrap();
+
+    let output = run(&["--compact", "-o", out, &input]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with(out), "{}", stderr(&output));
+
+    let output = run(&["--compact", "--create-dirs", "-o", out, &input]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(dir.read("x/y/out.json"), "[1]\n");
+
+    let output = run(&["--create-dirs", &input]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--create-dirs needs --output"));
+}
+
+#[test]
+fn output_may_replace_its_own_input() {
+    let dir = Dir::new("output-same");
+    let input = dir.file("in.json", r#"{"b": {"c": [1, 2]}, "a": 1}"#);
+
+    let output = run(&["--sort-keys", "-o", &input, &input]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        dir.read("in.json"),
+        "{\n  \"a\": 1,\n  \"b\": {\n    \"c\": [\n      1,\n      2\n    ]\n  }\n}\n"
+    );
+
+    let output = run(&["--query", "/b", "--compact", "-o", &input, &input]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(dir.read("in.json"), "{\"c\":[1,2]}\n");
+}
+
+const STATS_FIXTURE: &str = r#"{"users": [{"name": "ab", "tags": ["x", "y"]}, {"name": "c", "tags": []}], "count": 2, "ok": true, "ratio": 0.5, "none": null}"#;
+
+#[test]
+fn stats_prints_a_summary() {
+    let dir = Dir::new("stats");
+    let file = dir.file("s.json", STATS_FIXTURE);
+
+    let output = run(&["stats", &file]);
+    assert_eq!(output.status.code(), Some(0));
+    // The heap estimate depends on the platform.
+    let text = stdout(&output);
+    let summary: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.starts_with("estimated heap bytes:"))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            "nodes:                14",
+            "  objects:            3",
+            "  arrays:             3",
+            "  strings:            4",
+            "  integers:           1",
+            "  floats:             1",
+            "  booleans:           1",
+            "  nulls:              1",
+            "max depth:            4",
+            "longest array:        2",
+            "distinct keys:        7",
+            "string bytes:         42",
+            "top keys:",
+            "  name   2",
+            "  tags   2",
+            "  count  1",
+            "  none   1",
+            "  ok     1",
+            "  ratio  1",
+            "  users  1",
+        ]
+    );
+}
+
+#[test]
+fn stats_json_parses() {
+    let output = run_with_stdin(&["stats", "--json"], STATS_FIXTURE);
+    assert_eq!(output.status.code(), Some(0));
+
+    let mut summary = JsonValue::from(parse_from_str(&stdout(&output)).unwrap());
+    assert!(matches!(
+        summary.remove("estimated_heap_bytes").unwrap(),
+        Some(JsonValue::Integer(bytes)) if bytes > 0
+    ));
+    assert_eq!(
+        summary,
+        json!({
+            "distinct_keys": 7,
+            "longest_array": 2,
+            "max_depth": 4,
+            "nodes": {
+                "arrays": 3,
+                "booleans": 1,
+                "floats": 1,
+                "integers": 1,
+                "nulls": 1,
+                "objects": 3,
+                "strings": 4,
+                "total": 14
+            },
+            "string_bytes": 42,
+            "top_keys": [
+                {"count": 2, "key": "name"},
+                {"count": 2, "key": "tags"},
+                {"count": 1, "key": "count"},
+                {"count": 1, "key": "none"},
+                {"count": 1, "key": "ok"},
+                {"count": 1, "key": "ratio"},
+                {"count": 1, "key": "users"}
+            ]
+        })
+    );
+}
+
+#[test]
+fn stats_takes_one_document() {
+    let dir = Dir::new("stats-args");
+    let file = dir.file("s.json", "[]");
+    let bad = dir.file("bad.json", "[");
+
+    let output = run(&["stats", &file, &file]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("stats takes one file"));
+
+    assert_eq!(run(&["stats", "--bogus"]).status.code(), Some(2));
+    assert_eq!(run(&["stats", &bad]).status.code(), Some(1));
+}
+
+#[test]
+fn validate_accepts_a_good_file_silently() {
+    let dir = Dir::new("validate-good");
+    let good = dir.file("good.json", "{\"a\": [1, 2.5, true, null]}\n");
+
+    let output = run(&["--validate", &good]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn validate_reports_every_bad_file() {
+    let dir = Dir::new("validate-bad");
+    let good = dir.file("good.json", "[]");
+    let bad_inputs = [
+        "{garbage}",
+        "{\"a\":1 \"b\":2}",
+        "[1 2 3]",
+        "{\"a\":1,}",
+        "{,,\"a\":true}",
+        "[01, 1.]",
+        "[1],",
+        "{\"a\":[1]},",
+    ];
+
+    let mut args = vec!["--validate".to_string(), good];
+    for (i, source) in bad_inputs.iter().enumerate() {
+        args.push(dir.file(&format!("bad{}.json", i), source));
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let output = run(&args);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+
+    let errors = stderr(&output);
+    assert_eq!(errors.lines().count(), bad_inputs.len(), "{}", errors);
+    for (i, line) in errors.lines().enumerate() {
+        assert!(line.contains(&format!("bad{}.json:1:", i)), "{}", line);
+    }
+}
+
+#[test]
+fn validate_reports_a_missing_file() {
+    let dir = Dir::new("validate-missing");
+    let good = dir.file("good.json", "{}");
+    let missing = dir.path.join("missing.json");
+    let missing = missing.to_str().unwrap();
+
+    let output = run(&["--validate", missing, &good]);
+    assert_eq!(output.status.code(), Some(1));
+    let errors = stderr(&output);
+    assert!(errors.starts_with(&format!("{}: ", missing)), "{}", errors);
+    assert_eq!(errors.lines().count(), 1);
+}
+
+#[test]
+fn write_formats_files_in_place() {
+    let dir = Dir::new("write");
+    let formatted = dir.file("formatted.json", "{\n  \"a\": 1\n}\n");
+    let messy = dir.file("messy.json", r#"{"b":1,"a":2,"c":3,"d":[1,2]}"#);
+    let invalid = dir.file("invalid.json", "{\"a\":1,}");
+
+    let output = run(&["--write", &formatted, &messy, &invalid]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "formatted 1 file, 1 unchanged\n");
+    assert!(stderr(&output).contains("invalid.json:1:"));
+
+    assert_eq!(dir.read("formatted.json"), "{\n  \"a\": 1\n}\n");
+    assert_eq!(
+        dir.read("messy.json"),
+        "{\n  \"a\": 2,\n  \"b\": 1,\n  \"c\": 3,\n  \"d\": [\n    1,\n    2\n  ]\n}\n"
+    );
+    assert_eq!(dir.read("invalid.json"), "{\"a\":1,}");
+}
+
+#[test]
+fn write_settles_after_one_run() {
+    let dir = Dir::new("write-settles");
+    let messy = dir.file(
+        "messy.json",
+        r#"{"b":1,"a":2,"c":3,"d":[1,2],"e":{"z":1,"y":2}}"#,
+    );
+
+    let first = run(&["--write", &messy]);
+    assert_eq!(stdout(&first), "formatted 1 file, 0 unchanged\n");
+
+    for _ in 0..3 {
+        let again = run(&["--write", &messy]);
+        assert_eq!(again.status.code(), Some(0));
+        assert_eq!(stdout(&again), "formatted 0 files, 1 unchanged\n");
+    }
+}
+
+#[test]
+fn diff_ignores_formatting_and_key_order() {
+    let dir = Dir::new("diff-equal");
+    let a = dir.file("a.json", r#"{"a": {"b": 1, "c": [1, 2]}, "d": "x"}"#);
+    let b = dir.file("b.json", "{\"d\":\"x\",\n\"a\":{\"c\":[1,2],\"b\":1}}");
+
+    let output = run(&["diff", &a, &b]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn diff_lists_changes_by_pointer() {
+    let dir = Dir::new("diff-changes");
+    let a = dir.file("a.json", r#"{"a": {"b": 1, "c": [1, 2]}, "d": "x"}"#);
+    let b = dir.file("b.json", r#"{"a": {"b": 2, "c": [1]}, "e": null}"#);
+
+    let output = run(&["diff", &a, &b]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "~ /a/b: 1 -> 2\n- /a/c/1: 2\n- /d: \"x\"\n+ /e: null\n"
+    );
+
+    // The patch takes the first file to the second.
+    let output = run(&["diff", "--patch", &a, &b]);
+    assert_eq!(output.status.code(), Some(1));
+    let patch = JsonValue::from(parse_from_str(&stdout(&output)).unwrap());
+    let mut patched = JsonValue::from(parse_from_str(&dir.read("a.json")).unwrap());
+    patched.apply_patch(&patch).unwrap();
+    assert_eq!(
+        patched,
+        JsonValue::from(parse_from_str(&dir.read("b.json")).unwrap())
+    );
+}
+
+#[test]
+fn diff_errors_exit_with_two() {
+    let dir = Dir::new("diff-errors");
+    let good = dir.file("good.json", "[]");
+    let bad = dir.file("bad.json", "{\"a\":");
+
+    let output = run(&["diff", &good, &bad]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "");
+    assert!(
+        stderr(&output).contains("bad.json:1:5: Unexpected end of input"),
+        "{}",
+        stderr(&output)
+    );
+
+    assert_eq!(run(&["diff", &good]).status.code(), Some(2));
+    assert_eq!(
+        run(&["diff", "--brief", &good, &good]).status.code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn merge_overlays_three_config_layers() {
+    let dir = Dir::new("merge-layers");
+    let base = dir.file(
+        "base.json",
+        r#"{"server": {"host": "localhost", "port": 80, "tls": false}, "features": ["a"], "log": {"level": "info"}}"#,
+    );
+    let env = dir.file(
+        "env.json",
+        r#"{"server": {"port": 8080}, "features": ["b"], "log": "stderr"}"#,
+    );
+    let local = dir.file("local.json", r#"{"server": {"tls": true}, "debug": null}"#);
+
+    let output = run(&["merge", "--compact", "--sort-keys", &base, &env, &local]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "{\"debug\":null,\"features\":[\"b\"],\"log\":\"stderr\",\"server\":{\"host\":\"localhost\",\"port\":8080,\"tls\":true}}\n"
+    );
+    assert_eq!(
+        stderr(&output),
+        format!(
+            "warning: {}: '/log' replaces a value of a different kind\n",
+            env
+        )
+    );
+
+    let output = run(&["merge", "--arrays", "concat", "--compact", &base, &env]);
+    let merged = JsonValue::from(parse_from_str(&stdout(&output)).unwrap());
+    assert_eq!(merged.pointer("/features"), Some(&json!(["a", "b"])));
+}
+
+#[test]
+fn merge_patch_nulls_delete_keys() {
+    let dir = Dir::new("merge-patch");
+    let base = dir.file(
+        "base.json",
+        r#"{"a": {"b": 1, "c": 2}, "d": [1, 2], "e": "x"}"#,
+    );
+    let patch = dir.file(
+        "patch.json",
+        r#"{"a": {"c": null, "f": 3}, "d": [3], "e": null}"#,
+    );
+
+    let output = run(&[
+        "merge",
+        "--merge-patch",
+        "--compact",
+        "--sort-keys",
+        &base,
+        &patch,
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "{\"a\":{\"b\":1,\"f\":3},\"d\":[3]}\n");
+    assert_eq!(stderr(&output), "");
+
+    let output = run(&[
+        "merge",
+        "--merge-patch",
+        "--arrays",
+        "concat",
+        &base,
+        &patch,
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn merge_rejects_pretty_with_compact() {
+    let dir = Dir::new("merge-styles");
+    let a = dir.file("a.json", r#"{"a": 1}"#);
+    let b = dir.file("b.json", r#"{"b": 2}"#);
+
+    let output = run(&["merge", "--pretty", "--compact", &a, &b]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--pretty and --compact can't be used together"));
+
+    let output = run(&["merge", "--compact", "--sort-keys", &a, &b]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "{\"a\":1,\"b\":2}\n");
+}
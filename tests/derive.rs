@@ -0,0 +1,174 @@
+#![cfg(feature = "derive")]
+#![allow(clippy::needless_return)]
+
+// Run with `cargo test --features derive`.
+use std::collections::HashMap;
+
+use mini_json::{json, FromJson, FromJsonError, JsonValue, ToJson};
+
+#[derive(Debug, Clone, Copy, PartialEq, FromJson, ToJson)]
+pub enum Role {
+    Admin,
+    #[json(rename = "read-only")]
+    ReadOnly,
+}
+
+#[derive(Debug, Clone, PartialEq, FromJson, ToJson)]
+pub struct User {
+    #[json(rename = "userName")]
+    pub name: String,
+    pub role: Role,
+    #[json(default)]
+    pub tags: Vec<String>,
+    #[json(skip)]
+    cached: Option<u64>,
+    email: Option<String>,
+    r#type: String,
+}
+
+/// Doc comments and other attributes on the type are left alone.
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Team {
+    users: Vec<User>,
+    limits: HashMap<String, u32>,
+    lead: Option<User>,
+}
+
+// The same mapping written by hand, as the derive should generate it.
+#[derive(Debug, PartialEq)]
+struct HandWritten {
+    name: String,
+    role: String,
+    tags: Vec<String>,
+    email: Option<String>,
+    kind: String,
+}
+
+impl FromJson for HandWritten {
+    fn from_json(value: &JsonValue) -> Result<HandWritten, FromJsonError> {
+        return Ok(HandWritten {
+            name: value.field("userName")?,
+            role: value.field("role")?,
+            tags: value.field_or("tags", Vec::new())?,
+            email: value.field("email")?,
+            kind: value.field("type")?,
+        });
+    }
+}
+
+impl ToJson for HandWritten {
+    fn to_json(&self) -> JsonValue {
+        return json!({
+            "userName": self.name.to_json(),
+            "role": self.role.to_json(),
+            "tags": self.tags.to_json(),
+            "email": self.email.to_json(),
+            "type": self.kind.to_json(),
+        });
+    }
+}
+
+fn user() -> User {
+    return User {
+        name: "ada".to_string(),
+        role: Role::ReadOnly,
+        tags: vec!["ops".to_string()],
+        cached: Some(1),
+        email: None,
+        r#type: "person".to_string(),
+    };
+}
+
+#[test]
+fn structs_map_to_objects_with_renamed_and_skipped_fields() {
+    assert_eq!(
+        user().to_json(),
+        json!({
+            "userName": "ada",
+            "role": "read-only",
+            "tags": ["ops"],
+            "email": null,
+            "type": "person"
+        })
+    );
+
+    let parsed = User::from_json(&user().to_json()).unwrap();
+    assert_eq!(
+        parsed,
+        User {
+            cached: None,
+            ..user()
+        }
+    );
+}
+
+#[test]
+fn derived_and_hand_written_impls_agree() {
+    let hand = HandWritten::from_json(&user().to_json()).unwrap();
+    assert_eq!(hand.to_json(), user().to_json());
+
+    let derived = User::from_json(&hand.to_json()).unwrap();
+    assert_eq!(derived.to_json(), hand.to_json());
+}
+
+#[test]
+fn missing_default_fields_are_filled_in() {
+    let value = json!({"userName": "bo", "role": "Admin", "tags": null, "type": "bot"});
+
+    assert_eq!(
+        User::from_json(&value),
+        Ok(User {
+            name: "bo".to_string(),
+            role: Role::Admin,
+            tags: Vec::new(),
+            cached: None,
+            email: None,
+            r#type: "bot".to_string(),
+        })
+    );
+}
+
+#[test]
+fn errors_carry_the_path_through_nested_derives() {
+    let team = |users: JsonValue| json!({"users": users, "limits": {"seats": 5}, "lead": null});
+    let error = |value: JsonValue| Team::from_json(&value).unwrap_err().to_string();
+
+    assert_eq!(
+        error(team(
+            json!([user().to_json(), {"role": "Admin", "type": "x"}])
+        )),
+        "users[1].userName: missing field"
+    );
+    assert_eq!(
+        error(team(
+            json!([{"userName": "a", "role": "admin", "type": "x"}])
+        )),
+        "users[0].role: unknown variant `admin`"
+    );
+    assert_eq!(
+        error(team(json!([{"userName": "a", "role": "Admin", "type": 1}]))),
+        "users[0].type: expected string, found integer"
+    );
+    assert_eq!(
+        error(json!({"users": [], "limits": {"seats": -1}})),
+        "limits.seats: -1 is out of range for u32"
+    );
+
+    let parsed = Team::from_json(&team(json!([]))).unwrap();
+    assert_eq!(parsed.limits, HashMap::from([("seats".to_string(), 5)]));
+    assert_eq!(parsed.lead, None);
+}
+
+#[test]
+fn unit_enums_map_to_strings() {
+    assert_eq!(Role::Admin.to_json(), json!("Admin"));
+    assert_eq!(Role::from_json(&json!("read-only")), Ok(Role::ReadOnly));
+    assert_eq!(
+        Role::from_json(&json!("ReadOnly")).unwrap_err().to_string(),
+        "unknown variant `ReadOnly`"
+    );
+    assert_eq!(
+        Role::from_json(&json!(0)).unwrap_err().to_string(),
+        "expected string, found integer"
+    );
+}
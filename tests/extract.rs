@@ -0,0 +1,119 @@
+#![allow(clippy::needless_return)]
+
+use std::time::Instant;
+
+use mini_json::{extract_pointer, parse_from_str, JsonValue};
+
+const FIXTURES: [&str; 4] = [
+    include_str!("fixtures/goessner.json"),
+    include_str!("../fuzz/seeds/array"),
+    include_str!("../fuzz/seeds/object"),
+    r#"{"a\"b": {"~": [1, {"/": 2}]}, "dup": 1, "dup": [3], "": {"": 4},
+        "esc": {"café": 5, "😀": 6, "tab\t": 7}}"#,
+];
+
+// Every pointer into `value`, the root included.
+fn pointers(value: &JsonValue, prefix: &mut String, all: &mut Vec<String>) {
+    all.push(prefix.clone());
+
+    let len = prefix.len();
+    match value {
+        JsonValue::Object(object) => {
+            for (key, member) in object {
+                prefix.push('/');
+                prefix.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                pointers(member, prefix, all);
+                prefix.truncate(len);
+            }
+        }
+        JsonValue::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                prefix.push_str(&format!("/{}", index));
+                pointers(element, prefix, all);
+                prefix.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn matches_a_full_parse_at_every_pointer() {
+    for source in FIXTURES {
+        let value = JsonValue::from(parse_from_str(source).unwrap());
+        let mut all = Vec::new();
+        pointers(&value, &mut String::new(), &mut all);
+
+        for pointer in &all {
+            let extracted = extract_pointer(source, pointer).unwrap();
+            assert_eq!(extracted.as_ref(), value.pointer(pointer), "{}", pointer);
+        }
+    }
+}
+
+#[test]
+fn matches_a_full_parse_where_nothing_is_found() {
+    for source in FIXTURES {
+        let value = JsonValue::from(parse_from_str(source).unwrap());
+
+        for pointer in [
+            "/missing",
+            "/0/missing",
+            "/99",
+            "/-",
+            "/01",
+            "/store/book/9",
+        ] {
+            let extracted = extract_pointer(source, pointer).unwrap();
+            assert_eq!(extracted.as_ref(), value.pointer(pointer), "{}", pointer);
+        }
+    }
+}
+
+// A document of `count` records, each with a few nested members.
+fn records(count: usize) -> String {
+    let mut source = String::from("{\"results\": [");
+    for i in 0..count {
+        if i > 0 {
+            source.push(',');
+        }
+        source.push_str(&format!(
+            r#"{{"id": {}, "name": "record {}", "tags": ["a", "b"], "score": {}.5}}"#,
+            i, i, i
+        ));
+    }
+    source.push_str("]}");
+    return source;
+}
+
+// Times an early match against a full parse; run it with
+// `cargo test --release --test extract -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_early_match_against_full_parse() {
+    const RUNS: u32 = 20;
+    let source = records(200_000);
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        let json = parse_from_str(&source).unwrap();
+        assert!(JsonValue::from(json).pointer("/results/42/id").is_some());
+    }
+    let parse = start.elapsed() / RUNS;
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        assert!(extract_pointer(&source, "/results/42/id")
+            .unwrap()
+            .is_some());
+    }
+    let extract = start.elapsed() / RUNS;
+
+    println!(
+        "{} bytes: full parse {:?}, extract_pointer {:?}",
+        source.len(),
+        parse,
+        extract
+    );
+    assert!(extract * 10 < parse);
+}
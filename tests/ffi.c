@@ -0,0 +1,54 @@
+/* Walks a document through the C interface; built and run by tests/ffi.rs.
+ * Prints what it finds and exits 0 if every call behaved. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "mini_json.h"
+
+#define CHECK(condition)                                                   \
+    if (!(condition)) {                                                    \
+        fprintf(stderr, "line %d: %s\n", __LINE__, #condition);            \
+        return 1;                                                          \
+    }
+
+int main(void) {
+    const char *input = "{\"name\": \"mini\", \"sizes\": [1, 2.5, true]}";
+    MiniJsonDoc *doc = NULL;
+    MiniJsonError err = {0};
+
+    CHECK(mini_json_parse(input, strlen(input), &doc, &err) == 0);
+
+    const MiniJsonValue *root = mini_json_root(doc);
+    CHECK(mini_json_type(root) == MINI_JSON_OBJECT);
+
+    const char *name = NULL;
+    size_t name_len = 0;
+    CHECK(mini_json_get_string(mini_json_object_get(root, "name", 4), &name, &name_len) == 0);
+    printf("name: %.*s\n", (int)name_len, name);
+
+    const MiniJsonValue *sizes = mini_json_object_get(root, "sizes", 5);
+    CHECK(mini_json_len(sizes) == 3);
+
+    int64_t integer = 0;
+    double number = 0;
+    bool flag = false;
+    CHECK(mini_json_get_integer(mini_json_array_get(sizes, 0), &integer) == 0);
+    CHECK(mini_json_get_double(mini_json_array_get(sizes, 1), &number) == 0);
+    CHECK(mini_json_get_bool(mini_json_array_get(sizes, 2), &flag) == 0);
+    printf("sizes: %lld %.1f %d\n", (long long)integer, number, flag);
+
+    char *text = mini_json_to_string(sizes, 0);
+    CHECK(text != NULL);
+    printf("text: %s\n", text);
+    mini_json_string_free(text);
+    mini_json_free(doc);
+
+    const char *bad = "[1,\n 2,]";
+    CHECK(mini_json_parse(bad, strlen(bad), &doc, &err) == -1);
+    printf("error: %zu:%zu: %s\n", err.line, err.column, err.message);
+    mini_json_error_free(&err);
+    CHECK(err.message == NULL);
+
+    return 0;
+}
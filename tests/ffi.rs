@@ -0,0 +1,49 @@
+#![cfg(all(feature = "ffi", unix))]
+#![allow(clippy::needless_return)]
+
+// Run with `cargo test --features ffi`. Compiles tests/ffi.c against the
+// header and the cdylib that cargo built alongside this test, so it needs
+// a C compiler as `cc`.
+use std::{env, path::PathBuf, process::Command};
+
+#[test]
+fn a_c_program_parses_navigates_and_frees() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // The cdylib is next to this test's executable, in target/*/deps.
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let program = deps.join("mini-json-ffi-test");
+
+    let compiled = Command::new("cc")
+        .arg(root.join("tests/ffi.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg("-L")
+        .arg(&deps)
+        .arg(format!("-Wl,-rpath,{}", deps.display()))
+        .arg("-lmini_json")
+        .arg("-o")
+        .arg(&program)
+        .output()
+        .unwrap();
+    assert!(
+        compiled.status.success(),
+        "{}",
+        String::from_utf8_lossy(&compiled.stderr)
+    );
+
+    // Cargo's library path also holds a build of the cdylib without the
+    // feature, which would be found before the rpath.
+    let output = Command::new(&program)
+        .env_remove("LD_LIBRARY_PATH")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "name: mini\nsizes: 1 2.5 1\ntext: [1,2.5,true]\nerror: 2:4: Unexpected ']', expected a value\n"
+    );
+}
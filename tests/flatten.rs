@@ -0,0 +1,118 @@
+#![allow(clippy::needless_return)]
+
+use std::collections::HashMap;
+
+use mini_json::{json, parse_from_bytes, unflatten, JsonValue};
+
+const SEPARATORS: [&str; 4] = [".", "/", "::", "a"];
+
+fn fixtures() -> Vec<JsonValue> {
+    let sources: [&[u8]; 4] = [
+        include_bytes!("fixtures/goessner.json"),
+        include_bytes!("../fuzz/seeds/array"),
+        include_bytes!("../fuzz/seeds/non-ascii"),
+        include_bytes!("../fuzz/seeds/object"),
+    ];
+
+    return sources
+        .iter()
+        .map(|source| JsonValue::from(parse_from_bytes(source).unwrap()))
+        .collect();
+}
+
+fn assert_round_trips(value: &JsonValue) {
+    for sep in SEPARATORS {
+        let flat = value.flatten(sep);
+        assert_eq!(unflatten(&flat, sep).as_ref(), Ok(value), "{:?}", flat);
+    }
+}
+
+fn entries(pairs: &[(&str, JsonValue)]) -> HashMap<String, JsonValue> {
+    return pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect();
+}
+
+fn error_message(pairs: &[(&str, JsonValue)]) -> String {
+    return unflatten(&entries(pairs), ".").unwrap_err().message;
+}
+
+#[test]
+fn fixtures_round_trip() {
+    for fixture in fixtures() {
+        assert_round_trips(&fixture);
+    }
+}
+
+#[test]
+fn awkward_keys_round_trip() {
+    assert_round_trips(&json!({
+        "": {"": 1, "b": [{"": []}]},
+        "[]": {},
+        "a.b": {"a/b": {"a::b": 2}},
+        "[0]": "bracketed",
+        "back\\slash": [null, true],
+        "a": "the separator itself",
+        ".": ".",
+        "\\.": {"[": "]"},
+        "é": {"日本": 3.5}
+    }));
+    assert_round_trips(&json!([{"": [[{}]]}, [], {"x": [1, [2]]}]));
+}
+
+#[test]
+fn scalars_and_empty_containers_round_trip() {
+    for value in [json!(null), json!(1), json!("s"), json!({}), json!([])] {
+        assert_round_trips(&value);
+    }
+}
+
+#[test]
+fn values_that_are_also_parents_are_errors() {
+    assert_eq!(
+        error_message(&[("a", json!(1)), ("a.b", json!(2))]),
+        "Value is also a parent of other keys"
+    );
+    assert_eq!(
+        error_message(&[("a[0]", json!(1)), ("a[0][0]", json!(2))]),
+        "Value is also a parent of other keys"
+    );
+    assert_eq!(
+        error_message(&[("", json!(1)), ("a", json!(2))]),
+        "Root value is also a parent of other keys"
+    );
+}
+
+#[test]
+fn paths_used_as_object_and_array_are_errors() {
+    assert_eq!(
+        error_message(&[("a[0]", json!(1)), ("a.b", json!(2))]),
+        "Used as both an object and an array"
+    );
+    assert_eq!(
+        error_message(&[("[0]", json!(1)), ("a", json!(2))]),
+        "Used as both an object and an array"
+    );
+}
+
+#[test]
+fn gaps_in_array_indices_are_errors() {
+    let error = unflatten(&entries(&[("a[0]", json!(1)), ("a[2]", json!(2))]), ".").unwrap_err();
+    assert_eq!(error.message, "Missing array index");
+    assert_eq!(error.key, "a[1]");
+
+    assert_eq!(error_message(&[("a[1]", json!(1))]), "Missing array index");
+}
+
+#[test]
+fn malformed_keys_are_errors() {
+    assert_eq!(error_message(&[("a[0", json!(1))]), "Unclosed '['");
+    assert_eq!(error_message(&[("a[x]", json!(1))]), "Invalid array index");
+    assert_eq!(error_message(&[("a[-1]", json!(1))]), "Invalid array index");
+    assert_eq!(error_message(&[("a\\", json!(1))]), "Dangling escape");
+    assert_eq!(
+        error_message(&[("a[0]b", json!(1))]),
+        "Expect separator or '['"
+    );
+}
@@ -0,0 +1,203 @@
+#![allow(clippy::needless_return)]
+
+// Regressions for what the fuzz targets in `fuzz/` have found, and the seed
+// corpus run through the same checks, so they hold without `cargo fuzz`.
+use std::cmp::Ordering;
+
+use mini_json::{
+    from_cbor, from_xml, parse_from_bytes, parse_from_str, JsonValue, JsoncDocument, OutputStyle,
+    ParseErrorKind, SerializeOptions,
+};
+
+const SEEDS: [(&str, &[u8]); 8] = [
+    ("array", include_bytes!("../fuzz/seeds/array")),
+    ("deep-array", include_bytes!("../fuzz/seeds/deep-array")),
+    ("empty", include_bytes!("../fuzz/seeds/empty")),
+    ("huge-float", include_bytes!("../fuzz/seeds/huge-float")),
+    ("invalid-utf8", include_bytes!("../fuzz/seeds/invalid-utf8")),
+    ("non-ascii", include_bytes!("../fuzz/seeds/non-ascii")),
+    ("object", include_bytes!("../fuzz/seeds/object")),
+    ("open-brace", include_bytes!("../fuzz/seeds/open-brace")),
+];
+
+// The `roundtrip` target: whatever parses must serialize, in either style,
+// to text that parses back to the same tree.
+fn assert_round_trips(bytes: &[u8]) {
+    let json = match parse_from_bytes(bytes) {
+        Ok(json) => json,
+        Err(_) => return,
+    };
+
+    for style in [OutputStyle::Minified, OutputStyle::Pretty] {
+        let text = json.to_string_with(&SerializeOptions::new(style)).unwrap();
+        assert_eq!(parse_from_str(&text).unwrap(), json, "{}", text);
+    }
+}
+
+fn error_kind(bytes: &[u8]) -> ParseErrorKind {
+    return parse_from_bytes(bytes).unwrap_err().kind;
+}
+
+#[test]
+fn seeds_parse_and_round_trip() {
+    for (name, bytes) in SEEDS {
+        let parsed = parse_from_bytes(bytes);
+        match name {
+            "array" | "non-ascii" | "object" => assert!(parsed.is_ok(), "{}", name),
+            _ => assert!(parsed.is_err(), "{}", name),
+        }
+        assert_round_trips(bytes);
+    }
+}
+
+#[test]
+fn empty_and_truncated_documents_are_errors() {
+    assert_eq!(error_kind(b""), ParseErrorKind::EmptyDocument);
+    assert_eq!(error_kind(b"   \n"), ParseErrorKind::EmptyDocument);
+    for source in ["{", "[", "{\"a\"", "{\"a\":", "[1,"] {
+        assert!(
+            matches!(
+                error_kind(source.as_bytes()),
+                ParseErrorKind::UnexpectedEnd { .. }
+            ),
+            "{}",
+            source
+        );
+    }
+    for source in ["[\"", "[\"\\", "{\"a"] {
+        assert!(
+            matches!(
+                error_kind(source.as_bytes()),
+                ParseErrorKind::UnterminatedString { .. }
+            ),
+            "{}",
+            source
+        );
+    }
+    assert!(matches!(
+        error_kind(b"[\"\\u12"),
+        ParseErrorKind::InvalidEscape { .. }
+    ));
+}
+
+#[test]
+fn non_ascii_text_is_sliced_on_char_boundaries() {
+    let json = parse_from_str("{\"é\": \"café\", \"ü\": [\"日本語\", \"\\u00e9\"]}").unwrap();
+    let value = JsonValue::from(json);
+    assert_eq!(value.query("$['é']").unwrap(), [&JsonValue::from("café")]);
+    assert_eq!(value.query("$['ü'][1]").unwrap(), [&JsonValue::from("é")]);
+
+    // Errors after multi-byte characters must still report a position
+    // rather than slicing through one.
+    for source in ["[\"é\" é]", "{\"é\":é}", "é", "[\"日本\"", "[1, \"é\"],é"] {
+        let error = parse_from_str(source).unwrap_err();
+        assert_eq!(error.line, 1, "{}", source);
+    }
+    for source in ["[\"é\"]", "{\"\\u00e9\": \"é\"}"] {
+        assert_round_trips(source.as_bytes());
+    }
+}
+
+#[test]
+fn invalid_utf8_is_an_error_at_the_first_bad_byte() {
+    for (bytes, line, column) in [
+        (&b"[\xff]"[..], 1, 2),
+        (&b"[\"caf\xc3\"]"[..], 1, 6),
+        (&b"{\n  \"a\": \"\xed\xa0\x80\"\n}"[..], 2, 9),
+    ] {
+        let error = parse_from_bytes(bytes).unwrap_err();
+        assert!(
+            matches!(error.kind, ParseErrorKind::InvalidUtf8 { .. }),
+            "{:?}",
+            error
+        );
+        assert_eq!((error.line, error.column), (line, column), "{:?}", bytes);
+    }
+}
+
+#[test]
+fn malformed_structure_is_rejected() {
+    for source in [
+        "{garbage}",
+        "{\"a\":1 \"b\":2}",
+        "[1 2 3]",
+        "{\"a\":1,}",
+        "[1,]",
+        "{,,\"a\":true}",
+        "[,]",
+        "[01]",
+        "[1.]",
+        "[.5]",
+        "[1e]",
+        "[-]",
+        "[1],",
+        "{\"a\":[1]},",
+        "{\"a\" 1}",
+        "{1:2}",
+        "[\"\\x\"]",
+        "[\"\\ud800\"]",
+        "[\"a\nb\"]",
+        "[tru]",
+        "[nul]",
+    ] {
+        assert!(parse_from_str(source).is_err(), "{}", source);
+    }
+}
+
+#[test]
+fn huge_numbers_are_errors_rather_than_infinities() {
+    for source in ["[1e400]", "[-1e400]", "[99999999999999999999]"] {
+        assert!(
+            matches!(
+                error_kind(source.as_bytes()),
+                ParseErrorKind::InvalidNumber { .. }
+            ),
+            "{}",
+            source
+        );
+    }
+    assert_round_trips(b"[1e308, -1e-400, 9223372036854775807]");
+}
+
+#[test]
+fn deep_nesting_is_an_error_in_every_parser() {
+    const DEPTH: usize = 100_000;
+
+    let arrays = "[".repeat(DEPTH) + &"]".repeat(DEPTH);
+    let objects = "{\"a\":".repeat(DEPTH) + "1" + &"}".repeat(DEPTH);
+    for source in [&arrays, &objects, &"[".repeat(DEPTH)] {
+        assert!(matches!(
+            error_kind(source.as_bytes()),
+            ParseErrorKind::DepthLimitExceeded { .. }
+        ));
+        assert!(JsoncDocument::parse(source).is_err());
+    }
+
+    assert!(from_xml(&"<a>".repeat(DEPTH)).is_err());
+    assert!(from_cbor(&vec![0x81; DEPTH]).is_err());
+    #[cfg(feature = "toml")]
+    assert!(mini_json::from_toml_str(&format!("a = {}", "[".repeat(DEPTH))).is_err());
+    #[cfg(feature = "yaml")]
+    assert!(mini_json::from_yaml_str(&"- ".repeat(DEPTH)).is_err());
+}
+
+#[test]
+fn slices_with_huge_steps_end() {
+    let value = mini_json::json!([0, 1, 2, 3]);
+    let query = format!("$[1::{}]", isize::MAX);
+    assert_eq!(value.query(&query).unwrap(), [&mini_json::json!(1)]);
+    let query = format!("$[2::{}]", isize::MIN);
+    assert_eq!(value.query(&query).unwrap(), [&mini_json::json!(2)]);
+}
+
+#[test]
+fn number_order_is_transitive_past_two_to_the_53() {
+    let two_53 = 1_isize << 53;
+    let a = JsonValue::Integer(two_53);
+    let b = JsonValue::Float(two_53 as f64);
+    let c = JsonValue::Integer(two_53 + 1);
+
+    assert_eq!(a.compare(&b), Ordering::Equal);
+    assert_eq!(b.compare(&c), Ordering::Less);
+    assert_eq!(a.compare(&c), Ordering::Less);
+}
@@ -0,0 +1,38 @@
+#![allow(clippy::needless_return)]
+
+use mini_json::{parse_from_str, JsonValue};
+
+// Cases copied from tests.json and spec_tests.json of the json-patch-tests
+// corpus, less those that need duplicate keys or a scalar document root.
+// Each has a `patch` and either the `expected` result or an `error`.
+const CORPUS: &str = include_str!("fixtures/json-patch-tests.json");
+
+#[test]
+fn passes_the_json_patch_tests_corpus() {
+    let cases = JsonValue::from(parse_from_str(CORPUS).unwrap());
+    let mut errors = 0;
+
+    for case in cases.elements() {
+        let comment = case.pointer("/comment").unwrap();
+        let doc = case.pointer("/doc").unwrap();
+        let patch = case.pointer("/patch").unwrap();
+
+        let mut target = doc.clone();
+        let applied = target.apply_patch(patch);
+
+        match case.pointer("/expected") {
+            Some(expected) => {
+                assert_eq!(applied, Ok(()), "{}", comment);
+                assert_eq!(&target, expected, "{}", comment);
+            }
+            None => {
+                assert!(applied.is_err(), "{} should fail", comment);
+                assert_eq!(&target, doc, "{}", comment);
+                errors += 1;
+            }
+        }
+    }
+
+    assert!(cases.elements().count() > 80);
+    assert!(errors > 25, "only {} error cases", errors);
+}
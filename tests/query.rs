@@ -0,0 +1,147 @@
+#![allow(clippy::needless_return)]
+
+use mini_json::{json, Json, JsonValue, OutputStyle, SerializeOptions};
+
+fn text(value: &JsonValue) -> String {
+    let mut options = SerializeOptions::new(OutputStyle::Minified);
+    options.sort_keys = true;
+    return value.to_string_with(&options).unwrap();
+}
+
+fn bookstore() -> JsonValue {
+    let source = include_str!("fixtures/goessner.json");
+    return JsonValue::from(source.parse::<Json>().unwrap());
+}
+
+// The results of `query`, minified and sorted, since members of an object
+// come back in no particular order.
+fn results(query: &str) -> Vec<String> {
+    let doc = bookstore();
+    let mut results: Vec<String> = doc.query(query).unwrap().into_iter().map(text).collect();
+    results.sort();
+    return results;
+}
+
+fn sorted(values: &[JsonValue]) -> Vec<String> {
+    let mut values: Vec<String> = values.iter().map(text).collect();
+    values.sort();
+    return values;
+}
+
+#[test]
+fn authors_of_all_books() {
+    let expected = [
+        json!("Nigel Rees"),
+        json!("Evelyn Waugh"),
+        json!("Herman Melville"),
+        json!("J. R. R. Tolkien"),
+    ];
+    assert_eq!(results("$.store.book[*].author"), sorted(&expected));
+    assert_eq!(results("$..author"), sorted(&expected));
+    assert_eq!(
+        results("$['store']['book'][*]['author']"),
+        sorted(&expected)
+    );
+}
+
+#[test]
+fn everything_in_the_store() {
+    let doc = bookstore();
+    let store = doc.pointer("/store").unwrap();
+    let expected = [
+        store.pointer("/book").unwrap().clone(),
+        store.pointer("/bicycle").unwrap().clone(),
+    ];
+    assert_eq!(results("$.store.*"), sorted(&expected));
+}
+
+#[test]
+fn prices_of_everything() {
+    let expected = [
+        json!(8.95),
+        json!(12.99),
+        json!(8.99),
+        json!(22.99),
+        json!(19.95),
+    ];
+    assert_eq!(results("$.store..price"), sorted(&expected));
+}
+
+#[test]
+fn books_by_index() {
+    let doc = bookstore();
+    let book = |index: usize| {
+        doc.pointer(&format!("/store/book/{}", index))
+            .unwrap()
+            .clone()
+    };
+
+    assert_eq!(results("$..book[2]"), sorted(&[book(2)]));
+    assert_eq!(results("$..book[-1]"), sorted(&[book(3)]));
+    assert_eq!(results("$..book[0,1]"), sorted(&[book(0), book(1)]));
+    assert_eq!(results("$..book[:2]"), sorted(&[book(0), book(1)]));
+    assert_eq!(results("$..book[1:3]"), sorted(&[book(1), book(2)]));
+    assert_eq!(results("$..book[-2:]"), sorted(&[book(2), book(3)]));
+    assert_eq!(results("$..book[::2]"), sorted(&[book(0), book(2)]));
+    assert_eq!(results("$..book[::-1]").len(), 4);
+}
+
+#[test]
+fn slices_keep_array_order() {
+    let doc = bookstore();
+    let titles: Vec<&JsonValue> = doc.query("$.store.book[::-1].title").unwrap();
+    assert_eq!(
+        titles,
+        [
+            &json!("The Lord of the Rings"),
+            &json!("Moby Dick"),
+            &json!("Sword of Honour"),
+            &json!("Sayings of the Century"),
+        ]
+    );
+}
+
+#[test]
+fn isbns_only_where_present() {
+    assert_eq!(
+        results("$..book[*].isbn"),
+        sorted(&[json!("0-553-21311-3"), json!("0-395-19395-8")])
+    );
+}
+
+#[test]
+fn every_value_in_the_document() {
+    // The store, its book array and bicycle, the 4 books with 18 members
+    // between them, and the bicycle's 2 members.
+    assert_eq!(results("$..*").len(), 1 + 2 + 4 + 18 + 2);
+}
+
+#[test]
+fn missing_names_match_nothing() {
+    assert!(results("$.store.car").is_empty());
+    assert!(results("$..book[10]").is_empty());
+    assert!(results("$.store.bicycle[0]").is_empty());
+}
+
+#[test]
+fn huge_steps_end_the_slice() {
+    let doc = json!([0, 1, 2, 3]);
+    assert_eq!(doc.query("$[1::9223372036854775807]").unwrap(), [&json!(1)]);
+    assert_eq!(
+        doc.query("$[2::-9223372036854775808]").unwrap(),
+        [&json!(2)]
+    );
+}
+
+#[test]
+fn errors_give_positions() {
+    let doc = bookstore();
+    let error = doc.query("store").unwrap_err();
+    assert_eq!(error.position, 0);
+
+    let error = doc.query("$[1::0]").unwrap_err();
+    assert_eq!(error.message, "Slice step can't be zero");
+
+    let error = doc.query("$['unterminated").unwrap_err();
+    assert_eq!(error.position, 2);
+}
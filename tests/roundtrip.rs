@@ -0,0 +1,415 @@
+#![allow(clippy::needless_return)]
+
+// Property tests: random trees must survive serializing and parsing back,
+// in every output style, and a diff between two trees must patch one into
+// the other. Trees come from a seeded generator so failures reproduce, and
+// a failing tree is shrunk before it's reported.
+use std::collections::HashMap;
+
+use mini_json::{diff, parse_from_str, JsonValue};
+
+const CASES: u64 = 500;
+const MAX_DEPTH: usize = 5;
+const MAX_WIDTH: usize = 6;
+
+// xorshift64*, which is plenty for picking test cases.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // The state must never be zero.
+        return Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1);
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        return self.0.wrapping_mul(0x2545_f491_4f6c_dd1d);
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        return (self.next() % bound as u64) as usize;
+    }
+
+    fn pick<T: Copy>(&mut self, choices: &[T]) -> T {
+        return choices[self.below(choices.len())];
+    }
+}
+
+// Characters that need escaping, or are multi-byte, or are easy to get
+// wrong, weighted against plain ASCII.
+const SPECIAL_CHARS: [char; 16] = [
+    '"', '\\', '/', '\n', '\r', '\t', '\u{0}', '\u{8}', '\u{c}', '\u{1f}', '\u{7f}', 'é', '日',
+    '😀', '\u{2028}', '\u{fffd}',
+];
+
+fn string(rng: &mut Rng) -> String {
+    let len = rng.below(8);
+    return (0..len)
+        .map(|_| match rng.below(3) {
+            0 => rng.pick(&SPECIAL_CHARS),
+            _ => char::from(b' ' + rng.below(95) as u8),
+        })
+        .collect();
+}
+
+// Integers around the edges of `isize` and of the range where an `f64`
+// holds every integer exactly.
+fn integer(rng: &mut Rng) -> isize {
+    let base = rng.pick(&[0, 1 << 53, -(1 << 53), isize::MAX, isize::MIN, 1000]);
+    let offset = rng.below(5) as isize - 2;
+    return match rng.below(3) {
+        0 => rng.next() as isize,
+        _ => base.saturating_add(offset),
+    };
+}
+
+fn float(rng: &mut Rng) -> f64 {
+    let float = match rng.below(4) {
+        0 => f64::from_bits(rng.next()),
+        1 => rng.pick(&[
+            0.0,
+            -0.0,
+            2.0,
+            -1.5,
+            1e300,
+            5e-324,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+        ]),
+        2 => (1u64 << 53) as f64 + rng.pick(&[-2.0, 0.0, 2.0, 4.0]),
+        _ => (rng.next() % 100_000) as f64 / 1000.0,
+    };
+
+    // JSON can't hold infinities or NaN.
+    if float.is_finite() {
+        return float;
+    }
+    return 0.5;
+}
+
+fn value(rng: &mut Rng, depth: usize) -> JsonValue {
+    let kinds = if depth < MAX_DEPTH { 8 } else { 6 };
+
+    match rng.below(kinds) {
+        0 => {
+            return JsonValue::Null;
+        }
+        1 => {
+            return JsonValue::Boolean(rng.below(2) == 0);
+        }
+        2 => {
+            return JsonValue::Integer(integer(rng));
+        }
+        3 => {
+            return JsonValue::Float(float(rng));
+        }
+        4 | 5 => {
+            return JsonValue::String(string(rng));
+        }
+        6 => {
+            let len = rng.below(MAX_WIDTH);
+            return JsonValue::Array((0..len).map(|_| value(rng, depth + 1)).collect());
+        }
+        _ => {
+            let len = rng.below(MAX_WIDTH);
+            let mut object = HashMap::new();
+            for _ in 0..len {
+                object.insert(string(rng), value(rng, depth + 1));
+            }
+            return JsonValue::Object(object);
+        }
+    }
+}
+
+// Documents must be an object or an array.
+fn document(rng: &mut Rng) -> JsonValue {
+    match value(rng, 0) {
+        root @ (JsonValue::Object(_) | JsonValue::Array(_)) => {
+            return root;
+        }
+        scalar => {
+            return JsonValue::Array(vec![scalar]);
+        }
+    }
+}
+
+fn parse(text: &str) -> Result<JsonValue, String> {
+    return parse_from_str(text)
+        .map(JsonValue::from)
+        .map_err(|error| error.to_string());
+}
+
+// Checks every property for `value`, describing the first that fails.
+fn check(value: &JsonValue) -> Result<(), String> {
+    let minified = value
+        .to_string_minified()
+        .map_err(|error| error.to_string())?;
+    let pretty = value
+        .to_string_pretty()
+        .map_err(|error| error.to_string())?;
+
+    for (style, text) in [
+        ("to_string", value.to_string()),
+        ("minified", minified.clone()),
+        ("pretty", pretty.clone()),
+    ] {
+        match parse(&text) {
+            Ok(parsed) if parsed == *value => {}
+            Ok(parsed) => return Err(format!("{} reparsed as {:?}: {}", style, parsed, text)),
+            Err(error) => return Err(format!("{} didn't parse ({}): {}", style, error, text)),
+        }
+    }
+
+    if minified.len() > pretty.len() {
+        return Err(format!("minified is longer than pretty: {}", minified));
+    }
+    return Ok(());
+}
+
+// Smaller trees to try in place of `value`: its children, and itself with
+// one child, or part of a string, taken out.
+fn shrink(value: &JsonValue) -> Vec<JsonValue> {
+    let mut smaller = Vec::new();
+
+    match value {
+        JsonValue::Array(array) => {
+            smaller.extend(array.iter().cloned());
+            for index in 0..array.len() {
+                let mut fewer = array.clone();
+                fewer.remove(index);
+                smaller.push(JsonValue::Array(fewer));
+            }
+            for (index, element) in array.iter().enumerate() {
+                for element in shrink(element) {
+                    let mut array = array.clone();
+                    array[index] = element;
+                    smaller.push(JsonValue::Array(array));
+                }
+            }
+        }
+        JsonValue::Object(object) => {
+            smaller.extend(object.values().cloned());
+            for key in object.keys() {
+                let mut fewer = object.clone();
+                fewer.remove(key);
+                smaller.push(JsonValue::Object(fewer));
+            }
+            for (key, member) in object {
+                for member in shrink(member) {
+                    let mut object = object.clone();
+                    object.insert(key.clone(), member);
+                    smaller.push(JsonValue::Object(object));
+                }
+            }
+        }
+        JsonValue::String(string) => {
+            let chars: Vec<char> = string.chars().collect();
+            for index in 0..chars.len() {
+                let mut fewer = chars.clone();
+                fewer.remove(index);
+                smaller.push(JsonValue::String(fewer.into_iter().collect()));
+            }
+        }
+        JsonValue::Integer(integer) if *integer != 0 => {
+            smaller.push(JsonValue::Integer(0));
+        }
+        JsonValue::Float(float) if *float != 0.0 => {
+            smaller.push(JsonValue::Float(0.0));
+        }
+        _ => {}
+    }
+
+    return smaller;
+}
+
+// Shrinks a document that fails `property` while it still fails, so the
+// report shows a small counterexample rather than whatever was generated.
+fn minimize(
+    mut value: JsonValue,
+    mut failure: String,
+    property: impl Fn(&JsonValue) -> Result<(), String>,
+) -> (JsonValue, String) {
+    'outer: loop {
+        for candidate in shrink(&value) {
+            let candidate = match candidate {
+                root @ (JsonValue::Object(_) | JsonValue::Array(_)) => root,
+                _ => continue,
+            };
+            if let Err(error) = property(&candidate) {
+                value = candidate;
+                failure = error;
+                continue 'outer;
+            }
+        }
+
+        return (value, failure);
+    }
+}
+
+fn assert_property(
+    cases: u64,
+    generate: impl Fn(&mut Rng) -> JsonValue,
+    property: impl Fn(&JsonValue) -> Result<(), String> + Copy,
+) {
+    for seed in 0..cases {
+        let value = generate(&mut Rng::new(seed));
+
+        if let Err(failure) = property(&value) {
+            let (value, failure) = minimize(value, failure, property);
+            panic!("seed {} fails, shrunk to {:?}\n{}", seed, value, failure);
+        }
+    }
+}
+
+#[test]
+fn random_documents_round_trip() {
+    assert_property(CASES, document, check);
+}
+
+#[test]
+fn wide_flat_documents_round_trip() {
+    assert_property(
+        CASES / 5,
+        |rng| {
+            let len = rng.below(200);
+            return JsonValue::Array((0..len).map(|_| value(rng, MAX_DEPTH)).collect());
+        },
+        check,
+    );
+}
+
+// A copy of `original` with a few random changes, so that most of a diff
+// against it is nested edits rather than one replacement of the root.
+fn edit(rng: &mut Rng, original: &JsonValue, depth: usize) -> JsonValue {
+    if rng.below(8) == 0 {
+        return value(rng, depth);
+    }
+
+    match original {
+        JsonValue::Array(array) => {
+            let mut array: Vec<JsonValue> = array
+                .iter()
+                .map(|element| match rng.below(3) {
+                    0 => edit(rng, element, depth + 1),
+                    _ => element.clone(),
+                })
+                .collect();
+            if !array.is_empty() && rng.below(3) == 0 {
+                array.remove(rng.below(array.len()));
+            }
+            if rng.below(3) == 0 {
+                let index = rng.below(array.len() + 1);
+                array.insert(index, value(rng, depth + 1));
+            }
+            return JsonValue::Array(array);
+        }
+        JsonValue::Object(object) => {
+            // Sorted so the same seed makes the same edits.
+            let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+
+            let mut object = HashMap::new();
+            for (key, member) in members {
+                match rng.below(5) {
+                    0 => {}
+                    1 => {
+                        object.insert(key.clone(), edit(rng, member, depth + 1));
+                    }
+                    _ => {
+                        object.insert(key.clone(), member.clone());
+                    }
+                }
+            }
+            if rng.below(3) == 0 {
+                object.insert(string(rng), value(rng, depth + 1));
+            }
+            return JsonValue::Object(object);
+        }
+        _ => {
+            return original.clone();
+        }
+    }
+}
+
+// `pair` is `[old, new]`; anything else, which shrinking can produce,
+// passes trivially.
+fn patches_back(pair: &JsonValue) -> Result<(), String> {
+    let (old, new) = match pair {
+        JsonValue::Array(pair) if pair.len() == 2 => (&pair[0], &pair[1]),
+        _ => return Ok(()),
+    };
+
+    let patch = diff(old, new);
+    let mut patched = old.clone();
+    if let Err(error) = patched.apply_patch(&patch) {
+        return Err(format!("{} failed to apply: {:?}", error, patch));
+    }
+    if patched != *new {
+        return Err(format!("{:?} patched to {:?}", patch, patched));
+    }
+    if diff(new, new) != JsonValue::Array(Vec::new()) {
+        return Err(format!("{:?} differs from itself", new));
+    }
+    return Ok(());
+}
+
+#[test]
+fn diffs_patch_old_documents_into_new_ones() {
+    assert_property(
+        CASES,
+        |rng| {
+            let old = document(rng);
+            let new = match rng.below(4) {
+                0 => document(rng),
+                _ => edit(rng, &old, 0),
+            };
+            return JsonValue::Array(vec![old, new]);
+        },
+        patches_back,
+    );
+}
+
+#[test]
+fn the_generator_covers_every_kind() {
+    let mut rng = Rng::new(1);
+    let mut seen = [false; 7];
+
+    for _ in 0..200 {
+        let index = match value(&mut rng, 0) {
+            JsonValue::Null => 0,
+            JsonValue::Boolean(_) => 1,
+            JsonValue::Integer(_) => 2,
+            JsonValue::Float(_) => 3,
+            JsonValue::String(_) => 4,
+            JsonValue::Array(_) => 5,
+            JsonValue::Object(_) => 6,
+        };
+        seen[index] = true;
+    }
+    assert_eq!(seen, [true; 7]);
+}
+
+#[test]
+fn shrinking_finds_a_small_counterexample() {
+    // A stand-in property that fails on any 'é' in a string value.
+    let property = |value: &JsonValue| {
+        let mut found = false;
+        value.walk(|_, value| {
+            found |= matches!(value, JsonValue::String(string) if string.contains('é'));
+        });
+        return match found {
+            true => Err("found 'é'".to_string()),
+            false => Ok(()),
+        };
+    };
+
+    let large = (0..)
+        .map(|seed| document(&mut Rng::new(seed)))
+        .find(|value| property(value).is_err() && value.to_string_minified().unwrap().len() > 100)
+        .unwrap();
+    let (smallest, _) = minimize(large, String::new(), property);
+
+    assert_eq!(smallest, JsonValue::Array(vec![JsonValue::from("é")]));
+}
@@ -0,0 +1,78 @@
+#![allow(clippy::needless_return)]
+
+use mini_json::{infer_schema, infer_schema_with, parse_from_str, InferOptions, JsonValue, Schema};
+
+// Cases copied from the draft-07 directory of the official
+// JSON-Schema-Test-Suite, for the keywords `Schema` supports.
+const SUITE: &str = include_str!("fixtures/schema-test-suite.json");
+
+fn text(value: &JsonValue, pointer: &str) -> String {
+    match value.pointer(pointer) {
+        Some(JsonValue::String(text)) => {
+            return text.clone();
+        }
+        other => {
+            panic!("{} should be a string, found {:?}", pointer, other);
+        }
+    }
+}
+
+#[test]
+fn passes_the_json_schema_test_suite() {
+    let groups = JsonValue::from(parse_from_str(SUITE).unwrap());
+    let mut cases = 0;
+
+    for group in groups.elements() {
+        let description = text(group, "/description");
+        let schema = Schema::parse(group.pointer("/schema").unwrap())
+            .unwrap_or_else(|error| panic!("{}: {}", description, error));
+
+        for test in group.pointer("/tests").unwrap().elements() {
+            let data = test.pointer("/data").unwrap();
+            let valid = test.pointer("/valid") == Some(&JsonValue::Boolean(true));
+
+            assert_eq!(
+                schema.validate(data).is_ok(),
+                valid,
+                "{}: {}",
+                description,
+                text(test, "/description")
+            );
+            cases += 1;
+        }
+    }
+
+    assert!(cases > 150, "only {} cases", cases);
+}
+
+const FIXTURES: [&str; 4] = [
+    include_str!("fixtures/goessner.json"),
+    include_str!("fixtures/schema-test-suite.json"),
+    include_str!("../fuzz/seeds/array"),
+    include_str!("../fuzz/seeds/object"),
+];
+
+// Every value in a document, so inference is also tried on each part.
+fn subtrees(value: &JsonValue) -> Vec<&JsonValue> {
+    let mut all = vec![value];
+    for child in value.values().chain(value.elements()) {
+        all.extend(subtrees(child));
+    }
+    return all;
+}
+
+#[test]
+fn inferred_schemas_accept_their_sample() {
+    let relaxed = InferOptions { require_all: false };
+
+    for source in FIXTURES {
+        let doc = JsonValue::from(parse_from_str(source).unwrap());
+
+        for sample in subtrees(&doc) {
+            for schema in [infer_schema(sample), infer_schema_with(sample, &relaxed)] {
+                let compiled = Schema::parse(&schema).unwrap();
+                assert_eq!(compiled.validate(sample), Ok(()), "{}", schema);
+            }
+        }
+    }
+}